@@ -126,6 +126,19 @@ const TwoFAForm = document.getElementById("2fa-form");
 const TwoFAButton = document.getElementById("2fa-form-submit");
 const TwoFAErrAlter = document.getElementById("2fa-err-alert");
 
+// Single sign-on sends users who require 2FA back here with the login
+// attempt in the fragment.
+const ssoTwoFA = new URLSearchParams(window.location.hash.substring(1));
+if (ssoTwoFA.has("loginAttemptId") && ssoTwoFA.has("email")) {
+    TwoFAForm.email.value = ssoTwoFA.get("email");
+    TwoFAForm.login_attempt_id.value = ssoTwoFA.get("loginAttemptId");
+    history.replaceState(null, "", window.location.pathname + window.location.search);
+
+    loginSection.style.display = "none";
+    twoFASection.style.display = "block";
+    signupSection.style.display = "none";
+}
+
 TwoFAButton.addEventListener("click", (e) => {
     e.preventDefault();
 
@@ -0,0 +1,2 @@
+-- Add down migration script here
+ALTER TABLE users MODIFY password_hash TEXT NOT NULL;
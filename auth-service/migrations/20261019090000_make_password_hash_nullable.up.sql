@@ -0,0 +1,2 @@
+-- Users created through an external identity provider have no password
+ALTER TABLE users MODIFY password_hash TEXT NULL;
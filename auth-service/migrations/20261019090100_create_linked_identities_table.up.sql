@@ -0,0 +1,11 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS linked_identities (
+    provider VARCHAR(64) NOT NULL,
+    subject VARCHAR(255) NOT NULL,
+    email VARCHAR(255) NOT NULL,
+    linked_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (provider, subject),
+    UNIQUE KEY linked_identities_email_provider (email, provider),
+    CONSTRAINT linked_identities_user_fk FOREIGN KEY (email)
+        REFERENCES users (email) ON DELETE CASCADE ON UPDATE CASCADE
+);
@@ -0,0 +1,30 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS roles (
+    name VARCHAR(64) NOT NULL PRIMARY KEY
+);
+
+CREATE TABLE IF NOT EXISTS role_permissions (
+    role VARCHAR(64) NOT NULL,
+    permission VARCHAR(64) NOT NULL,
+    PRIMARY KEY (role, permission),
+    CONSTRAINT role_permissions_role_fk FOREIGN KEY (role)
+        REFERENCES roles (name) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS user_roles (
+    email VARCHAR(255) NOT NULL,
+    role VARCHAR(64) NOT NULL,
+    assigned_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (email, role),
+    CONSTRAINT user_roles_user_fk FOREIGN KEY (email)
+        REFERENCES users (email) ON DELETE CASCADE ON UPDATE CASCADE,
+    CONSTRAINT user_roles_role_fk FOREIGN KEY (role)
+        REFERENCES roles (name) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+INSERT IGNORE INTO roles (name) VALUES ('admin');
+
+INSERT IGNORE INTO role_permissions (role, permission) VALUES
+    ('admin', 'users:read'),
+    ('admin', 'users:write'),
+    ('admin', 'roles:manage');
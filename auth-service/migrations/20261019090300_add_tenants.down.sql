@@ -0,0 +1,31 @@
+-- Add down migration script here
+-- Accounts outside the default tenant can't be kept once emails are
+-- globally unique again
+DELETE FROM users WHERE tenant_id <> 'default';
+
+ALTER TABLE linked_identities DROP FOREIGN KEY linked_identities_user_fk;
+ALTER TABLE user_roles DROP FOREIGN KEY user_roles_user_fk;
+ALTER TABLE users DROP FOREIGN KEY users_tenant_fk;
+
+ALTER TABLE users
+    DROP PRIMARY KEY,
+    DROP COLUMN tenant_id,
+    ADD PRIMARY KEY (email);
+
+ALTER TABLE linked_identities
+    DROP PRIMARY KEY,
+    DROP INDEX linked_identities_email_provider,
+    DROP COLUMN tenant_id,
+    ADD PRIMARY KEY (provider, subject),
+    ADD UNIQUE KEY linked_identities_email_provider (email, provider),
+    ADD CONSTRAINT linked_identities_user_fk FOREIGN KEY (email)
+        REFERENCES users (email) ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE user_roles
+    DROP PRIMARY KEY,
+    DROP COLUMN tenant_id,
+    ADD PRIMARY KEY (email, role),
+    ADD CONSTRAINT user_roles_user_fk FOREIGN KEY (email)
+        REFERENCES users (email) ON DELETE CASCADE ON UPDATE CASCADE;
+
+DROP TABLE IF EXISTS tenants;
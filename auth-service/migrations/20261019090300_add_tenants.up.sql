@@ -0,0 +1,41 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS tenants (
+    id VARCHAR(64) NOT NULL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Every existing account moves to the default tenant
+INSERT IGNORE INTO tenants (id, name) VALUES ('default', 'Default');
+
+ALTER TABLE linked_identities DROP FOREIGN KEY linked_identities_user_fk;
+ALTER TABLE user_roles DROP FOREIGN KEY user_roles_user_fk;
+
+-- Emails are only unique within a tenant
+ALTER TABLE users
+    ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default' FIRST,
+    DROP PRIMARY KEY,
+    ADD PRIMARY KEY (tenant_id, email),
+    ADD CONSTRAINT users_tenant_fk FOREIGN KEY (tenant_id)
+        REFERENCES tenants (id) ON DELETE CASCADE;
+
+ALTER TABLE linked_identities
+    ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default' FIRST,
+    DROP PRIMARY KEY,
+    ADD PRIMARY KEY (tenant_id, provider, subject),
+    DROP INDEX linked_identities_email_provider,
+    ADD UNIQUE KEY linked_identities_email_provider (tenant_id, email, provider),
+    ADD CONSTRAINT linked_identities_user_fk FOREIGN KEY (tenant_id, email)
+        REFERENCES users (tenant_id, email) ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE user_roles
+    ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT 'default' FIRST,
+    DROP PRIMARY KEY,
+    ADD PRIMARY KEY (tenant_id, email, role),
+    ADD CONSTRAINT user_roles_user_fk FOREIGN KEY (tenant_id, email)
+        REFERENCES users (tenant_id, email) ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- From now on every query has to name the tenant
+ALTER TABLE users ALTER COLUMN tenant_id DROP DEFAULT;
+ALTER TABLE linked_identities ALTER COLUMN tenant_id DROP DEFAULT;
+ALTER TABLE user_roles ALTER COLUMN tenant_id DROP DEFAULT;
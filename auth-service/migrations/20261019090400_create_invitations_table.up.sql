@@ -0,0 +1,14 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS invitations (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    tenant_id VARCHAR(64) NOT NULL,
+    email VARCHAR(255) NOT NULL,
+    -- Not a foreign key: a role that no longer exists is reported on accept
+    role VARCHAR(64) NULL,
+    invited_by VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP NOT NULL,
+    KEY invitations_tenant (tenant_id, created_at),
+    CONSTRAINT invitations_tenant_fk FOREIGN KEY (tenant_id)
+        REFERENCES tenants (id) ON DELETE CASCADE
+);
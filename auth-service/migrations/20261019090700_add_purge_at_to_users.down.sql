@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP INDEX users_purge_at_idx ON users;
+
+ALTER TABLE users DROP COLUMN purge_at;
@@ -0,0 +1,4 @@
+-- Add up migration script here
+ALTER TABLE users ADD COLUMN purge_at TIMESTAMP NULL;
+
+CREATE INDEX users_purge_at_idx ON users (status, purge_at);
@@ -0,0 +1,14 @@
+-- Add up migration script here
+-- Not tied to users, so the history outlives the accounts it's about.
+CREATE TABLE IF NOT EXISTS audit_events (
+    id BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    tenant_id VARCHAR(64) NOT NULL,
+    actor VARCHAR(255) NOT NULL,
+    event VARCHAR(32) NOT NULL,
+    outcome VARCHAR(16) NOT NULL,
+    detail VARCHAR(255) NULL,
+    ip VARCHAR(45) NULL,
+    user_agent VARCHAR(512) NULL,
+    created_at TIMESTAMP(3) NOT NULL,
+    INDEX audit_events_actor_idx (tenant_id, actor, created_at)
+);
@@ -0,0 +1,30 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS webhook_endpoints (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    tenant_id VARCHAR(64) NOT NULL,
+    url VARCHAR(2048) NOT NULL,
+    -- Needed as is to sign payloads, so it can't be hashed
+    secret VARCHAR(64) NOT NULL,
+    -- Comma-separated event types the endpoint is subscribed to
+    events VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP(3) NOT NULL,
+    KEY webhook_endpoints_tenant (tenant_id, created_at),
+    CONSTRAINT webhook_endpoints_tenant_fk FOREIGN KEY (tenant_id)
+        REFERENCES tenants (id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    endpoint_id CHAR(36) NOT NULL,
+    event VARCHAR(32) NOT NULL,
+    payload TEXT NOT NULL,
+    status VARCHAR(16) NOT NULL,
+    attempts INT UNSIGNED NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP(3) NOT NULL,
+    last_error VARCHAR(512) NULL,
+    created_at TIMESTAMP(3) NOT NULL,
+    INDEX webhook_deliveries_due_idx (status, next_attempt_at),
+    INDEX webhook_deliveries_endpoint_idx (endpoint_id, created_at),
+    CONSTRAINT webhook_deliveries_endpoint_fk FOREIGN KEY (endpoint_id)
+        REFERENCES webhook_endpoints (id) ON DELETE CASCADE
+);
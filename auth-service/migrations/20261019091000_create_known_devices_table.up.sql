@@ -0,0 +1,13 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS known_devices (
+    tenant_id VARCHAR(64) NOT NULL,
+    email VARCHAR(255) NOT NULL,
+    -- SHA-256 of the user agent and network prefix
+    fingerprint CHAR(64) NOT NULL,
+    user_agent VARCHAR(512) NULL,
+    ip VARCHAR(45) NULL,
+    last_seen_at TIMESTAMP(3) NOT NULL,
+    PRIMARY KEY (tenant_id, email, fingerprint),
+    CONSTRAINT known_devices_user_fk FOREIGN KEY (tenant_id, email)
+        REFERENCES users (tenant_id, email) ON DELETE CASCADE ON UPDATE CASCADE
+);
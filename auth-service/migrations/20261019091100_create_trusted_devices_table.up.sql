@@ -0,0 +1,13 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS trusted_devices (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    tenant_id VARCHAR(64) NOT NULL,
+    email VARCHAR(255) NOT NULL,
+    user_agent VARCHAR(512) NULL,
+    ip VARCHAR(45) NULL,
+    created_at TIMESTAMP(3) NOT NULL,
+    expires_at TIMESTAMP(3) NOT NULL,
+    INDEX trusted_devices_user_idx (tenant_id, email),
+    CONSTRAINT trusted_devices_user_fk FOREIGN KEY (tenant_id, email)
+        REFERENCES users (tenant_id, email) ON DELETE CASCADE ON UPDATE CASCADE
+);
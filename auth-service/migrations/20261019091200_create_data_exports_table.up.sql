@@ -0,0 +1,14 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS data_exports (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    tenant_id VARCHAR(64) NOT NULL,
+    email VARCHAR(255) NOT NULL,
+    status VARCHAR(16) NOT NULL,
+    -- The JSON archive, once it's ready
+    archive LONGTEXT NULL,
+    requested_at TIMESTAMP(3) NOT NULL,
+    completed_at TIMESTAMP(3) NULL,
+    INDEX data_exports_user_idx (tenant_id, email),
+    CONSTRAINT data_exports_user_fk FOREIGN KEY (tenant_id, email)
+        REFERENCES users (tenant_id, email) ON DELETE CASCADE ON UPDATE CASCADE
+);
@@ -0,0 +1,9 @@
+-- Add up migration script here
+ALTER TABLE users ADD COLUMN id CHAR(36) NULL FIRST;
+
+-- Existing accounts get an id of their own
+UPDATE users SET id = UUID() WHERE id IS NULL;
+
+ALTER TABLE users
+    MODIFY COLUMN id CHAR(36) NOT NULL,
+    ADD UNIQUE KEY users_id (id);
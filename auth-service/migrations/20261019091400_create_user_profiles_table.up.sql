@@ -0,0 +1,13 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS user_profiles (
+    tenant_id VARCHAR(64) NOT NULL,
+    email VARCHAR(255) NOT NULL,
+    display_name VARCHAR(100) NULL,
+    locale VARCHAR(35) NULL,
+    timezone VARCHAR(64) NULL,
+    avatar_url VARCHAR(2048) NULL,
+    updated_at TIMESTAMP(3) NOT NULL,
+    PRIMARY KEY (tenant_id, email),
+    CONSTRAINT user_profiles_user_fk FOREIGN KEY (tenant_id, email)
+        REFERENCES users (tenant_id, email) ON DELETE CASCADE ON UPDATE CASCADE
+);
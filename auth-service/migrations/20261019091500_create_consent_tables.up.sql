@@ -0,0 +1,20 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS policy_documents (
+    tenant_id VARCHAR(64) NOT NULL,
+    kind VARCHAR(32) NOT NULL,
+    version VARCHAR(32) NOT NULL,
+    url VARCHAR(2048) NOT NULL,
+    published_at TIMESTAMP(3) NOT NULL,
+    PRIMARY KEY (tenant_id, kind, version)
+);
+
+CREATE TABLE IF NOT EXISTS user_consents (
+    tenant_id VARCHAR(64) NOT NULL,
+    email VARCHAR(255) NOT NULL,
+    kind VARCHAR(32) NOT NULL,
+    version VARCHAR(32) NOT NULL,
+    accepted_at TIMESTAMP(3) NOT NULL,
+    PRIMARY KEY (tenant_id, email, kind, version),
+    CONSTRAINT user_consents_user_fk FOREIGN KEY (tenant_id, email)
+        REFERENCES users (tenant_id, email) ON DELETE CASCADE ON UPDATE CASCADE
+);
@@ -0,0 +1,7 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS user_group_members;
+DROP TABLE IF EXISTS user_groups;
+
+ALTER TABLE tenants
+    DROP INDEX tenants_scim_token_hash,
+    DROP COLUMN scim_token_hash;
@@ -0,0 +1,26 @@
+-- Add up migration script here
+ALTER TABLE tenants
+    ADD COLUMN scim_token_hash CHAR(64) NULL,
+    ADD UNIQUE KEY tenants_scim_token_hash (scim_token_hash);
+
+CREATE TABLE IF NOT EXISTS user_groups (
+    tenant_id VARCHAR(64) NOT NULL,
+    id CHAR(36) NOT NULL,
+    display_name VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP(3) NOT NULL,
+    PRIMARY KEY (tenant_id, id),
+    UNIQUE KEY user_groups_display_name (tenant_id, display_name),
+    CONSTRAINT user_groups_tenant_fk FOREIGN KEY (tenant_id)
+        REFERENCES tenants (id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS user_group_members (
+    tenant_id VARCHAR(64) NOT NULL,
+    group_id CHAR(36) NOT NULL,
+    email VARCHAR(255) NOT NULL,
+    PRIMARY KEY (tenant_id, group_id, email),
+    CONSTRAINT user_group_members_group_fk FOREIGN KEY (tenant_id, group_id)
+        REFERENCES user_groups (tenant_id, id) ON DELETE CASCADE,
+    CONSTRAINT user_group_members_user_fk FOREIGN KEY (tenant_id, email)
+        REFERENCES users (tenant_id, email) ON DELETE CASCADE ON UPDATE CASCADE
+);
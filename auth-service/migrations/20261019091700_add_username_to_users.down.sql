@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP INDEX users_username ON users;
+
+ALTER TABLE users DROP COLUMN username;
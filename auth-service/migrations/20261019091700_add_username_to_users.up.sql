@@ -0,0 +1,5 @@
+-- Add up migration script here
+-- The collation ignores case, so `Jane` and `jane` are the same username
+ALTER TABLE users
+    ADD COLUMN username VARCHAR(32) COLLATE utf8mb4_0900_ai_ci NULL AFTER email,
+    ADD UNIQUE KEY users_username (tenant_id, username);
@@ -0,0 +1,298 @@
+use std::{path::PathBuf, sync::Arc};
+
+use clap::{Parser, Subcommand};
+use color_eyre::eyre::{eyre, Result};
+use secrecy::{ExposeSecret, Secret};
+use sqlx::MySqlPool;
+use tokio::{fs::File, io::BufReader, sync::RwLock};
+
+use auth_service::{
+    configure_redis,
+    domain::{AccountStatus, BannedTokenStore, Email, IntoShared, Password, TenantId, User, UserQuery, UserStore, WebhookEventType},
+    get_mysql_pool,
+    services::{
+        data_stores::{
+            my_sql_user_store::MySqlUserStore,
+            my_sql_webhook_store::MySqlWebhookStore,
+            redis_banned_token_store::RedisBannedTokenStore,
+        },
+        password_hashing::is_supported_hash,
+        user_transfer::{export_users, import_users, TransferFormat},
+    },
+    utils::{
+        constants::{DATABASE_NAME, DATABASE_URL, REDIS_HOST_NAME},
+        parsable::Parsable,
+        passwords::{configure_password_hashing, configure_password_policy},
+        webhooks::{publish_webhook_event, user_event_data},
+    },
+    UserStoreType,
+    WebhookStoreType,
+};
+
+// Operations tool for managing users without going through the HTTP API.
+// Connects to the same MySQL database and Redis instance as the service.
+#[derive(Parser)]
+#[command(name = "auth-admin", about = "Manage auth-service users from the command line")]
+struct Cli {
+    /// Tenant the command operates on
+    #[arg(long, global = true, default_value = "default")]
+    tenant: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the pending database migrations
+    Migrate,
+    /// Create a user with a password
+    CreateUser {
+        #[arg(long)]
+        email: String,
+        #[arg(long, env = "AUTH_ADMIN_PASSWORD", hide_env_values = true)]
+        password: Secret<String>,
+        #[arg(long)]
+        requires_2fa: bool,
+    },
+    /// Create a user with a password hash from another system (Argon2, bcrypt
+    /// or PBKDF2). It's replaced with a current one on their first sign in.
+    ImportUser {
+        #[arg(long)]
+        email: String,
+        #[arg(long, env = "AUTH_ADMIN_PASSWORD_HASH", hide_env_values = true)]
+        password_hash: Secret<String>,
+        #[arg(long)]
+        requires_2fa: bool,
+    },
+    /// Create users from a JSON Lines or CSV file with `email`, `password_hash`
+    /// and `requires_2fa` fields. Users that already exist are skipped.
+    ImportUsers {
+        /// Reads from stdin when left out
+        #[arg(long)]
+        file: Option<PathBuf>,
+        /// jsonl or csv
+        #[arg(long, default_value = "jsonl")]
+        format: String,
+    },
+    /// Write every user to stdout as JSON Lines or CSV
+    ExportUsers {
+        /// jsonl or csv
+        #[arg(long, default_value = "jsonl")]
+        format: String,
+        #[arg(long)]
+        include_password_hashes: bool,
+    },
+    /// Set a new password and sign the user out everywhere
+    ResetPassword {
+        #[arg(long)]
+        email: String,
+        #[arg(long, env = "AUTH_ADMIN_PASSWORD", hide_env_values = true)]
+        password: Secret<String>,
+    },
+    /// Turn 2FA on or off for a user
+    #[command(name = "set-2fa")]
+    Set2fa {
+        #[arg(long)]
+        email: String,
+        #[arg(long, action = clap::ArgAction::Set)]
+        required: bool,
+    },
+    /// Set the account status (active, suspended, pending_verification, deleted)
+    SetStatus {
+        #[arg(long)]
+        email: String,
+        #[arg(long)]
+        status: String,
+        /// Recorded for suspended accounts only
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    /// Ban a single auth token
+    BanToken {
+        token: Secret<String>,
+    },
+    /// Ban every token issued to a user so far
+    RevokeSessions {
+        #[arg(long)]
+        email: String,
+    },
+    /// List users, ordered by email
+    ListUsers {
+        #[arg(long)]
+        search: Option<String>,
+        #[arg(long, default_value_t = 1)]
+        page: u32,
+        #[arg(long, default_value_t = 50)]
+        per_page: u32,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    color_eyre::install()?;
+    let cli = Cli::parse();
+    let tenant = TenantId::parse(&cli.tenant)?;
+
+    match cli.command {
+        Command::Migrate => {
+            sqlx::migrate!().run(&connect_database().await?).await?;
+            println!("Migrations applied");
+        }
+        Command::CreateUser { email, password, requires_2fa } => {
+            let user = User::new(Secret::new(email), password, requires_2fa)?;
+            if let Some(password) = &user.password {
+                check_password(&user.email, password).await?;
+            }
+            let email = user.email.as_ref().expose_secret().to_owned();
+
+            user_store().await?.add_user(&tenant, user).await?;
+            let webhook_store = webhook_store().await?;
+            publish_webhook_event(&webhook_store, &tenant, WebhookEventType::UserSignedUp, user_event_data(&email)).await;
+            println!("Created {}", email);
+        }
+        Command::ImportUser { email, password_hash, requires_2fa } => {
+            if !is_supported_hash(password_hash.expose_secret()) {
+                return Err(eyre!("Unsupported password hash"));
+            }
+            let mut user = User::new_passwordless(Email::parse(&email)?);
+            user.requires_2fa = requires_2fa;
+
+            let mut user_store = user_store().await?;
+            user_store.add_user(&tenant, user).await?;
+            user_store.set_password_hash(&tenant, &email, password_hash).await?;
+            let webhook_store = webhook_store().await?;
+            publish_webhook_event(&webhook_store, &tenant, WebhookEventType::UserSignedUp, user_event_data(&email)).await;
+            println!("Imported {}", email);
+        }
+        Command::ImportUsers { file, format } => {
+            let format = TransferFormat::parse(&format)?;
+            let user_store: UserStoreType = user_store().await?.into_shared();
+            let report = match file {
+                Some(file) => {
+                    let reader = BufReader::new(File::open(file).await?);
+                    import_users(&user_store, &tenant, format, reader).await?
+                }
+                None => import_users(&user_store, &tenant, format, BufReader::new(tokio::io::stdin())).await?,
+            };
+
+            let webhook_store = webhook_store().await?;
+            for email in &report.created {
+                publish_webhook_event(&webhook_store, &tenant, WebhookEventType::UserSignedUp, user_event_data(email)).await;
+            }
+            for error in &report.errors {
+                eprintln!("line {}: {}", error.line, error.error);
+            }
+            println!(
+                "{} created, {} already existed, {} failed",
+                report.created.len(),
+                report.skipped,
+                report.errors.len(),
+            );
+            if !report.errors.is_empty() {
+                return Err(eyre!("Some users could not be imported"));
+            }
+        }
+        Command::ExportUsers { format, include_password_hashes } => {
+            let format = TransferFormat::parse(&format)?;
+            let user_store: UserStoreType = user_store().await?.into_shared();
+            let exported = export_users(&user_store, &tenant, format, include_password_hashes, tokio::io::stdout()).await?;
+            eprintln!("{} users exported", exported);
+        }
+        Command::ResetPassword { email, password } => {
+            let password = Password::parse(password.expose_secret())?;
+            check_password(&Email::parse(&email)?, &password).await?;
+
+            user_store().await?.set_password(&tenant, &email, Some(password)).await?;
+            revoke_sessions(&tenant, &email).await?;
+            println!("Password reset for {}", email);
+        }
+        Command::Set2fa { email, required } => {
+            user_store().await?.set_requires_2fa(&tenant, &email, required).await?;
+            println!("2FA {} for {}", if required { "required" } else { "not required" }, email);
+        }
+        Command::SetStatus { email, status, reason } => {
+            let status = AccountStatus::parse(&status)?;
+
+            let mut user_store = user_store().await?;
+            let previous_status = user_store.get_user(&tenant, &email).await?.status;
+            user_store.set_status(&tenant, &email, status, reason).await?;
+            if !status.is_active() {
+                revoke_sessions(&tenant, &email).await?;
+            }
+            if previous_status == AccountStatus::PendingVerification && status == AccountStatus::Active {
+                let webhook_store = webhook_store().await?;
+                publish_webhook_event(&webhook_store, &tenant, WebhookEventType::UserVerified, user_event_data(&email)).await;
+            }
+            println!("{} is now {}", email, status.as_ref());
+        }
+        Command::BanToken { token } => {
+            if !banned_token_store().store_token(&token).await {
+                return Err(eyre!("Failed to ban token"));
+            }
+            println!("Token banned");
+        }
+        Command::RevokeSessions { email } => {
+            user_store().await?.get_user(&tenant, &email).await?;
+            revoke_sessions(&tenant, &email).await?;
+            println!("Sessions revoked for {}", email);
+        }
+        Command::ListUsers { search, page, per_page } => {
+            let query = UserQuery { search, page, per_page };
+            let page = user_store().await?.list_users(&tenant, &query).await?;
+
+            for user in &page.users {
+                println!(
+                    "{}\t2fa={}\tstatus={}\tpassword={}",
+                    user.email.as_ref().expose_secret(),
+                    user.requires_2fa,
+                    user.status.as_ref(),
+                    user.password.is_some(),
+                );
+            }
+            println!("{} of {} users", page.users.len(), page.total);
+        }
+    }
+
+    Ok(())
+}
+
+// Unlike the service, only `migrate` touches the schema.
+async fn connect_database() -> Result<MySqlPool> {
+    let connection_string = format!("{}/{}", DATABASE_URL.expose_secret(), DATABASE_NAME.as_str());
+    Ok(get_mysql_pool(Secret::new(connection_string)).await?)
+}
+
+async fn user_store() -> Result<MySqlUserStore> {
+    Ok(MySqlUserStore::new(connect_database().await?).with_hashing_params(configure_password_hashing()?))
+}
+
+// Events are only queued here; the service's dispatcher delivers them.
+async fn webhook_store() -> Result<WebhookStoreType> {
+    Ok(MySqlWebhookStore::new(connect_database().await?).into_shared())
+}
+
+fn banned_token_store() -> RedisBannedTokenStore {
+    let conn = configure_redis(REDIS_HOST_NAME.to_string());
+    RedisBannedTokenStore::new(Arc::new(RwLock::new(conn)))
+}
+
+// Passwords set here are held to the same policy as those users choose.
+async fn check_password(email: &Email, password: &Password) -> Result<()> {
+    let rejections = Arc::new(configure_password_policy()?).check(password, email).await;
+
+    match rejections.is_empty() {
+        true => Ok(()),
+        false => Err(eyre!(
+            "Password rejected: {}",
+            rejections.iter().map(|rejection| rejection.as_ref()).collect::<Vec<_>>().join(", "),
+        )),
+    }
+}
+
+async fn revoke_sessions(tenant: &TenantId, email: &str) -> Result<()> {
+    match banned_token_store().ban_sessions(tenant, email).await {
+        true => Ok(()),
+        false => Err(eyre!("Failed to revoke sessions")),
+    }
+}
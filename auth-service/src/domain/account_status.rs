@@ -0,0 +1,68 @@
+use color_eyre::eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::parsable::Parsable;
+
+// Only active accounts can sign in or use their tokens. The others keep
+// their data, so they can be restored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountStatus {
+    #[default]
+    Active,
+    Suspended,
+    PendingVerification,
+    Deleted,
+}
+
+impl AccountStatus {
+    pub fn is_active(&self) -> bool {
+        *self == Self::Active
+    }
+}
+
+impl Parsable for AccountStatus {
+    fn parse<S>(input: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        match input.as_ref() {
+            "active" => Ok(Self::Active),
+            "suspended" => Ok(Self::Suspended),
+            "pending_verification" => Ok(Self::PendingVerification),
+            "deleted" => Ok(Self::Deleted),
+            input => Err(eyre!("Invalid account status: {}", input)),
+        }
+    }
+}
+
+impl AsRef<str> for AccountStatus {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Active => "active",
+            Self::Suspended => "suspended",
+            Self::PendingVerification => "pending_verification",
+            Self::Deleted => "deleted",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_account_status() {
+        for status in [
+            AccountStatus::Active,
+            AccountStatus::Suspended,
+            AccountStatus::PendingVerification,
+            AccountStatus::Deleted,
+        ] {
+            assert_eq!(AccountStatus::parse(status.as_ref()).unwrap(), status);
+            assert_eq!(serde_json::to_value(status).unwrap(), status.as_ref());
+        }
+        assert!(AccountStatus::parse("disabled").is_err());
+        assert!(AccountStatus::default().is_active());
+    }
+}
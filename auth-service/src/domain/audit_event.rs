@@ -0,0 +1,146 @@
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{domain::TenantId, utils::parsable::Parsable};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEventKind {
+    Signup,
+    Login,
+    #[serde(rename = "verify_2fa")]
+    Verify2FA,
+    Logout,
+    DeleteAccount,
+    ImpersonationStart,
+    ImpersonationStop,
+}
+
+impl Parsable for AuditEventKind {
+    fn parse<S>(input: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        match input.as_ref() {
+            "signup" => Ok(Self::Signup),
+            "login" => Ok(Self::Login),
+            "verify_2fa" => Ok(Self::Verify2FA),
+            "logout" => Ok(Self::Logout),
+            "delete_account" => Ok(Self::DeleteAccount),
+            "impersonation_start" => Ok(Self::ImpersonationStart),
+            "impersonation_stop" => Ok(Self::ImpersonationStop),
+            input => Err(eyre!("Invalid audit event kind: {}", input)),
+        }
+    }
+}
+
+impl AsRef<str> for AuditEventKind {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Signup => "signup",
+            Self::Login => "login",
+            Self::Verify2FA => "verify_2fa",
+            Self::Logout => "logout",
+            Self::DeleteAccount => "delete_account",
+            Self::ImpersonationStart => "impersonation_start",
+            Self::ImpersonationStop => "impersonation_stop",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Success,
+    Failure,
+}
+
+impl Parsable for AuditOutcome {
+    fn parse<S>(input: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        match input.as_ref() {
+            "success" => Ok(Self::Success),
+            "failure" => Ok(Self::Failure),
+            input => Err(eyre!("Invalid audit outcome: {}", input)),
+        }
+    }
+}
+
+impl AsRef<str> for AuditOutcome {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Success => "success",
+            Self::Failure => "failure",
+        }
+    }
+}
+
+// Where a request came from, as far as the service can tell.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClientInfo {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+// One authentication event of a user, as recorded in the audit log. The
+// actor is the email the request was made for, which may not be an existing
+// user when the event failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEvent {
+    pub tenant: TenantId,
+    pub actor: String,
+    pub kind: AuditEventKind,
+    pub outcome: AuditOutcome,
+    // Why the event failed, or what's left to do after it
+    pub detail: Option<String>,
+    pub client: ClientInfo,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AuditEvent {
+    pub fn new(tenant: TenantId, actor: String, kind: AuditEventKind, outcome: AuditOutcome, client: ClientInfo) -> Self {
+        Self {
+            tenant,
+            actor,
+            kind,
+            outcome,
+            detail: None,
+            client,
+            created_at: Utc::now(),
+        }
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_audit_event_kind() {
+        for kind in [
+            AuditEventKind::Signup,
+            AuditEventKind::Login,
+            AuditEventKind::Verify2FA,
+            AuditEventKind::Logout,
+            AuditEventKind::DeleteAccount,
+            AuditEventKind::ImpersonationStart,
+            AuditEventKind::ImpersonationStop,
+        ] {
+            assert_eq!(AuditEventKind::parse(kind.as_ref()).unwrap(), kind);
+            assert_eq!(serde_json::to_value(kind).unwrap(), kind.as_ref());
+        }
+        assert!(AuditEventKind::parse("unknown").is_err());
+
+        for outcome in [AuditOutcome::Success, AuditOutcome::Failure] {
+            assert_eq!(AuditOutcome::parse(outcome.as_ref()).unwrap(), outcome);
+        }
+    }
+}
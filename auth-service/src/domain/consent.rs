@@ -0,0 +1,133 @@
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::parsable::Parsable;
+
+const MAX_VERSION_LENGTH: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyKind {
+    TermsOfService,
+    PrivacyPolicy,
+}
+
+impl Parsable for PolicyKind {
+    fn parse<S>(input: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        match input.as_ref() {
+            "terms_of_service" => Ok(Self::TermsOfService),
+            "privacy_policy" => Ok(Self::PrivacyPolicy),
+            input => Err(eyre!("Invalid policy kind: {}", input)),
+        }
+    }
+}
+
+impl AsRef<str> for PolicyKind {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::TermsOfService => "terms_of_service",
+            Self::PrivacyPolicy => "privacy_policy",
+        }
+    }
+}
+
+// A label picked by whoever publishes the document, like `2026-10` or `3.1`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PolicyVersion(String);
+
+impl Parsable for PolicyVersion {
+    fn parse<S>(input: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        let input = input.as_ref().trim();
+        let is_valid = !input.is_empty()
+            && input.len() <= MAX_VERSION_LENGTH
+            && input.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+
+        match is_valid {
+            true => Ok(Self(input.to_owned())),
+            false => Err(eyre!("Invalid policy version")),
+        }
+    }
+}
+
+impl AsRef<str> for PolicyVersion {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+// A published version of the terms of service or privacy policy. The latest
+// of each kind is the one users have to accept.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyDocument {
+    pub kind: PolicyKind,
+    pub version: PolicyVersion,
+    pub url: String,
+    pub published_at: DateTime<Utc>,
+}
+
+// A user's acceptance of one version of a policy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Consent {
+    pub kind: PolicyKind,
+    pub version: PolicyVersion,
+    pub accepted_at: DateTime<Utc>,
+}
+
+impl Consent {
+    pub fn new(kind: PolicyKind, version: PolicyVersion) -> Self {
+        Self { kind, version, accepted_at: Utc::now() }
+    }
+}
+
+// The current documents the user hasn't accepted yet.
+pub fn pending_policies(current: Vec<PolicyDocument>, consents: &[Consent]) -> Vec<PolicyDocument> {
+    current
+        .into_iter()
+        .filter(|document| {
+            !consents.iter().any(|consent| consent.kind == document.kind && consent.version == document.version)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(kind: PolicyKind, version: &str) -> PolicyDocument {
+        PolicyDocument {
+            kind,
+            version: PolicyVersion::parse(version).unwrap(),
+            url: "https://example.com/legal".to_owned(),
+            published_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn should_only_count_acceptance_of_the_current_version() {
+        let current = vec![document(PolicyKind::TermsOfService, "2"), document(PolicyKind::PrivacyPolicy, "1")];
+        let consents = vec![
+            Consent::new(PolicyKind::TermsOfService, PolicyVersion::parse("1").unwrap()),
+            Consent::new(PolicyKind::PrivacyPolicy, PolicyVersion::parse("1").unwrap()),
+        ];
+
+        let pending = pending_policies(current, &consents);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].kind, PolicyKind::TermsOfService);
+        assert_eq!(pending[0].version.as_ref(), "2");
+    }
+
+    #[test]
+    fn test_parse_policy_version() {
+        assert_eq!(PolicyVersion::parse(" 2026-10 ").unwrap().as_ref(), "2026-10");
+        assert!(PolicyVersion::parse("").is_err());
+        assert!(PolicyVersion::parse("v 2").is_err());
+        assert!(PolicyKind::parse("cookie_policy").is_err());
+    }
+}
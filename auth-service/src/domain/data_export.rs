@@ -0,0 +1,137 @@
+use chrono::{DateTime, Duration, Utc};
+use color_eyre::eyre::{eyre, Context, Result};
+use uuid::Uuid;
+
+use crate::utils::parsable::Parsable;
+
+// How long a ready export can be downloaded before a new one has to be made.
+pub const DATA_EXPORT_TTL_DAYS: i64 = 7;
+// A pending export older than this is assumed lost, e.g. to a restart, and
+// is started over.
+pub const DATA_EXPORT_TIMEOUT_MINUTES: i64 = 60;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DataExportId(String);
+
+impl Parsable for DataExportId {
+    fn parse<S>(id: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        let parse_id = Uuid::parse_str(id.as_ref()).wrap_err("Invalid data export id")?;
+
+        Ok(Self(parse_id.to_string()))
+    }
+}
+
+impl Default for DataExportId {
+    fn default() -> Self {
+        Self(Uuid::new_v4().to_string())
+    }
+}
+
+impl AsRef<str> for DataExportId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataExportStatus {
+    Pending,
+    Ready,
+    Failed,
+}
+
+impl Parsable for DataExportStatus {
+    fn parse<S>(input: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        match input.as_ref() {
+            "pending" => Ok(Self::Pending),
+            "ready" => Ok(Self::Ready),
+            "failed" => Ok(Self::Failed),
+            input => Err(eyre!("Invalid data export status: {}", input)),
+        }
+    }
+}
+
+impl AsRef<str> for DataExportStatus {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Pending => "pending",
+            Self::Ready => "ready",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+// A copy of everything held about a user, made in the background because it
+// can take a while for long histories. The archive is set once it's ready.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataExport {
+    pub id: DataExportId,
+    pub status: DataExportStatus,
+    // JSON, as served to the user
+    pub archive: Option<String>,
+    pub requested_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl DataExport {
+    pub fn new() -> Self {
+        Self {
+            id: DataExportId::default(),
+            status: DataExportStatus::Pending,
+            archive: None,
+            requested_at: Utc::now(),
+            completed_at: None,
+        }
+    }
+
+    pub fn complete(&mut self, archive: String) {
+        self.status = DataExportStatus::Ready;
+        self.archive = Some(archive);
+        self.completed_at = Some(Utc::now());
+    }
+
+    pub fn fail(&mut self) {
+        self.status = DataExportStatus::Failed;
+        self.completed_at = Some(Utc::now());
+    }
+
+    pub fn is_downloadable(&self, now: DateTime<Utc>) -> bool {
+        self.status == DataExportStatus::Ready
+            && self.completed_at.is_some_and(|completed_at| completed_at + Duration::days(DATA_EXPORT_TTL_DAYS) > now)
+    }
+
+    pub fn is_in_progress(&self, now: DateTime<Utc>) -> bool {
+        self.status == DataExportStatus::Pending
+            && self.requested_at + Duration::minutes(DATA_EXPORT_TIMEOUT_MINUTES) > now
+    }
+}
+
+impl Default for DataExport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_only_serve_ready_exports_for_a_week() {
+        let mut export = DataExport::new();
+        assert!(export.is_in_progress(Utc::now()));
+        assert!(!export.is_in_progress(Utc::now() + Duration::minutes(61)));
+        assert!(!export.is_downloadable(Utc::now()));
+
+        export.complete("{}".to_owned());
+        assert!(!export.is_in_progress(Utc::now()));
+        assert!(export.is_downloadable(Utc::now() + Duration::days(6)));
+        assert!(!export.is_downloadable(Utc::now() + Duration::days(7)));
+    }
+}
@@ -1,6 +1,7 @@
+use chrono::{DateTime, Utc};
 use tokio::sync::RwLock;
 use std::sync::Arc;
-use super::{user::User, Email};
+use super::{user::{User, UserId, UserPage, UserQuery}, username::Username, AccountStatus, AuditEvent, Consent, DataExport, DeviceFingerprint, DeviceSighting, Email, Group, GroupId, GroupName, KnownDevice, TrustedDevice, TrustedDeviceId, Invitation, InvitationId, LinkedIdentity, MagicLinkId, OidcState, Password, PendingOidcLogin, PolicyDocument, PolicyKind, PolicyVersion, Profile, Role, ScimToken, Tenant, TenantId, UserAuthorization, WebhookDelivery, WebhookEndpoint, WebhookEndpointId};
 use uuid::Uuid;
 use rand;
 use color_eyre::eyre::{eyre, Context, Report, Result};
@@ -17,22 +18,210 @@ pub enum UserStoreError {
     UserNotFound,
     #[error("Invalid credentials")]
     InvalidCredentials,
+    #[error("Identity not found")]
+    IdentityNotFound,
+    #[error("Identity already linked")]
+    IdentityAlreadyLinked,
+    #[error("Cannot remove the last login method")]
+    LastLoginMethod,
+    #[error("Role not found")]
+    RoleNotFound,
+    #[error("Unsupported password hash")]
+    UnsupportedPasswordHash,
+    #[error("Group already exists")]
+    GroupAlreadyExists,
+    #[error("Group not found")]
+    GroupNotFound,
+    #[error("Username already taken")]
+    UsernameTaken,
     #[error("Unexpected error: {0}")]
     UnexpectedError(Report),
 }
 
+// Users are scoped to a tenant: every lookup only sees the users of the
+// tenant it is given.
 #[async_trait::async_trait]
 pub trait UserStore {
-    async fn add_user(&mut self, user: User) -> Result<(), UserStoreError>;
-    async fn get_user(&self, email: &str) -> Result<User, UserStoreError>;
-    async fn validate_user(&self, email: &str, password: &str) -> Result<(), UserStoreError>;
-    async fn delete_user(&mut self, email: &str) -> Result<(), UserStoreError>;
+    // Fails with `UsernameTaken` when another user of the tenant has the
+    // same username, ignoring case.
+    async fn add_user(&mut self, tenant: &TenantId, user: User) -> Result<(), UserStoreError>;
+    // Adds a user imported from another system along with the hash it made
+    // of their password, so they're never left without one. Fails like
+    // `add_user` and `set_password_hash`.
+    async fn add_user_with_password_hash(&mut self, tenant: &TenantId, user: User, password_hash: Secret<String>) -> Result<(), UserStoreError>;
+    async fn get_user(&self, tenant: &TenantId, email: &str) -> Result<User, UserStoreError>;
+    async fn get_user_by_id(&self, tenant: &TenantId, id: &UserId) -> Result<User, UserStoreError>;
+    // Usernames match ignoring case.
+    async fn get_user_by_username(&self, tenant: &TenantId, username: &Username) -> Result<User, UserStoreError>;
+    async fn validate_user(&self, tenant: &TenantId, email: &str, password: &str) -> Result<(), UserStoreError>;
+    async fn delete_user(&mut self, tenant: &TenantId, email: &str) -> Result<(), UserStoreError>;
+    async fn link_identity(&mut self, tenant: &TenantId, email: &str, identity: LinkedIdentity) -> Result<(), UserStoreError>;
+    // Fails with `LastLoginMethod` when the user has no password and this is
+    // their only linked identity.
+    async fn unlink_identity(&mut self, tenant: &TenantId, email: &str, provider: &str) -> Result<(), UserStoreError>;
+    async fn get_linked_identities(&self, tenant: &TenantId, email: &str) -> Result<Vec<LinkedIdentity>, UserStoreError>;
+    async fn get_user_by_identity(&self, tenant: &TenantId, provider: &str, subject: &str) -> Result<User, UserStoreError>;
+    // Fails with `RoleNotFound` when the role doesn't exist. Assigning a role
+    // the user already has, or removing one they don't, is a no-op.
+    async fn assign_role(&mut self, tenant: &TenantId, email: &str, role: &Role) -> Result<(), UserStoreError>;
+    async fn remove_role(&mut self, tenant: &TenantId, email: &str, role: &Role) -> Result<(), UserStoreError>;
+    // The user's roles along with every permission those roles grant.
+    async fn get_authorization(&self, tenant: &TenantId, email: &str) -> Result<UserAuthorization, UserStoreError>;
+    // Users ordered by email, one page at a time.
+    async fn list_users(&self, tenant: &TenantId, query: &UserQuery) -> Result<UserPage, UserStoreError>;
+    // The reason is only kept for suspensions and cleared otherwise. Any
+    // scheduled purge is cancelled.
+    async fn set_status(
+        &mut self,
+        tenant: &TenantId,
+        email: &str,
+        status: AccountStatus,
+        reason: Option<String>,
+    ) -> Result<(), UserStoreError>;
+    // `None` removes the password, so the user can only sign in through
+    // their linked identities until a new one is set.
+    async fn set_password(&mut self, tenant: &TenantId, email: &str, password: Option<Password>) -> Result<(), UserStoreError>;
+    // Stores a hash made by another system as is, for users imported from it.
+    // Fails with `UnsupportedPasswordHash` when it can't be verified.
+    async fn set_password_hash(&mut self, tenant: &TenantId, email: &str, password_hash: Secret<String>) -> Result<(), UserStoreError>;
+    async fn set_requires_2fa(&mut self, tenant: &TenantId, email: &str, requires_2fa: bool) -> Result<(), UserStoreError>;
+    // `None` removes the username. Fails with `UsernameTaken` like `add_user`.
+    async fn set_username(&mut self, tenant: &TenantId, email: &str, username: Option<Username>) -> Result<(), UserStoreError>;
+    // Marks the account deleted and keeps it until `purge_at`.
+    async fn schedule_deletion(&mut self, tenant: &TenantId, email: &str, purge_at: DateTime<Utc>) -> Result<(), UserStoreError>;
+    // Deleted accounts of every tenant whose purge time has passed.
+    async fn list_users_due_for_purge(&self, now: DateTime<Utc>) -> Result<Vec<(TenantId, User)>, UserStoreError>;
+    // Fails with `GroupAlreadyExists` when the tenant has a group of the same
+    // name, ignoring case.
+    async fn add_group(&mut self, tenant: &TenantId, group: Group) -> Result<(), UserStoreError>;
+    async fn get_group(&self, tenant: &TenantId, id: &GroupId) -> Result<Group, UserStoreError>;
+    // Groups ordered by name.
+    async fn list_groups(&self, tenant: &TenantId) -> Result<Vec<Group>, UserStoreError>;
+    async fn rename_group(&mut self, tenant: &TenantId, id: &GroupId, display_name: GroupName) -> Result<(), UserStoreError>;
+    // Members leave the group with it; deleting a user also removes them
+    // from their groups.
+    async fn delete_group(&mut self, tenant: &TenantId, id: &GroupId) -> Result<(), UserStoreError>;
+    // Adding a member twice, or removing someone who isn't one, is a no-op.
+    async fn add_group_member(&mut self, tenant: &TenantId, id: &GroupId, email: &str) -> Result<(), UserStoreError>;
+    async fn remove_group_member(&mut self, tenant: &TenantId, id: &GroupId, email: &str) -> Result<(), UserStoreError>;
+    // Members ordered by email.
+    async fn list_group_members(&self, tenant: &TenantId, id: &GroupId) -> Result<Vec<User>, UserStoreError>;
+    async fn get_user_groups(&self, tenant: &TenantId, email: &str) -> Result<Vec<Group>, UserStoreError>;
+}
+
+#[async_trait::async_trait]
+pub trait TenantStore {
+    async fn add_tenant(&mut self, tenant: Tenant) -> Result<(), TenantStoreError>;
+    async fn get_tenant(&self, id: &TenantId) -> Result<Tenant, TenantStoreError>;
+    async fn list_tenants(&self) -> Result<Vec<Tenant>, TenantStoreError>;
+    // Replaces the tenant's previous SCIM token, if any.
+    async fn set_scim_token(&mut self, id: &TenantId, token: &ScimToken) -> Result<(), TenantStoreError>;
+    async fn get_tenant_by_scim_token(&self, token: &ScimToken) -> Result<Tenant, TenantStoreError>;
+}
+
+#[async_trait::async_trait]
+pub trait InvitationStore {
+    async fn add_invitation(&mut self, invitation: Invitation) -> Result<(), InvitationStoreError>;
+    async fn get_invitation(&self, id: &InvitationId) -> Result<Invitation, InvitationStoreError>;
+    // Every invitation of the tenant that hasn't been removed yet, including
+    // expired ones.
+    async fn list_invitations(&self, tenant: &TenantId) -> Result<Vec<Invitation>, InvitationStoreError>;
+    // Fails with `InvitationNotFound` if it was already removed, so an
+    // invitation can only be used once.
+    async fn remove_invitation(&mut self, id: &InvitationId) -> Result<(), InvitationStoreError>;
+}
+
+// Append-only: events can be recorded and read back, never changed.
+#[async_trait::async_trait]
+pub trait AuditLogStore {
+    async fn record_event(&mut self, event: AuditEvent) -> Result<(), AuditLogStoreError>;
+    // The actor's most recent events first.
+    async fn list_events(&self, tenant: &TenantId, actor: &str, limit: u32) -> Result<Vec<AuditEvent>, AuditLogStoreError>;
+}
+
+#[async_trait::async_trait]
+pub trait KnownDeviceStore {
+    // Records a successful sign-in from the device, telling whether the user
+    // was seen on it before.
+    async fn record_sign_in(&mut self, tenant: &TenantId, email: &str, device: KnownDevice) -> Result<DeviceSighting, KnownDeviceStoreError>;
+    async fn forget_device(&mut self, tenant: &TenantId, email: &str, fingerprint: &DeviceFingerprint) -> Result<(), KnownDeviceStoreError>;
+    // Most recently seen first.
+    async fn list_devices(&self, tenant: &TenantId, email: &str) -> Result<Vec<KnownDevice>, KnownDeviceStoreError>;
+}
+
+#[async_trait::async_trait]
+pub trait TrustedDeviceStore {
+    async fn add_device(&mut self, tenant: &TenantId, email: &str, device: TrustedDevice) -> Result<(), TrustedDeviceStoreError>;
+    async fn get_device(&self, tenant: &TenantId, email: &str, id: &TrustedDeviceId) -> Result<TrustedDevice, TrustedDeviceStoreError>;
+    // Newest first, including expired devices.
+    async fn list_devices(&self, tenant: &TenantId, email: &str) -> Result<Vec<TrustedDevice>, TrustedDeviceStoreError>;
+    async fn remove_device(&mut self, tenant: &TenantId, email: &str, id: &TrustedDeviceId) -> Result<(), TrustedDeviceStoreError>;
+    async fn remove_all_devices(&mut self, tenant: &TenantId, email: &str) -> Result<(), TrustedDeviceStoreError>;
+}
+
+#[async_trait::async_trait]
+pub trait ProfileStore {
+    // An empty profile when the user never set one.
+    async fn get_profile(&self, tenant: &TenantId, email: &str) -> Result<Profile, ProfileStoreError>;
+    async fn set_profile(&mut self, tenant: &TenantId, email: &str, profile: Profile) -> Result<(), ProfileStoreError>;
+}
+
+// Published policy documents, and which versions each user accepted.
+#[async_trait::async_trait]
+pub trait ConsentStore {
+    async fn add_policy(&mut self, tenant: &TenantId, document: PolicyDocument) -> Result<(), ConsentStoreError>;
+    // The latest version of each kind of policy, if any was published.
+    async fn get_current_policies(&self, tenant: &TenantId) -> Result<Vec<PolicyDocument>, ConsentStoreError>;
+    async fn get_policy(
+        &self,
+        tenant: &TenantId,
+        kind: PolicyKind,
+        version: &PolicyVersion,
+    ) -> Result<PolicyDocument, ConsentStoreError>;
+
+    // Accepting a version again keeps the first acceptance.
+    async fn add_consent(&mut self, tenant: &TenantId, email: &str, consent: Consent) -> Result<(), ConsentStoreError>;
+    // Most recently accepted first.
+    async fn list_consents(&self, tenant: &TenantId, email: &str) -> Result<Vec<Consent>, ConsentStoreError>;
+}
+
+// Only the latest export of each user is kept.
+#[async_trait::async_trait]
+pub trait DataExportStore {
+    // Replaces the user's previous export, if any.
+    async fn add_export(&mut self, tenant: &TenantId, email: &str, export: DataExport) -> Result<(), DataExportStoreError>;
+    async fn get_latest_export(&self, tenant: &TenantId, email: &str) -> Result<DataExport, DataExportStoreError>;
+    async fn update_export(&mut self, tenant: &TenantId, email: &str, export: DataExport) -> Result<(), DataExportStoreError>;
+}
+
+#[async_trait::async_trait]
+pub trait WebhookStore {
+    async fn add_endpoint(&mut self, endpoint: WebhookEndpoint) -> Result<(), WebhookStoreError>;
+    async fn get_endpoint(&self, id: &WebhookEndpointId) -> Result<WebhookEndpoint, WebhookStoreError>;
+    async fn list_endpoints(&self, tenant: &TenantId) -> Result<Vec<WebhookEndpoint>, WebhookStoreError>;
+    // Also removes the endpoint's deliveries, including pending ones.
+    async fn remove_endpoint(&mut self, id: &WebhookEndpointId) -> Result<(), WebhookStoreError>;
+
+    async fn add_delivery(&mut self, delivery: WebhookDelivery) -> Result<(), WebhookStoreError>;
+    // Saves the outcome of an attempt to deliver.
+    async fn update_delivery(&mut self, delivery: &WebhookDelivery) -> Result<(), WebhookStoreError>;
+    // Pending deliveries whose next attempt is due by `now`, oldest first.
+    async fn list_due_deliveries(&self, now: DateTime<Utc>, limit: u32) -> Result<Vec<WebhookDelivery>, WebhookStoreError>;
+    // The endpoint's most recent deliveries first.
+    async fn list_deliveries(&self, endpoint_id: &WebhookEndpointId, limit: u32) -> Result<Vec<WebhookDelivery>, WebhookStoreError>;
 }
 
 #[async_trait::async_trait]
 pub trait BannedTokenStore  {
     async fn store_token(&mut self, token: &Secret<String>) -> bool;
     async fn is_token_banned(&self, token: &Secret<String>) -> bool;
+    // Bans a single-use token for as long as it stays valid. Returns false if
+    // it already was, so it's only ever used once, or if banning it failed.
+    async fn ban_token_once(&mut self, token: &Secret<String>, ttl_seconds: u64) -> bool;
+    // Bans every token issued to the user up to now, signing them out
+    // everywhere. Tokens issued afterwards are unaffected.
+    async fn ban_sessions(&mut self, tenant: &TenantId, email: &str) -> bool;
+    async fn is_session_banned(&self, tenant: &TenantId, email: &str, issued_at: usize) -> bool;
 }
 
 pub trait IntoShared {
@@ -45,6 +234,7 @@ pub trait IntoShared {
 pub trait TwoFACodeStore {
     async fn add_code(
         &mut self,
+        tenant: &TenantId,
         email: Email,
         login_attempt_id: &LoginAttemptId,
         code: TwoFACode,
@@ -52,15 +242,40 @@ pub trait TwoFACodeStore {
 
     async fn remove_code(
         &mut self,
+        tenant: &TenantId,
         email: Email,
     ) -> Result<(), TwoFACodeStoreError>;
 
     async fn get_code(
         &self,
+        tenant: &TenantId,
         email: Email,
     ) -> Result<(LoginAttemptId, TwoFACode), TwoFACodeStoreError>;
 }
 
+#[async_trait::async_trait]
+pub trait OidcStateStore {
+    async fn add_state(
+        &mut self,
+        state: &OidcState,
+        pending_login: PendingOidcLogin,
+    ) -> Result<(), OidcStateStoreError>;
+
+    // States are single use, so reading one also removes it.
+    async fn take_state(
+        &mut self,
+        state: &OidcState,
+    ) -> Result<PendingOidcLogin, OidcStateStoreError>;
+}
+
+#[async_trait::async_trait]
+pub trait MagicLinkStore {
+    async fn add_link(&mut self, id: &MagicLinkId) -> Result<(), MagicLinkStoreError>;
+
+    // Links are single use, so consuming one also removes it.
+    async fn consume_link(&mut self, id: &MagicLinkId) -> Result<(), MagicLinkStoreError>;
+}
+
 impl PartialEq for UserStoreError {
     fn eq(&self, other: &Self) -> bool {
         matches!(
@@ -68,6 +283,147 @@ impl PartialEq for UserStoreError {
             (Self::UserAlreadyExists, Self::UserAlreadyExists)
                 | (Self::UserNotFound, Self::UserNotFound)
                 | (Self::InvalidCredentials, Self::InvalidCredentials)
+                | (Self::IdentityNotFound, Self::IdentityNotFound)
+                | (Self::IdentityAlreadyLinked, Self::IdentityAlreadyLinked)
+                | (Self::LastLoginMethod, Self::LastLoginMethod)
+                | (Self::RoleNotFound, Self::RoleNotFound)
+                | (Self::UnsupportedPasswordHash, Self::UnsupportedPasswordHash)
+                | (Self::GroupAlreadyExists, Self::GroupAlreadyExists)
+                | (Self::GroupNotFound, Self::GroupNotFound)
+                | (Self::UsernameTaken, Self::UsernameTaken)
+                | (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TenantStoreError {
+    #[error("Tenant already exists")]
+    TenantAlreadyExists,
+    #[error("Tenant not found")]
+    TenantNotFound,
+    #[error("Unexpected error: {0}")]
+    UnexpectedError(Report),
+}
+
+impl PartialEq for TenantStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::TenantAlreadyExists, Self::TenantAlreadyExists)
+                | (Self::TenantNotFound, Self::TenantNotFound)
+                | (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum InvitationStoreError {
+    #[error("Invitation not found")]
+    InvitationNotFound,
+    #[error("Unexpected error: {0}")]
+    UnexpectedError(Report),
+}
+
+impl PartialEq for InvitationStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!((self, other),
+            (Self::InvitationNotFound, Self::InvitationNotFound) | (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AuditLogStoreError {
+    #[error("Unexpected error: {0}")]
+    UnexpectedError(Report),
+}
+
+#[derive(Debug, Error)]
+pub enum KnownDeviceStoreError {
+    #[error("Unexpected error: {0}")]
+    UnexpectedError(Report),
+}
+
+#[derive(Debug, Error)]
+pub enum TrustedDeviceStoreError {
+    #[error("Trusted device not found")]
+    DeviceNotFound,
+    #[error("Unexpected error: {0}")]
+    UnexpectedError(Report),
+}
+
+#[derive(Debug, Error)]
+pub enum ProfileStoreError {
+    #[error("Unexpected error: {0}")]
+    UnexpectedError(Report),
+}
+
+#[derive(Debug, Error)]
+pub enum ConsentStoreError {
+    #[error("Policy version already published")]
+    PolicyAlreadyExists,
+    #[error("Policy not found")]
+    PolicyNotFound,
+    #[error("Unexpected error: {0}")]
+    UnexpectedError(Report),
+}
+
+impl PartialEq for ConsentStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::PolicyAlreadyExists, Self::PolicyAlreadyExists)
+                | (Self::PolicyNotFound, Self::PolicyNotFound)
+                | (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum DataExportStoreError {
+    #[error("Data export not found")]
+    ExportNotFound,
+    #[error("Unexpected error: {0}")]
+    UnexpectedError(Report),
+}
+
+impl PartialEq for DataExportStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::ExportNotFound, Self::ExportNotFound)
+                | (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
+
+impl PartialEq for TrustedDeviceStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::DeviceNotFound, Self::DeviceNotFound)
+                | (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum WebhookStoreError {
+    #[error("Webhook endpoint not found")]
+    EndpointNotFound,
+    #[error("Webhook delivery not found")]
+    DeliveryNotFound,
+    #[error("Unexpected error: {0}")]
+    UnexpectedError(Report),
+}
+
+impl PartialEq for WebhookStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::EndpointNotFound, Self::EndpointNotFound)
+                | (Self::DeliveryNotFound, Self::DeliveryNotFound)
                 | (Self::UnexpectedError(_), Self::UnexpectedError(_))
         )
     }
@@ -89,6 +445,37 @@ impl PartialEq for TwoFACodeStoreError {
     }
 }
 
+#[derive(Debug, Error)]
+pub enum OidcStateStoreError {
+    #[error("OIDC state not found")]
+    StateNotFound,
+    #[error("Unexpected error: {0}")]
+    UnexpectedError(Report),
+}
+
+impl PartialEq for OidcStateStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!((self, other),
+            (Self::StateNotFound, Self::StateNotFound) | (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum MagicLinkStoreError {
+    #[error("Magic link not found")]
+    LinkNotFound,
+    #[error("Unexpected error: {0}")]
+    UnexpectedError(Report),
+}
+
+impl PartialEq for MagicLinkStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!((self, other),
+            (Self::LinkNotFound, Self::LinkNotFound) | (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct LoginAttemptId(Secret<String>);
@@ -0,0 +1,194 @@
+use std::net::IpAddr;
+
+use chrono::{DateTime, Duration, Utc};
+use color_eyre::eyre::{eyre, Context, Result};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::utils::parsable::Parsable;
+
+use super::ClientInfo;
+
+// Identifies what a user signs in from by their user agent and network
+// rather than their exact address, so moving around within one network
+// (the same /24 for IPv4, /48 for IPv6) isn't seen as a new device.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DeviceFingerprint(String);
+
+impl DeviceFingerprint {
+    pub fn from_client(client: &ClientInfo) -> Self {
+        let prefix = client.ip
+            .as_deref()
+            .and_then(|ip| ip.parse::<IpAddr>().ok())
+            .map(network_prefix)
+            .unwrap_or_default();
+
+        let mut hasher = Sha256::new();
+        hasher.update(client.user_agent.as_deref().unwrap_or_default().as_bytes());
+        hasher.update(b"\n");
+        hasher.update(prefix.as_bytes());
+        Self(format!("{:x}", hasher.finalize()))
+    }
+}
+
+fn network_prefix(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, c, _] = ip.octets();
+            format!("{}.{}.{}.0/24", a, b, c)
+        }
+        IpAddr::V6(ip) => {
+            let [a, b, c, ..] = ip.segments();
+            format!("{:x}:{:x}:{:x}::/48", a, b, c)
+        }
+    }
+}
+
+impl Parsable for DeviceFingerprint {
+    fn parse<S>(input: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        let input = input.as_ref();
+        match input.len() == 64 && input.chars().all(|c| c.is_ascii_hexdigit()) {
+            true => Ok(Self(input.to_ascii_lowercase())),
+            false => Err(eyre!("Invalid device fingerprint")),
+        }
+    }
+}
+
+impl AsRef<str> for DeviceFingerprint {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+// A device a user has successfully signed in from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KnownDevice {
+    pub fingerprint: DeviceFingerprint,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub last_seen_at: DateTime<Utc>,
+}
+
+impl KnownDevice {
+    pub fn new(client: &ClientInfo) -> Self {
+        Self {
+            fingerprint: DeviceFingerprint::from_client(client),
+            user_agent: client.user_agent.clone(),
+            ip: client.ip.clone(),
+            last_seen_at: Utc::now(),
+        }
+    }
+}
+
+// How a sign-in's device relates to the ones the user was seen on before.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceSighting {
+    // The user's first device ever, e.g. right after signing up
+    First,
+    Known,
+    New,
+}
+
+// How long a browser the user chose to remember skips 2FA for.
+pub const TRUSTED_DEVICE_TTL_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TrustedDeviceId(String);
+
+impl Parsable for TrustedDeviceId {
+    fn parse<S>(id: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        let parse_id = Uuid::parse_str(id.as_ref()).wrap_err("Invalid trusted device id")?;
+
+        Ok(Self(parse_id.to_string()))
+    }
+}
+
+impl Default for TrustedDeviceId {
+    fn default() -> Self {
+        Self(Uuid::new_v4().to_string())
+    }
+}
+
+impl AsRef<str> for TrustedDeviceId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+// A browser the user asked to remember after completing 2FA on it. It holds
+// a cookie naming this device, and signing in with that cookie skips 2FA
+// until the device expires or is revoked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrustedDevice {
+    pub id: TrustedDeviceId,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl TrustedDevice {
+    pub fn new(client: &ClientInfo) -> Self {
+        let created_at = Utc::now();
+
+        Self {
+            id: TrustedDeviceId::default(),
+            user_agent: client.user_agent.clone(),
+            ip: client.ip.clone(),
+            created_at,
+            expires_at: created_at + Duration::days(TRUSTED_DEVICE_TTL_DAYS),
+        }
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at <= now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(ip: &str, user_agent: &str) -> ClientInfo {
+        ClientInfo { ip: Some(ip.to_owned()), user_agent: Some(user_agent.to_owned()) }
+    }
+
+    #[test]
+    fn should_ignore_address_changes_within_a_network() {
+        let fingerprint = DeviceFingerprint::from_client(&client("203.0.113.7", "Firefox"));
+
+        assert_eq!(DeviceFingerprint::from_client(&client("203.0.113.200", "Firefox")), fingerprint);
+        assert_ne!(DeviceFingerprint::from_client(&client("203.0.114.7", "Firefox")), fingerprint);
+        assert_ne!(DeviceFingerprint::from_client(&client("203.0.113.7", "Chrome")), fingerprint);
+    }
+
+    #[test]
+    fn should_use_48_bit_prefix_for_ipv6() {
+        let fingerprint = DeviceFingerprint::from_client(&client("2001:db8:1:2::1", "Firefox"));
+
+        assert_eq!(DeviceFingerprint::from_client(&client("2001:db8:1:ffff::9", "Firefox")), fingerprint);
+        assert_ne!(DeviceFingerprint::from_client(&client("2001:db8:2::1", "Firefox")), fingerprint);
+    }
+
+    #[test]
+    fn should_trust_device_for_30_days() {
+        let device = TrustedDevice::new(&client("203.0.113.7", "Firefox"));
+
+        assert!(!device.is_expired(Utc::now() + Duration::days(29)));
+        assert!(device.is_expired(Utc::now() + Duration::days(30)));
+        assert_eq!(TrustedDeviceId::parse(device.id.as_ref()).unwrap(), device.id);
+    }
+
+    #[test]
+    fn test_parse_device_fingerprint() {
+        let fingerprint = DeviceFingerprint::from_client(&ClientInfo::default());
+        assert_eq!(DeviceFingerprint::parse(fingerprint.as_ref()).unwrap(), fingerprint);
+        assert!(DeviceFingerprint::parse("not-a-fingerprint").is_err());
+    }
+}
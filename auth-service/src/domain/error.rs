@@ -1,18 +1,88 @@
 use color_eyre::eyre::Report;
 use thiserror::Error;
 
+use super::{AccountStatus, PasswordRejection};
+
 #[derive(Debug, Error)]
 pub enum AuthAPIError {
     #[error("User already exists")]
     UserAlreadyExists,
+    #[error("Username already taken")]
+    UsernameTaken,
     #[error("Invalid credentials")]
     InvalidCredentials,
+    // Says what's wrong with the request
+    #[error("{0}")]
+    InvalidInput(String),
     #[error("Incorrect credentials")]
     IncorrectCredentials,
     #[error("Missing token")]
     MissingToken,
     #[error("Invalid token")]
     InvalidToken,
+    #[error("Unknown identity provider")]
+    UnknownProvider,
+    #[error("Identity not found")]
+    IdentityNotFound,
+    #[error("Identity already linked")]
+    IdentityAlreadyLinked,
+    #[error("Cannot unlink the last login method")]
+    LastLoginMethod,
+    #[error("Insufficient permissions")]
+    Forbidden,
+    #[error("User not found")]
+    UserNotFound,
+    #[error("Role not found")]
+    RoleNotFound,
+    #[error("Tenant not found")]
+    TenantNotFound,
+    #[error("Tenant already exists")]
+    TenantAlreadyExists,
+    #[error("Invitation not found")]
+    InvitationNotFound,
+    #[error("Account disabled: {}", .0.as_ref())]
+    AccountDisabled(AccountStatus),
+    #[error("Webhook not found")]
+    WebhookNotFound,
+    #[error("Trusted device not found")]
+    TrustedDeviceNotFound,
+    #[error("Password rejected")]
+    WeakPassword(Vec<PasswordRejection>),
+    #[error("Policy version already published")]
+    PolicyAlreadyExists,
+    #[error("Policy acceptance required")]
+    PolicyAcceptanceRequired,
+    #[error("Not allowed while impersonating")]
+    ImpersonatedSession,
+    #[error("Re-authentication required")]
+    ReauthenticationRequired,
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+// Errors of the SCIM endpoints, which identity systems expect in the shape of
+// RFC 7644 rather than our usual one. Most carry a detail for whoever set up
+// the provisioning.
+#[derive(Debug, Error)]
+pub enum ScimError {
+    #[error("Invalid SCIM token")]
+    InvalidToken,
+    #[error("Resource not found")]
+    NotFound,
+    #[error("{0}")]
+    InvalidFilter(String),
+    #[error("{0}")]
+    InvalidPath(String),
+    #[error("{0}")]
+    InvalidSyntax(String),
+    #[error("{0}")]
+    InvalidValue(String),
+    #[error("{0}")]
+    NoTarget(String),
+    #[error("{0}")]
+    Mutability(String),
+    #[error("{0}")]
+    Uniqueness(String),
     #[error("Unexpected error")]
     UnexpectedError(#[source] Report),
-}
\ No newline at end of file
+}
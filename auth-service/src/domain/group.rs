@@ -0,0 +1,102 @@
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::{eyre, Context, Result};
+use uuid::Uuid;
+
+use crate::utils::parsable::Parsable;
+
+const MAX_GROUP_NAME_LENGTH: usize = 255;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GroupId(String);
+
+impl Parsable for GroupId {
+    fn parse<S>(id: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        let parse_id = Uuid::parse_str(id.as_ref()).wrap_err("Invalid group id")?;
+
+        Ok(Self(parse_id.to_string()))
+    }
+}
+
+impl Default for GroupId {
+    fn default() -> Self {
+        Self(Uuid::new_v4().to_string())
+    }
+}
+
+impl AsRef<str> for GroupId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+// Unique within a tenant, ignoring case.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupName(String);
+
+impl Parsable for GroupName {
+    fn parse<S>(input: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        let input = input.as_ref().trim();
+        if input.is_empty() || input.chars().count() > MAX_GROUP_NAME_LENGTH || input.chars().any(char::is_control) {
+            return Err(eyre!("Invalid group name"));
+        }
+
+        Ok(Self(input.to_owned()))
+    }
+}
+
+impl AsRef<str> for GroupName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl GroupName {
+    pub fn is_same_as(&self, other: &GroupName) -> bool {
+        self.0.to_lowercase() == other.0.to_lowercase()
+    }
+}
+
+// A set of users kept in sync by the tenant's identity system. Unlike roles,
+// groups don't grant any permission.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Group {
+    pub id: GroupId,
+    pub display_name: GroupName,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Group {
+    pub fn new(display_name: GroupName) -> Self {
+        Self {
+            id: GroupId::default(),
+            display_name,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_group_name() {
+        assert_eq!(GroupName::parse("  Engineering ").unwrap().as_ref(), "Engineering");
+        assert!(GroupName::parse("").is_err());
+        assert!(GroupName::parse("Line\nbreak").is_err());
+        assert!(GroupName::parse("a".repeat(MAX_GROUP_NAME_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_group_names_ignore_case() {
+        let name = GroupName::parse("Engineering").unwrap();
+        assert!(name.is_same_as(&GroupName::parse("engineering").unwrap()));
+        assert!(!name.is_same_as(&GroupName::parse("Sales").unwrap()));
+    }
+}
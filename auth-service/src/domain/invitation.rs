@@ -0,0 +1,92 @@
+use chrono::{DateTime, Duration, Utc};
+use color_eyre::eyre::{Context, Result};
+use uuid::Uuid;
+
+use crate::utils::parsable::Parsable;
+
+use super::{Email, Role, TenantId};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InvitationId(String);
+
+// An invitation for `email` to join `tenant`, optionally with a role. It is
+// removed once accepted, declined or revoked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invitation {
+    pub id: InvitationId,
+    pub tenant: TenantId,
+    pub email: Email,
+    pub role: Option<Role>,
+    pub invited_by: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl Parsable for InvitationId {
+    fn parse<S>(id: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        let parse_id = Uuid::parse_str(id.as_ref()).wrap_err("Invalid invitation id")?;
+
+        Ok(Self(parse_id.to_string()))
+    }
+}
+
+impl Default for InvitationId {
+    fn default() -> Self {
+        Self(Uuid::new_v4().to_string())
+    }
+}
+
+impl AsRef<str> for InvitationId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Invitation {
+    pub fn new(tenant: TenantId, email: Email, role: Option<Role>, invited_by: String, ttl: Duration) -> Self {
+        let created_at = Utc::now();
+
+        Self {
+            id: InvitationId::default(),
+            tenant,
+            email,
+            role,
+            invited_by,
+            created_at,
+            expires_at: created_at + ttl,
+        }
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at <= now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_invitation_id() {
+        let id = InvitationId::default();
+        assert_eq!(InvitationId::parse(id.as_ref()).unwrap(), id);
+        assert!(InvitationId::parse("not-a-uuid").is_err());
+    }
+
+    #[test]
+    fn test_invitation_expiry() {
+        let invitation = Invitation::new(
+            TenantId::default(),
+            Email::parse("test@example.com").unwrap(),
+            None,
+            "admin@example.com".to_owned(),
+            Duration::hours(1),
+        );
+
+        assert!(!invitation.is_expired(Utc::now()));
+        assert!(invitation.is_expired(Utc::now() + Duration::hours(2)));
+    }
+}
@@ -0,0 +1,22 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// An account at an external identity provider that can be used to sign in
+// as a `User`, identified by the provider's stable subject identifier.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinkedIdentity {
+    pub provider: String,
+    pub subject: String,
+    #[serde(rename = "linkedAt")]
+    pub linked_at: DateTime<Utc>,
+}
+
+impl LinkedIdentity {
+    pub fn new(provider: String, subject: String) -> Self {
+        Self {
+            provider,
+            subject,
+            linked_at: Utc::now(),
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use color_eyre::eyre::{eyre, Context, Result};
+use rand::{distributions::Alphanumeric, Rng};
+use secrecy::{ExposeSecret, Secret};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::utils::parsable::Parsable;
+
+// Identifies a single magic link so it can only be used once.
+#[derive(Debug, Clone)]
+pub struct MagicLinkId(Secret<String>);
+
+// Random value kept in a cookie of the browser that asked for the link. Only
+// its fingerprint goes into the link, so a link bound to one browser can't be
+// used from another.
+#[derive(Debug, Clone)]
+pub struct MagicLinkBinding(Secret<String>);
+
+const BINDING_LENGTH: usize = 32;
+
+impl Parsable for MagicLinkId {
+    fn parse<S>(id: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        let parse_id = Uuid::parse_str(id.as_ref()).wrap_err("Invalid magic link id")?;
+
+        Ok(Self(Secret::new(parse_id.to_string())))
+    }
+}
+
+impl Default for MagicLinkId {
+    fn default() -> Self {
+        Self(Secret::new(Uuid::new_v4().to_string()))
+    }
+}
+
+impl AsRef<Secret<String>> for MagicLinkId {
+    fn as_ref(&self) -> &Secret<String> {
+        &self.0
+    }
+}
+
+impl PartialEq for MagicLinkId {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.expose_secret() == other.0.expose_secret()
+    }
+}
+
+impl MagicLinkBinding {
+    pub fn fingerprint(&self) -> String {
+        let digest = Sha256::digest(self.0.expose_secret().as_bytes());
+        URL_SAFE_NO_PAD.encode(digest)
+    }
+}
+
+impl Parsable for MagicLinkBinding {
+    fn parse<S>(input: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        let input = input.as_ref();
+        if input.len() != BINDING_LENGTH || !input.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(eyre!("Invalid magic link binding"));
+        }
+
+        Ok(Self(Secret::new(input.to_string())))
+    }
+}
+
+impl Default for MagicLinkBinding {
+    fn default() -> Self {
+        let binding = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(BINDING_LENGTH)
+            .map(char::from)
+            .collect();
+
+        Self(Secret::new(binding))
+    }
+}
+
+impl AsRef<Secret<String>> for MagicLinkBinding {
+    fn as_ref(&self) -> &Secret<String> {
+        &self.0
+    }
+}
+
+impl PartialEq for MagicLinkBinding {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.expose_secret() == other.0.expose_secret()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_magic_link_id() {
+        let id = MagicLinkId::default();
+        assert_eq!(MagicLinkId::parse(id.as_ref().expose_secret()).unwrap(), id);
+        assert!(MagicLinkId::parse("not-a-uuid").is_err());
+    }
+
+    #[test]
+    fn test_parse_magic_link_binding() {
+        let binding = MagicLinkBinding::default();
+        assert_eq!(MagicLinkBinding::parse(binding.as_ref().expose_secret()).unwrap(), binding);
+        assert!(MagicLinkBinding::parse("short").is_err());
+        assert!(MagicLinkBinding::parse("-".repeat(BINDING_LENGTH)).is_err());
+    }
+
+    #[test]
+    fn test_binding_fingerprint() {
+        let binding = MagicLinkBinding::default();
+        assert_eq!(binding.fingerprint(), binding.clone().fingerprint());
+        assert_ne!(binding.fingerprint(), MagicLinkBinding::default().fingerprint());
+        assert_ne!(&binding.fingerprint(), binding.as_ref().expose_secret());
+    }
+}
@@ -1,13 +1,45 @@
 mod user;
 mod email;
+mod username;
 mod password;
 mod error;
 mod data_stores;
 mod email_client;
+mod oidc;
+mod linked_identity;
+mod saml;
+mod magic_link;
+mod role;
+mod tenant;
+mod invitation;
+mod account_status;
+mod audit_event;
+mod webhook;
+mod device;
+mod data_export;
+mod profile;
+mod consent;
+mod group;
 
 pub use user::*;
 pub use email::*;
+pub use username::*;
 pub use error::*;
 pub use password::*;
 pub use data_stores::*;
-pub use email_client::*;
\ No newline at end of file
+pub use email_client::*;
+pub use oidc::*;
+pub use linked_identity::*;
+pub use saml::*;
+pub use magic_link::*;
+pub use role::*;
+pub use tenant::*;
+pub use invitation::*;
+pub use account_status::*;
+pub use audit_event::*;
+pub use webhook::*;
+pub use device::*;
+pub use data_export::*;
+pub use profile::*;
+pub use consent::*;
+pub use group::*;
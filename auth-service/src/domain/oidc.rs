@@ -0,0 +1,215 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use color_eyre::eyre::{eyre, Result};
+use rand::{distributions::Alphanumeric, Rng};
+use secrecy::{ExposeSecret, Secret};
+use sha2::{Digest, Sha256};
+
+use crate::utils::parsable::Parsable;
+
+use super::Email;
+
+#[derive(Debug, Clone)]
+pub struct OidcProviderConfig {
+    pub name: String,
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: Secret<String>,
+    pub redirect_uri: String,
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OidcState(Secret<String>);
+
+#[derive(Debug, Clone)]
+pub struct OidcNonce(Secret<String>);
+
+#[derive(Debug, Clone)]
+pub struct PkceVerifier(Secret<String>);
+
+// Everything we need to remember between redirecting the user to the
+// provider and receiving the callback.
+#[derive(Debug, Clone)]
+pub struct PendingOidcLogin {
+    pub provider: String,
+    pub nonce: OidcNonce,
+    pub pkce_verifier: PkceVerifier,
+    // Set when a signed-in user is linking the provider to their account
+    // rather than signing in with it.
+    pub link_email: Option<Email>,
+}
+
+const STATE_LENGTH: usize = 32;
+const PKCE_VERIFIER_LENGTH: usize = 64;
+
+fn random_token(length: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(length)
+        .map(char::from)
+        .collect()
+}
+
+fn is_url_safe(input: &str) -> bool {
+    input
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~'))
+}
+
+impl Parsable for OidcState {
+    fn parse<S>(input: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        let input = input.as_ref();
+        if input.is_empty() || !is_url_safe(input) {
+            return Err(eyre!("Invalid state"));
+        }
+
+        Ok(Self(Secret::new(input.to_string())))
+    }
+}
+
+impl Default for OidcState {
+    fn default() -> Self {
+        Self(Secret::new(random_token(STATE_LENGTH)))
+    }
+}
+
+impl AsRef<Secret<String>> for OidcState {
+    fn as_ref(&self) -> &Secret<String> {
+        &self.0
+    }
+}
+
+impl OidcState {
+    // Kept in a cookie of the browser that started the flow, so the callback
+    // only completes in that browser.
+    pub fn fingerprint(&self) -> String {
+        let digest = Sha256::digest(self.0.expose_secret().as_bytes());
+        URL_SAFE_NO_PAD.encode(digest)
+    }
+}
+
+impl PartialEq for OidcState {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.expose_secret() == other.0.expose_secret()
+    }
+}
+
+impl Parsable for OidcNonce {
+    fn parse<S>(input: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        let input = input.as_ref();
+        if input.is_empty() || !is_url_safe(input) {
+            return Err(eyre!("Invalid nonce"));
+        }
+
+        Ok(Self(Secret::new(input.to_string())))
+    }
+}
+
+impl Default for OidcNonce {
+    fn default() -> Self {
+        Self(Secret::new(random_token(STATE_LENGTH)))
+    }
+}
+
+impl AsRef<Secret<String>> for OidcNonce {
+    fn as_ref(&self) -> &Secret<String> {
+        &self.0
+    }
+}
+
+impl PartialEq for OidcNonce {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.expose_secret() == other.0.expose_secret()
+    }
+}
+
+impl PkceVerifier {
+    // S256 code challenge as defined in RFC 7636
+    pub fn challenge(&self) -> String {
+        let digest = Sha256::digest(self.0.expose_secret().as_bytes());
+        URL_SAFE_NO_PAD.encode(digest)
+    }
+}
+
+impl Parsable for PkceVerifier {
+    fn parse<S>(input: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        let input = input.as_ref();
+        if !(43..=128).contains(&input.len()) || !is_url_safe(input) {
+            return Err(eyre!("Invalid PKCE verifier"));
+        }
+
+        Ok(Self(Secret::new(input.to_string())))
+    }
+}
+
+impl Default for PkceVerifier {
+    fn default() -> Self {
+        Self(Secret::new(random_token(PKCE_VERIFIER_LENGTH)))
+    }
+}
+
+impl AsRef<Secret<String>> for PkceVerifier {
+    fn as_ref(&self) -> &Secret<String> {
+        &self.0
+    }
+}
+
+impl PartialEq for PkceVerifier {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.expose_secret() == other.0.expose_secret()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_state() {
+        assert!(OidcState::parse("abc-DEF_123.~").is_ok());
+        assert!(OidcState::parse("").is_err());
+        assert!(OidcState::parse("with space").is_err());
+    }
+
+    #[test]
+    fn test_default_state_is_parsable() {
+        let state = OidcState::default();
+        assert_eq!(OidcState::parse(state.as_ref().expose_secret()).unwrap(), state);
+    }
+
+    #[test]
+    fn test_state_fingerprint() {
+        let state = OidcState::default();
+        assert_eq!(state.fingerprint(), state.clone().fingerprint());
+        assert_ne!(state.fingerprint(), OidcState::default().fingerprint());
+        assert_ne!(&state.fingerprint(), state.as_ref().expose_secret());
+    }
+
+    #[test]
+    fn test_parse_pkce_verifier() {
+        assert!(PkceVerifier::parse("short").is_err());
+        assert!(PkceVerifier::parse("a".repeat(129)).is_err());
+        assert!(PkceVerifier::parse("a".repeat(43)).is_ok());
+    }
+
+    #[test]
+    fn test_default_pkce_verifier_is_parsable() {
+        let verifier = PkceVerifier::default();
+        assert!(PkceVerifier::parse(verifier.as_ref().expose_secret()).is_ok());
+    }
+
+    #[test]
+    fn test_pkce_challenge() {
+        let verifier = PkceVerifier::parse("dBjftJeZ4CVP-mJ92IAEWbtAmZpcRHvM6uo6P2sqb7s").unwrap();
+        assert_eq!(verifier.challenge(), "4wcxy7LmN_kbsP6o_onBE7OhealiaxdQ9DXd4qGXN64");
+    }
+}
@@ -33,6 +33,28 @@ impl PartialEq for Password { // New!
     }
 }
 
+// Why the password policy turned down a password a user was choosing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordRejection {
+    TooShort,
+    TooWeak,
+    Common,
+    ContainsEmail,
+    Breached,
+}
+
+impl AsRef<str> for PasswordRejection {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::TooShort => "Password is too short",
+            Self::TooWeak => "Password is too easy to guess",
+            Self::Common => "Password is too common",
+            Self::ContainsEmail => "Password contains the email address",
+            Self::Breached => "Password has appeared in a data breach",
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,191 @@
+use color_eyre::eyre::{eyre, Result};
+use reqwest::Url;
+
+use crate::utils::parsable::Parsable;
+
+const MAX_DISPLAY_NAME_LENGTH: usize = 100;
+const MAX_AVATAR_URL_LENGTH: usize = 2048;
+// Top-level areas of the IANA time zone database
+const TIMEZONE_AREAS: [&str; 11] = [
+    "Africa", "America", "Antarctica", "Arctic", "Asia", "Atlantic", "Australia", "Europe", "Indian", "Pacific", "Etc",
+];
+
+// What the user chose to be shown as; all of it is optional.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Profile {
+    pub display_name: Option<DisplayName>,
+    pub locale: Option<Locale>,
+    pub timezone: Option<TimeZone>,
+    pub avatar_url: Option<AvatarUrl>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisplayName(String);
+
+impl Parsable for DisplayName {
+    fn parse<S>(input: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        let input = input.as_ref().trim();
+        if input.is_empty() || input.chars().count() > MAX_DISPLAY_NAME_LENGTH || input.chars().any(char::is_control) {
+            return Err(eyre!("Invalid display name"));
+        }
+
+        Ok(Self(input.to_owned()))
+    }
+}
+
+impl AsRef<str> for DisplayName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+// A BCP 47 language tag like `en` or `pt-BR`, in its usual casing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Locale(String);
+
+impl Parsable for Locale {
+    fn parse<S>(input: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        let mut subtags = input.as_ref().trim().split(['-', '_']);
+        let language = subtags.next().unwrap_or_default();
+        if !(2..=3).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(eyre!("Invalid locale"));
+        }
+
+        let mut locale = language.to_ascii_lowercase();
+        for subtag in subtags {
+            if !(2..=8).contains(&subtag.len()) || !subtag.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(eyre!("Invalid locale"));
+            }
+
+            locale.push('-');
+            match subtag.len() {
+                // Region, like `BR`
+                2 => locale.push_str(&subtag.to_ascii_uppercase()),
+                // Script, like `Hant`
+                4 if subtag.chars().all(|c| c.is_ascii_alphabetic()) => {
+                    locale.push_str(&subtag[..1].to_ascii_uppercase());
+                    locale.push_str(&subtag[1..].to_ascii_lowercase());
+                }
+                _ => locale.push_str(&subtag.to_ascii_lowercase()),
+            }
+        }
+
+        Ok(Self(locale))
+    }
+}
+
+impl AsRef<str> for Locale {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+// An IANA time zone name like `Europe/Paris`, or `UTC`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeZone(String);
+
+impl Parsable for TimeZone {
+    fn parse<S>(input: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        let input = input.as_ref().trim();
+        if input == "UTC" {
+            return Ok(Self(input.to_owned()));
+        }
+
+        let mut parts = input.split('/');
+        let is_valid = parts.next().is_some_and(|area| TIMEZONE_AREAS.contains(&area))
+            && input.len() <= 64
+            && parts.clone().count() > 0
+            && parts.all(|part| {
+                !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '+'))
+            });
+
+        match is_valid {
+            true => Ok(Self(input.to_owned())),
+            false => Err(eyre!("Invalid time zone")),
+        }
+    }
+}
+
+impl AsRef<str> for TimeZone {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+// Avatars are loaded by browsers, so only HTTPS links are accepted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AvatarUrl(String);
+
+impl Parsable for AvatarUrl {
+    fn parse<S>(input: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        let input = input.as_ref().trim();
+        let url = Url::parse(input).map_err(|_| eyre!("Invalid avatar URL"))?;
+        if url.scheme() != "https" || url.host_str().is_none() || input.len() > MAX_AVATAR_URL_LENGTH {
+            return Err(eyre!("Invalid avatar URL"));
+        }
+
+        Ok(Self(url.to_string()))
+    }
+}
+
+impl AsRef<str> for AvatarUrl {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_display_name() {
+        assert_eq!(DisplayName::parse("  Jane Doe ").unwrap().as_ref(), "Jane Doe");
+        assert!(DisplayName::parse("   ").is_err());
+        assert!(DisplayName::parse("Jane\nDoe").is_err());
+        assert!(DisplayName::parse("x".repeat(101)).is_err());
+    }
+
+    #[test]
+    fn test_parse_locale() {
+        assert_eq!(Locale::parse("en").unwrap().as_ref(), "en");
+        assert_eq!(Locale::parse("pt_br").unwrap().as_ref(), "pt-BR");
+        assert_eq!(Locale::parse("ZH-hant-tw").unwrap().as_ref(), "zh-Hant-TW");
+        assert!(Locale::parse("english").is_err());
+        assert!(Locale::parse("en-").is_err());
+    }
+
+    #[test]
+    fn test_parse_timezone() {
+        assert!(TimeZone::parse("UTC").is_ok());
+        assert!(TimeZone::parse("Europe/Paris").is_ok());
+        assert!(TimeZone::parse("America/Argentina/Buenos_Aires").is_ok());
+        assert!(TimeZone::parse("Etc/GMT+5").is_ok());
+        assert!(TimeZone::parse("Europe").is_err());
+        assert!(TimeZone::parse("Mars/Olympus_Mons").is_err());
+        assert!(TimeZone::parse("Europe/../etc").is_err());
+    }
+
+    #[test]
+    fn test_parse_avatar_url() {
+        assert_eq!(
+            AvatarUrl::parse("https://cdn.example.com/a.png").unwrap().as_ref(),
+            "https://cdn.example.com/a.png"
+        );
+        assert!(AvatarUrl::parse("http://cdn.example.com/a.png").is_err());
+        assert!(AvatarUrl::parse("javascript:alert(1)").is_err());
+        assert!(AvatarUrl::parse("not a url").is_err());
+    }
+}
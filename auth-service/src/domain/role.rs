@@ -0,0 +1,105 @@
+use color_eyre::eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::parsable::Parsable;
+
+pub const ADMIN_ROLE: &str = "admin";
+pub const ADMIN_PERMISSIONS: [&str; 3] = ["users:read", "users:write", "roles:manage"];
+
+const MAX_NAME_LENGTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Role(String);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Permission(String);
+
+// What a user is allowed to do, as carried in their auth token.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UserAuthorization {
+    pub roles: Vec<Role>,
+    pub permissions: Vec<Permission>,
+}
+
+fn is_valid_name(name: &str, allowed: impl Fn(char) -> bool) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_NAME_LENGTH
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || allowed(c))
+}
+
+impl Role {
+    pub fn admin() -> Self {
+        Self(ADMIN_ROLE.to_owned())
+    }
+}
+
+impl Parsable for Role {
+    fn parse<S>(input: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        let input = input.as_ref();
+        if !is_valid_name(input, |c| c == '-' || c == '_') {
+            return Err(eyre!("Invalid role: {}", input));
+        }
+
+        Ok(Self(input.to_owned()))
+    }
+}
+
+impl AsRef<str> for Role {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+// Permissions are namespaced by resource, e.g. `users:read`.
+impl Parsable for Permission {
+    fn parse<S>(input: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        let input = input.as_ref();
+        if !is_valid_name(input, |c| c == '-' || c == '_' || c == ':') {
+            return Err(eyre!("Invalid permission: {}", input));
+        }
+
+        Ok(Self(input.to_owned()))
+    }
+}
+
+impl AsRef<str> for Permission {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl UserAuthorization {
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r.as_ref() == role)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_role() {
+        assert_eq!(Role::parse(ADMIN_ROLE).unwrap(), Role::admin());
+        assert!(Role::parse("support_agent-2").is_ok());
+        assert!(Role::parse("").is_err());
+        assert!(Role::parse("Admin").is_err());
+        assert!(Role::parse("users:read").is_err());
+        assert!(Role::parse("a".repeat(MAX_NAME_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_parse_permission() {
+        for permission in ADMIN_PERMISSIONS {
+            assert!(Permission::parse(permission).is_ok());
+        }
+        assert!(Permission::parse("users read").is_err());
+        assert!(Permission::parse("").is_err());
+    }
+}
@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone)]
+pub struct SamlServiceProviderConfig {
+    pub entity_id: String,
+    pub acs_url: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct SamlIdpConfig {
+    pub name: String,
+    pub entity_id: String,
+    // PEM encoded signing certificates. More than one can be configured so
+    // the IdP can roll its key over without downtime.
+    pub certificates: Vec<String>,
+    // Attribute holding the user's email. The NameID is used when unset.
+    pub email_attribute: Option<String>,
+}
+
+// The parts of a validated assertion we map onto our own users.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SamlAssertion {
+    pub id: String,
+    pub idp: String,
+    pub name_id: String,
+    pub email: String,
+    // The last moment it would still be accepted, clock skew included, so
+    // it's only remembered as used for as long as it could be replayed
+    pub valid_until: DateTime<Utc>,
+}
@@ -0,0 +1,131 @@
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::{eyre, Result};
+use rand::{distributions::Alphanumeric, Rng};
+use secrecy::{ExposeSecret, Secret};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::utils::parsable::Parsable;
+
+// Tenant that every account created before tenants existed belongs to, and
+// the one used by the routes that don't name a tenant.
+pub const DEFAULT_TENANT: &str = "default";
+
+const MAX_TENANT_ID_LENGTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TenantId(String);
+
+// An organization with its own, separate set of users. The same email can
+// sign up once in each tenant.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tenant {
+    pub id: TenantId,
+    pub name: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl TenantId {
+    pub fn is_default(&self) -> bool {
+        self.0 == DEFAULT_TENANT
+    }
+}
+
+impl Parsable for TenantId {
+    fn parse<S>(input: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        let input = input.as_ref();
+        let is_valid = !input.is_empty()
+            && input.len() <= MAX_TENANT_ID_LENGTH
+            && !input.starts_with('-')
+            && input.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+
+        if !is_valid {
+            return Err(eyre!("Invalid tenant id: {}", input));
+        }
+
+        Ok(Self(input.to_owned()))
+    }
+}
+
+impl Default for TenantId {
+    fn default() -> Self {
+        Self(DEFAULT_TENANT.to_owned())
+    }
+}
+
+impl AsRef<str> for TenantId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Tenant {
+    pub fn new(id: TenantId, name: String) -> Self {
+        Self {
+            id,
+            name,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+// Authenticates the tenant's identity system on the SCIM endpoints. Only its
+// hash is stored, so a lost token can only be replaced.
+#[derive(Debug, Clone)]
+pub struct ScimToken(Secret<String>);
+
+impl ScimToken {
+    pub fn new(token: Secret<String>) -> Self {
+        Self(token)
+    }
+
+    pub fn generate() -> Self {
+        let token: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(40)
+            .map(char::from)
+            .collect();
+
+        Self(Secret::new(format!("scim_{}", token)))
+    }
+
+    pub fn hash(&self) -> String {
+        hex::encode(Sha256::digest(self.0.expose_secret().as_bytes()))
+    }
+}
+
+impl AsRef<Secret<String>> for ScimToken {
+    fn as_ref(&self) -> &Secret<String> {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tenant_id() {
+        assert_eq!(TenantId::parse(DEFAULT_TENANT).unwrap(), TenantId::default());
+        assert!(TenantId::default().is_default());
+        assert!(TenantId::parse("acme-corp2").is_ok());
+        assert!(TenantId::parse("").is_err());
+        assert!(TenantId::parse("-acme").is_err());
+        assert!(TenantId::parse("Acme").is_err());
+        assert!(TenantId::parse("acme/corp").is_err());
+        assert!(TenantId::parse("a".repeat(MAX_TENANT_ID_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_scim_token_hash() {
+        let token = ScimToken::generate();
+        assert!(token.as_ref().expose_secret().starts_with("scim_"));
+        assert_eq!(token.hash(), ScimToken::new(token.as_ref().clone()).hash());
+        assert_ne!(token.hash(), ScimToken::generate().hash());
+        assert_eq!(token.hash().len(), 64);
+    }
+}
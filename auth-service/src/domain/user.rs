@@ -1,19 +1,75 @@
-use color_eyre::eyre::Result;
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::{Context, Result};
 use secrecy::{ExposeSecret, Secret};
+use uuid::Uuid;
 
 use crate::{
     utils::parsable::Parsable,
     domain::{
+        account_status::AccountStatus,
         email::Email,
         password::Password,
+        username::Username,
     },
 };
 
+// Stays the same when the user's email changes, unlike their email.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UserId(String);
+
+impl Parsable for UserId {
+    fn parse<S>(id: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        let parse_id = Uuid::parse_str(id.as_ref()).wrap_err("Invalid user id")?;
+
+        Ok(Self(parse_id.to_string()))
+    }
+}
+
+impl Default for UserId {
+    fn default() -> Self {
+        Self(Uuid::new_v4().to_string())
+    }
+}
+
+impl AsRef<str> for UserId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct User {
+    pub id: UserId,
     pub email: Email,
-    pub password: Password,
+    // Lets the user sign in with it instead of their email
+    pub username: Option<Username>,
+    pub password: Option<Password>,
     pub requires_2fa: bool,
+    pub status: AccountStatus,
+    // Why the account was suspended, for admins. Never shown to the user.
+    pub suspension_reason: Option<String>,
+    // When a deleted account is purged for good. Until then the user can
+    // restore it by signing in.
+    pub purge_at: Option<DateTime<Utc>>,
+}
+
+// A page of a tenant's users, optionally narrowed to the emails containing
+// `search`. Pages start at 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserQuery {
+    pub search: Option<String>,
+    pub page: u32,
+    pub per_page: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserPage {
+    pub users: Vec<User>,
+    // Number of users matching the query across all pages
+    pub total: u64,
 }
 
 impl User {
@@ -22,9 +78,86 @@ impl User {
 
         let password = Password::parse(password.expose_secret())?;
         Ok(Self {
+            id: UserId::default(),
             email,
-            password,
+            username: None,
+            password: Some(password),
             requires_2fa,
+            status: AccountStatus::Active,
+            suspension_reason: None,
+            purge_at: None,
         })
     }
-}
\ No newline at end of file
+
+    // Users created through an external identity provider never get a password,
+    // so they can only sign in through that provider.
+    pub fn new_passwordless(email: Email) -> Self {
+        Self {
+            id: UserId::default(),
+            email,
+            username: None,
+            password: None,
+            requires_2fa: false,
+            status: AccountStatus::Active,
+            suspension_reason: None,
+            purge_at: None,
+        }
+    }
+
+    pub fn with_username(mut self, username: Username) -> Self {
+        self.username = Some(username);
+        self
+    }
+
+    // Deleted accounts can be restored until they are purged.
+    pub fn is_restorable(&self, now: DateTime<Utc>) -> bool {
+        self.status == AccountStatus::Deleted && self.purge_at.is_some_and(|purge_at| purge_at > now)
+    }
+}
+
+impl UserQuery {
+    pub fn offset(&self) -> u64 {
+        u64::from(self.page.saturating_sub(1)) * u64::from(self.per_page)
+    }
+
+    pub fn matches(&self, email: &str) -> bool {
+        match &self.search {
+            Some(search) => email.to_lowercase().contains(&search.to_lowercase()),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_query_offset() {
+        let query = |page| UserQuery { search: None, page, per_page: 20 };
+        assert_eq!(query(0).offset(), 0);
+        assert_eq!(query(1).offset(), 0);
+        assert_eq!(query(3).offset(), 40);
+    }
+
+    #[test]
+    fn test_user_query_matches() {
+        let query = UserQuery { search: Some("Acme".to_owned()), page: 1, per_page: 20 };
+        assert!(query.matches("jane@acme.com"));
+        assert!(!query.matches("jane@example.com"));
+    }
+
+    #[test]
+    fn test_is_restorable_until_purge() {
+        let now = Utc::now();
+        let mut user = User::new_passwordless(Email::parse("jane@example.com").unwrap());
+        assert!(!user.is_restorable(now));
+
+        user.status = AccountStatus::Deleted;
+        assert!(!user.is_restorable(now));
+
+        user.purge_at = Some(now + chrono::Duration::days(1));
+        assert!(user.is_restorable(now));
+        assert!(!user.is_restorable(now + chrono::Duration::days(2)));
+    }
+}
@@ -0,0 +1,68 @@
+use color_eyre::eyre::{eyre, Result};
+
+use crate::utils::parsable::Parsable;
+
+const MIN_USERNAME_LENGTH: usize = 3;
+const MAX_USERNAME_LENGTH: usize = 32;
+
+// A handle users can sign in with instead of their email. Unique within a
+// tenant, ignoring case, and kept as typed. It can't contain `@`, so it's
+// never mistaken for an email.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Username(String);
+
+impl Parsable for Username {
+    fn parse<S>(input: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        let input = input.as_ref();
+        let valid_length = (MIN_USERNAME_LENGTH..=MAX_USERNAME_LENGTH).contains(&input.len());
+        let valid_chars = input.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+        let starts_with_alphanumeric = input.starts_with(|c: char| c.is_ascii_alphanumeric());
+
+        match valid_length && valid_chars && starts_with_alphanumeric {
+            true => Ok(Self(input.to_owned())),
+            false => Err(eyre!("Invalid username")),
+        }
+    }
+}
+
+impl AsRef<str> for Username {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Username {
+    pub fn is_same_as(&self, other: &Username) -> bool {
+        self.0.eq_ignore_ascii_case(&other.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_valid_usernames() {
+        for username in ["jane", "Jane.Doe", "jane_doe-42", "j42"] {
+            assert_eq!(Username::parse(username).unwrap().as_ref(), username);
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid_usernames() {
+        let too_long = "a".repeat(MAX_USERNAME_LENGTH + 1);
+        for username in ["", "jd", "jane@example.com", "jane doe", ".jane", "_jane", "jané", too_long.as_str()] {
+            assert!(Username::parse(username).is_err(), "{username}");
+        }
+    }
+
+    #[test]
+    fn test_usernames_compare_ignoring_case() {
+        let username = Username::parse("Jane.Doe").unwrap();
+        assert!(username.is_same_as(&Username::parse("jane.doe").unwrap()));
+        assert!(!username.is_same_as(&Username::parse("jane.doe2").unwrap()));
+    }
+}
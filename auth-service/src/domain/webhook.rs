@@ -0,0 +1,330 @@
+use chrono::{DateTime, Duration, Utc};
+use color_eyre::eyre::{eyre, Context, Result};
+use rand::{distributions::Alphanumeric, Rng};
+use secrecy::Secret;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::utils::parsable::Parsable;
+
+use super::TenantId;
+
+// Deliveries are given up on after this many failed attempts.
+pub const MAX_WEBHOOK_DELIVERY_ATTEMPTS: u32 = 10;
+const FIRST_RETRY_DELAY_SECONDS: i64 = 30;
+const MAX_RETRY_DELAY_SECONDS: i64 = 6 * 60 * 60;
+const MAX_DELIVERY_ERROR_LENGTH: usize = 512;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WebhookEventType {
+    #[serde(rename = "user.signed_up")]
+    UserSignedUp,
+    #[serde(rename = "user.verified")]
+    UserVerified,
+    // Nothing changes a user's email yet, so this is only subscribed to for now
+    #[serde(rename = "user.email_changed")]
+    UserEmailChanged,
+    #[serde(rename = "user.deleted")]
+    UserDeleted,
+}
+
+impl Parsable for WebhookEventType {
+    fn parse<S>(input: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        match input.as_ref() {
+            "user.signed_up" => Ok(Self::UserSignedUp),
+            "user.verified" => Ok(Self::UserVerified),
+            "user.email_changed" => Ok(Self::UserEmailChanged),
+            "user.deleted" => Ok(Self::UserDeleted),
+            input => Err(eyre!("Invalid webhook event type: {}", input)),
+        }
+    }
+}
+
+impl AsRef<str> for WebhookEventType {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::UserSignedUp => "user.signed_up",
+            Self::UserVerified => "user.verified",
+            Self::UserEmailChanged => "user.email_changed",
+            Self::UserDeleted => "user.deleted",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WebhookEndpointId(String);
+
+impl Parsable for WebhookEndpointId {
+    fn parse<S>(id: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        let parse_id = Uuid::parse_str(id.as_ref()).wrap_err("Invalid webhook id")?;
+
+        Ok(Self(parse_id.to_string()))
+    }
+}
+
+impl Default for WebhookEndpointId {
+    fn default() -> Self {
+        Self(Uuid::new_v4().to_string())
+    }
+}
+
+impl AsRef<str> for WebhookEndpointId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+// A URL of another system that wants to hear about some events of a tenant.
+// Payloads sent to it are signed with its secret.
+#[derive(Debug, Clone)]
+pub struct WebhookEndpoint {
+    pub id: WebhookEndpointId,
+    pub tenant: TenantId,
+    pub url: String,
+    pub secret: Secret<String>,
+    pub events: Vec<WebhookEventType>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl WebhookEndpoint {
+    pub fn new(tenant: TenantId, url: String, events: Vec<WebhookEventType>) -> Self {
+        let secret: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(32)
+            .map(char::from)
+            .collect();
+
+        Self {
+            id: WebhookEndpointId::default(),
+            tenant,
+            url,
+            secret: Secret::new(format!("whsec_{}", secret)),
+            events,
+            created_at: Utc::now(),
+        }
+    }
+
+    pub fn subscribes_to(&self, event_type: WebhookEventType) -> bool {
+        self.events.contains(&event_type)
+    }
+}
+
+// Something that happened to a user, as sent to every endpoint subscribed to
+// its type. The id is the same for all of them, so receivers can tell
+// retries apart from new events.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookEvent {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub event_type: WebhookEventType,
+    pub tenant: String,
+    pub data: serde_json::Value,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl WebhookEvent {
+    pub fn new(tenant: &TenantId, event_type: WebhookEventType, data: serde_json::Value) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            event_type,
+            tenant: tenant.as_ref().to_owned(),
+            data,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Delivered,
+    // Failed too many times to be retried
+    DeadLetter,
+}
+
+impl Parsable for WebhookDeliveryStatus {
+    fn parse<S>(input: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        match input.as_ref() {
+            "pending" => Ok(Self::Pending),
+            "delivered" => Ok(Self::Delivered),
+            "dead_letter" => Ok(Self::DeadLetter),
+            input => Err(eyre!("Invalid webhook delivery status: {}", input)),
+        }
+    }
+}
+
+impl AsRef<str> for WebhookDeliveryStatus {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Pending => "pending",
+            Self::Delivered => "delivered",
+            Self::DeadLetter => "dead_letter",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WebhookDeliveryId(String);
+
+impl Parsable for WebhookDeliveryId {
+    fn parse<S>(id: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        let parse_id = Uuid::parse_str(id.as_ref()).wrap_err("Invalid webhook delivery id")?;
+
+        Ok(Self(parse_id.to_string()))
+    }
+}
+
+impl Default for WebhookDeliveryId {
+    fn default() -> Self {
+        Self(Uuid::new_v4().to_string())
+    }
+}
+
+impl AsRef<str> for WebhookDeliveryId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+// One event to send to one endpoint, kept until it's delivered or given up
+// on. The payload is stored as sent, so every attempt is signed over the
+// same bytes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookDelivery {
+    pub id: WebhookDeliveryId,
+    pub endpoint_id: WebhookEndpointId,
+    pub event_type: WebhookEventType,
+    pub payload: String,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: u32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl WebhookDelivery {
+    pub fn new(endpoint: &WebhookEndpoint, event: &WebhookEvent) -> Result<Self> {
+        let payload = serde_json::to_string(event).wrap_err("Failed to serialize webhook event")?;
+
+        Ok(Self {
+            id: WebhookDeliveryId::default(),
+            endpoint_id: endpoint.id.clone(),
+            event_type: event.event_type,
+            payload,
+            status: WebhookDeliveryStatus::Pending,
+            attempts: 0,
+            next_attempt_at: event.created_at,
+            last_error: None,
+            created_at: event.created_at,
+        })
+    }
+
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.status == WebhookDeliveryStatus::Pending && self.next_attempt_at <= now
+    }
+
+    pub fn record_success(&mut self) {
+        self.attempts += 1;
+        self.status = WebhookDeliveryStatus::Delivered;
+        self.last_error = None;
+    }
+
+    // Schedules the next attempt with exponential backoff, or moves the
+    // delivery to the dead letters once it's out of attempts.
+    pub fn record_failure(&mut self, error: &str, now: DateTime<Utc>) {
+        self.attempts += 1;
+        self.last_error = Some(error.chars().take(MAX_DELIVERY_ERROR_LENGTH).collect());
+
+        match self.attempts >= MAX_WEBHOOK_DELIVERY_ATTEMPTS {
+            true => self.status = WebhookDeliveryStatus::DeadLetter,
+            false => self.next_attempt_at = now + retry_delay(self.attempts),
+        }
+    }
+}
+
+// 30 seconds after the first failure, doubling after each one up to 6 hours
+fn retry_delay(failed_attempts: u32) -> Duration {
+    let seconds = FIRST_RETRY_DELAY_SECONDS.saturating_mul(1 << failed_attempts.saturating_sub(1).min(20));
+    Duration::seconds(seconds.min(MAX_RETRY_DELAY_SECONDS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delivery() -> WebhookDelivery {
+        let endpoint = WebhookEndpoint::new(
+            TenantId::default(),
+            "https://example.com/hooks".to_owned(),
+            vec![WebhookEventType::UserSignedUp],
+        );
+        let event = WebhookEvent::new(&TenantId::default(), WebhookEventType::UserSignedUp, serde_json::json!({}));
+        WebhookDelivery::new(&endpoint, &event).unwrap()
+    }
+
+    #[test]
+    fn test_parse_webhook_event_type() {
+        for event_type in [
+            WebhookEventType::UserSignedUp,
+            WebhookEventType::UserVerified,
+            WebhookEventType::UserEmailChanged,
+            WebhookEventType::UserDeleted,
+        ] {
+            assert_eq!(WebhookEventType::parse(event_type.as_ref()).unwrap(), event_type);
+            assert_eq!(serde_json::to_value(event_type).unwrap(), event_type.as_ref());
+        }
+        assert!(WebhookEventType::parse("user.unknown").is_err());
+    }
+
+    #[test]
+    fn test_retry_delay_doubles_up_to_a_cap() {
+        assert_eq!(retry_delay(1), Duration::seconds(30));
+        assert_eq!(retry_delay(2), Duration::seconds(60));
+        assert_eq!(retry_delay(5), Duration::seconds(480));
+        assert_eq!(retry_delay(30), Duration::seconds(MAX_RETRY_DELAY_SECONDS));
+    }
+
+    #[test]
+    fn should_retry_failed_delivery_until_out_of_attempts() {
+        let mut delivery = delivery();
+        let now = Utc::now();
+        assert!(delivery.is_due(now));
+
+        delivery.record_failure("HTTP 500", now);
+        assert_eq!(delivery.status, WebhookDeliveryStatus::Pending);
+        assert_eq!(delivery.next_attempt_at, now + Duration::seconds(30));
+        assert!(!delivery.is_due(now));
+
+        for _ in 1..MAX_WEBHOOK_DELIVERY_ATTEMPTS {
+            delivery.record_failure("HTTP 500", now);
+        }
+        assert_eq!(delivery.status, WebhookDeliveryStatus::DeadLetter);
+        assert_eq!(delivery.attempts, MAX_WEBHOOK_DELIVERY_ATTEMPTS);
+        assert!(!delivery.is_due(now + Duration::days(1)));
+    }
+
+    #[test]
+    fn should_stop_retrying_once_delivered() {
+        let mut delivery = delivery();
+        delivery.record_failure("HTTP 503", Utc::now());
+        delivery.record_success();
+
+        assert_eq!(delivery.status, WebhookDeliveryStatus::Delivered);
+        assert_eq!(delivery.attempts, 2);
+        assert_eq!(delivery.last_error, None);
+    }
+}
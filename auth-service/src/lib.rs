@@ -1,25 +1,79 @@
 use std::error::Error;
 
 use axum::{
-    http::{Method, StatusCode},
+    http::{header::CONTENT_TYPE, Method, StatusCode},
     response::{IntoResponse, Response},
-    routing::post,
+    routing::{get, post},
     Json, Router
 };
 use serde::{Deserialize, Serialize};
 use sqlx::{MySqlPool, mysql::MySqlPoolOptions};
 use secrecy::{ExposeSecret, Secret};
 
-use domain::{AuthAPIError, BannedTokenStore, EmailClient, TwoFACodeStore, UserStore};
+use domain::{
+    AccountStatus, AuditLogStore, AuthAPIError, BannedTokenStore, ScimError, ConsentStore, DataExportStore, ProfileStore, EmailClient, IntoShared, InvitationStore, KnownDeviceStore, MagicLinkStore, TrustedDeviceStore, OidcStateStore, TenantStore, TwoFACodeStore,
+    UserStore, WebhookStore,
+};
 
 use tower_http::{cors::CorsLayer, services::ServeDir, trace::TraceLayer};
-use std::sync::Arc;
+use std::{collections::HashMap, net::SocketAddr, sync::Arc};
 use tokio::sync::RwLock;
 use redis::{RedisResult, Client};
 
 pub mod routes;
 use routes::{
-    login, logout, verify_2fa, delete_account, signup, verify_token, 
+    login, logout, verify_2fa, delete_account, signup, verify_token, oidc_login, oidc_link, oidc_callback,
+    list_identities, unlink_identity, saml_metadata, saml_acs, request_magic_link, verify_magic_link,
+    assign_role, remove_role, tenant_signup, tenant_login, tenant_verify_2fa, create_tenant, list_tenants,
+    create_invitation, list_invitations, revoke_invitation, accept_invitation, decline_invitation,
+    list_users, get_user_details, disable_user, enable_user, force_password_reset, force_2fa_reset, delete_user,
+    revoke_sessions, reset_password, reauthenticate, change_password, disable_2fa,
+    list_audit_events, create_webhook, list_webhooks, delete_webhook, list_webhook_deliveries,
+    confirm_login_report,
+    report_login,
+    list_trusted_devices,
+    revoke_trusted_device,
+    export_personal_data,
+    get_me,
+    update_me,
+    publish_policy,
+    list_policies,
+    list_tenant_policies,
+    accept_policies,
+    list_consents,
+    start_impersonation,
+    stop_impersonation,
+    create_scim_token,
+    create_scim_user,
+    get_scim_user,
+    list_scim_users,
+    patch_scim_user,
+    delete_scim_user,
+    create_scim_group,
+    get_scim_group,
+    list_scim_groups,
+    patch_scim_group,
+    delete_scim_group,
+};
+use services::{
+    data_stores::{
+        hashmap_oidc_state_store::HashmapOidcStateStore,
+        hashset_magic_link_store::HashSetMagicLinkStore,
+        hashmap_tenant_store::HashmapTenantStore,
+        hashmap_invitation_store::HashmapInvitationStore,
+        vec_audit_log_store::VecAuditLogStore,
+        hashmap_webhook_store::HashmapWebhookStore,
+        hashmap_known_device_store::HashmapKnownDeviceStore,
+        hashmap_trusted_device_store::HashmapTrustedDeviceStore,
+        hashmap_data_export_store::HashmapDataExportStore,
+        hashmap_profile_store::HashmapProfileStore,
+        hashmap_consent_store::HashmapConsentStore,
+    },
+    geoip_locator::GeoIpLocator,
+    password_policy::PasswordPolicy,
+    oidc_client::OidcClient,
+    saml_service_provider::SamlServiceProvider,
+    webhook_address_policy::WebhookAddressPolicy,
 };
 use utils::tracing::{make_span_with_request_id, on_request, on_response};
 
@@ -31,6 +85,20 @@ pub type UserStoreType = Arc<RwLock<dyn UserStore + Send + Sync>>;
 pub type BannedTokenStoreType = Arc<RwLock<dyn BannedTokenStore + Send + Sync>>;
 pub type TwoFACodeStoreType = Arc<RwLock<dyn TwoFACodeStore + Send + Sync>>;
 pub type EmailClientType = Arc<RwLock<dyn EmailClient + Send + Sync>>;
+pub type OidcStateStoreType = Arc<RwLock<dyn OidcStateStore + Send + Sync>>;
+pub type OidcClientsType = Arc<HashMap<String, OidcClient>>;
+pub type MagicLinkStoreType = Arc<RwLock<dyn MagicLinkStore + Send + Sync>>;
+pub type SamlServiceProviderType = Option<Arc<SamlServiceProvider>>;
+pub type TenantStoreType = Arc<RwLock<dyn TenantStore + Send + Sync>>;
+pub type InvitationStoreType = Arc<RwLock<dyn InvitationStore + Send + Sync>>;
+pub type AuditLogStoreType = Arc<RwLock<dyn AuditLogStore + Send + Sync>>;
+pub type WebhookStoreType = Arc<RwLock<dyn WebhookStore + Send + Sync>>;
+pub type KnownDeviceStoreType = Arc<RwLock<dyn KnownDeviceStore + Send + Sync>>;
+pub type TrustedDeviceStoreType = Arc<RwLock<dyn TrustedDeviceStore + Send + Sync>>;
+pub type DataExportStoreType = Arc<RwLock<dyn DataExportStore + Send + Sync>>;
+pub type ProfileStoreType = Arc<RwLock<dyn ProfileStore + Send + Sync>>;
+pub type ConsentStoreType = Arc<RwLock<dyn ConsentStore + Send + Sync>>;
+pub type GeoIpLocatorType = Option<Arc<GeoIpLocator>>;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -38,6 +106,22 @@ pub struct AppState {
     pub banned_token_store: BannedTokenStoreType,
     pub two_fa_code_store: TwoFACodeStoreType,
     pub email_client: EmailClientType,
+    pub oidc_clients: OidcClientsType,
+    pub oidc_state_store: OidcStateStoreType,
+    pub saml_service_provider: SamlServiceProviderType,
+    pub magic_link_store: MagicLinkStoreType,
+    pub tenant_store: TenantStoreType,
+    pub invitation_store: InvitationStoreType,
+    pub audit_log_store: AuditLogStoreType,
+    pub webhook_store: WebhookStoreType,
+    pub known_device_store: KnownDeviceStoreType,
+    pub trusted_device_store: TrustedDeviceStoreType,
+    pub data_export_store: DataExportStoreType,
+    pub profile_store: ProfileStoreType,
+    pub consent_store: ConsentStoreType,
+    pub geoip_locator: GeoIpLocatorType,
+    pub password_policy: Arc<PasswordPolicy>,
+    pub webhook_address_policy: WebhookAddressPolicy,
 }
 
 impl AppState {
@@ -47,35 +131,206 @@ impl AppState {
         two_fa_code_store: TwoFACodeStoreType,
         email_client: EmailClientType,
      ) -> Self {
-        Self { user_store, banned_token_store, two_fa_code_store, email_client }
+        Self {
+            user_store,
+            banned_token_store,
+            two_fa_code_store,
+            email_client,
+            oidc_clients: Arc::new(HashMap::new()),
+            oidc_state_store: HashmapOidcStateStore::default().into_shared(),
+            saml_service_provider: None,
+            magic_link_store: HashSetMagicLinkStore::default().into_shared(),
+            tenant_store: HashmapTenantStore::default().into_shared(),
+            invitation_store: HashmapInvitationStore::default().into_shared(),
+            audit_log_store: VecAuditLogStore::default().into_shared(),
+            webhook_store: HashmapWebhookStore::default().into_shared(),
+            known_device_store: HashmapKnownDeviceStore::default().into_shared(),
+            trusted_device_store: HashmapTrustedDeviceStore::default().into_shared(),
+            data_export_store: HashmapDataExportStore::default().into_shared(),
+            profile_store: HashmapProfileStore::default().into_shared(),
+            consent_store: HashmapConsentStore::default().into_shared(),
+            geoip_locator: None,
+            password_policy: Arc::new(PasswordPolicy::default()),
+            webhook_address_policy: WebhookAddressPolicy::default(),
+        }
+    }
+
+    pub fn with_oidc(mut self, oidc_clients: OidcClientsType, oidc_state_store: OidcStateStoreType) -> Self {
+        self.oidc_clients = oidc_clients;
+        self.oidc_state_store = oidc_state_store;
+        self
+    }
+
+    pub fn with_saml(mut self, saml_service_provider: Arc<SamlServiceProvider>) -> Self {
+        self.saml_service_provider = Some(saml_service_provider);
+        self
+    }
+
+    pub fn with_magic_link_store(mut self, magic_link_store: MagicLinkStoreType) -> Self {
+        self.magic_link_store = magic_link_store;
+        self
+    }
+
+    pub fn with_tenant_store(mut self, tenant_store: TenantStoreType) -> Self {
+        self.tenant_store = tenant_store;
+        self
+    }
+
+    pub fn with_invitation_store(mut self, invitation_store: InvitationStoreType) -> Self {
+        self.invitation_store = invitation_store;
+        self
+    }
+
+    pub fn with_audit_log_store(mut self, audit_log_store: AuditLogStoreType) -> Self {
+        self.audit_log_store = audit_log_store;
+        self
+    }
+
+    pub fn with_webhook_store(mut self, webhook_store: WebhookStoreType) -> Self {
+        self.webhook_store = webhook_store;
+        self
+    }
+
+    pub fn with_known_device_store(mut self, known_device_store: KnownDeviceStoreType) -> Self {
+        self.known_device_store = known_device_store;
+        self
+    }
+
+    pub fn with_trusted_device_store(mut self, trusted_device_store: TrustedDeviceStoreType) -> Self {
+        self.trusted_device_store = trusted_device_store;
+        self
+    }
+
+    pub fn with_data_export_store(mut self, data_export_store: DataExportStoreType) -> Self {
+        self.data_export_store = data_export_store;
+        self
+    }
+
+    pub fn with_profile_store(mut self, profile_store: ProfileStoreType) -> Self {
+        self.profile_store = profile_store;
+        self
+    }
+
+    pub fn with_consent_store(mut self, consent_store: ConsentStoreType) -> Self {
+        self.consent_store = consent_store;
+        self
+    }
+
+    pub fn with_geoip_locator(mut self, geoip_locator: Arc<GeoIpLocator>) -> Self {
+        self.geoip_locator = Some(geoip_locator);
+        self
+    }
+
+    pub fn with_password_policy(mut self, password_policy: Arc<PasswordPolicy>) -> Self {
+        self.password_policy = password_policy;
+        self
+    }
+
+    pub fn with_webhook_address_policy(mut self, webhook_address_policy: WebhookAddressPolicy) -> Self {
+        self.webhook_address_policy = webhook_address_policy;
+        self
     }
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct ErrorResponse {
     pub error: String,
+    // Every reason a password was rejected for
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reasons: Vec<String>,
 }
 
 impl IntoResponse for AuthAPIError {
     fn into_response(self) -> Response {
         log_error_chain(&self);
-        let (status, error_message) = match self {
+        let reasons = match &self {
+            AuthAPIError::WeakPassword(rejections) => rejections.iter().map(|r| r.as_ref().to_owned()).collect(),
+            _ => Vec::new(),
+        };
+        let (status, error_message) = match &self {
             AuthAPIError::UserAlreadyExists => (StatusCode::CONFLICT, "User already exists"),
+            AuthAPIError::UsernameTaken => (StatusCode::CONFLICT, "Username already taken"),
             AuthAPIError::InvalidCredentials => (StatusCode::BAD_REQUEST, "Invalid credentials"),
+            AuthAPIError::InvalidInput(message) => (StatusCode::BAD_REQUEST, message.as_str()),
             AuthAPIError::IncorrectCredentials => (StatusCode::UNAUTHORIZED, "Incorrect credentials"),
             AuthAPIError::MissingToken => (StatusCode::BAD_REQUEST, "Missing token"),
             AuthAPIError::InvalidToken => (StatusCode::UNAUTHORIZED, "Invalid token"),
+            AuthAPIError::UnknownProvider => (StatusCode::NOT_FOUND, "Unknown identity provider"),
+            AuthAPIError::IdentityNotFound => (StatusCode::NOT_FOUND, "Identity not found"),
+            AuthAPIError::IdentityAlreadyLinked => (StatusCode::CONFLICT, "Identity already linked to another account"),
+            AuthAPIError::LastLoginMethod => (StatusCode::CONFLICT, "Cannot unlink the last login method"),
+            AuthAPIError::Forbidden => (StatusCode::FORBIDDEN, "Insufficient permissions"),
+            AuthAPIError::UserNotFound => (StatusCode::NOT_FOUND, "User not found"),
+            AuthAPIError::RoleNotFound => (StatusCode::NOT_FOUND, "Role not found"),
+            AuthAPIError::TenantNotFound => (StatusCode::NOT_FOUND, "Tenant not found"),
+            AuthAPIError::TenantAlreadyExists => (StatusCode::CONFLICT, "Tenant already exists"),
+            AuthAPIError::InvitationNotFound => (StatusCode::NOT_FOUND, "Invitation not found"),
+            AuthAPIError::AccountDisabled(status) => (StatusCode::FORBIDDEN, match status {
+                AccountStatus::PendingVerification => "Account pending verification",
+                AccountStatus::Deleted => "Account deleted",
+                _ => "Account suspended",
+            }),
+            AuthAPIError::WebhookNotFound => (StatusCode::NOT_FOUND, "Webhook not found"),
+            AuthAPIError::TrustedDeviceNotFound => (StatusCode::NOT_FOUND, "Trusted device not found"),
+            AuthAPIError::WeakPassword(_) => (StatusCode::BAD_REQUEST, "Password rejected"),
+            AuthAPIError::PolicyAlreadyExists => (StatusCode::CONFLICT, "Policy version already published"),
+            AuthAPIError::PolicyAcceptanceRequired => (StatusCode::BAD_REQUEST, "Policy acceptance required"),
+            AuthAPIError::ImpersonatedSession => (StatusCode::FORBIDDEN, "Not allowed while impersonating"),
+            AuthAPIError::ReauthenticationRequired => (StatusCode::UNAUTHORIZED, "Re-authentication required"),
             AuthAPIError::UnexpectedError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "An unexpected error"),
         };
 
         let body = Json(ErrorResponse {
             error: error_message.to_string(),
+            reasons,
         });
 
         (status, body).into_response()
     }
 }
 
+// The error responses of RFC 7644, section 3.12.
+#[derive(Serialize, Deserialize)]
+pub struct ScimErrorResponse {
+    pub schemas: Vec<String>,
+    // The HTTP status, as a string
+    pub status: String,
+    #[serde(rename = "scimType", default, skip_serializing_if = "Option::is_none")]
+    pub scim_type: Option<String>,
+    pub detail: String,
+}
+
+impl IntoResponse for ScimError {
+    fn into_response(self) -> Response {
+        log_error_chain(&self);
+        let (status, scim_type) = match &self {
+            ScimError::InvalidToken => (StatusCode::UNAUTHORIZED, None),
+            ScimError::NotFound => (StatusCode::NOT_FOUND, None),
+            ScimError::InvalidFilter(_) => (StatusCode::BAD_REQUEST, Some("invalidFilter")),
+            ScimError::InvalidPath(_) => (StatusCode::BAD_REQUEST, Some("invalidPath")),
+            ScimError::InvalidSyntax(_) => (StatusCode::BAD_REQUEST, Some("invalidSyntax")),
+            ScimError::InvalidValue(_) => (StatusCode::BAD_REQUEST, Some("invalidValue")),
+            ScimError::NoTarget(_) => (StatusCode::BAD_REQUEST, Some("noTarget")),
+            ScimError::Mutability(_) => (StatusCode::BAD_REQUEST, Some("mutability")),
+            ScimError::Uniqueness(_) => (StatusCode::CONFLICT, Some("uniqueness")),
+            ScimError::UnexpectedError(_) => (StatusCode::INTERNAL_SERVER_ERROR, None),
+        };
+        let detail = match &self {
+            ScimError::UnexpectedError(_) => "An unexpected error".to_owned(),
+            e => e.to_string(),
+        };
+
+        let body = Json(ScimErrorResponse {
+            schemas: vec![services::scim::ERROR_SCHEMA.to_owned()],
+            status: status.as_str().to_owned(),
+            scim_type: scim_type.map(str::to_owned),
+            detail,
+        });
+
+        (status, [(CONTENT_TYPE, services::scim::CONTENT_TYPE)], body).into_response()
+    }
+}
+
 pub async fn get_mysql_pool(url: Secret<String>) -> Result<MySqlPool, sqlx::Error> {
     MySqlPoolOptions::new()
         .max_connections(5)
@@ -97,7 +352,7 @@ impl Application {
         ];
 
         let cors = CorsLayer::new()
-            .allow_methods([Method::POST, Method::GET])
+            .allow_methods([Method::POST, Method::GET, Method::PATCH])
             .allow_credentials(true)
             .allow_origin(allowed_origins);
 
@@ -105,10 +360,61 @@ impl Application {
             .fallback_service(ServeDir::new("assets"))
             .route("/signup", post(signup))
             .route("/login", post(login))
+            .route("/login/magic-link", post(request_magic_link))
+            .route("/login/magic-link/verify", get(verify_magic_link))
+            .route("/login/report", get(confirm_login_report).post(report_login))
             .route("/verify-2fa", post(verify_2fa))
             .route("/logout", post(logout))
             .route("/verify-token", post(verify_token))
             .route("/delete-account", post(delete_account))
+            .route("/reauthenticate", post(reauthenticate))
+            .route("/change-password", post(change_password))
+            .route("/disable-2fa", post(disable_2fa))
+            .route("/me", get(get_me).patch(update_me))
+            .route("/me/audit-events", get(list_audit_events))
+            .route("/me/consents", get(list_consents).post(accept_policies))
+            .route("/me/export", get(export_personal_data))
+            .route("/policies", get(list_policies))
+            .route("/password-reset", post(reset_password))
+            .route("/oidc/{provider}/login", get(oidc_login))
+            .route("/oidc/{provider}/link", get(oidc_link))
+            .route("/oidc/{provider}/callback", get(oidc_callback))
+            .route("/identities", get(list_identities))
+            .route("/identities/unlink", post(unlink_identity))
+            .route("/trusted-devices", get(list_trusted_devices))
+            .route("/trusted-devices/revoke", post(revoke_trusted_device))
+            .route("/saml/metadata", get(saml_metadata))
+            .route("/saml/acs", post(saml_acs))
+            .route("/admin/roles/assign", post(assign_role))
+            .route("/admin/roles/remove", post(remove_role))
+            .route("/admin/users", get(list_users))
+            .route("/admin/users/{email}", get(get_user_details))
+            .route("/admin/users/disable", post(disable_user))
+            .route("/admin/users/enable", post(enable_user))
+            .route("/admin/users/reset-password", post(force_password_reset))
+            .route("/admin/users/reset-2fa", post(force_2fa_reset))
+            .route("/admin/users/delete", post(delete_user))
+            .route("/admin/users/revoke-sessions", post(revoke_sessions))
+            .route("/admin/tenants", get(list_tenants).post(create_tenant))
+            .route("/admin/invitations", get(list_invitations).post(create_invitation))
+            .route("/admin/invitations/revoke", post(revoke_invitation))
+            .route("/admin/policies", post(publish_policy))
+            .route("/admin/impersonate", post(start_impersonation))
+            .route("/impersonate/stop", post(stop_impersonation))
+            .route("/admin/scim/token", post(create_scim_token))
+            .route("/scim/v2/Users", get(list_scim_users).post(create_scim_user))
+            .route("/scim/v2/Users/{id}", get(get_scim_user).patch(patch_scim_user).delete(delete_scim_user))
+            .route("/scim/v2/Groups", get(list_scim_groups).post(create_scim_group))
+            .route("/scim/v2/Groups/{id}", get(get_scim_group).patch(patch_scim_group).delete(delete_scim_group))
+            .route("/admin/webhooks", get(list_webhooks).post(create_webhook))
+            .route("/admin/webhooks/delete", post(delete_webhook))
+            .route("/admin/webhooks/{id}/deliveries", get(list_webhook_deliveries))
+            .route("/invitations/accept", post(accept_invitation))
+            .route("/invitations/decline", post(decline_invitation))
+            .route("/tenants/{tenant}/signup", post(tenant_signup))
+            .route("/tenants/{tenant}/login", post(tenant_login))
+            .route("/tenants/{tenant}/verify-2fa", post(tenant_verify_2fa))
+            .route("/tenants/{tenant}/policies", get(list_tenant_policies))
             .with_state(app_state)
             .layer(cors)
             .layer(
@@ -126,7 +432,8 @@ impl Application {
 
     pub async fn run(self) -> Result<(), std::io::Error> {
         tracing::info!("listening on {}", &self.address);
-        axum::serve(self.listener, self.router).await
+        // Peer addresses are recorded in the audit log, or X-Real-IP when the peer is a trusted proxy
+        axum::serve(self.listener, self.router.into_make_service_with_connect_info::<SocketAddr>()).await
     }
 }
 
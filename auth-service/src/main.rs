@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 use tokio::sync::RwLock;
 use sqlx::mysql::MySqlPool;
 use secrecy::{Secret, ExposeSecret};
@@ -6,15 +6,32 @@ use reqwest::Client;
 
 use auth_service::{
     configure_redis,
-    domain::{Email, IntoShared},
+    domain::{Email, IntoShared, OidcProviderConfig, SamlIdpConfig, SamlServiceProviderConfig},
     get_mysql_pool,
     services::{
         data_stores::{
             my_sql_user_store::MySqlUserStore,
+            my_sql_tenant_store::MySqlTenantStore,
+            my_sql_invitation_store::MySqlInvitationStore,
+            my_sql_audit_log_store::MySqlAuditLogStore,
+            my_sql_webhook_store::MySqlWebhookStore,
+            my_sql_known_device_store::MySqlKnownDeviceStore,
+            my_sql_trusted_device_store::MySqlTrustedDeviceStore,
+            my_sql_data_export_store::MySqlDataExportStore,
+            my_sql_profile_store::MySqlProfileStore,
+            my_sql_consent_store::MySqlConsentStore,
             redis_banned_token_store::RedisBannedTokenStore,
             redis_two_fa_code_store::RedisTwoFACodeStore,
+            redis_oidc_state_store::RedisOidcStateStore,
+            redis_magic_link_store::RedisMagicLinkStore,
         },
+        account_purger::AccountPurger,
+        geoip_locator::GeoIpLocator,
         mailgun_email_client::MailgunEmailClient,
+        oidc_client::OidcClient,
+        saml_service_provider::SamlServiceProvider,
+        webhook_address_policy::WebhookAddressPolicy,
+        webhook_dispatcher::WebhookDispatcher,
     },
     utils::{
         constants::{
@@ -23,8 +40,18 @@ use auth_service::{
             DATABASE_URL,
             REDIS_HOST_NAME,
             MAIL_AUTH_TOKEN,
+            OIDC_PROVIDERS,
+            DEFAULT_OIDC_SCOPES,
+            oidc_provider_env_var,
+            oidc_provider_env_var_or_default,
+            SAML_IDPS,
+            GEOIP_DATABASE_PATH,
+            saml_idp_env_var,
+            saml_idp_env_var_or_none,
+            saml_sp_env_var,
         },
         parsable::Parsable,
+        passwords::{configure_password_hashing, configure_password_policy},
         tracing::init_tracing,
     },
     AppState, Application,
@@ -36,16 +63,52 @@ async fn main() {
     init_tracing().expect("Failed to initialize tracing");
     let db_pool = configure_database().await;
     let redis_client = Arc::new(RwLock::new(configure_redis(REDIS_HOST_NAME.to_string())));
-    let user_store = MySqlUserStore::new(db_pool).into_shared();
+    let user_store = MySqlUserStore::new(db_pool.clone())
+        .with_hashing_params(configure_password_hashing().expect("Failed to configure password hashing"))
+        .into_shared();
+    let tenant_store = MySqlTenantStore::new(db_pool.clone()).into_shared();
+    let invitation_store = MySqlInvitationStore::new(db_pool.clone()).into_shared();
+    let audit_log_store = MySqlAuditLogStore::new(db_pool.clone()).into_shared();
+    let webhook_store = MySqlWebhookStore::new(db_pool.clone()).into_shared();
+    let known_device_store = MySqlKnownDeviceStore::new(db_pool.clone()).into_shared();
+    let trusted_device_store = MySqlTrustedDeviceStore::new(db_pool.clone()).into_shared();
+    let data_export_store = MySqlDataExportStore::new(db_pool.clone()).into_shared();
+    let profile_store = MySqlProfileStore::new(db_pool.clone()).into_shared();
+    let consent_store = MySqlConsentStore::new(db_pool).into_shared();
     let banned_token_store = RedisBannedTokenStore::new(redis_client.clone()).into_shared();
     let hashmap_two_fa_code_store = RedisTwoFACodeStore::new(redis_client.clone()).into_shared();
     let email_client = configure_postmark_email_client().into_shared();
-    let app_state = AppState::new(
+    let oidc_state_store = RedisOidcStateStore::new(redis_client.clone()).into_shared();
+    let magic_link_store = RedisMagicLinkStore::new(redis_client.clone()).into_shared();
+    let mut app_state = AppState::new(
         user_store,
         banned_token_store,
         hashmap_two_fa_code_store,
         email_client,
-    );
+    )
+    .with_oidc(Arc::new(configure_oidc_clients()), oidc_state_store)
+    .with_magic_link_store(magic_link_store)
+    .with_tenant_store(tenant_store)
+    .with_invitation_store(invitation_store)
+    .with_audit_log_store(audit_log_store)
+    .with_webhook_store(webhook_store)
+    .with_known_device_store(known_device_store)
+    .with_trusted_device_store(trusted_device_store)
+    .with_data_export_store(data_export_store)
+    .with_profile_store(profile_store)
+    .with_consent_store(consent_store)
+    .with_password_policy(Arc::new(configure_password_policy().expect("Failed to configure password policy")));
+    if let Some(geoip_locator) = configure_geoip_locator() {
+        app_state = app_state.with_geoip_locator(Arc::new(geoip_locator));
+    }
+    if let Some(saml_service_provider) = configure_saml_service_provider() {
+        app_state = app_state.with_saml(Arc::new(saml_service_provider));
+    }
+    AccountPurger::new(app_state.user_store.clone(), app_state.email_client.clone(), app_state.webhook_store.clone())
+        .spawn(prod::account_purger::INTERVAL);
+    WebhookDispatcher::new(app_state.webhook_store.clone(), configure_webhook_http_client())
+        .spawn(prod::webhooks::INTERVAL);
+
     let app = Application::build(app_state, prod::APP_ADDRESS)
         .await
         .expect("Failed to build app");
@@ -83,3 +146,90 @@ fn configure_postmark_email_client() -> MailgunEmailClient {
         http_client,
     )
 }
+
+// Locations are left out of new device emails without a GeoIP database
+fn configure_geoip_locator() -> Option<GeoIpLocator> {
+    if GEOIP_DATABASE_PATH.is_empty() {
+        return None;
+    }
+
+    Some(GeoIpLocator::open(GEOIP_DATABASE_PATH.as_str()).expect("Failed to open GeoIP database"))
+}
+
+// Redirects aren't followed, since they could lead anywhere, including to
+// addresses webhooks aren't allowed to reach.
+fn configure_webhook_http_client() -> Client {
+    Client::builder()
+        .timeout(prod::webhooks::TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .dns_resolver(WebhookAddressPolicy::default())
+        .build()
+        .expect("Failed to build HTTP client")
+}
+
+fn configure_oidc_clients() -> HashMap<String, OidcClient> {
+    let http_client = Client::builder()
+        .timeout(prod::oidc::TIMEOUT)
+        .build()
+        .expect("Failed to build HTTP client");
+
+    OIDC_PROVIDERS
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            tracing::info!("OIDC provider: {}", name);
+
+            let config = OidcProviderConfig {
+                name: name.to_owned(),
+                issuer: oidc_provider_env_var(name, "ISSUER"),
+                client_id: oidc_provider_env_var(name, "CLIENT_ID"),
+                client_secret: Secret::new(oidc_provider_env_var(name, "CLIENT_SECRET")),
+                redirect_uri: oidc_provider_env_var(name, "REDIRECT_URI"),
+                scopes: oidc_provider_env_var_or_default(name, "SCOPES", DEFAULT_OIDC_SCOPES)
+                    .split_whitespace()
+                    .map(str::to_owned)
+                    .collect(),
+            };
+
+            (name.to_owned(), OidcClient::new(config, http_client.clone()))
+        })
+        .collect()
+}
+
+fn configure_saml_service_provider() -> Option<SamlServiceProvider> {
+    let idps: Vec<SamlIdpConfig> = SAML_IDPS
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            tracing::info!("SAML IdP: {}", name);
+
+            SamlIdpConfig {
+                name: name.to_owned(),
+                entity_id: saml_idp_env_var(name, "ENTITY_ID"),
+                certificates: saml_idp_env_var(name, "CERTIFICATE_PATHS")
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|path| !path.is_empty())
+                    .map(|path| {
+                        std::fs::read_to_string(path)
+                            .unwrap_or_else(|_| panic!("Failed to read SAML certificate {}", path))
+                    })
+                    .collect(),
+                email_attribute: saml_idp_env_var_or_none(name, "EMAIL_ATTRIBUTE"),
+            }
+        })
+        .collect();
+
+    if idps.is_empty() {
+        return None;
+    }
+
+    let config = SamlServiceProviderConfig {
+        entity_id: saml_sp_env_var("ENTITY_ID"),
+        acs_url: saml_sp_env_var("ACS_URL"),
+    };
+
+    Some(SamlServiceProvider::new(config, idps).expect("Failed to configure SAML service provider"))
+}
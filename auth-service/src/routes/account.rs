@@ -0,0 +1,63 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use axum_extra::extract::CookieJar;
+use secrecy::{ExposeSecret, Secret};
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    domain::{AuthAPIError, Email, Password},
+    utils::{auth::validate_auth_cookie, parsable::Parsable, passwords::enforce_password_policy},
+};
+
+// Changes to the signed-in user's own credentials. Both need a recent
+// sign-in, see `/reauthenticate`.
+#[tracing::instrument(name = "Change password", skip_all)]
+pub async fn change_password(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Json(request): Json<ChangePasswordRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let claims = validate_auth_cookie(&jar, &state).await?;
+    claims.require_recent_auth()?;
+    let tenant = claims.tenant_id()?;
+    let email = Email::parse_or_error(&claims.sub, |_| AuthAPIError::InvalidToken)?;
+    let password = Password::parse_or_error(request.new_password.expose_secret(), |_| AuthAPIError::InvalidCredentials)?;
+    enforce_password_policy(&state.password_policy, &email, &password).await?;
+
+    state.user_store
+        .write()
+        .await
+        .set_password(&tenant, &claims.sub, Some(password))
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    Ok(StatusCode::OK)
+}
+
+#[tracing::instrument(name = "Disable 2FA", skip_all)]
+pub async fn disable_2fa(
+    State(state): State<AppState>,
+    jar: CookieJar,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let claims = validate_auth_cookie(&jar, &state).await?;
+    claims.require_recent_auth()?;
+    let tenant = claims.tenant_id()?;
+    let email = Email::parse_or_error(&claims.sub, |_| AuthAPIError::InvalidToken)?;
+
+    state.user_store
+        .write()
+        .await
+        .set_requires_2fa(&tenant, &claims.sub, false)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    let _ = state.two_fa_code_store.write().await.remove_code(&tenant, email).await;
+
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+pub struct ChangePasswordRequest {
+    #[serde(rename = "newPassword")]
+    pub new_password: Secret<String>,
+}
@@ -0,0 +1,341 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use reqwest::Url;
+use secrecy::ExposeSecret;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState,
+    domain::{AccountStatus, AuthAPIError, Email, TenantId, User, UserQuery, UserStoreError, WebhookEventType},
+    utils::{
+        auth::{generate_password_reset_token, Admin, RequireRole, PASSWORD_RESET_TTL_SECONDS},
+        constants::PASSWORD_RESET_URL,
+        parsable::Parsable,
+        webhooks::{publish_webhook_event, user_event_data},
+    },
+};
+
+const DEFAULT_PER_PAGE: u32 = 20;
+const MAX_PER_PAGE: u32 = 100;
+const MAX_SUSPENSION_REASON_LENGTH: usize = 512;
+
+// Admins manage the users of their own tenant. Actions that lock a user out
+// also sign them out everywhere.
+#[tracing::instrument(name = "List users", skip_all)]
+pub async fn list_users(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+    Query(params): Query<ListUsersParams>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let tenant = admin.claims.tenant_id()?;
+
+    let per_page = params.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    if per_page == 0 || per_page > MAX_PER_PAGE {
+        return Err(AuthAPIError::InvalidCredentials);
+    }
+
+    let query = UserQuery {
+        search: params.search.map(|search| search.trim().to_owned()).filter(|search| !search.is_empty()),
+        page: params.page.unwrap_or(1).max(1),
+        per_page,
+    };
+
+    let page = state.user_store
+        .read()
+        .await
+        .list_users(&tenant, &query)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    Ok((StatusCode::OK, Json(ListUsersResponse {
+        users: page.users.iter().map(UserSummary::from).collect(),
+        page: query.page,
+        per_page: query.per_page,
+        total: page.total,
+    })))
+}
+
+#[tracing::instrument(name = "Get user", skip_all)]
+pub async fn get_user_details(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+    Path(email): Path<String>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let tenant = admin.claims.tenant_id()?;
+    let email = Email::parse_or_error(&email, |_| AuthAPIError::UserNotFound)?;
+
+    let user_store = state.user_store.read().await;
+    let user = user_store
+        .get_user(&tenant, email.as_ref().expose_secret())
+        .await
+        .map_err(map_user_error)?;
+    let authorization = user_store
+        .get_authorization(&tenant, email.as_ref().expose_secret())
+        .await
+        .map_err(map_user_error)?;
+    let identities = user_store
+        .get_linked_identities(&tenant, email.as_ref().expose_secret())
+        .await
+        .map_err(map_user_error)?;
+
+    Ok((StatusCode::OK, Json(UserDetailsResponse {
+        user: UserSummary::from(&user),
+        suspension_reason: user.suspension_reason.clone(),
+        has_password: user.password.is_some(),
+        roles: authorization.roles.iter().map(|role| role.as_ref().to_owned()).collect(),
+        identities: identities.into_iter().map(|identity| identity.provider).collect(),
+    })))
+}
+
+// Suspends the user, optionally recording why. The reason is shown to admins
+// only; the user is just told their account is suspended.
+#[tracing::instrument(name = "Disable user", skip_all)]
+pub async fn disable_user(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+    Json(request): Json<DisableUserRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (tenant, email) = request.user.parse_for(&admin)?;
+    reject_self(&admin, &email)?;
+
+    let reason = request.reason.map(|reason| reason.trim().to_owned()).filter(|reason| !reason.is_empty());
+    if reason.as_ref().is_some_and(|reason| reason.len() > MAX_SUSPENSION_REASON_LENGTH) {
+        return Err(AuthAPIError::InvalidCredentials);
+    }
+
+    state.user_store
+        .write()
+        .await
+        .set_status(&tenant, email.as_ref().expose_secret(), AccountStatus::Suspended, reason)
+        .await
+        .map_err(map_user_error)?;
+    revoke_user_sessions(&state, &tenant, &email).await?;
+
+    Ok(StatusCode::OK)
+}
+
+// Enabling an account that was pending verification verifies it.
+#[tracing::instrument(name = "Enable user", skip_all)]
+pub async fn enable_user(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+    Json(request): Json<UserActionRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (tenant, email) = request.parse_for(&admin)?;
+    let email = email.as_ref().expose_secret();
+
+    let mut user_store = state.user_store.write().await;
+    let previous_status = user_store.get_user(&tenant, email).await.map_err(map_user_error)?.status;
+    user_store
+        .set_status(&tenant, email, AccountStatus::Active, None)
+        .await
+        .map_err(map_user_error)?;
+    drop(user_store);
+
+    if previous_status == AccountStatus::PendingVerification {
+        publish_webhook_event(&state.webhook_store, &tenant, WebhookEventType::UserVerified, user_event_data(email)).await;
+    }
+
+    Ok(StatusCode::OK)
+}
+
+// Removes the user's password and emails them a link to choose a new one.
+// Their linked identities keep working in the meantime.
+#[tracing::instrument(name = "Force password reset", skip_all)]
+pub async fn force_password_reset(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+    Json(request): Json<UserActionRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (tenant, email) = request.parse_for(&admin)?;
+
+    state.user_store
+        .write()
+        .await
+        .set_password(&tenant, email.as_ref().expose_secret(), None)
+        .await
+        .map_err(map_user_error)?;
+    revoke_user_sessions(&state, &tenant, &email).await?;
+
+    let token = generate_password_reset_token(&tenant, &email).map_err(AuthAPIError::UnexpectedError)?;
+    let link = Url::parse_with_params(PASSWORD_RESET_URL.as_str(), &[("token", token.expose_secret())])
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    let content = format!(
+        "An administrator has reset your password. Follow this link to choose a new one: {}\n\nThe link expires in {} minutes.",
+        link,
+        PASSWORD_RESET_TTL_SECONDS / 60,
+    );
+
+    state.email_client
+        .read()
+        .await
+        .send_email(&email, "Reset your password", &content)
+        .await
+        .map_err(AuthAPIError::UnexpectedError)?;
+
+    Ok(StatusCode::OK)
+}
+
+// Turns off 2FA and drops any pending code, for users who can't complete it.
+#[tracing::instrument(name = "Force 2FA reset", skip_all)]
+pub async fn force_2fa_reset(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+    Json(request): Json<UserActionRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (tenant, email) = request.parse_for(&admin)?;
+
+    state.user_store
+        .write()
+        .await
+        .set_requires_2fa(&tenant, email.as_ref().expose_secret(), false)
+        .await
+        .map_err(map_user_error)?;
+
+    let _ = state.two_fa_code_store.write().await.remove_code(&tenant, email).await;
+
+    Ok(StatusCode::OK)
+}
+
+#[tracing::instrument(name = "Delete user", skip_all)]
+pub async fn delete_user(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+    Json(request): Json<UserActionRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (tenant, email) = request.parse_for(&admin)?;
+    reject_self(&admin, &email)?;
+
+    let mut user_store = state.user_store.write().await;
+    user_store.get_user(&tenant, email.as_ref().expose_secret()).await.map_err(map_user_error)?;
+    user_store.delete_user(&tenant, email.as_ref().expose_secret()).await.map_err(map_user_error)?;
+    drop(user_store);
+
+    let data = user_event_data(email.as_ref().expose_secret());
+    publish_webhook_event(&state.webhook_store, &tenant, WebhookEventType::UserDeleted, data).await;
+
+    revoke_user_sessions(&state, &tenant, &email).await?;
+
+    Ok(StatusCode::OK)
+}
+
+#[tracing::instrument(name = "Revoke user sessions", skip_all)]
+pub async fn revoke_sessions(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+    Json(request): Json<UserActionRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (tenant, email) = request.parse_for(&admin)?;
+
+    state.user_store
+        .read()
+        .await
+        .get_user(&tenant, email.as_ref().expose_secret())
+        .await
+        .map_err(map_user_error)?;
+    revoke_user_sessions(&state, &tenant, &email).await?;
+
+    Ok(StatusCode::OK)
+}
+
+pub(crate) async fn revoke_user_sessions(state: &AppState, tenant: &TenantId, email: &Email) -> Result<(), AuthAPIError> {
+    let banned = state.banned_token_store
+        .write()
+        .await
+        .ban_sessions(tenant, email.as_ref().expose_secret())
+        .await;
+
+    match banned {
+        true => Ok(()),
+        false => Err(AuthAPIError::UnexpectedError(color_eyre::eyre::eyre!("Failed to revoke sessions"))),
+    }
+}
+
+// Admins can't disable or delete their own account, so a tenant can't be
+// left without an admin by accident.
+pub(crate) fn reject_self(admin: &RequireRole<Admin>, email: &Email) -> Result<(), AuthAPIError> {
+    match admin.claims.sub == *email.as_ref().expose_secret() {
+        true => Err(AuthAPIError::Forbidden),
+        false => Ok(()),
+    }
+}
+
+pub(crate) fn map_user_error(e: UserStoreError) -> AuthAPIError {
+    match e {
+        UserStoreError::UserNotFound => AuthAPIError::UserNotFound,
+        e => AuthAPIError::UnexpectedError(e.into()),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ListUsersParams {
+    pub search: Option<String>,
+    pub page: Option<u32>,
+    #[serde(rename = "perPage")]
+    pub per_page: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct UserActionRequest {
+    pub email: String,
+}
+
+#[derive(Deserialize)]
+pub struct DisableUserRequest {
+    #[serde(flatten)]
+    pub user: UserActionRequest,
+    pub reason: Option<String>,
+}
+
+impl UserActionRequest {
+    pub(crate) fn parse_for(&self, admin: &RequireRole<Admin>) -> Result<(TenantId, Email), AuthAPIError> {
+        let tenant = admin.claims.tenant_id()?;
+        let email = Email::parse_or_error(&self.email, |_| AuthAPIError::InvalidCredentials)?;
+        Ok((tenant, email))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct UserSummary {
+    pub email: String,
+    #[serde(rename = "requires2FA")]
+    pub requires_2fa: bool,
+    pub status: AccountStatus,
+}
+
+impl From<&User> for UserSummary {
+    fn from(user: &User) -> Self {
+        Self {
+            email: user.email.as_ref().expose_secret().to_owned(),
+            requires_2fa: user.requires_2fa,
+            status: user.status,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListUsersResponse {
+    pub users: Vec<UserSummary>,
+    pub page: u32,
+    #[serde(rename = "perPage")]
+    pub per_page: u32,
+    pub total: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UserDetailsResponse {
+    #[serde(flatten)]
+    pub user: UserSummary,
+    #[serde(rename = "suspensionReason")]
+    pub suspension_reason: Option<String>,
+    #[serde(rename = "hasPassword")]
+    pub has_password: bool,
+    pub roles: Vec<String>,
+    // Providers of the user's linked identities
+    pub identities: Vec<String>,
+}
@@ -0,0 +1,80 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use axum_extra::extract::CookieJar;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState,
+    domain::{AuditEvent, AuditEventKind, AuditOutcome, AuthAPIError},
+    utils::auth::validate_auth_cookie,
+};
+
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 200;
+
+// The signed-in user's own authentication history, most recent first.
+#[tracing::instrument(name = "List audit events", skip_all)]
+pub async fn list_audit_events(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Query(params): Query<ListAuditEventsParams>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let claims = validate_auth_cookie(&jar, &state).await?;
+    let tenant = claims.tenant_id()?;
+
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT);
+    if limit == 0 || limit > MAX_LIMIT {
+        return Err(AuthAPIError::InvalidCredentials);
+    }
+
+    let events = state.audit_log_store
+        .read()
+        .await
+        .list_events(&tenant, &claims.sub, limit)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    Ok((StatusCode::OK, Json(ListAuditEventsResponse {
+        events: events.into_iter().map(AuditEventResponse::from).collect(),
+    })))
+}
+
+#[derive(Deserialize)]
+pub struct ListAuditEventsParams {
+    pub limit: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct AuditEventResponse {
+    pub event: AuditEventKind,
+    pub outcome: AuditOutcome,
+    pub detail: Option<String>,
+    pub ip: Option<String>,
+    #[serde(rename = "userAgent")]
+    pub user_agent: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<AuditEvent> for AuditEventResponse {
+    fn from(event: AuditEvent) -> Self {
+        Self {
+            event: event.kind,
+            outcome: event.outcome,
+            detail: event.detail,
+            ip: event.client.ip,
+            user_agent: event.client.user_agent,
+            created_at: event.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListAuditEventsResponse {
+    pub events: Vec<AuditEventResponse>,
+}
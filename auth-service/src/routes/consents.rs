@@ -0,0 +1,238 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use axum_extra::extract::CookieJar;
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use super::tenants::find_tenant;
+use crate::{
+    AppState,
+    domain::{AuthAPIError, Consent, ConsentStoreError, PolicyDocument, PolicyKind, PolicyVersion, TenantId},
+    utils::{auth::{validate_auth_cookie, Admin, RequireRole}, parsable::Parsable},
+};
+
+const MAX_URL_LENGTH: usize = 2048;
+
+// Publishing a new version makes every user of the tenant accept it the next
+// time they log in.
+#[tracing::instrument(name = "Publish policy", skip_all)]
+pub async fn publish_policy(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+    Json(request): Json<PublishPolicyRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let tenant = admin.claims.tenant_id()?;
+
+    let kind = PolicyKind::parse_or_error(&request.kind, invalid_input)?;
+    let version = PolicyVersion::parse_or_error(&request.version, invalid_input)?;
+    let url = Url::parse(&request.url).map_err(|_| AuthAPIError::InvalidInput("Invalid policy URL".to_owned()))?;
+    if !matches!(url.scheme(), "http" | "https") || request.url.len() > MAX_URL_LENGTH {
+        return Err(AuthAPIError::InvalidInput("Invalid policy URL".to_owned()));
+    }
+
+    let document = PolicyDocument { kind, version, url: url.to_string(), published_at: Utc::now() };
+    state.consent_store
+        .write()
+        .await
+        .add_policy(&tenant, document.clone())
+        .await
+        .map_err(|e| match e {
+            ConsentStoreError::PolicyAlreadyExists => AuthAPIError::PolicyAlreadyExists,
+            e => AuthAPIError::UnexpectedError(e.into()),
+        })?;
+
+    Ok((StatusCode::CREATED, Json(PolicyResponse::from(document))))
+}
+
+// The versions someone signing up has to accept.
+#[tracing::instrument(name = "List policies", skip_all)]
+pub async fn list_policies(State(state): State<AppState>) -> Result<impl IntoResponse, AuthAPIError> {
+    policies_response(&state, &TenantId::default()).await
+}
+
+#[tracing::instrument(name = "List tenant policies", skip_all)]
+pub async fn list_tenant_policies(
+    State(state): State<AppState>,
+    Path(tenant): Path<String>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let tenant = find_tenant(&state, &tenant).await?;
+    policies_response(&state, &tenant).await
+}
+
+async fn policies_response(state: &AppState, tenant: &TenantId) -> Result<impl IntoResponse, AuthAPIError> {
+    let policies = current_policies(state, tenant).await?;
+
+    Ok((StatusCode::OK, Json(ListPoliciesResponse {
+        policies: policies.into_iter().map(PolicyResponse::from).collect(),
+    })))
+}
+
+// Lets signed-in users accept a new version without logging in again.
+#[tracing::instrument(name = "Accept policies", skip_all)]
+pub async fn accept_policies(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Json(request): Json<AcceptPoliciesRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let claims = validate_auth_cookie(&jar, &state).await?;
+    // Only the user can agree to a policy
+    claims.forbid_impersonation()?;
+    let tenant = claims.tenant_id()?;
+
+    let current = current_policies(&state, &tenant).await?;
+    let consents = parse_acceptance(&current, &request.policies)?;
+    if consents.is_empty() {
+        return Err(AuthAPIError::InvalidInput("No policy accepted".to_owned()));
+    }
+    record_consents(&state, &tenant, &claims.sub, consents).await?;
+
+    consents_response(&state, &tenant, &claims.sub).await
+}
+
+// Every version the signed-in user accepted and when, most recent first.
+#[tracing::instrument(name = "List consents", skip_all)]
+pub async fn list_consents(
+    State(state): State<AppState>,
+    jar: CookieJar,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let claims = validate_auth_cookie(&jar, &state).await?;
+    let tenant = claims.tenant_id()?;
+
+    consents_response(&state, &tenant, &claims.sub).await
+}
+
+async fn consents_response(state: &AppState, tenant: &TenantId, email: &str) -> Result<impl IntoResponse, AuthAPIError> {
+    let consents = user_consents(state, tenant, email).await?;
+
+    Ok((StatusCode::OK, Json(ListConsentsResponse {
+        consents: consents.into_iter().map(ConsentResponse::from).collect(),
+    })))
+}
+
+pub(crate) async fn current_policies(state: &AppState, tenant: &TenantId) -> Result<Vec<PolicyDocument>, AuthAPIError> {
+    state.consent_store
+        .read()
+        .await
+        .get_current_policies(tenant)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))
+}
+
+pub(crate) async fn user_consents(state: &AppState, tenant: &TenantId, email: &str) -> Result<Vec<Consent>, AuthAPIError> {
+    state.consent_store
+        .read()
+        .await
+        .list_consents(tenant, email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))
+}
+
+pub(crate) async fn record_consents(
+    state: &AppState,
+    tenant: &TenantId,
+    email: &str,
+    consents: Vec<Consent>,
+) -> Result<(), AuthAPIError> {
+    let mut consent_store = state.consent_store.write().await;
+    for consent in consents {
+        consent_store
+            .add_consent(tenant, email, consent)
+            .await
+            .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+    }
+
+    Ok(())
+}
+
+// Only the current version of a policy can be accepted.
+pub(crate) fn parse_acceptance(
+    current: &[PolicyDocument],
+    accepted: &[AcceptedPolicy],
+) -> Result<Vec<Consent>, AuthAPIError> {
+    accepted
+        .iter()
+        .map(|accepted| {
+            let kind = PolicyKind::parse_or_error(&accepted.kind, invalid_input)?;
+            let version = PolicyVersion::parse_or_error(&accepted.version, invalid_input)?;
+            match current.iter().any(|document| document.kind == kind && document.version == version) {
+                true => Ok(Consent::new(kind, version)),
+                false => Err(AuthAPIError::InvalidInput("Not the current policy version".to_owned())),
+            }
+        })
+        .collect()
+}
+
+fn invalid_input(e: color_eyre::eyre::Error) -> AuthAPIError {
+    AuthAPIError::InvalidInput(e.to_string())
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AcceptedPolicy {
+    pub kind: String,
+    pub version: String,
+}
+
+#[derive(Deserialize)]
+pub struct PublishPolicyRequest {
+    pub kind: String,
+    pub version: String,
+    pub url: String,
+}
+
+#[derive(Deserialize)]
+pub struct AcceptPoliciesRequest {
+    pub policies: Vec<AcceptedPolicy>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct PolicyResponse {
+    pub kind: PolicyKind,
+    pub version: String,
+    pub url: String,
+    #[serde(rename = "publishedAt")]
+    pub published_at: DateTime<Utc>,
+}
+
+impl From<PolicyDocument> for PolicyResponse {
+    fn from(document: PolicyDocument) -> Self {
+        Self {
+            kind: document.kind,
+            version: document.version.as_ref().to_owned(),
+            url: document.url,
+            published_at: document.published_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListPoliciesResponse {
+    pub policies: Vec<PolicyResponse>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ConsentResponse {
+    pub kind: PolicyKind,
+    pub version: String,
+    #[serde(rename = "acceptedAt")]
+    pub accepted_at: DateTime<Utc>,
+}
+
+impl From<Consent> for ConsentResponse {
+    fn from(consent: Consent) -> Self {
+        Self {
+            kind: consent.kind,
+            version: consent.version.as_ref().to_owned(),
+            accepted_at: consent.accepted_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListConsentsResponse {
+    pub consents: Vec<ConsentResponse>,
+}
@@ -0,0 +1,73 @@
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use axum_extra::extract::CookieJar;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState,
+    domain::{AuthAPIError, DataExport, DataExportStoreError, Email},
+    utils::{auth::validate_auth_cookie, data_export::spawn_data_export, parsable::Parsable},
+};
+
+// Serves the user's personal data once it's ready. Until then, the first
+// request starts putting it together and later ones are told it's pending;
+// the user is emailed when it's done.
+#[tracing::instrument(name = "Export personal data", skip_all)]
+pub async fn export_personal_data(
+    State(state): State<AppState>,
+    jar: CookieJar,
+) -> Result<Response, AuthAPIError> {
+    let claims = validate_auth_cookie(&jar, &state).await?;
+    claims.forbid_impersonation()?;
+    let tenant = claims.tenant_id()?;
+    let email = Email::parse_or_error(&claims.sub, |_| AuthAPIError::InvalidToken)?;
+    let now = Utc::now();
+
+    let mut data_export_store = state.data_export_store.write().await;
+    let latest = match data_export_store.get_latest_export(&tenant, &claims.sub).await {
+        Ok(export) => Some(export),
+        Err(DataExportStoreError::ExportNotFound) => None,
+        Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+    };
+
+    let export = match latest {
+        Some(export) if export.is_downloadable(now) => {
+            let archive = export.archive.unwrap_or_default();
+            return Ok((
+                StatusCode::OK,
+                [
+                    (header::CONTENT_TYPE, "application/json"),
+                    (header::CONTENT_DISPOSITION, "attachment; filename=\"personal-data.json\""),
+                ],
+                archive,
+            ).into_response());
+        }
+        Some(export) if export.is_in_progress(now) => export,
+        _ => {
+            let export = DataExport::new();
+            data_export_store
+                .add_export(&tenant, &claims.sub, export.clone())
+                .await
+                .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+            spawn_data_export(state.clone(), tenant, email, export.clone());
+            export
+        }
+    };
+
+    Ok((StatusCode::ACCEPTED, Json(DataExportPendingResponse {
+        status: export.status.as_ref().to_owned(),
+        requested_at: export.requested_at,
+    })).into_response())
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DataExportPendingResponse {
+    pub status: String,
+    #[serde(rename = "requestedAt")]
+    pub requested_at: DateTime<Utc>,
+}
@@ -1,44 +1,82 @@
-use axum::{extract::State, http::StatusCode, response::IntoResponse};
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
 use axum_extra::extract::CookieJar;
-use serde::Deserialize;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
 use secrecy::Secret;
 
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{eyre, Result};
 
 use crate::{
     AppState,
-    domain::AuthAPIError,
+    domain::{AuditEventKind, AuthAPIError, ClientInfo, Email, TenantId},
     utils::{
-        auth::validate_token,
-        constants::JWT_COOKIE_NAME,
+        audit::{audit_event, record_audit_event},
+        auth::{validate_token, Claims},
+        constants::{ACCOUNT_DELETION_GRACE_DAYS, JWT_COOKIE_NAME},
+        parsable::Parsable,
     },
 };
 
+// Deletion is deferred: the account is marked deleted and only purged once
+// the grace period is over, so signing in again before then restores it.
+// Requires a recent sign-in.
 #[tracing::instrument(name = "delete account", skip_all)]
-pub async fn delete_account(jar: CookieJar, State(state): State<AppState>) -> Result<(CookieJar, impl IntoResponse), AuthAPIError> {
-    let banned_token_store = state.banned_token_store.clone();
-    match jar.get(JWT_COOKIE_NAME) {
-        Some(cookie) => {
-            let token = Secret::new(cookie.value().to_string());
-            match validate_token(banned_token_store, &token).await {
-                Ok(claims) => {
-                    let email = claims.sub;
-                    let cookie_clone = cookie.clone().into_owned();
-                    let mut user_store = state.user_store.write().await;
-                    if let Err(e) = user_store.delete_user(&email).await {
-                        Err(AuthAPIError::UnexpectedError(e.into()))
-                    } else {
-                        Ok((jar.remove(cookie_clone), StatusCode::OK.into_response()))
-                    }
-                },
-                Err(_) => Err(AuthAPIError::InvalidToken),
-            }
-        }
-        None => Err(AuthAPIError::MissingToken),
+pub async fn delete_account(
+    jar: CookieJar,
+    State(state): State<AppState>,
+    client: ClientInfo,
+) -> Result<(CookieJar, impl IntoResponse), AuthAPIError> {
+    let cookie = jar.get(JWT_COOKIE_NAME).ok_or(AuthAPIError::MissingToken)?.clone().into_owned();
+    let token = Secret::new(cookie.value().to_string());
+    let claims = validate_token(state.banned_token_store.clone(), state.user_store.clone(), &token).await?;
+    let tenant = claims.tenant_id()?;
+
+    let result = schedule_deletion(&state, &tenant, &claims).await;
+    record_audit_event(&state, audit_event(&tenant, &claims.sub, AuditEventKind::DeleteAccount, client, &result)).await;
+    let purge_at = result?;
+
+    Ok((jar.remove(cookie), (StatusCode::OK, Json(DeleteAccountResponse { purge_at }))))
+}
+
+async fn schedule_deletion(state: &AppState, tenant: &TenantId, claims: &Claims) -> Result<DateTime<Utc>, AuthAPIError> {
+    claims.require_recent_auth()?;
+    let email = Email::parse_or_error(&claims.sub, |_| AuthAPIError::InvalidToken)?;
+
+    let purge_at = Utc::now() + Duration::days(*ACCOUNT_DELETION_GRACE_DAYS);
+    state.user_store
+        .write()
+        .await
+        .schedule_deletion(tenant, &claims.sub, purge_at)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    // Other sessions end along with this one.
+    if !state.banned_token_store.write().await.ban_sessions(tenant, &claims.sub).await {
+        return Err(AuthAPIError::UnexpectedError(eyre!("Failed to revoke sessions")));
     }
+
+    let content = format!(
+        "Your account has been scheduled for deletion and will be removed for good on {}.\n\nChanged your mind? Sign in before then to keep it.",
+        purge_at.format("%Y-%m-%d %H:%M UTC"),
+    );
+    state.email_client
+        .read()
+        .await
+        .send_email(&email, "Your account will be deleted", &content)
+        .await
+        .map_err(AuthAPIError::UnexpectedError)?;
+
+    Ok(purge_at)
 }
 
 #[derive(Deserialize)]
 pub struct DeleteAccountRequest {
     pub email: String,
-}
\ No newline at end of file
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DeleteAccountResponse {
+    // When the account is purged unless the user signs in again
+    #[serde(rename = "purgeAt")]
+    pub purge_at: DateTime<Utc>,
+}
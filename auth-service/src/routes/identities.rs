@@ -0,0 +1,155 @@
+use axum::{extract::State, http::StatusCode, response::{IntoResponse, Redirect}, Json};
+use axum_extra::extract::CookieJar;
+use secrecy::ExposeSecret;
+use serde::{Deserialize, Serialize};
+
+use super::login::start_2fa_challenge;
+use crate::{
+    AppState,
+    domain::{AuthAPIError, Email, LinkedIdentity, TenantId, User, UserStoreError, WebhookEventType},
+    utils::{
+        auth::{issue_auth_cookie, validate_auth_cookie},
+        devices::is_trusted_device,
+        parsable::Parsable,
+        webhooks::{publish_webhook_event, user_event_data},
+    },
+};
+
+#[tracing::instrument(name = "List linked identities", skip_all)]
+pub async fn list_identities(
+    State(state): State<AppState>,
+    jar: CookieJar,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let claims = validate_auth_cookie(&jar, &state).await?;
+    let tenant = claims.tenant_id()?;
+
+    let identities = state.user_store
+        .read()
+        .await
+        .get_linked_identities(&tenant, &claims.sub)
+        .await
+        .map_err(|e| match e {
+            UserStoreError::UserNotFound => AuthAPIError::InvalidToken,
+            e => AuthAPIError::UnexpectedError(e.into()),
+        })?;
+
+    Ok((StatusCode::OK, Json(ListIdentitiesResponse { identities })))
+}
+
+#[tracing::instrument(name = "Unlink identity", skip_all)]
+pub async fn unlink_identity(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Json(request): Json<UnlinkIdentityRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let claims = validate_auth_cookie(&jar, &state).await?;
+    claims.forbid_impersonation()?;
+    let tenant = claims.tenant_id()?;
+
+    state.user_store
+        .write()
+        .await
+        .unlink_identity(&tenant, &claims.sub, &request.provider)
+        .await
+        .map_err(|e| match e {
+            UserStoreError::IdentityNotFound => AuthAPIError::IdentityNotFound,
+            UserStoreError::LastLoginMethod => AuthAPIError::LastLoginMethod,
+            UserStoreError::UserNotFound => AuthAPIError::InvalidToken,
+            e => AuthAPIError::UnexpectedError(e.into()),
+        })?;
+
+    Ok(StatusCode::OK)
+}
+
+// Users are matched by the identity they signed in with first, then by the
+// email the provider vouches for, and are created without a password when
+// neither exists.
+#[tracing::instrument(name = "Sign in with external identity", skip_all)]
+pub(crate) async fn sign_in_with_identity(
+    state: &AppState,
+    tenant: &TenantId,
+    verified_email: Option<&str>,
+    identity: LinkedIdentity,
+) -> Result<User, AuthAPIError> {
+    let mut user_store = state.user_store.write().await;
+
+    match user_store.get_user_by_identity(tenant, &identity.provider, &identity.subject).await {
+        Ok(user) => return Ok(user),
+        Err(UserStoreError::UserNotFound) => {}
+        Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+    }
+
+    let email = match verified_email {
+        Some(email) => Email::parse_or_error(email, |_| AuthAPIError::InvalidCredentials)?,
+        None => return Err(AuthAPIError::IncorrectCredentials),
+    };
+
+    let (user, created) = match user_store.get_user(tenant, email.as_ref().expose_secret()).await {
+        Ok(user) => (user, false),
+        Err(UserStoreError::UserNotFound) => {
+            let user = User::new_passwordless(email);
+            user_store
+                .add_user(tenant, user.clone())
+                .await
+                .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+            (user, true)
+        }
+        Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+    };
+
+    user_store
+        .link_identity(tenant, user.email.as_ref().expose_secret(), identity)
+        .await
+        .map_err(map_user_store_error)?;
+    drop(user_store);
+
+    if created {
+        let data = user_event_data(user.email.as_ref().expose_secret());
+        publish_webhook_event(&state.webhook_store, tenant, WebhookEventType::UserSignedUp, data).await;
+    }
+
+    Ok(user)
+}
+
+// Users who require 2FA get a code by email like at `/login`, unless this is
+// one of their trusted devices, whether or not their identity provider checked
+// a second factor. Instead of the auth cookie, the redirect then carries what
+// `/verify-2fa` needs in its fragment, which browsers don't send on.
+pub(crate) async fn complete_identity_sign_in(
+    state: &AppState,
+    tenant: &TenantId,
+    jar: CookieJar,
+    user: &User,
+    redirect: &str,
+) -> Result<(CookieJar, Redirect), AuthAPIError> {
+    if !user.requires_2fa || is_trusted_device(state, tenant, &user.email, &jar).await {
+        let auth_cookie = issue_auth_cookie(state.user_store.clone(), tenant, &user.email).await?;
+        return Ok((jar.add(auth_cookie), Redirect::to(redirect)));
+    }
+
+    let login_attempt_id = start_2fa_challenge(state, tenant, &user.email).await?;
+    let fragment = form_urlencoded::Serializer::new(String::new())
+        .append_pair("loginAttemptId", login_attempt_id.as_ref().expose_secret())
+        .append_pair("email", user.email.as_ref().expose_secret())
+        .finish();
+
+    Ok((jar, Redirect::to(&format!("{}#{}", redirect, fragment))))
+}
+
+pub(crate) fn map_user_store_error(e: UserStoreError) -> AuthAPIError {
+    match e {
+        UserStoreError::IdentityAlreadyLinked => AuthAPIError::IdentityAlreadyLinked,
+        UserStoreError::UserNotFound => AuthAPIError::InvalidToken,
+        e => AuthAPIError::UnexpectedError(e.into()),
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListIdentitiesResponse {
+    pub identities: Vec<LinkedIdentity>,
+}
+
+#[derive(Deserialize)]
+pub struct UnlinkIdentityRequest {
+    pub provider: String,
+}
@@ -0,0 +1,129 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use axum_extra::extract::{cookie::Cookie, CookieJar};
+use chrono::{DateTime, Duration, Utc};
+use secrecy::{ExposeSecret, Secret};
+use serde::{Deserialize, Serialize};
+
+use super::admin_users::{map_user_error, reject_self, UserActionRequest};
+use crate::{
+    AppState,
+    domain::{AuditEventKind, AuthAPIError, ClientInfo, Email, TenantId, ADMIN_ROLE},
+    utils::{
+        audit::{audit_event, record_audit_event},
+        auth::{
+            create_auth_cookie, create_impersonator_cookie, ensure_active, generate_impersonation_cookie,
+            validate_auth_cookie, Admin, RequireRole, IMPERSONATION_TTL_SECONDS,
+        },
+        constants::{IMPERSONATOR_COOKIE_NAME, JWT_COOKIE_NAME},
+    },
+};
+
+// Signs the admin in as one of their tenant's users, for support to see what
+// the user sees. Other admins can't be impersonated. The admin's own session
+// is set aside until they stop.
+#[tracing::instrument(name = "Start impersonation", skip_all)]
+pub async fn start_impersonation(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+    jar: CookieJar,
+    client: ClientInfo,
+    Json(request): Json<UserActionRequest>,
+) -> Result<(CookieJar, impl IntoResponse), AuthAPIError> {
+    let tenant = admin.claims.tenant_id()?;
+    let result = impersonate(&state, &admin, &request).await;
+
+    let mut event = audit_event(&tenant, &admin.claims.sub, AuditEventKind::ImpersonationStart, client.clone(), &result);
+    if result.is_ok() {
+        event = event.with_detail(format!("Impersonating {}", request.email.trim()));
+    }
+    record_audit_event(&state, event).await;
+
+    let (email, cookie) = result?;
+    let target = email.as_ref().expose_secret().to_owned();
+    let event = audit_event(&tenant, &target, AuditEventKind::ImpersonationStart, client, &Ok::<_, AuthAPIError>(()))
+        .with_detail(format!("Impersonated by {}", admin.claims.sub));
+    record_audit_event(&state, event).await;
+
+    let jar = match jar.get(JWT_COOKIE_NAME).map(|own| own.value().to_owned()) {
+        Some(own) => jar.add(create_impersonator_cookie(own)),
+        None => jar,
+    };
+
+    Ok((jar.add(cookie), (StatusCode::OK, Json(ImpersonationResponse {
+        email: target,
+        expires_at: Utc::now() + Duration::seconds(IMPERSONATION_TTL_SECONDS),
+    }))))
+}
+
+async fn impersonate(
+    state: &AppState,
+    admin: &RequireRole<Admin>,
+    request: &UserActionRequest,
+) -> Result<(Email, Cookie<'static>), AuthAPIError> {
+    admin.claims.forbid_impersonation()?;
+    let (tenant, email) = request.parse_for(admin)?;
+    reject_self(admin, &email)?;
+
+    let user_store = state.user_store.read().await;
+    let user = user_store
+        .get_user(&tenant, email.as_ref().expose_secret())
+        .await
+        .map_err(map_user_error)?;
+    ensure_active(&user)?;
+
+    let authorization = user_store
+        .get_authorization(&tenant, email.as_ref().expose_secret())
+        .await
+        .map_err(map_user_error)?;
+    if authorization.roles.iter().any(|role| role.as_ref() == ADMIN_ROLE) {
+        return Err(AuthAPIError::Forbidden);
+    }
+    drop(user_store);
+
+    let cookie = generate_impersonation_cookie(&tenant, &email, &authorization, &admin.claims.sub)
+        .map_err(AuthAPIError::UnexpectedError)?;
+    Ok((email, cookie))
+}
+
+// Ends the impersonated session and gives the admin their own session back,
+// if it's still around.
+#[tracing::instrument(name = "Stop impersonation", skip_all)]
+pub async fn stop_impersonation(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    client: ClientInfo,
+) -> Result<(CookieJar, impl IntoResponse), AuthAPIError> {
+    let claims = validate_auth_cookie(&jar, &state).await?;
+    let Some(actor) = &claims.act else {
+        return Err(AuthAPIError::InvalidCredentials);
+    };
+    let tenant = claims.tenant_id()?;
+
+    if let Some(cookie) = jar.get(JWT_COOKIE_NAME) {
+        let token = Secret::new(cookie.value().to_owned());
+        state.banned_token_store.write().await.store_token(&token).await;
+    }
+
+    record_stop(&state, &tenant, &actor.sub, format!("Stopped impersonating {}", claims.sub), client.clone()).await;
+    record_stop(&state, &tenant, &claims.sub, format!("No longer impersonated by {}", actor.sub), client).await;
+
+    let jar = match jar.get(IMPERSONATOR_COOKIE_NAME).map(|cookie| cookie.value().to_owned()) {
+        Some(own) => jar.remove(IMPERSONATOR_COOKIE_NAME).add(create_auth_cookie(own)),
+        None => jar.remove(JWT_COOKIE_NAME),
+    };
+
+    Ok((jar, StatusCode::OK))
+}
+
+async fn record_stop(state: &AppState, tenant: &TenantId, actor: &str, detail: String, client: ClientInfo) {
+    let result: Result<(), AuthAPIError> = Ok(());
+    let event = audit_event(tenant, actor, AuditEventKind::ImpersonationStop, client, &result).with_detail(detail);
+    record_audit_event(state, event).await;
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ImpersonationResponse {
+    pub email: String,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: DateTime<Utc>,
+}
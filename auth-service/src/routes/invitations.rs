@@ -0,0 +1,303 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use axum_extra::extract::CookieJar;
+use chrono::{DateTime, Duration, Utc};
+use reqwest::Url;
+use secrecy::{ExposeSecret, Secret};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState,
+    domain::{
+        AccountStatus, AuthAPIError, Email, Invitation, InvitationId, InvitationStoreError, Password, Role, User, UserId, UserStoreError,
+        WebhookEventType,
+    },
+    utils::{
+        auth::{generate_invitation_token, issue_auth_cookie, validate_invitation_token, Admin, RequireRole},
+        constants::INVITATION_URL,
+        parsable::Parsable,
+        passwords::enforce_password_policy,
+        webhooks::{publish_webhook_event, user_event_data},
+    },
+};
+
+const DEFAULT_INVITATION_TTL_HOURS: u32 = 7 * 24;
+const MAX_INVITATION_TTL_HOURS: u32 = 30 * 24;
+
+// Admins invite into their own tenant. The invitee gets an email with a
+// signed link to a page where they can accept or decline.
+#[tracing::instrument(name = "Create invitation", skip_all)]
+pub async fn create_invitation(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+    Json(request): Json<CreateInvitationRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let tenant = admin.claims.tenant_id()?;
+    let email = Email::parse_or_error(&request.email, |_| AuthAPIError::InvalidCredentials)?;
+    let role = request.role
+        .map(|role| Role::parse_or_error(&role, |_| AuthAPIError::InvalidCredentials))
+        .transpose()?;
+
+    let ttl_hours = request.expires_in_hours.unwrap_or(DEFAULT_INVITATION_TTL_HOURS);
+    if ttl_hours == 0 || ttl_hours > MAX_INVITATION_TTL_HOURS {
+        return Err(AuthAPIError::InvalidCredentials);
+    }
+
+    let invitation = Invitation::new(tenant, email, role, admin.claims.sub, Duration::hours(ttl_hours.into()));
+    let token = generate_invitation_token(&invitation).map_err(AuthAPIError::UnexpectedError)?;
+
+    state.invitation_store
+        .write()
+        .await
+        .add_invitation(invitation.clone())
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    let link = Url::parse_with_params(INVITATION_URL.as_str(), &[("token", token.expose_secret())])
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    let content = format!(
+        "{} invited you to join {}. Follow this link to accept or decline: {}\n\nThe invitation expires on {}.",
+        invitation.invited_by,
+        invitation.tenant.as_ref(),
+        link,
+        invitation.expires_at.format("%Y-%m-%d %H:%M UTC"),
+    );
+
+    state.email_client
+        .read()
+        .await
+        .send_email(&invitation.email, "You have been invited", &content)
+        .await
+        .map_err(AuthAPIError::UnexpectedError)?;
+
+    Ok((StatusCode::CREATED, Json(InvitationResponse::from(&invitation))))
+}
+
+// Only pending invitations are listed; expired ones are left out.
+#[tracing::instrument(name = "List invitations", skip_all)]
+pub async fn list_invitations(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let tenant = admin.claims.tenant_id()?;
+    let now = Utc::now();
+
+    let invitations = state.invitation_store
+        .read()
+        .await
+        .list_invitations(&tenant)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?
+        .iter()
+        .filter(|invitation| !invitation.is_expired(now))
+        .map(InvitationResponse::from)
+        .collect();
+
+    Ok((StatusCode::OK, Json(ListInvitationsResponse { invitations })))
+}
+
+#[tracing::instrument(name = "Revoke invitation", skip_all)]
+pub async fn revoke_invitation(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+    Json(request): Json<RevokeInvitationRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let tenant = admin.claims.tenant_id()?;
+    let id = InvitationId::parse_or_error(&request.id, |_| AuthAPIError::InvitationNotFound)?;
+
+    let mut invitation_store = state.invitation_store.write().await;
+
+    // Invitations of other tenants are reported as missing.
+    let invitation = invitation_store.get_invitation(&id).await.map_err(map_invitation_error)?;
+    if invitation.tenant != tenant {
+        return Err(AuthAPIError::InvitationNotFound);
+    }
+
+    invitation_store.remove_invitation(&id).await.map_err(map_invitation_error)?;
+
+    Ok(StatusCode::OK)
+}
+
+// Creates the user when the email has no account in the tenant yet, in which
+// case a password is required and the new user is signed in. An existing
+// user is only given the invited role and signs in as usual, since the link
+// alone doesn't prove they know their password.
+#[tracing::instrument(name = "Accept invitation", skip_all)]
+pub async fn accept_invitation(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Json(request): Json<AcceptInvitationRequest>,
+) -> Result<(CookieJar, impl IntoResponse), AuthAPIError> {
+    let invitation = find_invitation(&state, &request.token).await?;
+    let tenant = &invitation.tenant;
+    let email = invitation.email.as_ref().expose_secret();
+
+    let new_user = match state.user_store.read().await.get_user(tenant, email).await {
+        Ok(_) => None,
+        Err(UserStoreError::UserNotFound) => {
+            let password = request.password.ok_or(AuthAPIError::InvalidCredentials)?;
+            let password = Password::parse_or_error(password.expose_secret(), |_| AuthAPIError::InvalidCredentials)?;
+            enforce_password_policy(&state.password_policy, &invitation.email, &password).await?;
+            Some(User {
+                id: UserId::default(),
+                email: invitation.email.clone(),
+                username: None,
+                password: Some(password),
+                requires_2fa: request.requires_2fa,
+                status: AccountStatus::Active,
+                suspension_reason: None,
+                purge_at: None,
+            })
+        }
+        Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+    };
+
+    // Removed before anything else, so the same link can't be accepted twice.
+    state.invitation_store
+        .write()
+        .await
+        .remove_invitation(&invitation.id)
+        .await
+        .map_err(|_| AuthAPIError::InvalidToken)?;
+
+    let mut user_store = state.user_store.write().await;
+    let created = new_user.is_some();
+    if let Some(user) = new_user {
+        user_store.add_user(tenant, user).await.map_err(|e| match e {
+            UserStoreError::UserAlreadyExists => AuthAPIError::UserAlreadyExists,
+            e => AuthAPIError::UnexpectedError(e.into()),
+        })?;
+    }
+
+    if let Some(role) = &invitation.role {
+        user_store.assign_role(tenant, email, role).await.map_err(|e| match e {
+            UserStoreError::RoleNotFound => AuthAPIError::RoleNotFound,
+            e => AuthAPIError::UnexpectedError(e.into()),
+        })?;
+    }
+    drop(user_store);
+
+    let jar = match created {
+        true => {
+            publish_webhook_event(&state.webhook_store, tenant, WebhookEventType::UserSignedUp, user_event_data(email)).await;
+            jar.add(issue_auth_cookie(state.user_store.clone(), tenant, &invitation.email).await?)
+        }
+        false => jar,
+    };
+
+    Ok((jar, (StatusCode::OK, Json(AcceptInvitationResponse {
+        tenant: tenant.as_ref().to_owned(),
+        created,
+    }))))
+}
+
+#[tracing::instrument(name = "Decline invitation", skip_all)]
+pub async fn decline_invitation(
+    State(state): State<AppState>,
+    Json(request): Json<DeclineInvitationRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let invitation = find_invitation(&state, &request.token).await?;
+
+    state.invitation_store
+        .write()
+        .await
+        .remove_invitation(&invitation.id)
+        .await
+        .map_err(|_| AuthAPIError::InvalidToken)?;
+
+    Ok(StatusCode::OK)
+}
+
+// Any token that doesn't point to a pending invitation, including revoked
+// and already used ones, is an invalid token.
+async fn find_invitation(state: &AppState, token: &Secret<String>) -> Result<Invitation, AuthAPIError> {
+    let claims = validate_invitation_token(token).map_err(|_| AuthAPIError::InvalidToken)?;
+    let id = InvitationId::parse_or_error(&claims.jti, |_| AuthAPIError::InvalidToken)?;
+
+    let invitation = state.invitation_store
+        .read()
+        .await
+        .get_invitation(&id)
+        .await
+        .map_err(|_| AuthAPIError::InvalidToken)?;
+
+    let matches = invitation.tenant.as_ref() == claims.tenant
+        && invitation.email.as_ref().expose_secret() == &claims.sub
+        && !invitation.is_expired(Utc::now());
+
+    match matches {
+        true => Ok(invitation),
+        false => Err(AuthAPIError::InvalidToken),
+    }
+}
+
+fn map_invitation_error(e: InvitationStoreError) -> AuthAPIError {
+    match e {
+        InvitationStoreError::InvitationNotFound => AuthAPIError::InvitationNotFound,
+        e => AuthAPIError::UnexpectedError(e.into()),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct CreateInvitationRequest {
+    pub email: String,
+    pub role: Option<String>,
+    #[serde(rename = "expiresInHours")]
+    pub expires_in_hours: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct RevokeInvitationRequest {
+    pub id: String,
+}
+
+#[derive(Deserialize)]
+pub struct AcceptInvitationRequest {
+    pub token: Secret<String>,
+    pub password: Option<Secret<String>>,
+    #[serde(rename = "requires2FA", default)]
+    pub requires_2fa: bool,
+}
+
+#[derive(Deserialize)]
+pub struct DeclineInvitationRequest {
+    pub token: Secret<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct InvitationResponse {
+    pub id: String,
+    pub email: String,
+    pub role: Option<String>,
+    #[serde(rename = "invitedBy")]
+    pub invited_by: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: DateTime<Utc>,
+}
+
+impl From<&Invitation> for InvitationResponse {
+    fn from(invitation: &Invitation) -> Self {
+        Self {
+            id: invitation.id.as_ref().to_owned(),
+            email: invitation.email.as_ref().expose_secret().to_owned(),
+            role: invitation.role.as_ref().map(|role| role.as_ref().to_owned()),
+            invited_by: invitation.invited_by.clone(),
+            created_at: invitation.created_at,
+            expires_at: invitation.expires_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListInvitationsResponse {
+    pub invitations: Vec<InvitationResponse>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct AcceptInvitationResponse {
+    pub tenant: String,
+    // Whether a new account was created for the invitee
+    pub created: bool,
+}
@@ -1,19 +1,35 @@
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use axum::{extract::{Path, State}, http::StatusCode, response::IntoResponse, Json};
 use axum_extra::extract::{cookie, CookieJar};
 use serde::{Deserialize, Serialize};
+use color_eyre::eyre::eyre;
 use secrecy::{ExposeSecret, Secret};
 
+use super::{
+    consents::{current_policies, parse_acceptance, record_consents, user_consents, AcceptedPolicy, PolicyResponse},
+    tenants::find_tenant,
+};
 use crate::{
     domain::{
+        pending_policies,
+        AuditEventKind,
         AuthAPIError,
+        ClientInfo,
         Email,
         LoginAttemptId,
         Password,
+        PolicyDocument,
+        TenantId,
         TwoFACode,
+        UserStoreError,
+        Username,
     },
+    services::password_hashing::simulate_password_verification,
     utils::{
-        auth::generate_auth_cookie,
+        audit::{audit_event, record_audit_event},
+        auth::{ensure_can_sign_in, issue_auth_cookie, validate_auth_cookie},
+        devices::{check_sign_in_device, is_trusted_device},
         parsable::Parsable,
+        passwords::configure_password_hashing,
     }, AppState,
 };
 
@@ -21,12 +37,112 @@ use crate::{
 pub async fn login(
     State(state): State<AppState>,
     jar: CookieJar,
+    client: ClientInfo,
+    Json(request): Json<LoginRequest>
+) -> Result<(CookieJar, impl IntoResponse), AuthAPIError> {
+    log_in(&state, &TenantId::default(), jar, client, request).await
+}
+
+#[tracing::instrument(name = "Tenant login", skip_all)]
+pub async fn tenant_login(
+    State(state): State<AppState>,
+    Path(tenant): Path<String>,
+    jar: CookieJar,
+    client: ClientInfo,
     Json(request): Json<LoginRequest>
-) -> Result<(CookieJar, impl IntoResponse), AuthAPIError> {   
-    let email = request.email;
+) -> Result<(CookieJar, impl IntoResponse), AuthAPIError> {
+    let tenant = find_tenant(&state, &tenant).await?;
+    log_in(&state, &tenant, jar, client, request).await
+}
+
+// Signs the current user in again to refresh `auth_time` before a sensitive
+// change. Responds like `login`, so users with 2FA finish through
+// `verify-2fa`. Users without a password re-authenticate through their
+// identity provider instead.
+#[tracing::instrument(name = "Reauthenticate", skip_all)]
+pub async fn reauthenticate(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    client: ClientInfo,
+    Json(request): Json<ReauthenticateRequest>,
+) -> Result<(CookieJar, impl IntoResponse), AuthAPIError> {
+    let claims = validate_auth_cookie(&jar, &state).await?;
+    claims.forbid_impersonation()?;
+    let tenant = claims.tenant_id()?;
+
+    let request = LoginRequest {
+        identifier: LoginIdentifier::Email(claims.sub),
+        password: request.password,
+        accepted_policies: Vec::new(),
+    };
+    log_in(&state, &tenant, jar, client, request).await
+}
+
+type LoginResult = Result<(CookieJar, (StatusCode, Json<LoginResponse>)), AuthAPIError>;
+
+async fn log_in(
+    state: &AppState,
+    tenant: &TenantId,
+    jar: CookieJar,
+    client: ClientInfo,
+    request: LoginRequest,
+) -> LoginResult {
+    let email = find_login_email(state, tenant, &request).await;
+    let actor = match (&email, &request.identifier) {
+        (Ok(email), _) => email.clone(),
+        (Err(_), LoginIdentifier::Email(identifier) | LoginIdentifier::Username(identifier)) => identifier.clone(),
+    };
+    let result = match email {
+        Ok(email) => check_credentials(state, tenant, jar, &email, request).await,
+        Err(e) => Err(e),
+    };
+
+    if let (Ok((_, (StatusCode::OK, _))), Ok(email)) = (&result, Email::parse(&actor)) {
+        check_sign_in_device(state, tenant, &email, &client).await;
+    }
+
+    let mut event = audit_event(tenant, &actor, AuditEventKind::Login, client, &result);
+    match &result {
+        Ok((_, (StatusCode::PARTIAL_CONTENT, _))) => event = event.with_detail("2FA required"),
+        Ok((_, (StatusCode::PRECONDITION_REQUIRED, _))) => event = event.with_detail("Policy acceptance required"),
+        _ => {}
+    }
+    record_audit_event(state, event).await;
+
+    result
+}
+
+// Usernames are only another way to find the user, who is then signed in by
+// their email. Unknown usernames are rejected only after as long as a wrong
+// password takes, so they can't be told apart by timing.
+async fn find_login_email(state: &AppState, tenant: &TenantId, request: &LoginRequest) -> Result<String, AuthAPIError> {
+    let username = match &request.identifier {
+        LoginIdentifier::Email(email) => return Ok(email.clone()),
+        LoginIdentifier::Username(username) => Username::parse_or_error(username, |_| AuthAPIError::InvalidCredentials)?,
+    };
+
+    let user = state.user_store.read().await.get_user_by_username(tenant, &username).await;
+    match user {
+        Ok(user) => Ok(user.email.as_ref().expose_secret().to_owned()),
+        Err(UserStoreError::UserNotFound) => {
+            let params = configure_password_hashing().map_err(AuthAPIError::UnexpectedError)?;
+            simulate_password_verification(request.password.clone(), params).await;
+            Err(AuthAPIError::IncorrectCredentials)
+        }
+        Err(e) => Err(AuthAPIError::UnexpectedError(e.into())),
+    }
+}
+
+async fn check_credentials(
+    state: &AppState,
+    tenant: &TenantId,
+    jar: CookieJar,
+    email: &str,
+    request: LoginRequest,
+) -> LoginResult {
     let password = request.password;
 
-    let email = match Email::parse(&email) {
+    let email = match Email::parse(email) {
         Ok(email) => email,
         Err(_) => return Err(AuthAPIError::InvalidCredentials),
     };
@@ -38,32 +154,92 @@ pub async fn login(
 
     let user_store = state.user_store.read().await;
 
-    if user_store.validate_user(email.as_ref().expose_secret(), password.as_ref().expose_secret()).await.is_err() {
+    if user_store.validate_user(tenant, email.as_ref().expose_secret(), password.as_ref().expose_secret()).await.is_err() {
         return Err(AuthAPIError::IncorrectCredentials);
     }
 
-    let user = user_store.get_user(email.as_ref().expose_secret()).await
+    let user = user_store.get_user(tenant, email.as_ref().expose_secret()).await
         .map_err(|_| AuthAPIError::IncorrectCredentials)?;
 
-    match user.requires_2fa {
-        true => handle_2fa(&user.email, &state, jar).await,
-        false => handle_no_2fa(jar, email).await,
+    ensure_can_sign_in(&user)?;
+    drop(user_store);
+
+    let pending = check_policies(state, tenant, email.as_ref().expose_secret(), &request.accepted_policies).await?;
+    if !pending.is_empty() {
+        return Ok((
+            jar,
+            (StatusCode::PRECONDITION_REQUIRED,
+            Json(LoginResponse::PolicyAcceptanceRequired(PolicyAcceptanceResponse {
+                message: "Policy acceptance required".to_string(),
+                policies: pending.into_iter().map(PolicyResponse::from).collect(),
+            }))
+        )));
+    }
+
+    match user.requires_2fa && !is_trusted_device(state, tenant, &user.email, &jar).await {
+        true => handle_2fa(tenant, &user.email, state, jar).await,
+        false => handle_no_2fa(jar, tenant, email, state).await,
     }
 }
 
+// Records the policies accepted along with the login, then returns the
+// current ones the user still hasn't accepted. Nothing is recorded unless all
+// of them were.
+async fn check_policies(
+    state: &AppState,
+    tenant: &TenantId,
+    email: &str,
+    accepted: &[AcceptedPolicy],
+) -> Result<Vec<PolicyDocument>, AuthAPIError> {
+    let current = current_policies(state, tenant).await?;
+    if current.is_empty() {
+        return Ok(current);
+    }
+
+    let accepted = parse_acceptance(&current, accepted)?;
+    let mut consents = user_consents(state, tenant, email).await?;
+    consents.extend(accepted.iter().cloned());
+
+    let pending = pending_policies(current, &consents);
+    if pending.is_empty() {
+        record_consents(state, tenant, email, accepted).await?;
+    }
+    Ok(pending)
+}
+
 #[tracing::instrument(name = "handle 2FA", skip_all)]
 async fn handle_2fa(
+    tenant: &TenantId,
     email: &Email,
     state: &AppState,
     jar: cookie::CookieJar
 ) -> Result<(CookieJar, (StatusCode, Json<LoginResponse>)), AuthAPIError> {
+    let login_attempt_id = start_2fa_challenge(state, tenant, email).await?;
+
+    Ok((
+        jar,
+        (StatusCode::PARTIAL_CONTENT,
+        Json(LoginResponse::TwoFactorAuth(TwoFactorAuthResponse {
+            message: "2FA required".to_string(),
+            login_attempt_id: login_attempt_id.as_ref().expose_secret().to_owned(),
+        }))
+    )))
+}
+
+// Emails the user a new 2FA code. The login attempt it returns is completed
+// through `/verify-2fa`.
+pub(crate) async fn start_2fa_challenge(
+    state: &AppState,
+    tenant: &TenantId,
+    email: &Email,
+) -> Result<LoginAttemptId, AuthAPIError> {
     let login_attempt_id = LoginAttemptId::default();
     let two_fa_code = TwoFACode::default();
 
     if let Err(e) = state.two_fa_code_store
         .write()
         .await
-        .add_code(email.clone(), &login_attempt_id, two_fa_code.clone())
+        .add_code(tenant, email.clone(), &login_attempt_id, two_fa_code.clone())
         .await
     {
         return Err(AuthAPIError::UnexpectedError(e.into()));
@@ -82,29 +258,61 @@ async fn handle_2fa(
         return Err(AuthAPIError::UnexpectedError(e));
     }
 
-    Ok((
-        jar,
-        (StatusCode::PARTIAL_CONTENT,
-        Json(LoginResponse::TwoFactorAuth(TwoFactorAuthResponse {
-            message: "2FA required".to_string(),
-            login_attempt_id: login_attempt_id.as_ref().expose_secret().to_owned(),
-        }))
-    )))
+    Ok(login_attempt_id)
 }
 
 #[tracing::instrument(name = "handle no 2FA", skip_all)]
-async fn handle_no_2fa(jar: cookie::CookieJar, email: Email) -> Result<(CookieJar, (StatusCode, Json<LoginResponse>)), AuthAPIError> {
-    if let Ok(auth_cookie) = generate_auth_cookie(&email) {
-        let update_jar = jar.add(auth_cookie);
-        Ok((update_jar, (StatusCode::OK, Json(LoginResponse::RegularAuth))))
-    } else {
-        Ok((jar, (StatusCode::OK, Json(LoginResponse::RegularAuth))))
-    }
+async fn handle_no_2fa(
+    jar: cookie::CookieJar,
+    tenant: &TenantId,
+    email: Email,
+    state: &AppState,
+) -> Result<(CookieJar, (StatusCode, Json<LoginResponse>)), AuthAPIError> {
+    let auth_cookie = issue_auth_cookie(state.user_store.clone(), tenant, &email).await?;
+    Ok((jar.add(auth_cookie), (StatusCode::OK, Json(LoginResponse::RegularAuth))))
 }
 
 #[derive(Deserialize, Debug)]
+#[serde(try_from = "LoginRequestBody")]
 pub struct LoginRequest {
-    pub email: String,
+    // Sent as either `email` or `username`, never both
+    pub identifier: LoginIdentifier,
+    pub password: Secret<String>,
+    // Sent again along with the credentials after a `PolicyAcceptanceRequired`
+    pub accepted_policies: Vec<AcceptedPolicy>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoginIdentifier {
+    Email(String),
+    Username(String),
+}
+
+#[derive(Deserialize)]
+struct LoginRequestBody {
+    email: Option<String>,
+    username: Option<String>,
+    password: Secret<String>,
+    #[serde(rename = "acceptedPolicies", default)]
+    accepted_policies: Vec<AcceptedPolicy>,
+}
+
+impl TryFrom<LoginRequestBody> for LoginRequest {
+    type Error = color_eyre::eyre::Report;
+
+    fn try_from(body: LoginRequestBody) -> Result<Self, Self::Error> {
+        let identifier = match (body.email, body.username) {
+            (Some(email), None) => LoginIdentifier::Email(email),
+            (None, Some(username)) => LoginIdentifier::Username(username),
+            _ => return Err(eyre!("Expected either an email or a username")),
+        };
+
+        Ok(Self { identifier, password: body.password, accepted_policies: body.accepted_policies })
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ReauthenticateRequest {
     pub password: Secret<String>,
 }
 
@@ -112,6 +320,7 @@ pub struct LoginRequest {
 pub enum LoginResponse {
     RegularAuth,
     TwoFactorAuth(TwoFactorAuthResponse),
+    PolicyAcceptanceRequired(PolicyAcceptanceResponse),
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
@@ -120,3 +329,10 @@ pub struct TwoFactorAuthResponse {
     #[serde(rename = "loginAttemptId")]
     pub login_attempt_id: String,
 }
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct PolicyAcceptanceResponse {
+    pub message: String,
+    // The versions to accept, in `acceptedPolicies`, when logging in again
+    pub policies: Vec<PolicyResponse>,
+}
@@ -0,0 +1,120 @@
+use axum::{
+    extract::{Query, State},
+    response::{Html, Redirect},
+    Form,
+};
+use reqwest::Url;
+use secrecy::{ExposeSecret, Secret};
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    domain::{AuthAPIError, DeviceFingerprint, Email, TenantId},
+    utils::{
+        auth::{generate_password_reset_token, remaining_validity_seconds, validate_login_report_token, LoginReportClaims},
+        constants::PASSWORD_RESET_URL,
+        parsable::Parsable,
+    },
+};
+
+// The "this wasn't me" link of new-device sign-in emails. Opening it only asks
+// the user to confirm, since mail scanners and link previews open links too;
+// the report itself is made by submitting the page.
+#[tracing::instrument(name = "Confirm login report", skip_all)]
+pub async fn confirm_login_report(
+    State(state): State<AppState>,
+    Query(params): Query<LoginReportParams>,
+) -> Result<Html<String>, AuthAPIError> {
+    let token = Secret::new(params.token);
+    let claims = validate_login_report_token(&token).map_err(|_| AuthAPIError::InvalidToken)?;
+    if state.banned_token_store.read().await.is_token_banned(&used_token_id(&claims)).await {
+        return Err(AuthAPIError::InvalidToken);
+    }
+
+    // Valid tokens hold nothing but base64url characters and dots, so it
+    // needs no escaping.
+    Ok(Html(format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head><meta charset="utf-8"><meta name="robots" content="noindex"><title>Report sign-in</title></head>
+<body>
+<h1>Wasn't you?</h1>
+<p>Reporting this sign-in signs you out everywhere and removes your password, so you'll need to choose a new one.</p>
+<form method="post">
+<input type="hidden" name="token" value="{}">
+<button type="submit">Report sign-in and secure my account</button>
+</form>
+</body>
+</html>"#,
+        token.expose_secret()
+    )))
+}
+
+// Signs the user out everywhere and removes their password, so whoever
+// signed in can't again, then sends them to choose a new one. The reported
+// device is forgotten, so signing in from it again is reported again, and no
+// device skips 2FA anymore.
+#[tracing::instrument(name = "Report login", skip_all)]
+pub async fn report_login(
+    State(state): State<AppState>,
+    Form(params): Form<LoginReportParams>,
+) -> Result<Redirect, AuthAPIError> {
+    let claims = validate_login_report_token(&Secret::new(params.token)).map_err(|_| AuthAPIError::InvalidToken)?;
+
+    // Each report link works once, so it can't be used again to take away a
+    // password the user has chosen since. It's remembered as used for as long
+    // as it would still be accepted.
+    let used = state.banned_token_store
+        .write()
+        .await
+        .ban_token_once(&used_token_id(&claims), remaining_validity_seconds(claims.exp))
+        .await;
+    if !used {
+        return Err(AuthAPIError::InvalidToken);
+    }
+
+    let tenant = TenantId::parse_or_error(&claims.tenant, |_| AuthAPIError::InvalidToken)?;
+    let email = Email::parse_or_error(&claims.sub, |_| AuthAPIError::InvalidToken)?;
+    let device = DeviceFingerprint::parse_or_error(&claims.dev, |_| AuthAPIError::InvalidToken)?;
+
+    let mut user_store = state.user_store.write().await;
+    user_store.get_user(&tenant, &claims.sub).await.map_err(|_| AuthAPIError::InvalidToken)?;
+    user_store
+        .set_password(&tenant, &claims.sub, None)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+    drop(user_store);
+
+    if !state.banned_token_store.write().await.ban_sessions(&tenant, &claims.sub).await {
+        return Err(AuthAPIError::UnexpectedError(color_eyre::eyre::eyre!("Failed to revoke sessions")));
+    }
+
+    state.known_device_store
+        .write()
+        .await
+        .forget_device(&tenant, &claims.sub, &device)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    state.trusted_device_store
+        .write()
+        .await
+        .remove_all_devices(&tenant, &claims.sub)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    let token = generate_password_reset_token(&tenant, &email).map_err(AuthAPIError::UnexpectedError)?;
+    let link = Url::parse_with_params(PASSWORD_RESET_URL.as_str(), &[("token", token.expose_secret())])
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    Ok(Redirect::to(link.as_str()))
+}
+
+fn used_token_id(claims: &LoginReportClaims) -> Secret<String> {
+    Secret::new(format!("login_report:{}", claims.jti))
+}
+
+#[derive(Deserialize)]
+pub struct LoginReportParams {
+    pub token: String,
+}
@@ -4,7 +4,9 @@ use secrecy::Secret;
 
 use crate::{
     AuthAPIError,
+    domain::{AuditEventKind, ClientInfo},
     utils::{
+        audit::{audit_event, record_audit_event},
         auth::validate_token,
         constants::JWT_COOKIE_NAME,
     },
@@ -15,16 +17,22 @@ use crate::{
 pub async fn logout(
     State(state): State<AppState>,
     jar: CookieJar,
+    client: ClientInfo,
 ) -> Result<(CookieJar, impl IntoResponse), AuthAPIError>
 {
-    let banned_token_store = state.banned_token_store.clone();
     match jar.get(JWT_COOKIE_NAME) {
         Some(cookie) => {
             let token = Secret::new(cookie.value().to_string());
-            match validate_token(banned_token_store, &token).await {
-                Ok(_) => {
+            match validate_token(state.banned_token_store.clone(), state.user_store.clone(), &token).await {
+                Ok(claims) => {
                     let mut banned_token_store = state.banned_token_store.write().await;
                     banned_token_store.store_token(&token).await;
+                    drop(banned_token_store);
+
+                    let result: Result<(), AuthAPIError> = Ok(());
+                    let event = audit_event(&claims.tenant_id()?, &claims.sub, AuditEventKind::Logout, client, &result);
+                    record_audit_event(&state, event).await;
+
                     let cookie_clone = cookie.clone().into_owned();
                     Ok((jar.remove(cookie_clone), StatusCode::OK.into_response()))
                 },
@@ -34,4 +42,3 @@ pub async fn logout(
         None => Err(AuthAPIError::MissingToken),
     }
 }
-
@@ -0,0 +1,155 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Redirect},
+    Json,
+};
+use axum_extra::extract::{cookie::Cookie, CookieJar};
+use reqwest::Url;
+use secrecy::{ExposeSecret, Secret};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState,
+    domain::{AuthAPIError, Email, MagicLinkBinding, MagicLinkId, TenantId, UserStoreError},
+    utils::{
+        auth::{
+            create_magic_link_binding_cookie,
+            generate_magic_link_token,
+            issue_auth_cookie,
+            validate_magic_link_token,
+            MAGIC_LINK_TTL_SECONDS,
+        },
+        constants::{AUTH_SERVICE_URL, MAGIC_LINK_BINDING_COOKIE_NAME, MAGIC_LINK_POST_LOGIN_REDIRECT},
+        parsable::Parsable,
+    },
+};
+
+const MAGIC_LINK_SENT_MESSAGE: &str = "If the account exists, a sign-in link has been sent";
+
+// Responds the same whether or not the account exists, so the endpoint can't
+// be used to find out which emails are registered. Magic links only sign in
+// to the default tenant.
+#[tracing::instrument(name = "Request magic link", skip_all)]
+pub async fn request_magic_link(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Json(request): Json<MagicLinkRequest>,
+) -> Result<(CookieJar, impl IntoResponse), AuthAPIError> {
+    let email = Email::parse_or_error(&request.email, |_| AuthAPIError::InvalidCredentials)?;
+
+    let response = (StatusCode::OK, Json(MagicLinkResponse {
+        message: MAGIC_LINK_SENT_MESSAGE.to_owned(),
+    }));
+
+    match state.user_store.read().await.get_user(&TenantId::default(), email.as_ref().expose_secret()).await {
+        Ok(_) => {}
+        Err(UserStoreError::UserNotFound) => return Ok((jar, response)),
+        Err(e) => return Err(AuthAPIError::UnexpectedError(e.into())),
+    }
+
+    let id = MagicLinkId::default();
+    let binding = request.require_same_browser.then(MagicLinkBinding::default);
+
+    let token = generate_magic_link_token(&email, &id, binding.as_ref())
+        .map_err(AuthAPIError::UnexpectedError)?;
+
+    state.magic_link_store
+        .write()
+        .await
+        .add_link(&id)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    let link = Url::parse_with_params(
+        &format!("{}/login/magic-link/verify", AUTH_SERVICE_URL.trim_end_matches('/')),
+        &[("token", token.expose_secret())],
+    )
+    .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    let content = format!(
+        "Follow this link to sign in: {}\n\nThe link can only be used once and expires in {} minutes.",
+        link,
+        MAGIC_LINK_TTL_SECONDS / 60,
+    );
+
+    state.email_client
+        .read()
+        .await
+        .send_email(&email, "Your sign-in link", &content)
+        .await
+        .map_err(AuthAPIError::UnexpectedError)?;
+
+    let jar = match binding {
+        Some(binding) => jar.add(create_magic_link_binding_cookie(&binding)),
+        None => jar,
+    };
+
+    Ok((jar, response))
+}
+
+// Like the 2FA code, the link is delivered by email, so following it already
+// proves the second factor and no code is asked for.
+#[tracing::instrument(name = "Verify magic link", skip_all)]
+pub async fn verify_magic_link(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Query(params): Query<MagicLinkParams>,
+) -> Result<(CookieJar, Redirect), AuthAPIError> {
+    let claims = validate_magic_link_token(&Secret::new(params.token))
+        .map_err(|_| AuthAPIError::InvalidToken)?;
+
+    // Checked before the link is consumed, so opening it in the wrong
+    // browser doesn't use it up.
+    if let Some(fingerprint) = &claims.bnd {
+        let binding = jar
+            .get(MAGIC_LINK_BINDING_COOKIE_NAME)
+            .and_then(|cookie| MagicLinkBinding::parse(cookie.value()).ok())
+            .ok_or(AuthAPIError::InvalidToken)?;
+
+        if &binding.fingerprint() != fingerprint {
+            return Err(AuthAPIError::InvalidToken);
+        }
+    }
+
+    let id = MagicLinkId::parse_or_error(&claims.jti, |_| AuthAPIError::InvalidToken)?;
+    state.magic_link_store
+        .write()
+        .await
+        .consume_link(&id)
+        .await
+        .map_err(|_| AuthAPIError::InvalidToken)?;
+
+    let tenant = TenantId::default();
+    let user = state.user_store
+        .read()
+        .await
+        .get_user(&tenant, &claims.sub)
+        .await
+        .map_err(|_| AuthAPIError::InvalidToken)?;
+
+    let auth_cookie = issue_auth_cookie(state.user_store.clone(), &tenant, &user.email).await?;
+
+    let jar = jar
+        .remove(Cookie::build(MAGIC_LINK_BINDING_COOKIE_NAME).path("/"))
+        .add(auth_cookie);
+
+    Ok((jar, Redirect::to(MAGIC_LINK_POST_LOGIN_REDIRECT.as_str())))
+}
+
+#[derive(Deserialize)]
+pub struct MagicLinkRequest {
+    pub email: String,
+    #[serde(rename = "requireSameBrowser", default)]
+    pub require_same_browser: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct MagicLinkResponse {
+    pub message: String,
+}
+
+#[derive(Deserialize)]
+pub struct MagicLinkParams {
+    pub token: String,
+}
@@ -0,0 +1,165 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use axum_extra::extract::CookieJar;
+use secrecy::ExposeSecret;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::{
+    AppState,
+    domain::{AuthAPIError, Profile, TenantId, User, UserStoreError},
+    utils::{auth::validate_auth_cookie, parsable::Parsable},
+};
+
+// Who the request is signed in as.
+#[tracing::instrument(name = "Get current user", skip_all)]
+pub async fn get_me(
+    State(state): State<AppState>,
+    jar: CookieJar,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let claims = validate_auth_cookie(&jar, &state).await?;
+    let tenant = claims.tenant_id()?;
+
+    let user = get_user(&state, &tenant, &claims.sub).await?;
+    let profile = state.profile_store
+        .read()
+        .await
+        .get_profile(&tenant, &claims.sub)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    Ok((StatusCode::OK, Json(MeResponse::new(&user, &profile))))
+}
+
+// Fields left out of the request are kept, and `null` clears them.
+#[tracing::instrument(name = "Update current user", skip_all)]
+pub async fn update_me(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Json(request): Json<UpdateMeRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let claims = validate_auth_cookie(&jar, &state).await?;
+    // Usernames sign in, so only the user can change theirs
+    if request.username.is_some() {
+        claims.forbid_impersonation()?;
+    }
+    let tenant = claims.tenant_id()?;
+
+    let mut user = get_user(&state, &tenant, &claims.sub).await?;
+    let mut profile_store = state.profile_store.write().await;
+    let mut profile = profile_store
+        .get_profile(&tenant, &claims.sub)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    let mut username = user.username.clone();
+    apply_change(&mut username, request.username)?;
+    apply_change(&mut profile.display_name, request.display_name)?;
+    apply_change(&mut profile.locale, request.locale)?;
+    apply_change(&mut profile.timezone, request.timezone)?;
+    apply_change(&mut profile.avatar_url, request.avatar_url)?;
+
+    if username != user.username {
+        state.user_store
+            .write()
+            .await
+            .set_username(&tenant, &claims.sub, username.clone())
+            .await
+            .map_err(|e| match e {
+                UserStoreError::UsernameTaken => AuthAPIError::UsernameTaken,
+                e => AuthAPIError::UnexpectedError(e.into()),
+            })?;
+        user.username = username;
+    }
+
+    profile_store
+        .set_profile(&tenant, &claims.sub, profile.clone())
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    Ok((StatusCode::OK, Json(MeResponse::new(&user, &profile))))
+}
+
+async fn get_user(state: &AppState, tenant: &TenantId, email: &str) -> Result<User, AuthAPIError> {
+    state.user_store
+        .read()
+        .await
+        .get_user(tenant, email)
+        .await
+        .map_err(|e| match e {
+            UserStoreError::UserNotFound => AuthAPIError::InvalidToken,
+            e => AuthAPIError::UnexpectedError(e.into()),
+        })
+}
+
+fn apply_change<T: Parsable>(field: &mut Option<T>, change: Option<Option<String>>) -> Result<(), AuthAPIError> {
+    match change {
+        None => {}
+        Some(None) => *field = None,
+        Some(Some(value)) => *field = Some(T::parse_or_error(value, |e| AuthAPIError::InvalidInput(e.to_string()))?),
+    }
+    Ok(())
+}
+
+// Tells a field set to `null` apart from one that's missing.
+fn deserialize_change<'de, D>(deserializer: D) -> Result<Option<Option<String>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer).map(Some)
+}
+
+#[derive(Deserialize)]
+pub struct UpdateMeRequest {
+    #[serde(default, deserialize_with = "deserialize_change")]
+    pub username: Option<Option<String>>,
+    #[serde(default, rename = "displayName", deserialize_with = "deserialize_change")]
+    pub display_name: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_change")]
+    pub locale: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_change")]
+    pub timezone: Option<Option<String>>,
+    #[serde(default, rename = "avatarUrl", deserialize_with = "deserialize_change")]
+    pub avatar_url: Option<Option<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ProfileResponse {
+    #[serde(rename = "displayName")]
+    pub display_name: Option<String>,
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+    #[serde(rename = "avatarUrl")]
+    pub avatar_url: Option<String>,
+}
+
+impl From<&Profile> for ProfileResponse {
+    fn from(profile: &Profile) -> Self {
+        Self {
+            display_name: profile.display_name.as_ref().map(|name| name.as_ref().to_owned()),
+            locale: profile.locale.as_ref().map(|locale| locale.as_ref().to_owned()),
+            timezone: profile.timezone.as_ref().map(|timezone| timezone.as_ref().to_owned()),
+            avatar_url: profile.avatar_url.as_ref().map(|url| url.as_ref().to_owned()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MeResponse {
+    pub id: String,
+    pub email: String,
+    pub username: Option<String>,
+    #[serde(rename = "requires2FA")]
+    pub requires_2fa: bool,
+    pub profile: ProfileResponse,
+}
+
+impl MeResponse {
+    fn new(user: &User, profile: &Profile) -> Self {
+        Self {
+            id: user.id.as_ref().to_owned(),
+            email: user.email.as_ref().expose_secret().to_owned(),
+            username: user.username.as_ref().map(|username| username.as_ref().to_owned()),
+            requires_2fa: user.requires_2fa,
+            profile: ProfileResponse::from(profile),
+        }
+    }
+}
@@ -4,6 +4,25 @@ mod signup;
 mod delete_account;
 mod verify_2fa;
 mod verify_token;
+mod oidc;
+mod identities;
+mod saml;
+mod magic_link;
+mod roles;
+mod tenants;
+mod invitations;
+mod admin_users;
+mod password_reset;
+mod account;
+mod audit_events;
+mod webhooks;
+mod login_report;
+mod trusted_devices;
+mod data_export;
+mod me;
+mod consents;
+mod impersonation;
+mod scim;
 
 pub use login::*;
 pub use logout::*;
@@ -11,3 +30,22 @@ pub use signup::*;
 pub use verify_2fa::*;
 pub use verify_token::*;
 pub use delete_account::*;
+pub use oidc::*;
+pub use identities::*;
+pub use saml::*;
+pub use magic_link::*;
+pub use roles::*;
+pub use tenants::*;
+pub use invitations::*;
+pub use admin_users::*;
+pub use password_reset::*;
+pub use account::*;
+pub use audit_events::*;
+pub use webhooks::*;
+pub use login_report::*;
+pub use trusted_devices::*;
+pub use data_export::*;
+pub use me::*;
+pub use consents::*;
+pub use impersonation::*;
+pub use scim::*;
@@ -0,0 +1,192 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::Redirect,
+};
+use axum_extra::extract::{cookie::Cookie, CookieJar};
+use serde::Deserialize;
+use secrecy::ExposeSecret;
+use subtle::ConstantTimeEq;
+
+use super::identities::{complete_identity_sign_in, map_user_store_error, sign_in_with_identity};
+use crate::{
+    AppState,
+    domain::{
+        AuthAPIError,
+        Email,
+        LinkedIdentity,
+        OidcNonce,
+        OidcState,
+        PendingOidcLogin,
+        PkceVerifier,
+        TenantId,
+        User,
+    },
+    utils::{
+        auth::{create_oidc_state_cookie, issue_auth_cookie, validate_auth_cookie},
+        constants::{OIDC_POST_LOGIN_REDIRECT, OIDC_STATE_COOKIE_NAME},
+        parsable::Parsable,
+    },
+};
+
+#[tracing::instrument(name = "OIDC login", skip_all)]
+pub async fn oidc_login(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+    jar: CookieJar,
+) -> Result<(CookieJar, Redirect), AuthAPIError> {
+    start_oidc_flow(&state, jar, provider, None).await
+}
+
+#[tracing::instrument(name = "OIDC link", skip_all)]
+pub async fn oidc_link(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+    jar: CookieJar,
+) -> Result<(CookieJar, Redirect), AuthAPIError> {
+    let claims = validate_auth_cookie(&jar, &state).await?;
+    claims.forbid_impersonation()?;
+    let email = Email::parse_or_error(&claims.sub, |_| AuthAPIError::InvalidToken)?;
+
+    // Identity providers are configured for the default tenant only
+    if !claims.tenant_id()?.is_default() {
+        return Err(AuthAPIError::Forbidden);
+    }
+
+    start_oidc_flow(&state, jar, provider, Some(email)).await
+}
+
+async fn start_oidc_flow(
+    state: &AppState,
+    jar: CookieJar,
+    provider: String,
+    link_email: Option<Email>,
+) -> Result<(CookieJar, Redirect), AuthAPIError> {
+    let oidc_client = state.oidc_clients
+        .get(&provider)
+        .ok_or(AuthAPIError::UnknownProvider)?;
+
+    let oidc_state = OidcState::default();
+    let pending_login = PendingOidcLogin {
+        provider,
+        nonce: OidcNonce::default(),
+        pkce_verifier: PkceVerifier::default(),
+        link_email,
+    };
+
+    let authorization_url = oidc_client
+        .authorization_url(&oidc_state, &pending_login.nonce, &pending_login.pkce_verifier)
+        .await
+        .map_err(AuthAPIError::UnexpectedError)?;
+
+    state.oidc_state_store
+        .write()
+        .await
+        .add_state(&oidc_state, pending_login)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    Ok((jar.add(create_oidc_state_cookie(&oidc_state)), Redirect::to(authorization_url.as_str())))
+}
+
+// A verified ID token is enough to issue our own auth cookie, unless the user
+// requires 2FA.
+#[tracing::instrument(name = "OIDC callback", skip_all)]
+pub async fn oidc_callback(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+    jar: CookieJar,
+    Query(params): Query<OidcCallbackParams>,
+) -> Result<(CookieJar, Redirect), AuthAPIError> {
+    let oidc_client = state.oidc_clients
+        .get(&provider)
+        .ok_or(AuthAPIError::UnknownProvider)?;
+
+    let oidc_state = OidcState::parse_or_error(&params.state, |_| AuthAPIError::InvalidToken)?;
+
+    // The flow must end in the browser that started it, or anyone could have
+    // a victim complete a flow of theirs, signing the victim into the
+    // attacker's account or linking the victim's identity to it. Checked
+    // before the state is taken, so a callback opened elsewhere doesn't use
+    // it up.
+    let started_here = jar
+        .get(OIDC_STATE_COOKIE_NAME)
+        .is_some_and(|cookie| bool::from(cookie.value().as_bytes().ct_eq(oidc_state.fingerprint().as_bytes())));
+    if !started_here {
+        return Err(AuthAPIError::InvalidToken);
+    }
+    let jar = jar.remove(Cookie::build(OIDC_STATE_COOKIE_NAME).path("/"));
+
+    let pending_login = state.oidc_state_store
+        .write()
+        .await
+        .take_state(&oidc_state)
+        .await
+        .map_err(|_| AuthAPIError::InvalidToken)?;
+
+    if pending_login.provider != provider {
+        return Err(AuthAPIError::InvalidToken);
+    }
+
+    let code = match (params.code, params.error) {
+        (Some(code), None) => code,
+        _ => return Err(AuthAPIError::IncorrectCredentials),
+    };
+
+    let claims = oidc_client
+        .authenticate(&code, &pending_login)
+        .await
+        .map_err(|e| {
+            tracing::warn!("Failed to authenticate with {}: {:?}", provider, e);
+            AuthAPIError::InvalidToken
+        })?;
+
+    let identity = LinkedIdentity::new(provider, claims.sub.clone());
+    let tenant = TenantId::default();
+
+    // Linking happens from an existing session, so it doesn't ask for 2FA
+    // again. That session must still be the one signed in.
+    if let Some(email) = pending_login.link_email {
+        let session = validate_auth_cookie(&jar, &state).await?;
+        session.forbid_impersonation()?;
+        if session.sub != *email.as_ref().expose_secret() || !session.tenant_id()?.is_default() {
+            return Err(AuthAPIError::Forbidden);
+        }
+
+        let user = link_identity(&state, &tenant, email, identity).await?;
+        let auth_cookie = issue_auth_cookie(state.user_store.clone(), &tenant, &user.email).await?;
+        return Ok((jar.add(auth_cookie), Redirect::to(OIDC_POST_LOGIN_REDIRECT.as_str())));
+    }
+
+    let verified_email = match (claims.email, claims.email_verified) {
+        (Some(email), true) => Some(email),
+        _ => None,
+    };
+    let user = sign_in_with_identity(&state, &tenant, verified_email.as_deref(), identity).await?;
+
+    complete_identity_sign_in(&state, &tenant, jar, &user, OIDC_POST_LOGIN_REDIRECT.as_str()).await
+}
+
+#[tracing::instrument(name = "Link OIDC identity", skip_all)]
+async fn link_identity(
+    state: &AppState,
+    tenant: &TenantId,
+    email: Email,
+    identity: LinkedIdentity,
+) -> Result<User, AuthAPIError> {
+    let mut user_store = state.user_store.write().await;
+    let email = email.as_ref().expose_secret();
+
+    user_store
+        .link_identity(tenant, email, identity)
+        .await
+        .map_err(map_user_store_error)?;
+
+    user_store.get_user(tenant, email).await.map_err(map_user_store_error)
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OidcCallbackParams {
+    pub state: String,
+    pub code: Option<String>,
+    pub error: Option<String>,
+}
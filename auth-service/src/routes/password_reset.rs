@@ -0,0 +1,43 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use secrecy::{ExposeSecret, Secret};
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    domain::{AuthAPIError, Email, Password, TenantId},
+    utils::{auth::validate_password_reset_token, parsable::Parsable, passwords::enforce_password_policy},
+};
+
+// Completes a password reset forced by an admin. The link only works while
+// the user has no password, so it can't be used again once a new one is set.
+#[tracing::instrument(name = "Reset password", skip_all)]
+pub async fn reset_password(
+    State(state): State<AppState>,
+    Json(request): Json<ResetPasswordRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let claims = validate_password_reset_token(&request.token).map_err(|_| AuthAPIError::InvalidToken)?;
+    let tenant = TenantId::parse_or_error(&claims.tenant, |_| AuthAPIError::InvalidToken)?;
+    let email = Email::parse_or_error(&claims.sub, |_| AuthAPIError::InvalidToken)?;
+    let password = Password::parse_or_error(request.password.expose_secret(), |_| AuthAPIError::InvalidCredentials)?;
+    enforce_password_policy(&state.password_policy, &email, &password).await?;
+
+    let mut user_store = state.user_store.write().await;
+
+    let user = user_store.get_user(&tenant, &claims.sub).await.map_err(|_| AuthAPIError::InvalidToken)?;
+    if user.password.is_some() {
+        return Err(AuthAPIError::InvalidToken);
+    }
+
+    user_store
+        .set_password(&tenant, &claims.sub, Some(password))
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+pub struct ResetPasswordRequest {
+    pub token: Secret<String>,
+    pub password: Secret<String>,
+}
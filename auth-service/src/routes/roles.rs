@@ -0,0 +1,75 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use secrecy::ExposeSecret;
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    domain::{AuthAPIError, Email, Role, UserStoreError},
+    utils::{
+        auth::{Admin, RequireRole},
+        parsable::Parsable,
+    },
+};
+
+// Role changes apply to tokens issued after the change, so the user has to
+// sign in again to pick them up. Admins only manage users of their own tenant.
+#[tracing::instrument(name = "Assign role", skip_all)]
+pub async fn assign_role(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+    Json(request): Json<RoleAssignmentRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let tenant = admin.claims.tenant_id()?;
+    let (email, role) = request.parse()?;
+
+    state.user_store
+        .write()
+        .await
+        .assign_role(&tenant, email.as_ref().expose_secret(), &role)
+        .await
+        .map_err(map_role_error)?;
+
+    Ok(StatusCode::OK)
+}
+
+#[tracing::instrument(name = "Remove role", skip_all)]
+pub async fn remove_role(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+    Json(request): Json<RoleAssignmentRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let tenant = admin.claims.tenant_id()?;
+    let (email, role) = request.parse()?;
+
+    state.user_store
+        .write()
+        .await
+        .remove_role(&tenant, email.as_ref().expose_secret(), &role)
+        .await
+        .map_err(map_role_error)?;
+
+    Ok(StatusCode::OK)
+}
+
+fn map_role_error(e: UserStoreError) -> AuthAPIError {
+    match e {
+        UserStoreError::UserNotFound => AuthAPIError::UserNotFound,
+        UserStoreError::RoleNotFound => AuthAPIError::RoleNotFound,
+        e => AuthAPIError::UnexpectedError(e.into()),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RoleAssignmentRequest {
+    pub email: String,
+    pub role: String,
+}
+
+impl RoleAssignmentRequest {
+    fn parse(&self) -> Result<(Email, Role), AuthAPIError> {
+        let email = Email::parse_or_error(&self.email, |_| AuthAPIError::InvalidCredentials)?;
+        let role = Role::parse_or_error(&self.role, |_| AuthAPIError::InvalidCredentials)?;
+
+        Ok((email, role))
+    }
+}
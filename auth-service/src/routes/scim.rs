@@ -0,0 +1,632 @@
+use axum::{
+    extract::{rejection::JsonRejection, Path, Query, State},
+    http::{header::{CONTENT_TYPE, LOCATION}, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use secrecy::ExposeSecret;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::admin_users::revoke_user_sessions;
+use crate::{
+    AppState,
+    domain::{
+        AccountStatus, AuthAPIError, Email, Group, GroupId, GroupName, Profile, ScimError, ScimToken, TenantId,
+        TenantStoreError, User, UserId, UserQuery, UserStoreError, WebhookEventType,
+    },
+    services::scim::{
+        as_bool, get_attribute, Filter, PatchRequest, CONTENT_TYPE as SCIM_CONTENT_TYPE, GROUP_SCHEMA,
+        LIST_RESPONSE_SCHEMA, USER_SCHEMA,
+    },
+    utils::{
+        auth::{Admin, RequireRole, ScimClient},
+        parsable::Parsable,
+        webhooks::{publish_webhook_event, user_event_data},
+    },
+};
+
+const DEFAULT_COUNT: usize = 100;
+const MAX_COUNT: usize = 500;
+// Users are read this many at a time when a filter has to look at all of them
+const SCAN_PAGE_SIZE: u32 = 100;
+const DEACTIVATION_REASON: &str = "Deactivated by the identity provider";
+
+// Issues the tenant's SCIM token, replacing the previous one. It's only shown
+// in this response.
+#[tracing::instrument(name = "Create SCIM token", skip_all)]
+pub async fn create_scim_token(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let tenant = admin.claims.tenant_id()?;
+
+    let token = ScimToken::generate();
+    state.tenant_store
+        .write()
+        .await
+        .set_scim_token(&tenant, &token)
+        .await
+        .map_err(|e| match e {
+            TenantStoreError::TenantNotFound => AuthAPIError::TenantNotFound,
+            e => AuthAPIError::UnexpectedError(e.into()),
+        })?;
+
+    Ok((StatusCode::CREATED, Json(ScimTokenResponse {
+        token: token.as_ref().expose_secret().to_owned(),
+    })))
+}
+
+// Users are created without a password, so they sign in through single
+// sign-on or by resetting it.
+#[tracing::instrument(name = "Create SCIM user", skip_all)]
+pub async fn create_scim_user(
+    State(state): State<AppState>,
+    client: ScimClient,
+    body: Result<Json<Value>, JsonRejection>,
+) -> Result<Response, ScimError> {
+    let tenant = client.tenant;
+    let request = parse_body(body)?;
+    let request = request.as_object().ok_or_else(|| ScimError::InvalidSyntax("Users are objects".to_owned()))?;
+
+    let email = get_attribute(request, "userName")
+        .and_then(Value::as_str)
+        .and_then(|user_name| Email::parse(user_name).ok())
+        .ok_or_else(|| ScimError::InvalidValue("userName must be an email address".to_owned()))?;
+    let active = match get_attribute(request, "active") {
+        None | Some(Value::Null) => true,
+        Some(active) => as_bool(active).ok_or_else(|| ScimError::InvalidValue("active must be a boolean".to_owned()))?,
+    };
+    let mut profile = Profile::default();
+    apply_profile_attributes(&mut profile, request)?;
+
+    let user = User::new_passwordless(email.clone());
+    let email = email.as_ref().expose_secret();
+    let mut user_store = state.user_store.write().await;
+    user_store.add_user(&tenant, user.clone()).await.map_err(map_user_error)?;
+    if !active {
+        user_store
+            .set_status(&tenant, email, AccountStatus::Suspended, Some(DEACTIVATION_REASON.to_owned()))
+            .await
+            .map_err(map_user_error)?;
+    }
+    drop(user_store);
+
+    if profile != Profile::default() {
+        set_profile(&state, &tenant, email, profile).await?;
+    }
+    publish_webhook_event(&state.webhook_store, &tenant, WebhookEventType::UserSignedUp, user_event_data(email)).await;
+
+    let resource = user_resource(&state, &tenant, &get_user(&state, &tenant, user.id.as_ref()).await?).await?;
+    Ok(created(&resource.meta.location, &resource))
+}
+
+#[tracing::instrument(name = "Get SCIM user", skip_all)]
+pub async fn get_scim_user(
+    State(state): State<AppState>,
+    client: ScimClient,
+    Path(id): Path<String>,
+) -> Result<Response, ScimError> {
+    let user = get_user(&state, &client.tenant, &id).await?;
+    let resource = user_resource(&state, &client.tenant, &user).await?;
+
+    Ok(scim_response(StatusCode::OK, &resource))
+}
+
+#[tracing::instrument(name = "List SCIM users", skip_all)]
+pub async fn list_scim_users(
+    State(state): State<AppState>,
+    client: ScimClient,
+    Query(params): Query<ListParams>,
+) -> Result<Response, ScimError> {
+    let tenant = client.tenant;
+    let filter = params.filter.as_deref().map(Filter::parse).transpose()?;
+
+    // Identity systems look users up by userName before creating them, so
+    // that one doesn't need to go through every user.
+    let users = match filter.as_ref().and_then(|filter| filter.equality_on("userName")) {
+        Some(user_name) => {
+            let user = state.user_store.read().await.get_user(&tenant, &user_name.to_lowercase()).await;
+            match user {
+                Ok(user) => vec![user],
+                Err(UserStoreError::UserNotFound | UserStoreError::InvalidCredentials) => Vec::new(),
+                Err(e) => return Err(map_user_error(e)),
+            }
+        }
+        None => all_users(&state, &tenant).await?,
+    };
+
+    let mut resources = Vec::new();
+    for user in &users {
+        resources.push(to_value(user_resource(&state, &tenant, user).await?)?);
+    }
+
+    list_response(resources, filter.as_ref(), &params)
+}
+
+// Only `active`, `displayName`, `locale` and `timezone` can change. The
+// userName is the user's email, which stays the same.
+#[tracing::instrument(name = "Patch SCIM user", skip_all)]
+pub async fn patch_scim_user(
+    State(state): State<AppState>,
+    client: ScimClient,
+    Path(id): Path<String>,
+    body: Result<Json<PatchRequest>, JsonRejection>,
+) -> Result<Response, ScimError> {
+    let tenant = client.tenant;
+    let request = parse_body(body)?;
+    request.validate()?;
+
+    let user = get_user(&state, &tenant, &id).await?;
+    let email = user.email.as_ref().expose_secret();
+    let original = to_value(user_resource(&state, &tenant, &user).await?)?;
+    let mut patched = original.clone();
+    request.apply(&mut patched)?;
+
+    let original = original.as_object().cloned().unwrap_or_default();
+    let patched = patched.as_object().cloned().unwrap_or_default();
+    let user_name = get_attribute(&patched, "userName").and_then(Value::as_str);
+    if !user_name.is_some_and(|user_name| user_name.eq_ignore_ascii_case(email)) {
+        return Err(ScimError::Mutability("userName can't be changed".to_owned()));
+    }
+
+    let active = match get_attribute(&patched, "active") {
+        None | Some(Value::Null) => None,
+        Some(active) => Some(as_bool(active).ok_or_else(|| ScimError::InvalidValue("active must be a boolean".to_owned()))?),
+    };
+    let profile_changed = ["displayName", "locale", "timezone"]
+        .iter()
+        .any(|attr| get_attribute(&original, attr) != get_attribute(&patched, attr));
+    let profile = match profile_changed {
+        true => {
+            let mut profile = get_profile(&state, &tenant, email).await?;
+            apply_profile_attributes(&mut profile, &patched)?;
+            Some(profile)
+        }
+        false => None,
+    };
+
+    match active {
+        Some(true) if user.status != AccountStatus::Active => {
+            set_status(&state, &tenant, email, AccountStatus::Active, None).await?;
+            if user.status == AccountStatus::PendingVerification {
+                publish_webhook_event(&state.webhook_store, &tenant, WebhookEventType::UserVerified, user_event_data(email)).await;
+            }
+        }
+        Some(false) if user.status == AccountStatus::Active => {
+            set_status(&state, &tenant, email, AccountStatus::Suspended, Some(DEACTIVATION_REASON.to_owned())).await?;
+            revoke_user_sessions(&state, &tenant, &user.email).await.map_err(|e| ScimError::UnexpectedError(e.into()))?;
+        }
+        _ => {}
+    }
+    if let Some(profile) = profile {
+        set_profile(&state, &tenant, email, profile).await?;
+    }
+
+    let resource = user_resource(&state, &tenant, &get_user(&state, &tenant, &id).await?).await?;
+    Ok(scim_response(StatusCode::OK, &resource))
+}
+
+#[tracing::instrument(name = "Delete SCIM user", skip_all)]
+pub async fn delete_scim_user(
+    State(state): State<AppState>,
+    client: ScimClient,
+    Path(id): Path<String>,
+) -> Result<Response, ScimError> {
+    let tenant = client.tenant;
+    let user = get_user(&state, &tenant, &id).await?;
+    let email = user.email.as_ref().expose_secret();
+
+    state.user_store.write().await.delete_user(&tenant, email).await.map_err(map_user_error)?;
+    publish_webhook_event(&state.webhook_store, &tenant, WebhookEventType::UserDeleted, user_event_data(email)).await;
+    revoke_user_sessions(&state, &tenant, &user.email).await.map_err(|e| ScimError::UnexpectedError(e.into()))?;
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+#[tracing::instrument(name = "Create SCIM group", skip_all)]
+pub async fn create_scim_group(
+    State(state): State<AppState>,
+    client: ScimClient,
+    body: Result<Json<Value>, JsonRejection>,
+) -> Result<Response, ScimError> {
+    let tenant = client.tenant;
+    let request = parse_body(body)?;
+    let request = request.as_object().ok_or_else(|| ScimError::InvalidSyntax("Groups are objects".to_owned()))?;
+
+    let display_name = parse_group_name(get_attribute(request, "displayName"))?;
+    let members = parse_members(&state, &tenant, get_attribute(request, "members")).await?;
+
+    let group = Group::new(display_name);
+    let mut user_store = state.user_store.write().await;
+    user_store.add_group(&tenant, group.clone()).await.map_err(map_user_error)?;
+    for member in &members {
+        user_store
+            .add_group_member(&tenant, &group.id, member.email.as_ref().expose_secret())
+            .await
+            .map_err(map_user_error)?;
+    }
+    drop(user_store);
+
+    let resource = group_resource(&state, &tenant, &group).await?;
+    Ok(created(&resource.meta.location, &resource))
+}
+
+#[tracing::instrument(name = "Get SCIM group", skip_all)]
+pub async fn get_scim_group(
+    State(state): State<AppState>,
+    client: ScimClient,
+    Path(id): Path<String>,
+) -> Result<Response, ScimError> {
+    let group = get_group(&state, &client.tenant, &id).await?;
+    let resource = group_resource(&state, &client.tenant, &group).await?;
+
+    Ok(scim_response(StatusCode::OK, &resource))
+}
+
+#[tracing::instrument(name = "List SCIM groups", skip_all)]
+pub async fn list_scim_groups(
+    State(state): State<AppState>,
+    client: ScimClient,
+    Query(params): Query<ListParams>,
+) -> Result<Response, ScimError> {
+    let tenant = client.tenant;
+    let filter = params.filter.as_deref().map(Filter::parse).transpose()?;
+
+    let groups = state.user_store.read().await.list_groups(&tenant).await.map_err(map_user_error)?;
+    let mut resources = Vec::new();
+    for group in &groups {
+        resources.push(to_value(group_resource(&state, &tenant, group).await?)?);
+    }
+
+    list_response(resources, filter.as_ref(), &params)
+}
+
+// Renames the group and changes its members to whatever the operations leave
+// in `displayName` and `members`.
+#[tracing::instrument(name = "Patch SCIM group", skip_all)]
+pub async fn patch_scim_group(
+    State(state): State<AppState>,
+    client: ScimClient,
+    Path(id): Path<String>,
+    body: Result<Json<PatchRequest>, JsonRejection>,
+) -> Result<Response, ScimError> {
+    let tenant = client.tenant;
+    let request = parse_body(body)?;
+    request.validate()?;
+
+    let group = get_group(&state, &tenant, &id).await?;
+    let original = group_resource(&state, &tenant, &group).await?;
+    let mut patched = to_value(&original)?;
+    request.apply(&mut patched)?;
+
+    let patched = patched.as_object().cloned().unwrap_or_default();
+    let display_name = parse_group_name(get_attribute(&patched, "displayName"))?;
+    let members = parse_members(&state, &tenant, get_attribute(&patched, "members")).await?;
+
+    let mut user_store = state.user_store.write().await;
+    if display_name != group.display_name {
+        user_store.rename_group(&tenant, &group.id, display_name).await.map_err(map_user_error)?;
+    }
+    for removed in original.members.iter().filter(|member| !members.iter().any(|user| user.id.as_ref() == member.value)) {
+        user_store.remove_group_member(&tenant, &group.id, &removed.display).await.map_err(map_user_error)?;
+    }
+    for added in members.iter().filter(|user| !original.members.iter().any(|member| member.value == user.id.as_ref())) {
+        user_store
+            .add_group_member(&tenant, &group.id, added.email.as_ref().expose_secret())
+            .await
+            .map_err(map_user_error)?;
+    }
+    drop(user_store);
+
+    let resource = group_resource(&state, &tenant, &get_group(&state, &tenant, &id).await?).await?;
+    Ok(scim_response(StatusCode::OK, &resource))
+}
+
+#[tracing::instrument(name = "Delete SCIM group", skip_all)]
+pub async fn delete_scim_group(
+    State(state): State<AppState>,
+    client: ScimClient,
+    Path(id): Path<String>,
+) -> Result<Response, ScimError> {
+    let group = get_group(&state, &client.tenant, &id).await?;
+
+    state.user_store
+        .write()
+        .await
+        .delete_group(&client.tenant, &group.id)
+        .await
+        .map_err(map_user_error)?;
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+async fn get_user(state: &AppState, tenant: &TenantId, id: &str) -> Result<User, ScimError> {
+    let id = UserId::parse_or_error(id, |_| ScimError::NotFound)?;
+
+    state.user_store.read().await.get_user_by_id(tenant, &id).await.map_err(map_user_error)
+}
+
+async fn get_group(state: &AppState, tenant: &TenantId, id: &str) -> Result<Group, ScimError> {
+    let id = GroupId::parse_or_error(id, |_| ScimError::NotFound)?;
+
+    state.user_store.read().await.get_group(tenant, &id).await.map_err(map_user_error)
+}
+
+async fn all_users(state: &AppState, tenant: &TenantId) -> Result<Vec<User>, ScimError> {
+    let user_store = state.user_store.read().await;
+    let mut users = Vec::new();
+    let mut query = UserQuery { search: None, page: 1, per_page: SCAN_PAGE_SIZE };
+
+    loop {
+        let page = user_store.list_users(tenant, &query).await.map_err(map_user_error)?;
+        let done = page.users.len() < SCAN_PAGE_SIZE as usize;
+        users.extend(page.users);
+        if done || users.len() as u64 >= page.total {
+            return Ok(users);
+        }
+        query.page += 1;
+    }
+}
+
+async fn get_profile(state: &AppState, tenant: &TenantId, email: &str) -> Result<Profile, ScimError> {
+    state.profile_store
+        .read()
+        .await
+        .get_profile(tenant, email)
+        .await
+        .map_err(|e| ScimError::UnexpectedError(e.into()))
+}
+
+async fn set_profile(state: &AppState, tenant: &TenantId, email: &str, profile: Profile) -> Result<(), ScimError> {
+    state.profile_store
+        .write()
+        .await
+        .set_profile(tenant, email, profile)
+        .await
+        .map_err(|e| ScimError::UnexpectedError(e.into()))
+}
+
+async fn set_status(
+    state: &AppState,
+    tenant: &TenantId,
+    email: &str,
+    status: AccountStatus,
+    reason: Option<String>,
+) -> Result<(), ScimError> {
+    state.user_store
+        .write()
+        .await
+        .set_status(tenant, email, status, reason)
+        .await
+        .map_err(map_user_error)
+}
+
+// Profile attributes left out or set to `null` are cleared.
+fn apply_profile_attributes(profile: &mut Profile, resource: &serde_json::Map<String, Value>) -> Result<(), ScimError> {
+    profile.display_name = parse_optional(get_attribute(resource, "displayName"), "displayName")?;
+    profile.locale = parse_optional(get_attribute(resource, "locale"), "locale")?;
+    profile.timezone = parse_optional(get_attribute(resource, "timezone"), "timezone")?;
+    Ok(())
+}
+
+fn parse_optional<T: Parsable>(value: Option<&Value>, attr: &str) -> Result<Option<T>, ScimError> {
+    match value {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(value)) => T::parse_or_error(value, |_| ScimError::InvalidValue(format!("Invalid {}", attr))).map(Some),
+        Some(_) => Err(ScimError::InvalidValue(format!("{} must be a string", attr))),
+    }
+}
+
+fn parse_group_name(value: Option<&Value>) -> Result<GroupName, ScimError> {
+    value
+        .and_then(Value::as_str)
+        .and_then(|name| GroupName::parse(name).ok())
+        .ok_or_else(|| ScimError::InvalidValue("displayName is required".to_owned()))
+}
+
+// Members are given by user id. They all have to be users of the tenant.
+async fn parse_members(state: &AppState, tenant: &TenantId, value: Option<&Value>) -> Result<Vec<User>, ScimError> {
+    let items = match value {
+        None | Some(Value::Null) => return Ok(Vec::new()),
+        Some(Value::Array(items)) => items,
+        Some(_) => return Err(ScimError::InvalidValue("members must be a list".to_owned())),
+    };
+
+    let mut members: Vec<User> = Vec::new();
+    for item in items {
+        let id = item
+            .as_object()
+            .and_then(|item| get_attribute(item, "value"))
+            .and_then(Value::as_str)
+            .ok_or_else(|| ScimError::InvalidValue("Members need a value".to_owned()))?;
+        let user = get_user(state, tenant, id).await.map_err(|e| match e {
+            ScimError::NotFound => ScimError::InvalidValue(format!("Unknown member {}", id)),
+            e => e,
+        })?;
+        if !members.iter().any(|member| member.id == user.id) {
+            members.push(user);
+        }
+    }
+    Ok(members)
+}
+
+async fn user_resource(state: &AppState, tenant: &TenantId, user: &User) -> Result<ScimUser, ScimError> {
+    let email = user.email.as_ref().expose_secret();
+    let profile = get_profile(state, tenant, email).await?;
+    let groups = state.user_store.read().await.get_user_groups(tenant, email).await.map_err(map_user_error)?;
+
+    Ok(ScimUser {
+        schemas: vec![USER_SCHEMA.to_owned()],
+        id: user.id.as_ref().to_owned(),
+        user_name: email.to_owned(),
+        display_name: profile.display_name.map(|name| name.as_ref().to_owned()),
+        locale: profile.locale.map(|locale| locale.as_ref().to_owned()),
+        timezone: profile.timezone.map(|timezone| timezone.as_ref().to_owned()),
+        active: user.status == AccountStatus::Active,
+        emails: vec![ScimEmail { value: email.to_owned(), kind: "work".to_owned(), primary: true }],
+        groups: groups
+            .iter()
+            .map(|group| ScimReference {
+                value: group.id.as_ref().to_owned(),
+                display: group.display_name.as_ref().to_owned(),
+            })
+            .collect(),
+        meta: ScimMeta::new("User", user.id.as_ref()),
+    })
+}
+
+async fn group_resource(state: &AppState, tenant: &TenantId, group: &Group) -> Result<ScimGroup, ScimError> {
+    let members = state.user_store.read().await.list_group_members(tenant, &group.id).await.map_err(map_user_error)?;
+
+    Ok(ScimGroup {
+        schemas: vec![GROUP_SCHEMA.to_owned()],
+        id: group.id.as_ref().to_owned(),
+        display_name: group.display_name.as_ref().to_owned(),
+        members: members
+            .iter()
+            .map(|user| ScimReference {
+                value: user.id.as_ref().to_owned(),
+                display: user.email.as_ref().expose_secret().to_owned(),
+            })
+            .collect(),
+        meta: ScimMeta::new("Group", group.id.as_ref()),
+    })
+}
+
+// Filters the resources, then returns the page asked for. `startIndex` starts
+// at 1 and a `count` of 0 only returns the total.
+fn list_response(resources: Vec<Value>, filter: Option<&Filter>, params: &ListParams) -> Result<Response, ScimError> {
+    let resources: Vec<Value> = resources
+        .into_iter()
+        .filter(|resource| filter.is_none_or(|filter| filter.matches(resource)))
+        .collect();
+
+    let start_index = params.start_index.unwrap_or(1).max(1) as usize;
+    let count = params.count.map_or(DEFAULT_COUNT, |count| count.max(0) as usize).min(MAX_COUNT);
+    let total_results = resources.len();
+    let page: Vec<Value> = resources.into_iter().skip(start_index - 1).take(count).collect();
+
+    Ok(scim_response(StatusCode::OK, &ListResponse {
+        schemas: vec![LIST_RESPONSE_SCHEMA.to_owned()],
+        total_results,
+        start_index,
+        items_per_page: page.len(),
+        resources: page,
+    }))
+}
+
+fn parse_body<T>(body: Result<Json<T>, JsonRejection>) -> Result<T, ScimError> {
+    body.map(|Json(body)| body).map_err(|e| ScimError::InvalidSyntax(e.body_text()))
+}
+
+fn to_value<T: Serialize>(resource: T) -> Result<Value, ScimError> {
+    serde_json::to_value(resource).map_err(|e| ScimError::UnexpectedError(e.into()))
+}
+
+fn scim_response<T: Serialize>(status: StatusCode, body: &T) -> Response {
+    (status, [(CONTENT_TYPE, SCIM_CONTENT_TYPE)], Json(body)).into_response()
+}
+
+fn created<T: Serialize>(location: &str, body: &T) -> Response {
+    let mut response = scim_response(StatusCode::CREATED, body);
+    if let Ok(location) = location.parse() {
+        response.headers_mut().insert(LOCATION, location);
+    }
+    response
+}
+
+fn map_user_error(e: UserStoreError) -> ScimError {
+    match e {
+        UserStoreError::UserNotFound | UserStoreError::GroupNotFound => ScimError::NotFound,
+        UserStoreError::UserAlreadyExists => ScimError::Uniqueness("A user with this userName already exists".to_owned()),
+        UserStoreError::GroupAlreadyExists => ScimError::Uniqueness("A group with this displayName already exists".to_owned()),
+        e => ScimError::UnexpectedError(e.into()),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ListParams {
+    pub filter: Option<String>,
+    #[serde(rename = "startIndex")]
+    pub start_index: Option<i64>,
+    pub count: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ScimTokenResponse {
+    pub token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ScimReference {
+    pub value: String,
+    pub display: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ScimEmail {
+    pub value: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub primary: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ScimMeta {
+    #[serde(rename = "resourceType")]
+    pub resource_type: String,
+    pub location: String,
+}
+
+impl ScimMeta {
+    fn new(resource_type: &str, id: &str) -> Self {
+        Self {
+            resource_type: resource_type.to_owned(),
+            location: format!("/scim/v2/{}s/{}", resource_type, id),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ScimUser {
+    pub schemas: Vec<String>,
+    pub id: String,
+    #[serde(rename = "userName")]
+    pub user_name: String,
+    #[serde(rename = "displayName", default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    pub active: bool,
+    pub emails: Vec<ScimEmail>,
+    #[serde(default)]
+    pub groups: Vec<ScimReference>,
+    pub meta: ScimMeta,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ScimGroup {
+    pub schemas: Vec<String>,
+    pub id: String,
+    #[serde(rename = "displayName")]
+    pub display_name: String,
+    #[serde(default)]
+    pub members: Vec<ScimReference>,
+    pub meta: ScimMeta,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListResponse {
+    pub schemas: Vec<String>,
+    #[serde(rename = "totalResults")]
+    pub total_results: usize,
+    #[serde(rename = "startIndex")]
+    pub start_index: usize,
+    #[serde(rename = "itemsPerPage")]
+    pub items_per_page: usize,
+    #[serde(rename = "Resources")]
+    pub resources: Vec<Value>,
+}
@@ -1,11 +1,55 @@
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use axum::{extract::{Path, State}, http::StatusCode, response::{IntoResponse, Response}, Json};
 use serde::{Deserialize, Serialize};
 use secrecy::{ExposeSecret, Secret};
 
-use crate::{domain::{AuthAPIError, User}, AppState};
+use super::{
+    consents::{current_policies, parse_acceptance, record_consents, AcceptedPolicy},
+    tenants::find_tenant,
+};
+use crate::{
+    domain::{pending_policies, AuditEventKind, AuthAPIError, ClientInfo, TenantId, User, UserStoreError, Username, WebhookEventType},
+    utils::{
+        audit::{audit_event, record_audit_event},
+        parsable::Parsable,
+        passwords::enforce_password_policy,
+        webhooks::{publish_webhook_event, user_event_data},
+    },
+    AppState,
+};
 
 #[tracing::instrument(name = "Signup", skip_all)]
-pub async fn signup(State(state): State<AppState>, Json(request): Json<SignupRequest>) -> Result<impl IntoResponse, AuthAPIError> {
+pub async fn signup(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Json(request): Json<SignupRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    sign_up(&state, &TenantId::default(), client, request).await
+}
+
+#[tracing::instrument(name = "Tenant signup", skip_all)]
+pub async fn tenant_signup(
+    State(state): State<AppState>,
+    Path(tenant): Path<String>,
+    client: ClientInfo,
+    Json(request): Json<SignupRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let tenant = find_tenant(&state, &tenant).await?;
+    sign_up(&state, &tenant, client, request).await
+}
+
+async fn sign_up(
+    state: &AppState,
+    tenant: &TenantId,
+    client: ClientInfo,
+    request: SignupRequest,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let actor = request.email.expose_secret().clone();
+    let result = create_user(state, tenant, request).await;
+    record_audit_event(state, audit_event(tenant, &actor, AuditEventKind::Signup, client, &result)).await;
+    result
+}
+
+async fn create_user(state: &AppState, tenant: &TenantId, request: SignupRequest) -> Result<Response, AuthAPIError> {
     let email = request.email;
     let password = request.password;
 
@@ -14,21 +58,40 @@ pub async fn signup(State(state): State<AppState>, Json(request): Json<SignupReq
         Ok(user) => user,
         Err(_) => return Err(AuthAPIError::InvalidCredentials),
     };
+    let user = match request.username {
+        Some(username) => user.with_username(Username::parse_or_error(username, |_| AuthAPIError::InvalidCredentials)?),
+        None => user,
+    };
 
+    if let Some(password) = &user.password {
+        enforce_password_policy(&state.password_policy, &user.email, password).await?;
+    }
+
+    let current = current_policies(state, tenant).await?;
+    let consents = parse_acceptance(&current, &request.accepted_policies)?;
+    if !pending_policies(current, &consents).is_empty() {
+        return Err(AuthAPIError::PolicyAcceptanceRequired);
+    }
+
+    let email = user.email.as_ref().expose_secret().to_owned();
     let mut user_store = state.user_store.write().await;
 
-    if user_store.get_user(user.email.as_ref().expose_secret()).await.is_ok() {
+    if user_store.get_user(tenant, &email).await.is_ok() {
         return Err(AuthAPIError::UserAlreadyExists);
     }
 
-    match user_store.add_user(user).await {
-        Ok(_) => {
-            Ok((StatusCode::CREATED, Json(SignupResponse {
-                message: "User created successfully".to_string(),
-            })).into_response())
-        },
-        Err(e) => Err(AuthAPIError::UnexpectedError(e.into())),
-    }
+    user_store.add_user(tenant, user).await.map_err(|e| match e {
+        UserStoreError::UsernameTaken => AuthAPIError::UsernameTaken,
+        e => AuthAPIError::UnexpectedError(e.into()),
+    })?;
+    drop(user_store);
+
+    record_consents(state, tenant, &email, consents).await?;
+    publish_webhook_event(&state.webhook_store, tenant, WebhookEventType::UserSignedUp, user_event_data(&email)).await;
+
+    Ok((StatusCode::CREATED, Json(SignupResponse {
+        message: "User created successfully".to_string(),
+    })).into_response())
 }
 
 #[derive(Deserialize)]
@@ -37,6 +100,12 @@ pub struct SignupRequest {
     pub password: Secret<String>,
     #[serde(rename = "requires2FA")]
     pub requires_2fa: bool,
+    // Lets the user sign in with it instead of their email
+    #[serde(default)]
+    pub username: Option<String>,
+    // Every current policy, as listed by `/policies`, once any is published
+    #[serde(rename = "acceptedPolicies", default)]
+    pub accepted_policies: Vec<AcceptedPolicy>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
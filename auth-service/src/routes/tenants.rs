@@ -0,0 +1,93 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState,
+    domain::{AuthAPIError, Tenant, TenantId, TenantStoreError},
+    utils::{
+        auth::{Admin, RequireRole},
+        parsable::Parsable,
+    },
+};
+
+// Tenants are managed by the admins of the default tenant, who run the
+// deployment. Admins of other tenants only manage their own users.
+#[tracing::instrument(name = "Create tenant", skip_all)]
+pub async fn create_tenant(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+    Json(request): Json<CreateTenantRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    require_default_tenant(&admin)?;
+
+    let id = TenantId::parse_or_error(&request.id, |_| AuthAPIError::InvalidCredentials)?;
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(AuthAPIError::InvalidCredentials);
+    }
+
+    let tenant = Tenant::new(id, name.to_owned());
+    state.tenant_store
+        .write()
+        .await
+        .add_tenant(tenant.clone())
+        .await
+        .map_err(|e| match e {
+            TenantStoreError::TenantAlreadyExists => AuthAPIError::TenantAlreadyExists,
+            e => AuthAPIError::UnexpectedError(e.into()),
+        })?;
+
+    Ok((StatusCode::CREATED, Json(tenant)))
+}
+
+#[tracing::instrument(name = "List tenants", skip_all)]
+pub async fn list_tenants(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    require_default_tenant(&admin)?;
+
+    let tenants = state.tenant_store
+        .read()
+        .await
+        .list_tenants()
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    Ok((StatusCode::OK, Json(ListTenantsResponse { tenants })))
+}
+
+fn require_default_tenant(admin: &RequireRole<Admin>) -> Result<(), AuthAPIError> {
+    match admin.claims.tenant_id()?.is_default() {
+        true => Ok(()),
+        false => Err(AuthAPIError::Forbidden),
+    }
+}
+
+// Resolves the tenant named in a route path. Malformed ids are reported as
+// unknown tenants rather than bad requests.
+pub(crate) async fn find_tenant(state: &AppState, id: &str) -> Result<TenantId, AuthAPIError> {
+    let id = TenantId::parse_or_error(id, |_| AuthAPIError::TenantNotFound)?;
+
+    state.tenant_store
+        .read()
+        .await
+        .get_tenant(&id)
+        .await
+        .map(|tenant| tenant.id)
+        .map_err(|e| match e {
+            TenantStoreError::TenantNotFound => AuthAPIError::TenantNotFound,
+            e => AuthAPIError::UnexpectedError(e.into()),
+        })
+}
+
+#[derive(Deserialize)]
+pub struct CreateTenantRequest {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListTenantsResponse {
+    pub tenants: Vec<Tenant>,
+}
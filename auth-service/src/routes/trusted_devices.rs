@@ -0,0 +1,110 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use axum_extra::extract::{cookie::Cookie, CookieJar};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState,
+    domain::{AuthAPIError, TrustedDevice, TrustedDeviceId, TrustedDeviceStoreError},
+    utils::{
+        auth::{validate_auth_cookie, validate_trusted_device_cookie},
+        constants::TRUSTED_DEVICE_COOKIE_NAME,
+        parsable::Parsable,
+    },
+};
+
+// The user's unexpired trusted devices, marking the one the request came from.
+#[tracing::instrument(name = "List trusted devices", skip_all)]
+pub async fn list_trusted_devices(
+    State(state): State<AppState>,
+    jar: CookieJar,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let claims = validate_auth_cookie(&jar, &state).await?;
+    let tenant = claims.tenant_id()?;
+    let current = current_device_id(&jar);
+    let now = Utc::now();
+
+    let devices = state.trusted_device_store
+        .read()
+        .await
+        .list_devices(&tenant, &claims.sub)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?
+        .iter()
+        .filter(|device| !device.is_expired(now))
+        .map(|device| TrustedDeviceResponse::new(device, current.as_ref()))
+        .collect();
+
+    Ok((StatusCode::OK, Json(ListTrustedDevicesResponse { devices })))
+}
+
+// Revoking the device the request came from also drops its cookie.
+#[tracing::instrument(name = "Revoke trusted device", skip_all)]
+pub async fn revoke_trusted_device(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Json(request): Json<RevokeTrustedDeviceRequest>,
+) -> Result<(CookieJar, impl IntoResponse), AuthAPIError> {
+    let claims = validate_auth_cookie(&jar, &state).await?;
+    claims.forbid_impersonation()?;
+    let tenant = claims.tenant_id()?;
+    let id = TrustedDeviceId::parse_or_error(&request.id, |_| AuthAPIError::TrustedDeviceNotFound)?;
+
+    state.trusted_device_store
+        .write()
+        .await
+        .remove_device(&tenant, &claims.sub, &id)
+        .await
+        .map_err(|e| match e {
+            TrustedDeviceStoreError::DeviceNotFound => AuthAPIError::TrustedDeviceNotFound,
+            e => AuthAPIError::UnexpectedError(e.into()),
+        })?;
+
+    let jar = match current_device_id(&jar) == Some(id) {
+        true => jar.remove(Cookie::build(TRUSTED_DEVICE_COOKIE_NAME).path("/")),
+        false => jar,
+    };
+
+    Ok((jar, StatusCode::OK))
+}
+
+fn current_device_id(jar: &CookieJar) -> Option<TrustedDeviceId> {
+    validate_trusted_device_cookie(jar).and_then(|claims| TrustedDeviceId::parse(claims.jti).ok())
+}
+
+#[derive(Deserialize)]
+pub struct RevokeTrustedDeviceRequest {
+    pub id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct TrustedDeviceResponse {
+    pub id: String,
+    #[serde(rename = "userAgent")]
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: DateTime<Utc>,
+    // Whether this is the device the request came from
+    pub current: bool,
+}
+
+impl TrustedDeviceResponse {
+    fn new(device: &TrustedDevice, current: Option<&TrustedDeviceId>) -> Self {
+        Self {
+            id: device.id.as_ref().to_owned(),
+            user_agent: device.user_agent.clone(),
+            ip: device.ip.clone(),
+            created_at: device.created_at,
+            expires_at: device.expires_at,
+            current: current == Some(&device.id),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListTrustedDevicesResponse {
+    pub devices: Vec<TrustedDeviceResponse>,
+}
@@ -1,5 +1,5 @@
 use axum::{
-    extract::State,
+    extract::{Path, State},
     http::StatusCode,
     Json,
     response::IntoResponse,
@@ -8,23 +8,75 @@ use axum_extra::extract::CookieJar;
 use serde::Deserialize;
 use color_eyre::eyre::Result;
 
+use super::tenants::find_tenant;
 use crate::{
     AppState,
     domain::{
+        AuditEventKind,
         AuthAPIError,
+        ClientInfo,
         Email,
         LoginAttemptId,
+        TenantId,
         TwoFACode
     },
-    utils::{auth::generate_auth_cookie, parsable::Parsable},
+    utils::{
+        audit::{audit_event, record_audit_event},
+        auth::issue_auth_cookie,
+        devices::{check_sign_in_device, trust_device},
+        parsable::Parsable,
+    },
 };
 
 #[tracing::instrument(name = "verify 2FA", skip_all)]
 pub async fn verify_2fa(
     State(state): State<AppState>,
     jar: CookieJar,
+    client: ClientInfo,
     Json(request): Json<Verify2FARequest>,
 ) -> Result<(CookieJar, impl IntoResponse), AuthAPIError> {
+    verify_code(&state, &TenantId::default(), jar, client, request).await
+}
+
+#[tracing::instrument(name = "Tenant verify 2FA", skip_all)]
+pub async fn tenant_verify_2fa(
+    State(state): State<AppState>,
+    Path(tenant): Path<String>,
+    jar: CookieJar,
+    client: ClientInfo,
+    Json(request): Json<Verify2FARequest>,
+) -> Result<(CookieJar, impl IntoResponse), AuthAPIError> {
+    let tenant = find_tenant(&state, &tenant).await?;
+    verify_code(&state, &tenant, jar, client, request).await
+}
+
+async fn verify_code(
+    state: &AppState,
+    tenant: &TenantId,
+    jar: CookieJar,
+    client: ClientInfo,
+    request: Verify2FARequest,
+) -> Result<(CookieJar, StatusCode), AuthAPIError> {
+    let actor = request.email.clone();
+    let result = check_code(state, tenant, jar, &client, request).await;
+
+    if let (Ok(_), Ok(email)) = (&result, Email::parse(&actor)) {
+        check_sign_in_device(state, tenant, &email, &client).await;
+    }
+
+    record_audit_event(state, audit_event(tenant, &actor, AuditEventKind::Verify2FA, client, &result)).await;
+    result
+}
+
+// Codes are stored per tenant, so a code sent for one tenant's login can't
+// complete a login to another tenant.
+async fn check_code(
+    state: &AppState,
+    tenant: &TenantId,
+    jar: CookieJar,
+    client: &ClientInfo,
+    request: Verify2FARequest,
+) -> Result<(CookieJar, StatusCode), AuthAPIError> {
     let email = Email::parse_or_error(&request.email, |_| AuthAPIError::InvalidCredentials)?;
 
     let login_attempt_id = LoginAttemptId::parse_or_error(&request.login_attempt_id, |_| AuthAPIError::InvalidCredentials)?;
@@ -33,7 +85,7 @@ pub async fn verify_2fa(
 
     let mut two_fa_code_store = state.two_fa_code_store.write().await;
     
-    let code_tuple = two_fa_code_store.get_code(email.clone()).await
+    let code_tuple = two_fa_code_store.get_code(tenant, email.clone()).await
         .map_err(|_| AuthAPIError::IncorrectCredentials)?;
 
     if login_attempt_id !=  code_tuple.0 {
@@ -44,14 +96,17 @@ pub async fn verify_2fa(
         return Err(AuthAPIError::IncorrectCredentials);
     }
 
-    let _ = two_fa_code_store.remove_code(email.clone()).await;
+    let _ = two_fa_code_store.remove_code(tenant, email.clone()).await;
 
-    if let Ok(auth_cookie) = generate_auth_cookie(&email) {
-        let update_jar = jar.add(auth_cookie);
-        Ok((update_jar, StatusCode::OK))
-    } else {
-        Ok((jar, StatusCode::OK))
+    // The account may have been suspended since the code was sent.
+    let auth_cookie = issue_auth_cookie(state.user_store.clone(), tenant, &email).await?;
+    let mut jar = jar.add(auth_cookie);
+
+    if request.remember_device {
+        jar = jar.add(trust_device(state, tenant, &email, client).await?);
     }
+
+    Ok((jar, StatusCode::OK))
 }
 
 #[derive(Deserialize)]
@@ -61,4 +116,7 @@ pub struct Verify2FARequest {
     pub login_attempt_id: String,
     #[serde(rename = "2FACode")]
     pub two_fa_code: String,
+    // Skip 2FA on this browser for the next 30 days
+    #[serde(default, rename = "rememberDevice")]
+    pub remember_device: bool,
 }
\ No newline at end of file
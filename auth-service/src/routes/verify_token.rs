@@ -1,10 +1,10 @@
 use axum::{extract::State, response::IntoResponse, Json};
 use reqwest::StatusCode;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use secrecy::Secret;
 
 use crate::utils::auth::validate_token;
-use crate::{AuthAPIError, AppState};
+use crate::AppState;
 
 #[tracing::instrument(name = "verify token", skip_all)]
 pub async fn verify_token(
@@ -12,10 +12,14 @@ pub async fn verify_token(
     Json(request): Json<VerifyTokenRequest>
 ) -> impl IntoResponse {
     let token = Secret::new(request.token);
-    let banned_token_store = state.banned_token_store.clone();
-    match validate_token(banned_token_store, &token).await {
-        Ok(_) => StatusCode::OK.into_response(),
-        Err(_) => AuthAPIError::InvalidToken.into_response(),
+    match validate_token(state.banned_token_store.clone(), state.user_store.clone(), &token).await {
+        Ok(claims) => (StatusCode::OK, Json(VerifyTokenResponse {
+            email: claims.sub,
+            tenant: claims.tenant,
+            roles: claims.roles,
+            permissions: claims.permissions,
+        })).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
@@ -23,3 +27,13 @@ pub async fn verify_token(
 pub struct VerifyTokenRequest {
     pub token: String,
 }
+
+// Lets callers such as app-service authorize requests without decoding the
+// token themselves.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct VerifyTokenResponse {
+    pub email: String,
+    pub tenant: String,
+    pub roles: Vec<String>,
+    pub permissions: Vec<String>,
+}
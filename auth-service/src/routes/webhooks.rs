@@ -0,0 +1,250 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+use secrecy::ExposeSecret;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState,
+    domain::{
+        AuthAPIError, TenantId, WebhookDelivery, WebhookDeliveryStatus, WebhookEndpoint, WebhookEndpointId,
+        WebhookEventType, WebhookStoreError,
+    },
+    utils::{auth::{Admin, RequireRole}, parsable::Parsable},
+};
+
+const MAX_URL_LENGTH: usize = 2048;
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 200;
+
+// Admins subscribe endpoints to the user events of their own tenant. The
+// signing secret is only ever shown in this response.
+#[tracing::instrument(name = "Create webhook", skip_all)]
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+    Json(request): Json<CreateWebhookRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let tenant = admin.claims.tenant_id()?;
+
+    let url = Url::parse(&request.url).map_err(|_| AuthAPIError::InvalidCredentials)?;
+    if !matches!(url.scheme(), "http" | "https") || request.url.len() > MAX_URL_LENGTH {
+        return Err(AuthAPIError::InvalidCredentials);
+    }
+    if let Err(e) = state.webhook_address_policy.check_url(&url).await {
+        tracing::info!("Rejected webhook URL: {}", e);
+        return Err(AuthAPIError::InvalidCredentials);
+    }
+
+    let mut events = Vec::new();
+    for event in &request.events {
+        let event = WebhookEventType::parse_or_error(event, |_| AuthAPIError::InvalidCredentials)?;
+        if !events.contains(&event) {
+            events.push(event);
+        }
+    }
+    if events.is_empty() {
+        return Err(AuthAPIError::InvalidCredentials);
+    }
+
+    let endpoint = WebhookEndpoint::new(tenant, url.to_string(), events);
+    state.webhook_store
+        .write()
+        .await
+        .add_endpoint(endpoint.clone())
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    Ok((StatusCode::CREATED, Json(CreateWebhookResponse {
+        webhook: WebhookResponse::from(&endpoint),
+        secret: endpoint.secret.expose_secret().to_owned(),
+    })))
+}
+
+#[tracing::instrument(name = "List webhooks", skip_all)]
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let tenant = admin.claims.tenant_id()?;
+
+    let webhooks = state.webhook_store
+        .read()
+        .await
+        .list_endpoints(&tenant)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?
+        .iter()
+        .map(WebhookResponse::from)
+        .collect();
+
+    Ok((StatusCode::OK, Json(ListWebhooksResponse { webhooks })))
+}
+
+// Pending deliveries to the endpoint are dropped along with it.
+#[tracing::instrument(name = "Delete webhook", skip_all)]
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+    Json(request): Json<DeleteWebhookRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let tenant = admin.claims.tenant_id()?;
+    let endpoint = find_webhook(&state, &tenant, &request.id).await?;
+
+    state.webhook_store
+        .write()
+        .await
+        .remove_endpoint(&endpoint.id)
+        .await
+        .map_err(map_webhook_error)?;
+
+    Ok(StatusCode::OK)
+}
+
+// Recent deliveries to the endpoint, most recent first, with where each one
+// stands and why it last failed.
+#[tracing::instrument(name = "List webhook deliveries", skip_all)]
+pub async fn list_webhook_deliveries(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+    Path(id): Path<String>,
+    Query(params): Query<ListWebhookDeliveriesParams>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let tenant = admin.claims.tenant_id()?;
+    let endpoint = find_webhook(&state, &tenant, &id).await?;
+
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT);
+    if limit == 0 || limit > MAX_LIMIT {
+        return Err(AuthAPIError::InvalidCredentials);
+    }
+
+    let deliveries = state.webhook_store
+        .read()
+        .await
+        .list_deliveries(&endpoint.id, limit)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?
+        .into_iter()
+        .map(WebhookDeliveryResponse::try_from)
+        .collect::<Result<_, _>>()?;
+
+    Ok((StatusCode::OK, Json(ListWebhookDeliveriesResponse { deliveries })))
+}
+
+// Endpoints of other tenants are reported as missing.
+async fn find_webhook(state: &AppState, tenant: &TenantId, id: &str) -> Result<WebhookEndpoint, AuthAPIError> {
+    let id = WebhookEndpointId::parse_or_error(id, |_| AuthAPIError::WebhookNotFound)?;
+
+    let endpoint = state.webhook_store
+        .read()
+        .await
+        .get_endpoint(&id)
+        .await
+        .map_err(map_webhook_error)?;
+
+    match &endpoint.tenant == tenant {
+        true => Ok(endpoint),
+        false => Err(AuthAPIError::WebhookNotFound),
+    }
+}
+
+fn map_webhook_error(e: WebhookStoreError) -> AuthAPIError {
+    match e {
+        WebhookStoreError::EndpointNotFound => AuthAPIError::WebhookNotFound,
+        e => AuthAPIError::UnexpectedError(e.into()),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    pub events: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct DeleteWebhookRequest {
+    pub id: String,
+}
+
+#[derive(Deserialize)]
+pub struct ListWebhookDeliveriesParams {
+    pub limit: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct WebhookResponse {
+    pub id: String,
+    pub url: String,
+    pub events: Vec<WebhookEventType>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&WebhookEndpoint> for WebhookResponse {
+    fn from(endpoint: &WebhookEndpoint) -> Self {
+        Self {
+            id: endpoint.id.as_ref().to_owned(),
+            url: endpoint.url.clone(),
+            events: endpoint.events.clone(),
+            created_at: endpoint.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateWebhookResponse {
+    #[serde(flatten)]
+    pub webhook: WebhookResponse,
+    // Used to sign payloads; not shown again
+    pub secret: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListWebhooksResponse {
+    pub webhooks: Vec<WebhookResponse>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WebhookDeliveryResponse {
+    pub id: String,
+    pub event: WebhookEventType,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: u32,
+    #[serde(rename = "nextAttemptAt")]
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    #[serde(rename = "lastError")]
+    pub last_error: Option<String>,
+    pub payload: serde_json::Value,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl TryFrom<WebhookDelivery> for WebhookDeliveryResponse {
+    type Error = AuthAPIError;
+
+    fn try_from(delivery: WebhookDelivery) -> Result<Self, Self::Error> {
+        let payload = serde_json::from_str(&delivery.payload).map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+        Ok(Self {
+            id: delivery.id.as_ref().to_owned(),
+            event: delivery.event_type,
+            status: delivery.status,
+            attempts: delivery.attempts,
+            // Only pending deliveries have another attempt coming
+            next_attempt_at: (delivery.status == WebhookDeliveryStatus::Pending).then_some(delivery.next_attempt_at),
+            last_error: delivery.last_error,
+            payload,
+            created_at: delivery.created_at,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListWebhookDeliveriesResponse {
+    pub deliveries: Vec<WebhookDeliveryResponse>,
+}
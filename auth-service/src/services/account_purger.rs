@@ -0,0 +1,132 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::Result;
+use secrecy::ExposeSecret;
+use tokio::task::JoinHandle;
+
+use crate::{
+    domain::{AccountStatus, WebhookEventType},
+    utils::webhooks::{publish_webhook_event, user_event_data},
+    EmailClientType, UserStoreType, WebhookStoreType,
+};
+
+// Hard-deletes accounts whose deletion grace period is over, along with their
+// linked identities and roles, and lets each user know by email.
+pub struct AccountPurger {
+    user_store: UserStoreType,
+    email_client: EmailClientType,
+    webhook_store: WebhookStoreType,
+}
+
+impl AccountPurger {
+    pub fn new(user_store: UserStoreType, email_client: EmailClientType, webhook_store: WebhookStoreType) -> Self {
+        Self { user_store, email_client, webhook_store }
+    }
+
+    // Runs a purge every `interval` until the process exits.
+    pub fn spawn(self, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.purge(Utc::now()).await {
+                    tracing::error!("Failed to purge deleted accounts: {:?}", e);
+                }
+            }
+        })
+    }
+
+    // Purges every account due by `now` and returns how many were purged.
+    #[tracing::instrument(name = "Purge deleted accounts", skip_all)]
+    pub async fn purge(&self, now: DateTime<Utc>) -> Result<usize> {
+        let due = self.user_store.read().await.list_users_due_for_purge(now).await?;
+        let mut purged = 0;
+
+        for (tenant, user) in due {
+            let email = user.email.as_ref().expose_secret().to_owned();
+            let mut user_store = self.user_store.write().await;
+
+            // The user may have signed in since the accounts were listed.
+            let still_due = user_store.get_user(&tenant, &email).await.is_ok_and(|user| {
+                user.status == AccountStatus::Deleted && user.purge_at.is_some_and(|purge_at| purge_at <= now)
+            });
+            if !still_due {
+                continue;
+            }
+
+            user_store.delete_user(&tenant, &email).await?;
+            drop(user_store);
+            purged += 1;
+
+            publish_webhook_event(&self.webhook_store, &tenant, WebhookEventType::UserDeleted, user_event_data(&email)).await;
+
+            // Audit events outlive the account, so the email doesn't claim
+            // everything is gone
+            let content = "Your account has now been permanently deleted. A record of its sign-in and security \
+                activity is kept for auditing.";
+            if let Err(e) = self.email_client.read().await.send_email(&user.email, "Your account has been deleted", content).await {
+                tracing::warn!("Failed to send account deletion email: {:?}", e);
+            }
+        }
+
+        Ok(purged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use secrecy::Secret;
+
+    use super::*;
+    use crate::{
+        domain::{Email, EmailClient, IntoShared, TenantId, User, UserStore},
+        services::data_stores::{hashmap_user_store::HashmapUserStore, hashmap_webhook_store::HashmapWebhookStore},
+    };
+
+    #[derive(Default, Clone)]
+    struct RecordingEmailClient {
+        recipients: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl EmailClient for RecordingEmailClient {
+        async fn send_email(&self, recipient: &Email, _subject: &str, _content: &str) -> Result<()> {
+            self.recipients.lock().unwrap().push(recipient.as_ref().expose_secret().to_owned());
+            Ok(())
+        }
+    }
+
+    impl IntoShared for RecordingEmailClient {}
+
+    #[tokio::test]
+    async fn should_purge_only_accounts_past_their_grace_period() {
+        let tenant = TenantId::default();
+        let now = Utc::now();
+        let mut user_store = HashmapUserStore::default();
+        for email in ["due@test.com", "later@test.com", "active@test.com"] {
+            let user = User::new(Secret::new(email.to_owned()), Secret::new("password".to_owned()), false).unwrap();
+            user_store.add_user(&tenant, user).await.unwrap();
+        }
+        user_store.schedule_deletion(&tenant, "due@test.com", now - chrono::Duration::hours(1)).await.unwrap();
+        user_store.schedule_deletion(&tenant, "later@test.com", now + chrono::Duration::days(1)).await.unwrap();
+
+        let user_store = user_store.into_shared();
+        let email_client = RecordingEmailClient::default();
+        let purger = AccountPurger::new(
+            user_store.clone(),
+            email_client.clone().into_shared(),
+            HashmapWebhookStore::default().into_shared(),
+        );
+
+        assert_eq!(purger.purge(now).await.unwrap(), 1);
+        assert!(user_store.read().await.get_user(&tenant, "due@test.com").await.is_err());
+        assert!(user_store.read().await.get_user(&tenant, "later@test.com").await.is_ok());
+        assert!(user_store.read().await.get_user(&tenant, "active@test.com").await.is_ok());
+        assert_eq!(*email_client.recipients.lock().unwrap(), vec!["due@test.com".to_owned()]);
+
+        assert_eq!(purger.purge(now).await.unwrap(), 0);
+    }
+}
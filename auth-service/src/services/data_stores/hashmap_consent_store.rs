@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use crate::domain::{Consent, ConsentStore, ConsentStoreError, IntoShared, PolicyDocument, PolicyKind, PolicyVersion, TenantId};
+
+#[derive(Default)]
+pub struct HashmapConsentStore {
+    // Oldest first
+    policies: HashMap<TenantId, Vec<PolicyDocument>>,
+    consents: HashMap<(TenantId, String), Vec<Consent>>,
+}
+
+#[async_trait::async_trait]
+impl ConsentStore for HashmapConsentStore {
+    async fn add_policy(&mut self, tenant: &TenantId, document: PolicyDocument) -> Result<(), ConsentStoreError> {
+        let policies = self.policies.entry(tenant.clone()).or_default();
+        if policies.iter().any(|policy| policy.kind == document.kind && policy.version == document.version) {
+            return Err(ConsentStoreError::PolicyAlreadyExists);
+        }
+
+        policies.push(document);
+        Ok(())
+    }
+
+    async fn get_current_policies(&self, tenant: &TenantId) -> Result<Vec<PolicyDocument>, ConsentStoreError> {
+        let mut current: Vec<PolicyDocument> = Vec::new();
+        for document in self.policies.get(tenant).into_iter().flatten().rev() {
+            if !current.iter().any(|policy| policy.kind == document.kind) {
+                current.push(document.clone());
+            }
+        }
+
+        Ok(current)
+    }
+
+    async fn get_policy(
+        &self,
+        tenant: &TenantId,
+        kind: PolicyKind,
+        version: &PolicyVersion,
+    ) -> Result<PolicyDocument, ConsentStoreError> {
+        self.policies
+            .get(tenant)
+            .into_iter()
+            .flatten()
+            .find(|policy| policy.kind == kind && &policy.version == version)
+            .cloned()
+            .ok_or(ConsentStoreError::PolicyNotFound)
+    }
+
+    async fn add_consent(&mut self, tenant: &TenantId, email: &str, consent: Consent) -> Result<(), ConsentStoreError> {
+        let consents = self.consents.entry((tenant.clone(), email.to_owned())).or_default();
+        if !consents.iter().any(|accepted| accepted.kind == consent.kind && accepted.version == consent.version) {
+            consents.push(consent);
+        }
+
+        Ok(())
+    }
+
+    async fn list_consents(&self, tenant: &TenantId, email: &str) -> Result<Vec<Consent>, ConsentStoreError> {
+        let mut consents = self.consents.get(&(tenant.clone(), email.to_owned())).cloned().unwrap_or_default();
+        consents.sort_by_key(|consent| std::cmp::Reverse(consent.accepted_at));
+        Ok(consents)
+    }
+}
+
+impl IntoShared for HashmapConsentStore {}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::utils::parsable::Parsable;
+
+    fn document(kind: PolicyKind, version: &str) -> PolicyDocument {
+        PolicyDocument {
+            kind,
+            version: PolicyVersion::parse(version).unwrap(),
+            url: format!("https://example.com/legal/{}", version),
+            published_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_serve_the_latest_version_of_each_policy() {
+        let mut store = HashmapConsentStore::default();
+        let tenant = TenantId::default();
+
+        store.add_policy(&tenant, document(PolicyKind::TermsOfService, "1")).await.unwrap();
+        store.add_policy(&tenant, document(PolicyKind::PrivacyPolicy, "1")).await.unwrap();
+        store.add_policy(&tenant, document(PolicyKind::TermsOfService, "2")).await.unwrap();
+        assert_eq!(
+            store.add_policy(&tenant, document(PolicyKind::TermsOfService, "2")).await,
+            Err(ConsentStoreError::PolicyAlreadyExists)
+        );
+
+        let current = store.get_current_policies(&tenant).await.unwrap();
+        let versions: Vec<_> = current.iter().map(|policy| (policy.kind, policy.version.as_ref())).collect();
+        assert_eq!(versions, vec![(PolicyKind::TermsOfService, "2"), (PolicyKind::PrivacyPolicy, "1")]);
+        assert!(store.get_current_policies(&TenantId::parse("other").unwrap()).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_keep_the_first_acceptance_of_a_version() {
+        let mut store = HashmapConsentStore::default();
+        let tenant = TenantId::default();
+        let version = PolicyVersion::parse("1").unwrap();
+
+        let first = Consent::new(PolicyKind::TermsOfService, version.clone());
+        store.add_consent(&tenant, "test@example.com", first.clone()).await.unwrap();
+        store.add_consent(&tenant, "test@example.com", Consent::new(PolicyKind::TermsOfService, version)).await.unwrap();
+
+        assert_eq!(store.list_consents(&tenant, "test@example.com").await.unwrap(), vec![first]);
+        assert!(store.list_consents(&tenant, "other@example.com").await.unwrap().is_empty());
+    }
+}
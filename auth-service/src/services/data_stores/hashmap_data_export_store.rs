@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+use crate::domain::{DataExport, DataExportStore, DataExportStoreError, IntoShared, TenantId};
+
+#[derive(Default)]
+pub struct HashmapDataExportStore {
+    exports: HashMap<(TenantId, String), DataExport>,
+}
+
+#[async_trait::async_trait]
+impl DataExportStore for HashmapDataExportStore {
+    async fn add_export(&mut self, tenant: &TenantId, email: &str, export: DataExport) -> Result<(), DataExportStoreError> {
+        self.exports.insert((tenant.clone(), email.to_owned()), export);
+        Ok(())
+    }
+
+    async fn get_latest_export(&self, tenant: &TenantId, email: &str) -> Result<DataExport, DataExportStoreError> {
+        self.exports
+            .get(&(tenant.clone(), email.to_owned()))
+            .cloned()
+            .ok_or(DataExportStoreError::ExportNotFound)
+    }
+
+    async fn update_export(&mut self, tenant: &TenantId, email: &str, export: DataExport) -> Result<(), DataExportStoreError> {
+        match self.exports.get_mut(&(tenant.clone(), email.to_owned())) {
+            Some(existing) if existing.id == export.id => {
+                *existing = export;
+                Ok(())
+            }
+            _ => Err(DataExportStoreError::ExportNotFound),
+        }
+    }
+}
+
+impl IntoShared for HashmapDataExportStore {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn should_only_keep_the_latest_export() {
+        let mut store = HashmapDataExportStore::default();
+        let tenant = TenantId::default();
+        let first = DataExport::new();
+        let mut second = DataExport::new();
+
+        store.add_export(&tenant, "test@example.com", first.clone()).await.unwrap();
+        store.add_export(&tenant, "test@example.com", second.clone()).await.unwrap();
+        assert_eq!(store.get_latest_export(&tenant, "test@example.com").await.unwrap(), second);
+
+        // A superseded export can't overwrite the latest one
+        assert_eq!(
+            store.update_export(&tenant, "test@example.com", first).await,
+            Err(DataExportStoreError::ExportNotFound)
+        );
+
+        second.complete("{}".to_owned());
+        store.update_export(&tenant, "test@example.com", second.clone()).await.unwrap();
+        assert_eq!(store.get_latest_export(&tenant, "test@example.com").await.unwrap(), second);
+        assert_eq!(
+            store.get_latest_export(&tenant, "other@example.com").await,
+            Err(DataExportStoreError::ExportNotFound)
+        );
+    }
+}
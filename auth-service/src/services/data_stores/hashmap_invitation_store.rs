@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use crate::domain::{IntoShared, Invitation, InvitationId, InvitationStore, InvitationStoreError, TenantId};
+
+#[derive(Default)]
+pub struct HashmapInvitationStore {
+    invitations: HashMap<InvitationId, Invitation>,
+}
+
+#[async_trait::async_trait]
+impl InvitationStore for HashmapInvitationStore {
+    async fn add_invitation(&mut self, invitation: Invitation) -> Result<(), InvitationStoreError> {
+        self.invitations.insert(invitation.id.clone(), invitation);
+        Ok(())
+    }
+
+    async fn get_invitation(&self, id: &InvitationId) -> Result<Invitation, InvitationStoreError> {
+        self.invitations.get(id).cloned().ok_or(InvitationStoreError::InvitationNotFound)
+    }
+
+    async fn list_invitations(&self, tenant: &TenantId) -> Result<Vec<Invitation>, InvitationStoreError> {
+        let mut invitations: Vec<Invitation> = self.invitations
+            .values()
+            .filter(|invitation| &invitation.tenant == tenant)
+            .cloned()
+            .collect();
+        invitations.sort_by_key(|invitation| invitation.created_at);
+        Ok(invitations)
+    }
+
+    async fn remove_invitation(&mut self, id: &InvitationId) -> Result<(), InvitationStoreError> {
+        self.invitations
+            .remove(id)
+            .map(|_| ())
+            .ok_or(InvitationStoreError::InvitationNotFound)
+    }
+}
+
+impl IntoShared for HashmapInvitationStore {}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::{domain::Email, utils::parsable::Parsable};
+
+    fn invitation(tenant: &str) -> Invitation {
+        Invitation::new(
+            TenantId::parse(tenant).unwrap(),
+            Email::parse("test@example.com").unwrap(),
+            None,
+            "admin@example.com".to_owned(),
+            Duration::days(1),
+        )
+    }
+
+    #[tokio::test]
+    async fn should_remove_an_invitation_only_once() {
+        let mut store = HashmapInvitationStore::default();
+        let invitation = invitation("acme");
+        store.add_invitation(invitation.clone()).await.unwrap();
+
+        assert_eq!(store.get_invitation(&invitation.id).await, Ok(invitation.clone()));
+        assert_eq!(store.remove_invitation(&invitation.id).await, Ok(()));
+        assert_eq!(
+            store.remove_invitation(&invitation.id).await,
+            Err(InvitationStoreError::InvitationNotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn should_only_list_invitations_of_the_tenant() {
+        let mut store = HashmapInvitationStore::default();
+        let invitation = invitation("acme");
+        store.add_invitation(invitation.clone()).await.unwrap();
+        store.add_invitation(self::invitation("other")).await.unwrap();
+
+        assert_eq!(store.list_invitations(&invitation.tenant).await, Ok(vec![invitation]));
+    }
+}
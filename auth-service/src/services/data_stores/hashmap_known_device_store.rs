@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+
+use crate::domain::{
+    DeviceFingerprint, DeviceSighting, IntoShared, KnownDevice, KnownDeviceStore, KnownDeviceStoreError, TenantId,
+};
+
+#[derive(Default)]
+pub struct HashmapKnownDeviceStore {
+    devices: HashMap<(TenantId, String), HashMap<DeviceFingerprint, KnownDevice>>,
+}
+
+#[async_trait::async_trait]
+impl KnownDeviceStore for HashmapKnownDeviceStore {
+    async fn record_sign_in(&mut self, tenant: &TenantId, email: &str, device: KnownDevice) -> Result<DeviceSighting, KnownDeviceStoreError> {
+        let devices = self.devices.entry((tenant.clone(), email.to_owned())).or_default();
+        let sighting = match (devices.is_empty(), devices.contains_key(&device.fingerprint)) {
+            (true, _) => DeviceSighting::First,
+            (false, true) => DeviceSighting::Known,
+            (false, false) => DeviceSighting::New,
+        };
+
+        devices.insert(device.fingerprint.clone(), device);
+        Ok(sighting)
+    }
+
+    async fn forget_device(&mut self, tenant: &TenantId, email: &str, fingerprint: &DeviceFingerprint) -> Result<(), KnownDeviceStoreError> {
+        if let Some(devices) = self.devices.get_mut(&(tenant.clone(), email.to_owned())) {
+            devices.remove(fingerprint);
+        }
+        Ok(())
+    }
+
+    async fn list_devices(&self, tenant: &TenantId, email: &str) -> Result<Vec<KnownDevice>, KnownDeviceStoreError> {
+        let mut devices: Vec<KnownDevice> = self.devices
+            .get(&(tenant.clone(), email.to_owned()))
+            .map(|devices| devices.values().cloned().collect())
+            .unwrap_or_default();
+
+        devices.sort_by_key(|device| std::cmp::Reverse(device.last_seen_at));
+        Ok(devices)
+    }
+}
+
+impl IntoShared for HashmapKnownDeviceStore {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ClientInfo;
+
+    fn device(user_agent: &str) -> KnownDevice {
+        KnownDevice::new(&ClientInfo { ip: Some("203.0.113.7".to_owned()), user_agent: Some(user_agent.to_owned()) })
+    }
+
+    #[tokio::test]
+    async fn should_tell_first_known_and_new_devices_apart() {
+        let mut store = HashmapKnownDeviceStore::default();
+        let tenant = TenantId::default();
+
+        assert_eq!(store.record_sign_in(&tenant, "test@example.com", device("Firefox")).await.unwrap(), DeviceSighting::First);
+        assert_eq!(store.record_sign_in(&tenant, "test@example.com", device("Firefox")).await.unwrap(), DeviceSighting::Known);
+        assert_eq!(store.record_sign_in(&tenant, "test@example.com", device("Chrome")).await.unwrap(), DeviceSighting::New);
+        assert_eq!(store.record_sign_in(&tenant, "other@example.com", device("Chrome")).await.unwrap(), DeviceSighting::First);
+
+        let devices = store.list_devices(&tenant, "test@example.com").await.unwrap();
+        assert_eq!(devices.iter().map(|d| d.user_agent.as_deref()).collect::<Vec<_>>(), vec![Some("Chrome"), Some("Firefox")]);
+
+        store.forget_device(&tenant, "test@example.com", &device("Chrome").fingerprint).await.unwrap();
+        assert_eq!(store.record_sign_in(&tenant, "test@example.com", device("Chrome")).await.unwrap(), DeviceSighting::New);
+    }
+}
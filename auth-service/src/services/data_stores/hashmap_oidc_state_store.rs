@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use secrecy::ExposeSecret;
+
+use crate::domain::{
+    IntoShared,
+    OidcState,
+    OidcStateStore,
+    OidcStateStoreError,
+    PendingOidcLogin,
+};
+
+#[derive(Default)]
+pub struct HashmapOidcStateStore {
+    states: HashMap<String, PendingOidcLogin>,
+}
+
+#[async_trait::async_trait]
+impl OidcStateStore for HashmapOidcStateStore {
+    async fn add_state(
+        &mut self,
+        state: &OidcState,
+        pending_login: PendingOidcLogin,
+    ) -> Result<(), OidcStateStoreError> {
+        self.states.insert(state.as_ref().expose_secret().to_owned(), pending_login);
+        Ok(())
+    }
+
+    async fn take_state(
+        &mut self,
+        state: &OidcState,
+    ) -> Result<PendingOidcLogin, OidcStateStoreError> {
+        self.states
+            .remove(state.as_ref().expose_secret())
+            .ok_or(OidcStateStoreError::StateNotFound)
+    }
+}
+
+impl IntoShared for HashmapOidcStateStore {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{OidcNonce, PkceVerifier};
+
+    fn pending_login() -> PendingOidcLogin {
+        PendingOidcLogin {
+            provider: "google".to_string(),
+            nonce: OidcNonce::default(),
+            pkce_verifier: PkceVerifier::default(),
+            link_email: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn should_add_a_state() {
+        let mut store = HashmapOidcStateStore::default();
+        let state = OidcState::default();
+        assert_eq!(store.add_state(&state, pending_login()).await, Ok(()));
+    }
+
+    #[tokio::test]
+    async fn should_take_a_state() {
+        let mut store = HashmapOidcStateStore::default();
+        let state = OidcState::default();
+        let pending_login = pending_login();
+        store.add_state(&state, pending_login.clone()).await.unwrap();
+
+        let taken = store.take_state(&state).await.unwrap();
+        assert_eq!(taken.provider, pending_login.provider);
+        assert_eq!(taken.nonce, pending_login.nonce);
+        assert_eq!(taken.pkce_verifier, pending_login.pkce_verifier);
+    }
+
+    #[tokio::test]
+    async fn should_not_take_a_state_twice() {
+        let mut store = HashmapOidcStateStore::default();
+        let state = OidcState::default();
+        store.add_state(&state, pending_login()).await.unwrap();
+        store.take_state(&state).await.unwrap();
+
+        assert!(matches!(
+            store.take_state(&state).await,
+            Err(OidcStateStoreError::StateNotFound)
+        ));
+    }
+}
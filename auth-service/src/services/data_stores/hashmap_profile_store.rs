@@ -0,0 +1,40 @@
+use std::collections::HashMap;
+
+use crate::domain::{IntoShared, Profile, ProfileStore, ProfileStoreError, TenantId};
+
+#[derive(Default)]
+pub struct HashmapProfileStore {
+    profiles: HashMap<(TenantId, String), Profile>,
+}
+
+#[async_trait::async_trait]
+impl ProfileStore for HashmapProfileStore {
+    async fn get_profile(&self, tenant: &TenantId, email: &str) -> Result<Profile, ProfileStoreError> {
+        Ok(self.profiles.get(&(tenant.clone(), email.to_owned())).cloned().unwrap_or_default())
+    }
+
+    async fn set_profile(&mut self, tenant: &TenantId, email: &str, profile: Profile) -> Result<(), ProfileStoreError> {
+        self.profiles.insert((tenant.clone(), email.to_owned()), profile);
+        Ok(())
+    }
+}
+
+impl IntoShared for HashmapProfileStore {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{domain::DisplayName, utils::parsable::Parsable};
+
+    #[tokio::test]
+    async fn should_default_to_an_empty_profile() {
+        let mut store = HashmapProfileStore::default();
+        let tenant = TenantId::default();
+        assert_eq!(store.get_profile(&tenant, "test@example.com").await.unwrap(), Profile::default());
+
+        let profile = Profile { display_name: Some(DisplayName::parse("Jane").unwrap()), ..Profile::default() };
+        store.set_profile(&tenant, "test@example.com", profile.clone()).await.unwrap();
+        assert_eq!(store.get_profile(&tenant, "test@example.com").await.unwrap(), profile);
+        assert_eq!(store.get_profile(&tenant, "other@example.com").await.unwrap(), Profile::default());
+    }
+}
@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+use crate::domain::{IntoShared, ScimToken, Tenant, TenantId, TenantStore, TenantStoreError};
+
+#[derive(Debug)]
+pub struct HashmapTenantStore {
+    tenants: HashMap<TenantId, Tenant>,
+    // Hashes of the SCIM tokens
+    scim_tokens: HashMap<TenantId, String>,
+}
+
+// Seeded with the default tenant, like the database migrations.
+impl Default for HashmapTenantStore {
+    fn default() -> Self {
+        let default_tenant = Tenant::new(TenantId::default(), "Default".to_owned());
+
+        Self {
+            tenants: HashMap::from([(default_tenant.id.clone(), default_tenant)]),
+            scim_tokens: HashMap::new(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TenantStore for HashmapTenantStore {
+    async fn add_tenant(&mut self, tenant: Tenant) -> Result<(), TenantStoreError> {
+        if self.tenants.contains_key(&tenant.id) {
+            return Err(TenantStoreError::TenantAlreadyExists);
+        }
+
+        self.tenants.insert(tenant.id.clone(), tenant);
+        Ok(())
+    }
+
+    async fn get_tenant(&self, id: &TenantId) -> Result<Tenant, TenantStoreError> {
+        self.tenants.get(id).cloned().ok_or(TenantStoreError::TenantNotFound)
+    }
+
+    async fn list_tenants(&self) -> Result<Vec<Tenant>, TenantStoreError> {
+        let mut tenants: Vec<Tenant> = self.tenants.values().cloned().collect();
+        tenants.sort_by(|a, b| a.id.as_ref().cmp(b.id.as_ref()));
+        Ok(tenants)
+    }
+
+    async fn set_scim_token(&mut self, id: &TenantId, token: &ScimToken) -> Result<(), TenantStoreError> {
+        if !self.tenants.contains_key(id) {
+            return Err(TenantStoreError::TenantNotFound);
+        }
+
+        self.scim_tokens.insert(id.clone(), token.hash());
+        Ok(())
+    }
+
+    async fn get_tenant_by_scim_token(&self, token: &ScimToken) -> Result<Tenant, TenantStoreError> {
+        let hash = token.hash();
+        self.scim_tokens
+            .iter()
+            .find(|(_, token_hash)| **token_hash == hash)
+            .and_then(|(id, _)| self.tenants.get(id))
+            .cloned()
+            .ok_or(TenantStoreError::TenantNotFound)
+    }
+}
+
+impl IntoShared for HashmapTenantStore {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::parsable::Parsable;
+
+    #[tokio::test]
+    async fn should_include_the_default_tenant() {
+        let store = HashmapTenantStore::default();
+        assert!(store.get_tenant(&TenantId::default()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn should_add_a_tenant_once() {
+        let mut store = HashmapTenantStore::default();
+        let tenant = Tenant::new(TenantId::parse("acme").unwrap(), "Acme".to_owned());
+
+        assert_eq!(store.add_tenant(tenant.clone()).await, Ok(()));
+        assert_eq!(store.add_tenant(tenant.clone()).await, Err(TenantStoreError::TenantAlreadyExists));
+        assert_eq!(store.get_tenant(&tenant.id).await, Ok(tenant));
+        assert_eq!(store.list_tenants().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn should_not_find_an_unknown_tenant() {
+        let store = HashmapTenantStore::default();
+        assert_eq!(
+            store.get_tenant(&TenantId::parse("unknown").unwrap()).await,
+            Err(TenantStoreError::TenantNotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn should_find_a_tenant_by_its_latest_scim_token() {
+        let mut store = HashmapTenantStore::default();
+        let tenant = TenantId::default();
+        let old_token = ScimToken::generate();
+        let new_token = ScimToken::generate();
+
+        store.set_scim_token(&tenant, &old_token).await.unwrap();
+        store.set_scim_token(&tenant, &new_token).await.unwrap();
+
+        assert_eq!(store.get_tenant_by_scim_token(&new_token).await.map(|found| found.id), Ok(tenant));
+        assert_eq!(store.get_tenant_by_scim_token(&old_token).await, Err(TenantStoreError::TenantNotFound));
+        assert_eq!(
+            store.set_scim_token(&TenantId::parse("unknown").unwrap(), &new_token).await,
+            Err(TenantStoreError::TenantNotFound)
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use crate::domain::{IntoShared, TenantId, TrustedDevice, TrustedDeviceId, TrustedDeviceStore, TrustedDeviceStoreError};
+
+#[derive(Default)]
+pub struct HashmapTrustedDeviceStore {
+    devices: HashMap<(TenantId, String), Vec<TrustedDevice>>,
+}
+
+#[async_trait::async_trait]
+impl TrustedDeviceStore for HashmapTrustedDeviceStore {
+    async fn add_device(&mut self, tenant: &TenantId, email: &str, device: TrustedDevice) -> Result<(), TrustedDeviceStoreError> {
+        self.devices.entry((tenant.clone(), email.to_owned())).or_default().push(device);
+        Ok(())
+    }
+
+    async fn get_device(&self, tenant: &TenantId, email: &str, id: &TrustedDeviceId) -> Result<TrustedDevice, TrustedDeviceStoreError> {
+        self.devices
+            .get(&(tenant.clone(), email.to_owned()))
+            .and_then(|devices| devices.iter().find(|device| &device.id == id))
+            .cloned()
+            .ok_or(TrustedDeviceStoreError::DeviceNotFound)
+    }
+
+    async fn list_devices(&self, tenant: &TenantId, email: &str) -> Result<Vec<TrustedDevice>, TrustedDeviceStoreError> {
+        let mut devices = self.devices.get(&(tenant.clone(), email.to_owned())).cloned().unwrap_or_default();
+        devices.sort_by_key(|device| std::cmp::Reverse(device.created_at));
+        Ok(devices)
+    }
+
+    async fn remove_device(&mut self, tenant: &TenantId, email: &str, id: &TrustedDeviceId) -> Result<(), TrustedDeviceStoreError> {
+        let devices = self.devices
+            .get_mut(&(tenant.clone(), email.to_owned()))
+            .ok_or(TrustedDeviceStoreError::DeviceNotFound)?;
+        let index = devices
+            .iter()
+            .position(|device| &device.id == id)
+            .ok_or(TrustedDeviceStoreError::DeviceNotFound)?;
+
+        devices.remove(index);
+        Ok(())
+    }
+
+    async fn remove_all_devices(&mut self, tenant: &TenantId, email: &str) -> Result<(), TrustedDeviceStoreError> {
+        self.devices.remove(&(tenant.clone(), email.to_owned()));
+        Ok(())
+    }
+}
+
+impl IntoShared for HashmapTrustedDeviceStore {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ClientInfo;
+
+    #[tokio::test]
+    async fn should_keep_devices_per_user() {
+        let mut store = HashmapTrustedDeviceStore::default();
+        let tenant = TenantId::default();
+        let device = TrustedDevice::new(&ClientInfo::default());
+
+        store.add_device(&tenant, "test@example.com", device.clone()).await.unwrap();
+        assert_eq!(store.get_device(&tenant, "test@example.com", &device.id).await.unwrap(), device);
+        assert_eq!(
+            store.get_device(&tenant, "other@example.com", &device.id).await,
+            Err(TrustedDeviceStoreError::DeviceNotFound),
+        );
+        assert_eq!(
+            store.remove_device(&tenant, "other@example.com", &device.id).await,
+            Err(TrustedDeviceStoreError::DeviceNotFound),
+        );
+
+        store.remove_device(&tenant, "test@example.com", &device.id).await.unwrap();
+        assert!(store.list_devices(&tenant, "test@example.com").await.unwrap().is_empty());
+    }
+}
@@ -7,38 +7,42 @@ use crate::domain::{
     TwoFACodeStoreError,
     Email,
     IntoShared,
+    TenantId,
 };
 
 #[derive(Default)]
 pub struct HashmapTwoFACodeStore {
-    codes: HashMap<Email, (LoginAttemptId, TwoFACode)>,
+    codes: HashMap<(TenantId, Email), (LoginAttemptId, TwoFACode)>,
 }
 
 #[async_trait::async_trait]
 impl TwoFACodeStore for HashmapTwoFACodeStore {
     async fn add_code(
         &mut self,
+        tenant: &TenantId,
         email: Email,
         login_attempt_id: &LoginAttemptId,
         code: TwoFACode,
     ) -> Result<(), TwoFACodeStoreError> {
-        self.codes.insert(email, (login_attempt_id.clone(), code.clone()));
+        self.codes.insert((tenant.clone(), email), (login_attempt_id.clone(), code.clone()));
         Ok(())
     }
 
     async fn remove_code(
         &mut self,
+        tenant: &TenantId,
         email: Email,
     ) -> Result<(), TwoFACodeStoreError> {
-        self.codes.remove(&email);
+        self.codes.remove(&(tenant.clone(), email));
         Ok(())
     }
 
     async fn get_code(
         &self,
+        tenant: &TenantId,
         email: Email,
     ) -> Result<(LoginAttemptId, TwoFACode), TwoFACodeStoreError> {
-        match self.codes.get(&email) {
+        match self.codes.get(&(tenant.clone(), email)) {
             Some((login_attempt_id, code)) => Ok((login_attempt_id.clone(), code.clone())),
             None => Err(TwoFACodeStoreError::LoginAttemptIdNotFound),
         }
@@ -58,7 +62,7 @@ mod tests {
         let email = Email::parse("hi@test.com").unwrap();
         let login_attempt_id = LoginAttemptId::default();
         let code = TwoFACode::default();
-        assert_eq!(store.add_code(email.clone(), &login_attempt_id, code.clone()).await, Ok(()));
+        assert_eq!(store.add_code(&TenantId::default(), email.clone(), &login_attempt_id, code.clone()).await, Ok(()));
     }
 
     #[tokio::test]
@@ -67,11 +71,11 @@ mod tests {
         let email = Email::parse("hi@test.com").unwrap();
         let login_attempt_id = LoginAttemptId::default();
         let code = TwoFACode::default();
-        store.add_code(email.clone(), &login_attempt_id, code.clone()).await.unwrap();
+        store.add_code(&TenantId::default(), email.clone(), &login_attempt_id, code.clone()).await.unwrap();
         let login_attempt_id = LoginAttemptId::default();
         let code = TwoFACode::default();
         
-        assert_eq!(store.add_code(email.clone(), &login_attempt_id, code.clone()).await, Ok(()));
+        assert_eq!(store.add_code(&TenantId::default(), email.clone(), &login_attempt_id, code.clone()).await, Ok(()));
     }
 
     #[tokio::test]
@@ -80,9 +84,9 @@ mod tests {
         let email = Email::parse("hi@test.com").unwrap();
         let login_attempt_id = LoginAttemptId::default();
         let code = TwoFACode::default();
-        store.add_code(email.clone(), &login_attempt_id, code.clone()).await.unwrap();
+        store.add_code(&TenantId::default(), email.clone(), &login_attempt_id, code.clone()).await.unwrap();
 
-        assert_eq!(store.remove_code(email.clone()).await, Ok(()));
+        assert_eq!(store.remove_code(&TenantId::default(), email.clone()).await, Ok(()));
     }
 
     #[tokio::test]
@@ -91,8 +95,21 @@ mod tests {
         let email = Email::parse("hi@test.com").unwrap();
         let login_attempt_id = LoginAttemptId::default();
         let code = TwoFACode::default();
-        store.add_code(email.clone(), &login_attempt_id, code.clone()).await.unwrap();
+        store.add_code(&TenantId::default(), email.clone(), &login_attempt_id, code.clone()).await.unwrap();
 
-        assert_eq!(store.get_code(email.clone()).await, Ok((login_attempt_id, code)));
+        assert_eq!(store.get_code(&TenantId::default(), email.clone()).await, Ok((login_attempt_id, code)));
+    }
+
+    #[tokio::test]
+    async fn should_keep_codes_of_each_tenant_apart() {
+        let mut store = HashmapTwoFACodeStore::default();
+        let email = Email::parse("hi@test.com").unwrap();
+        let other_tenant = TenantId::parse("acme").unwrap();
+        store.add_code(&TenantId::default(), email.clone(), &LoginAttemptId::default(), TwoFACode::default()).await.unwrap();
+
+        assert_eq!(
+            store.get_code(&other_tenant, email.clone()).await,
+            Err(TwoFACodeStoreError::LoginAttemptIdNotFound)
+        );
     }
 }
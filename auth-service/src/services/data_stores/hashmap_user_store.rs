@@ -1,51 +1,478 @@
 use std::collections::HashMap;
 
-use secrecy::ExposeSecret;
+use chrono::{DateTime, Utc};
+use secrecy::{ExposeSecret, Secret};
 
 use crate::{
     domain::{
-        Email, User, UserStore, UserStoreError, IntoShared
+        AccountStatus, Email, Group, GroupId, GroupName, LinkedIdentity, Password, Permission, Role, TenantId, User,
+        UserAuthorization, UserId, UserPage, UserQuery, UserStore, UserStoreError, Username, IntoShared, ADMIN_PERMISSIONS,
     },
+    services::password_hashing::{is_supported_hash, verify_password_hash, HashingParams},
     utils::parsable::Parsable,
 };
 
-#[derive(Default, Debug)]
+type UserKey = (TenantId, Email);
+type GroupKey = (TenantId, GroupId);
+
+#[derive(Debug)]
 pub struct HashmapUserStore {
-    pub users: HashMap<Email, User>,
+    pub users: HashMap<UserKey, User>,
+    pub identities: HashMap<UserKey, Vec<LinkedIdentity>>,
+    pub roles: HashMap<Role, Vec<Permission>>,
+    pub user_roles: HashMap<UserKey, Vec<Role>>,
+    // Hashes imported from other systems. Every other password is kept as is.
+    pub password_hashes: HashMap<UserKey, Secret<String>>,
+    pub groups: HashMap<GroupKey, Group>,
+    pub group_members: HashMap<GroupKey, Vec<Email>>,
+}
+
+// Seeded with the same roles as the database migrations.
+impl Default for HashmapUserStore {
+    fn default() -> Self {
+        let admin_permissions = ADMIN_PERMISSIONS
+            .iter()
+            .map(|permission| Permission::parse(permission).expect("Invalid built-in permission"))
+            .collect();
+
+        Self {
+            users: HashMap::new(),
+            identities: HashMap::new(),
+            roles: HashMap::from([(Role::admin(), admin_permissions)]),
+            user_roles: HashMap::new(),
+            password_hashes: HashMap::new(),
+            groups: HashMap::new(),
+            group_members: HashMap::new(),
+        }
+    }
+}
+
+fn user_key(tenant: &TenantId, email: &str) -> Result<UserKey, UserStoreError> {
+    let email = Email::parse_or_error(email, |_| UserStoreError::InvalidCredentials)?;
+    Ok((tenant.clone(), email))
 }
 
 #[async_trait::async_trait]
 impl UserStore for HashmapUserStore {
-    async fn add_user(&mut self, user: User) -> Result<(), UserStoreError> {
-        if self.users.contains_key(&user.email) {
+    async fn add_user(&mut self, tenant: &TenantId, user: User) -> Result<(), UserStoreError> {
+        let key = (tenant.clone(), user.email.clone());
+        if self.users.contains_key(&key) {
             return Err(UserStoreError::UserAlreadyExists);
         }
+        if let Some(username) = &user.username {
+            self.ensure_username_is_free(tenant, username, None)?;
+        }
+
+        self.users.insert(key, user);
+        Ok(())
+    }
+
+    async fn add_user_with_password_hash(&mut self, tenant: &TenantId, mut user: User, password_hash: Secret<String>) -> Result<(), UserStoreError> {
+        if !is_supported_hash(password_hash.expose_secret()) {
+            return Err(UserStoreError::UnsupportedPasswordHash);
+        }
 
-        self.users.insert(user.email.clone(), user);
+        let key = (tenant.clone(), user.email.clone());
+        user.password = Some(
+            Password::parse_or_error(password_hash.expose_secret(), UserStoreError::UnexpectedError)?
+        );
+        self.add_user(tenant, user).await?;
+        self.password_hashes.insert(key, password_hash);
         Ok(())
     }
 
-    async fn get_user(&self, email: &str) -> Result<User, UserStoreError> {
-        let email = Email::parse_or_error(email, |_| UserStoreError::InvalidCredentials)?;
-        self.users.get(&email).ok_or(UserStoreError::UserNotFound).cloned()
+    async fn get_user(&self, tenant: &TenantId, email: &str) -> Result<User, UserStoreError> {
+        let key = user_key(tenant, email)?;
+        self.users.get(&key).ok_or(UserStoreError::UserNotFound).cloned()
     }
 
-    async fn validate_user(&self, email: &str, password: &str) -> Result<(), UserStoreError> {
-        let email = Email::parse_or_error(email, |_| UserStoreError::InvalidCredentials)?;
-        self.users.get(&email).ok_or(UserStoreError::UserNotFound).and_then(|user| {
-            if user.password.as_ref().expose_secret() == password {
-                Ok(())
-            } else {
-                Err(UserStoreError::InvalidCredentials)
+    async fn get_user_by_id(&self, tenant: &TenantId, id: &UserId) -> Result<User, UserStoreError> {
+        self.users
+            .iter()
+            .find(|((owner_tenant, _), user)| owner_tenant == tenant && &user.id == id)
+            .map(|(_, user)| user.clone())
+            .ok_or(UserStoreError::UserNotFound)
+    }
+
+    async fn get_user_by_username(&self, tenant: &TenantId, username: &Username) -> Result<User, UserStoreError> {
+        self.users
+            .iter()
+            .find(|((owner_tenant, _), user)| {
+                owner_tenant == tenant && user.username.as_ref().is_some_and(|taken| taken.is_same_as(username))
+            })
+            .map(|(_, user)| user.clone())
+            .ok_or(UserStoreError::UserNotFound)
+    }
+
+    async fn validate_user(&self, tenant: &TenantId, email: &str, password: &str) -> Result<(), UserStoreError> {
+        let key = user_key(tenant, email)?;
+        if let Some(password_hash) = self.password_hashes.get(&key) {
+            return verify_password_hash(password_hash.clone(), Secret::new(password.to_owned()), HashingParams::default())
+                .await
+                .map(|_| ())
+                .map_err(|_| UserStoreError::InvalidCredentials);
+        }
+
+        self.users.get(&key).ok_or(UserStoreError::UserNotFound).and_then(|user| {
+            match &user.password {
+                Some(user_password) if user_password.as_ref().expose_secret() == password => Ok(()),
+                _ => Err(UserStoreError::InvalidCredentials),
             }
         })
     }
 
-    async fn delete_user(&mut self, email: &str) -> Result<(), UserStoreError> {
-        let email = Email::parse(email).map_err(|_| UserStoreError::InvalidCredentials)?;
-        self.users.remove(&email).ok_or(UserStoreError::UserNotFound)?;
+    async fn delete_user(&mut self, tenant: &TenantId, email: &str) -> Result<(), UserStoreError> {
+        let key = user_key(tenant, email)?;
+        self.users.remove(&key).ok_or(UserStoreError::UserNotFound)?;
+        self.identities.remove(&key);
+        self.user_roles.remove(&key);
+        self.password_hashes.remove(&key);
+        for ((group_tenant, _), members) in self.group_members.iter_mut() {
+            if group_tenant == tenant {
+                members.retain(|member| member != &key.1);
+            }
+        }
+        Ok(())
+    }
+
+    async fn link_identity(&mut self, tenant: &TenantId, email: &str, identity: LinkedIdentity) -> Result<(), UserStoreError> {
+        let key = user_key(tenant, email)?;
+        if !self.users.contains_key(&key) {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        let linked_elsewhere = self.identities.iter().any(|(owner, identities)| {
+            owner.0 == key.0 && owner != &key && identities.iter().any(|linked| {
+                linked.provider == identity.provider && linked.subject == identity.subject
+            })
+        });
+        if linked_elsewhere {
+            return Err(UserStoreError::IdentityAlreadyLinked);
+        }
+
+        let identities = self.identities.entry(key).or_default();
+        match identities.iter().find(|linked| linked.provider == identity.provider) {
+            Some(linked) if linked.subject == identity.subject => Ok(()),
+            Some(_) => Err(UserStoreError::IdentityAlreadyLinked),
+            None => {
+                identities.push(identity);
+                Ok(())
+            }
+        }
+    }
+
+    async fn unlink_identity(&mut self, tenant: &TenantId, email: &str, provider: &str) -> Result<(), UserStoreError> {
+        let key = user_key(tenant, email)?;
+        let user = self.users.get(&key).ok_or(UserStoreError::UserNotFound)?;
+        let identities = self.identities.get_mut(&key).ok_or(UserStoreError::IdentityNotFound)?;
+
+        let position = identities
+            .iter()
+            .position(|linked| linked.provider == provider)
+            .ok_or(UserStoreError::IdentityNotFound)?;
+
+        if user.password.is_none() && identities.len() == 1 {
+            return Err(UserStoreError::LastLoginMethod);
+        }
+
+        identities.remove(position);
+        Ok(())
+    }
+
+    async fn get_linked_identities(&self, tenant: &TenantId, email: &str) -> Result<Vec<LinkedIdentity>, UserStoreError> {
+        let key = user_key(tenant, email)?;
+        if !self.users.contains_key(&key) {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        Ok(self.identities.get(&key).cloned().unwrap_or_default())
+    }
+
+    async fn get_user_by_identity(&self, tenant: &TenantId, provider: &str, subject: &str) -> Result<User, UserStoreError> {
+        self.identities
+            .iter()
+            .find(|((owner_tenant, _), identities)| {
+                owner_tenant == tenant
+                    && identities.iter().any(|linked| linked.provider == provider && linked.subject == subject)
+            })
+            .and_then(|(key, _)| self.users.get(key))
+            .cloned()
+            .ok_or(UserStoreError::UserNotFound)
+    }
+
+    async fn assign_role(&mut self, tenant: &TenantId, email: &str, role: &Role) -> Result<(), UserStoreError> {
+        let key = user_key(tenant, email)?;
+        if !self.users.contains_key(&key) {
+            return Err(UserStoreError::UserNotFound);
+        }
+        if !self.roles.contains_key(role) {
+            return Err(UserStoreError::RoleNotFound);
+        }
+
+        let roles = self.user_roles.entry(key).or_default();
+        if !roles.contains(role) {
+            roles.push(role.clone());
+        }
+        Ok(())
+    }
+
+    async fn remove_role(&mut self, tenant: &TenantId, email: &str, role: &Role) -> Result<(), UserStoreError> {
+        let key = user_key(tenant, email)?;
+        if !self.users.contains_key(&key) {
+            return Err(UserStoreError::UserNotFound);
+        }
+        if !self.roles.contains_key(role) {
+            return Err(UserStoreError::RoleNotFound);
+        }
+
+        if let Some(roles) = self.user_roles.get_mut(&key) {
+            roles.retain(|assigned| assigned != role);
+        }
+        Ok(())
+    }
+
+    async fn get_authorization(&self, tenant: &TenantId, email: &str) -> Result<UserAuthorization, UserStoreError> {
+        let key = user_key(tenant, email)?;
+        if !self.users.contains_key(&key) {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        let roles = self.user_roles.get(&key).cloned().unwrap_or_default();
+        let mut permissions: Vec<Permission> = Vec::new();
+        for permission in roles.iter().filter_map(|role| self.roles.get(role)).flatten() {
+            if !permissions.contains(permission) {
+                permissions.push(permission.clone());
+            }
+        }
+
+        Ok(UserAuthorization { roles, permissions })
+    }
+
+    async fn list_users(&self, tenant: &TenantId, query: &UserQuery) -> Result<UserPage, UserStoreError> {
+        let mut users: Vec<&User> = self.users
+            .iter()
+            .filter(|((owner_tenant, email), _)| owner_tenant == tenant && query.matches(email.as_ref().expose_secret()))
+            .map(|(_, user)| user)
+            .collect();
+        users.sort_by(|a, b| a.email.as_ref().expose_secret().cmp(b.email.as_ref().expose_secret()));
+
+        let total = users.len() as u64;
+        let users = users
+            .into_iter()
+            .skip(query.offset() as usize)
+            .take(query.per_page as usize)
+            .cloned()
+            .collect();
+
+        Ok(UserPage { users, total })
+    }
+
+    async fn set_status(
+        &mut self,
+        tenant: &TenantId,
+        email: &str,
+        status: AccountStatus,
+        reason: Option<String>,
+    ) -> Result<(), UserStoreError> {
+        let user = self.get_user_mut(tenant, email)?;
+        user.status = status;
+        user.suspension_reason = reason.filter(|_| status == AccountStatus::Suspended);
+        user.purge_at = None;
+        Ok(())
+    }
+
+    async fn set_password(&mut self, tenant: &TenantId, email: &str, password: Option<Password>) -> Result<(), UserStoreError> {
+        self.get_user_mut(tenant, email)?.password = password;
+        self.password_hashes.remove(&user_key(tenant, email)?);
         Ok(())
     }
+
+    async fn set_password_hash(&mut self, tenant: &TenantId, email: &str, password_hash: Secret<String>) -> Result<(), UserStoreError> {
+        if !is_supported_hash(password_hash.expose_secret()) {
+            return Err(UserStoreError::UnsupportedPasswordHash);
+        }
+
+        let user = self.get_user_mut(tenant, email)?;
+        user.password = Some(
+            Password::parse_or_error(password_hash.expose_secret(), UserStoreError::UnexpectedError)?
+        );
+        self.password_hashes.insert(user_key(tenant, email)?, password_hash);
+        Ok(())
+    }
+
+    async fn set_requires_2fa(&mut self, tenant: &TenantId, email: &str, requires_2fa: bool) -> Result<(), UserStoreError> {
+        self.get_user_mut(tenant, email)?.requires_2fa = requires_2fa;
+        Ok(())
+    }
+
+    async fn set_username(&mut self, tenant: &TenantId, email: &str, username: Option<Username>) -> Result<(), UserStoreError> {
+        let key = user_key(tenant, email)?;
+        if let Some(username) = &username {
+            self.ensure_username_is_free(tenant, username, Some(&key.1))?;
+        }
+
+        self.get_user_mut(tenant, email)?.username = username;
+        Ok(())
+    }
+
+    async fn schedule_deletion(&mut self, tenant: &TenantId, email: &str, purge_at: DateTime<Utc>) -> Result<(), UserStoreError> {
+        let user = self.get_user_mut(tenant, email)?;
+        user.status = AccountStatus::Deleted;
+        user.suspension_reason = None;
+        user.purge_at = Some(purge_at);
+        Ok(())
+    }
+
+    async fn list_users_due_for_purge(&self, now: DateTime<Utc>) -> Result<Vec<(TenantId, User)>, UserStoreError> {
+        Ok(self.users
+            .iter()
+            .filter(|(_, user)| user.status == AccountStatus::Deleted && user.purge_at.is_some_and(|purge_at| purge_at <= now))
+            .map(|((tenant, _), user)| (tenant.clone(), user.clone()))
+            .collect())
+    }
+
+    async fn add_group(&mut self, tenant: &TenantId, group: Group) -> Result<(), UserStoreError> {
+        self.ensure_group_name_is_free(tenant, &group.display_name, None)?;
+
+        self.groups.insert((tenant.clone(), group.id.clone()), group);
+        Ok(())
+    }
+
+    async fn get_group(&self, tenant: &TenantId, id: &GroupId) -> Result<Group, UserStoreError> {
+        self.groups.get(&(tenant.clone(), id.clone())).cloned().ok_or(UserStoreError::GroupNotFound)
+    }
+
+    async fn list_groups(&self, tenant: &TenantId) -> Result<Vec<Group>, UserStoreError> {
+        let mut groups: Vec<Group> = self.groups
+            .iter()
+            .filter(|((group_tenant, _), _)| group_tenant == tenant)
+            .map(|(_, group)| group.clone())
+            .collect();
+        groups.sort_by(|a, b| a.display_name.as_ref().cmp(b.display_name.as_ref()));
+        Ok(groups)
+    }
+
+    async fn rename_group(&mut self, tenant: &TenantId, id: &GroupId, display_name: GroupName) -> Result<(), UserStoreError> {
+        self.ensure_group_name_is_free(tenant, &display_name, Some(id))?;
+
+        let group = self.groups.get_mut(&(tenant.clone(), id.clone())).ok_or(UserStoreError::GroupNotFound)?;
+        group.display_name = display_name;
+        Ok(())
+    }
+
+    async fn delete_group(&mut self, tenant: &TenantId, id: &GroupId) -> Result<(), UserStoreError> {
+        let key = (tenant.clone(), id.clone());
+        self.groups.remove(&key).ok_or(UserStoreError::GroupNotFound)?;
+        self.group_members.remove(&key);
+        Ok(())
+    }
+
+    async fn add_group_member(&mut self, tenant: &TenantId, id: &GroupId, email: &str) -> Result<(), UserStoreError> {
+        let (key, email) = self.group_member_keys(tenant, id, email)?;
+
+        let members = self.group_members.entry(key).or_default();
+        if !members.contains(&email) {
+            members.push(email);
+        }
+        Ok(())
+    }
+
+    async fn remove_group_member(&mut self, tenant: &TenantId, id: &GroupId, email: &str) -> Result<(), UserStoreError> {
+        let (key, email) = self.group_member_keys(tenant, id, email)?;
+
+        if let Some(members) = self.group_members.get_mut(&key) {
+            members.retain(|member| member != &email);
+        }
+        Ok(())
+    }
+
+    async fn list_group_members(&self, tenant: &TenantId, id: &GroupId) -> Result<Vec<User>, UserStoreError> {
+        let key = (tenant.clone(), id.clone());
+        if !self.groups.contains_key(&key) {
+            return Err(UserStoreError::GroupNotFound);
+        }
+
+        let mut members: Vec<User> = self.group_members
+            .get(&key)
+            .into_iter()
+            .flatten()
+            .filter_map(|email| self.users.get(&(tenant.clone(), email.clone())))
+            .cloned()
+            .collect();
+        members.sort_by(|a, b| a.email.as_ref().expose_secret().cmp(b.email.as_ref().expose_secret()));
+        Ok(members)
+    }
+
+    async fn get_user_groups(&self, tenant: &TenantId, email: &str) -> Result<Vec<Group>, UserStoreError> {
+        let key = user_key(tenant, email)?;
+        if !self.users.contains_key(&key) {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        let mut groups: Vec<Group> = self.group_members
+            .iter()
+            .filter(|((group_tenant, _), members)| group_tenant == tenant && members.contains(&key.1))
+            .filter_map(|(group_key, _)| self.groups.get(group_key))
+            .cloned()
+            .collect();
+        groups.sort_by(|a, b| a.display_name.as_ref().cmp(b.display_name.as_ref()));
+        Ok(groups)
+    }
+}
+
+impl HashmapUserStore {
+    fn get_user_mut(&mut self, tenant: &TenantId, email: &str) -> Result<&mut User, UserStoreError> {
+        let key = user_key(tenant, email)?;
+        self.users.get_mut(&key).ok_or(UserStoreError::UserNotFound)
+    }
+
+    // `owner` is the user being given the username, who may already have it.
+    fn ensure_username_is_free(
+        &self,
+        tenant: &TenantId,
+        username: &Username,
+        owner: Option<&Email>,
+    ) -> Result<(), UserStoreError> {
+        let taken = self.users.iter().any(|((user_tenant, email), user)| {
+            user_tenant == tenant
+                && Some(email) != owner
+                && user.username.as_ref().is_some_and(|taken| taken.is_same_as(username))
+        });
+
+        match taken {
+            true => Err(UserStoreError::UsernameTaken),
+            false => Ok(()),
+        }
+    }
+
+    fn ensure_group_name_is_free(
+        &self,
+        tenant: &TenantId,
+        display_name: &GroupName,
+        renamed: Option<&GroupId>,
+    ) -> Result<(), UserStoreError> {
+        let taken = self.groups.iter().any(|((group_tenant, id), group)| {
+            group_tenant == tenant && Some(id) != renamed && group.display_name.is_same_as(display_name)
+        });
+
+        match taken {
+            true => Err(UserStoreError::GroupAlreadyExists),
+            false => Ok(()),
+        }
+    }
+
+    fn group_member_keys(&self, tenant: &TenantId, id: &GroupId, email: &str) -> Result<(GroupKey, Email), UserStoreError> {
+        let (_, email) = user_key(tenant, email)?;
+        let key = (tenant.clone(), id.clone());
+        if !self.groups.contains_key(&key) {
+            return Err(UserStoreError::GroupNotFound);
+        }
+        if !self.users.contains_key(&(tenant.clone(), email.clone())) {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        Ok((key, email))
+    }
 }
 
 impl IntoShared for HashmapUserStore {}
@@ -61,73 +488,432 @@ mod tests {
         let mut user_store = HashmapUserStore::default();
         let user = User::new(Secret::new("test@test.com".to_string()), Secret::new("password".to_string()), false).unwrap();
 
-        assert_eq!(user_store.add_user(user.clone()).await, Ok(()));
+        assert_eq!(user_store.add_user(&TenantId::default(), user.clone()).await, Ok(()));
     }
 
     #[tokio::test]
     async fn test_add_user_twice() {
         let mut user_store = HashmapUserStore::default();
         let user = User::new(Secret::new("test@test.com".to_string()), Secret::new("password".to_string()), false).unwrap();
-        user_store.add_user(user.clone()).await.unwrap();
+        user_store.add_user(&TenantId::default(), user.clone()).await.unwrap();
 
-        assert_eq!(user_store.add_user(user.clone()).await, Err(UserStoreError::UserAlreadyExists));
+        assert_eq!(user_store.add_user(&TenantId::default(), user.clone()).await, Err(UserStoreError::UserAlreadyExists));
     }
 
     #[tokio::test]
     async fn test_get_user() {
         let mut user_store = HashmapUserStore::default();
         let user = User::new(Secret::new("test@test.com".to_string()), Secret::new("password".to_string()), false).unwrap();
-        user_store.add_user(user.clone()).await.unwrap();
+        user_store.add_user(&TenantId::default(), user.clone()).await.unwrap();
 
-        assert_eq!(user_store.get_user("test@test.com").await, Ok(user));
+        assert_eq!(user_store.get_user(&TenantId::default(), "test@test.com").await, Ok(user));
     }
 
     #[tokio::test]
     async fn test_get_user_not_found() {
         let user_store = HashmapUserStore::default();
-        assert_eq!(user_store.get_user("test@test.com").await, Err(UserStoreError::UserNotFound));
+        assert_eq!(user_store.get_user(&TenantId::default(), "test@test.com").await, Err(UserStoreError::UserNotFound));
     }
 
     #[tokio::test]
     async fn test_validate_user() {
         let mut user_store = HashmapUserStore::default();
         let user = User::new(Secret::new("test@test.com".to_string()), Secret::new("password".to_string()), false).unwrap();
-        user_store.add_user(user.clone()).await.unwrap();
+        user_store.add_user(&TenantId::default(), user.clone()).await.unwrap();
 
-        assert!(user_store.validate_user("test@test.com", "password").await.is_ok());
+        assert!(user_store.validate_user(&TenantId::default(), "test@test.com", "password").await.is_ok());
     }
 
     #[tokio::test]
     async fn test_validate_user_not_found() {
         let user_store = HashmapUserStore::default();
 
-        assert_eq!(user_store.validate_user("test@test.com", "password").await, Err(UserStoreError::UserNotFound));
+        assert_eq!(user_store.validate_user(&TenantId::default(), "test@test.com", "password").await, Err(UserStoreError::UserNotFound));
     }
 
     #[tokio::test]
     async fn test_validate_user_invalid_credentials() {
         let mut user_store = HashmapUserStore::default();
         let user = User::new(Secret::new("test@test.com".to_string()), Secret::new("password".to_string()), false).unwrap();
-        user_store.add_user(user.clone()).await.unwrap();
+        user_store.add_user(&TenantId::default(), user.clone()).await.unwrap();
 
-        assert_eq!(user_store.validate_user("test@test.com", "wrong_password").await, Err(UserStoreError::InvalidCredentials));
+        assert_eq!(user_store.validate_user(&TenantId::default(), "test@test.com", "wrong_password").await, Err(UserStoreError::InvalidCredentials));
     }
 
     #[tokio::test]
     async fn test_delete_user() {
         let mut user_store = HashmapUserStore::default();
         let user = User::new(Secret::new("test@test.com".to_string()), Secret::new("password".to_string()), false).unwrap();
-        user_store.add_user(user.clone()).await.unwrap();
+        user_store.add_user(&TenantId::default(), user.clone()).await.unwrap();
+
+        assert_eq!(user_store.delete_user(&TenantId::default(), "test@test.com").await, Ok(()));
+    }
+
+    #[tokio::test]
+    async fn test_link_identity() {
+        let mut user_store = HashmapUserStore::default();
+        let user = User::new(Secret::new("test@test.com".to_string()), Secret::new("password".to_string()), false).unwrap();
+        user_store.add_user(&TenantId::default(), user.clone()).await.unwrap();
+        let identity = LinkedIdentity::new("google".to_string(), "subject".to_string());
+
+        assert_eq!(user_store.link_identity(&TenantId::default(), "test@test.com", identity.clone()).await, Ok(()));
+        assert_eq!(user_store.get_linked_identities(&TenantId::default(), "test@test.com").await, Ok(vec![identity]));
+        assert_eq!(user_store.get_user_by_identity(&TenantId::default(), "google", "subject").await, Ok(user));
+    }
+
+    #[tokio::test]
+    async fn test_link_identity_already_linked_to_another_user() {
+        let mut user_store = HashmapUserStore::default();
+        for email in ["test@test.com", "other@test.com"] {
+            let user = User::new(Secret::new(email.to_string()), Secret::new("password".to_string()), false).unwrap();
+            user_store.add_user(&TenantId::default(), user).await.unwrap();
+        }
+        let identity = LinkedIdentity::new("google".to_string(), "subject".to_string());
+        user_store.link_identity(&TenantId::default(), "test@test.com", identity.clone()).await.unwrap();
+
+        assert_eq!(
+            user_store.link_identity(&TenantId::default(), "other@test.com", identity).await,
+            Err(UserStoreError::IdentityAlreadyLinked)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unlink_identity() {
+        let mut user_store = HashmapUserStore::default();
+        let user = User::new(Secret::new("test@test.com".to_string()), Secret::new("password".to_string()), false).unwrap();
+        user_store.add_user(&TenantId::default(), user).await.unwrap();
+        let identity = LinkedIdentity::new("google".to_string(), "subject".to_string());
+        user_store.link_identity(&TenantId::default(), "test@test.com", identity).await.unwrap();
+
+        assert_eq!(user_store.unlink_identity(&TenantId::default(), "test@test.com", "google").await, Ok(()));
+        assert_eq!(user_store.get_linked_identities(&TenantId::default(), "test@test.com").await, Ok(vec![]));
+        assert_eq!(
+            user_store.unlink_identity(&TenantId::default(), "test@test.com", "google").await,
+            Err(UserStoreError::IdentityNotFound)
+        );
+    }
 
-        assert_eq!(user_store.delete_user("test@test.com").await, Ok(()));
+    #[tokio::test]
+    async fn test_unlink_last_login_method() {
+        let mut user_store = HashmapUserStore::default();
+        let user = User::new_passwordless(Email::parse("test@test.com").unwrap());
+        user_store.add_user(&TenantId::default(), user).await.unwrap();
+        let identity = LinkedIdentity::new("google".to_string(), "subject".to_string());
+        user_store.link_identity(&TenantId::default(), "test@test.com", identity).await.unwrap();
+
+        assert_eq!(
+            user_store.unlink_identity(&TenantId::default(), "test@test.com", "google").await,
+            Err(UserStoreError::LastLoginMethod)
+        );
     }
 
     #[tokio::test]
     async fn test_delete_user_not_found() {
         let mut user_store = HashmapUserStore::default();
         assert_eq!(
-            user_store.delete_user("test@test.com").await,
+            user_store.delete_user(&TenantId::default(), "test@test.com").await,
+            Err(UserStoreError::UserNotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_users_are_isolated_by_tenant() {
+        let mut user_store = HashmapUserStore::default();
+        let other_tenant = TenantId::parse("acme").unwrap();
+        let user = User::new(Secret::new("test@test.com".to_string()), Secret::new("password".to_string()), false).unwrap();
+        let other_user = User::new(Secret::new("test@test.com".to_string()), Secret::new("other_password".to_string()), false).unwrap();
+        user_store.add_user(&TenantId::default(), user).await.unwrap();
+
+        assert_eq!(user_store.get_user(&other_tenant, "test@test.com").await, Err(UserStoreError::UserNotFound));
+        assert_eq!(user_store.add_user(&other_tenant, other_user).await, Ok(()));
+        assert_eq!(
+            user_store.validate_user(&other_tenant, "test@test.com", "password").await,
+            Err(UserStoreError::InvalidCredentials)
+        );
+
+        user_store.delete_user(&other_tenant, "test@test.com").await.unwrap();
+        assert!(user_store.get_user(&TenantId::default(), "test@test.com").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_assign_and_remove_role() {
+        let mut user_store = HashmapUserStore::default();
+        let user = User::new(Secret::new("test@test.com".to_string()), Secret::new("password".to_string()), false).unwrap();
+        user_store.add_user(&TenantId::default(), user).await.unwrap();
+
+        assert_eq!(user_store.get_authorization(&TenantId::default(), "test@test.com").await, Ok(UserAuthorization::default()));
+
+        user_store.assign_role(&TenantId::default(), "test@test.com", &Role::admin()).await.unwrap();
+        assert_eq!(user_store.assign_role(&TenantId::default(), "test@test.com", &Role::admin()).await, Ok(()));
+
+        let authorization = user_store.get_authorization(&TenantId::default(), "test@test.com").await.unwrap();
+        assert_eq!(authorization.roles, vec![Role::admin()]);
+        assert_eq!(authorization.permissions.len(), ADMIN_PERMISSIONS.len());
+
+        user_store.remove_role(&TenantId::default(), "test@test.com", &Role::admin()).await.unwrap();
+        assert_eq!(user_store.get_authorization(&TenantId::default(), "test@test.com").await, Ok(UserAuthorization::default()));
+    }
+
+    #[tokio::test]
+    async fn test_assign_unknown_role() {
+        let mut user_store = HashmapUserStore::default();
+        let user = User::new(Secret::new("test@test.com".to_string()), Secret::new("password".to_string()), false).unwrap();
+        user_store.add_user(&TenantId::default(), user).await.unwrap();
+
+        assert_eq!(
+            user_store.assign_role(&TenantId::default(), "test@test.com", &Role::parse("auditor").unwrap()).await,
+            Err(UserStoreError::RoleNotFound)
+        );
+        assert_eq!(
+            user_store.assign_role(&TenantId::default(), "other@test.com", &Role::admin()).await,
+            Err(UserStoreError::UserNotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_users() {
+        let mut user_store = HashmapUserStore::default();
+        for email in ["b@acme.com", "a@acme.com", "c@example.com"] {
+            let user = User::new(Secret::new(email.to_string()), Secret::new("password".to_string()), false).unwrap();
+            user_store.add_user(&TenantId::default(), user).await.unwrap();
+        }
+        let other_tenant = TenantId::parse("other").unwrap();
+        let user = User::new(Secret::new("d@acme.com".to_string()), Secret::new("password".to_string()), false).unwrap();
+        user_store.add_user(&other_tenant, user).await.unwrap();
+
+        let query = UserQuery { search: Some("acme".to_owned()), page: 1, per_page: 1 };
+        let page = user_store.list_users(&TenantId::default(), &query).await.unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.users.len(), 1);
+        assert_eq!(page.users[0].email.as_ref().expose_secret(), "a@acme.com");
+
+        let query = UserQuery { page: 2, ..query };
+        let page = user_store.list_users(&TenantId::default(), &query).await.unwrap();
+        assert_eq!(page.users[0].email.as_ref().expose_secret(), "b@acme.com");
+    }
+
+    #[tokio::test]
+    async fn test_update_user_settings() {
+        let mut user_store = HashmapUserStore::default();
+        let tenant = TenantId::default();
+        let user = User::new(Secret::new("test@test.com".to_string()), Secret::new("password".to_string()), true).unwrap();
+        user_store.add_user(&tenant, user).await.unwrap();
+
+        user_store.set_status(&tenant, "test@test.com", AccountStatus::Suspended, Some("abuse".to_owned())).await.unwrap();
+        user_store.set_password(&tenant, "test@test.com", None).await.unwrap();
+        user_store.set_requires_2fa(&tenant, "test@test.com", false).await.unwrap();
+
+        let user = user_store.get_user(&tenant, "test@test.com").await.unwrap();
+        assert_eq!(user.status, AccountStatus::Suspended);
+        assert_eq!(user.suspension_reason.as_deref(), Some("abuse"));
+        assert!(user.password.is_none());
+        assert!(!user.requires_2fa);
+
+        user_store.set_status(&tenant, "test@test.com", AccountStatus::Active, Some("ignored".to_owned())).await.unwrap();
+        let user = user_store.get_user(&tenant, "test@test.com").await.unwrap();
+        assert_eq!(user.suspension_reason, None);
+        assert_eq!(
+            user_store.set_status(&tenant, "other@test.com", AccountStatus::Suspended, None).await,
+            Err(UserStoreError::UserNotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_schedule_deletion() {
+        let mut user_store = HashmapUserStore::default();
+        let tenant = TenantId::default();
+        let now = Utc::now();
+        for email in ["due@test.com", "later@test.com"] {
+            let user = User::new(Secret::new(email.to_string()), Secret::new("password".to_string()), false).unwrap();
+            user_store.add_user(&tenant, user).await.unwrap();
+        }
+
+        user_store.schedule_deletion(&tenant, "due@test.com", now - chrono::Duration::minutes(1)).await.unwrap();
+        user_store.schedule_deletion(&tenant, "later@test.com", now + chrono::Duration::days(1)).await.unwrap();
+
+        let due = user_store.list_users_due_for_purge(now).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].1.email.as_ref().expose_secret(), "due@test.com");
+        assert_eq!(due[0].1.status, AccountStatus::Deleted);
+
+        // Restoring the account cancels the purge
+        user_store.set_status(&tenant, "due@test.com", AccountStatus::Active, None).await.unwrap();
+        assert!(user_store.list_users_due_for_purge(now).await.unwrap().is_empty());
+        assert_eq!(user_store.get_user(&tenant, "due@test.com").await.unwrap().purge_at, None);
+    }
+
+    #[tokio::test]
+    async fn test_import_password_hash() {
+        let mut user_store = HashmapUserStore::default();
+        let tenant = TenantId::default();
+        let user = User::new(Secret::new("test@test.com".to_string()), Secret::new("password".to_string()), false).unwrap();
+        user_store.add_user(&tenant, user).await.unwrap();
+
+        assert_eq!(
+            user_store.set_password_hash(&tenant, "test@test.com", Secret::new("not-a-hash".to_owned())).await,
+            Err(UserStoreError::UnsupportedPasswordHash)
+        );
+
+        let hash = bcrypt::hash("imported123", 4).unwrap();
+        user_store.set_password_hash(&tenant, "test@test.com", Secret::new(hash)).await.unwrap();
+        assert_eq!(user_store.validate_user(&tenant, "test@test.com", "imported123").await, Ok(()));
+        assert_eq!(
+            user_store.validate_user(&tenant, "test@test.com", "password").await,
+            Err(UserStoreError::InvalidCredentials)
+        );
+
+        // Setting a password replaces the imported hash
+        let password = Password::parse("password456").unwrap();
+        user_store.set_password(&tenant, "test@test.com", Some(password)).await.unwrap();
+        assert_eq!(user_store.validate_user(&tenant, "test@test.com", "password456").await, Ok(()));
+    }
+
+    #[tokio::test]
+    async fn test_add_user_with_password_hash() {
+        let mut user_store = HashmapUserStore::default();
+        let tenant = TenantId::default();
+        let user = User::new_passwordless(Email::parse("test@test.com").unwrap());
+
+        assert_eq!(
+            user_store.add_user_with_password_hash(&tenant, user.clone(), Secret::new("not-a-hash".to_owned())).await,
+            Err(UserStoreError::UnsupportedPasswordHash)
+        );
+        assert_eq!(user_store.get_user(&tenant, "test@test.com").await, Err(UserStoreError::UserNotFound));
+
+        let hash = bcrypt::hash("imported123", 4).unwrap();
+        user_store.add_user_with_password_hash(&tenant, user, Secret::new(hash)).await.unwrap();
+        assert_eq!(user_store.validate_user(&tenant, "test@test.com", "imported123").await, Ok(()));
+    }
+
+    #[tokio::test]
+    async fn test_get_user_by_id() {
+        let mut user_store = HashmapUserStore::default();
+        let user = User::new(Secret::new("test@test.com".to_string()), Secret::new("password".to_string()), false).unwrap();
+        user_store.add_user(&TenantId::default(), user.clone()).await.unwrap();
+
+        assert_eq!(user_store.get_user_by_id(&TenantId::default(), &user.id).await, Ok(user.clone()));
+        assert_eq!(
+            user_store.get_user_by_id(&TenantId::parse("acme").unwrap(), &user.id).await,
             Err(UserStoreError::UserNotFound)
         );
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_get_user_by_username() {
+        let mut user_store = HashmapUserStore::default();
+        let tenant = TenantId::default();
+        let user = User::new(Secret::new("test@test.com".to_string()), Secret::new("password".to_string()), false)
+            .unwrap()
+            .with_username(Username::parse("Jane.Doe").unwrap());
+        user_store.add_user(&tenant, user.clone()).await.unwrap();
+
+        assert_eq!(user_store.get_user_by_username(&tenant, &Username::parse("jane.doe").unwrap()).await, Ok(user.clone()));
+        assert_eq!(
+            user_store.get_user_by_username(&TenantId::parse("acme").unwrap(), &Username::parse("jane.doe").unwrap()).await,
+            Err(UserStoreError::UserNotFound)
+        );
+
+        user_store.set_username(&tenant, "test@test.com", None).await.unwrap();
+        assert_eq!(
+            user_store.get_user_by_username(&tenant, &Username::parse("jane.doe").unwrap()).await,
+            Err(UserStoreError::UserNotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_usernames_are_unique_per_tenant() {
+        let mut user_store = HashmapUserStore::default();
+        let tenant = TenantId::default();
+        let username = Username::parse("jane").unwrap();
+        let jane = User::new(Secret::new("jane@test.com".to_string()), Secret::new("password".to_string()), false).unwrap();
+        let john = User::new(Secret::new("john@test.com".to_string()), Secret::new("password".to_string()), false).unwrap();
+        user_store.add_user(&tenant, jane.with_username(username.clone())).await.unwrap();
+
+        assert_eq!(
+            user_store.add_user(&tenant, john.clone().with_username(Username::parse("JANE").unwrap())).await,
+            Err(UserStoreError::UsernameTaken)
+        );
+        user_store.add_user(&tenant, john).await.unwrap();
+        assert_eq!(
+            user_store.set_username(&tenant, "john@test.com", Some(username.clone())).await,
+            Err(UserStoreError::UsernameTaken)
+        );
+        // Setting a user's own username again is fine
+        assert_eq!(user_store.set_username(&tenant, "jane@test.com", Some(username.clone())).await, Ok(()));
+
+        let other_tenant = TenantId::parse("acme").unwrap();
+        let user = User::new(Secret::new("jane@test.com".to_string()), Secret::new("password".to_string()), false).unwrap();
+        assert_eq!(user_store.add_user(&other_tenant, user.with_username(username)).await, Ok(()));
+    }
+
+    #[tokio::test]
+    async fn test_group_names_are_unique_per_tenant() {
+        let mut user_store = HashmapUserStore::default();
+        let tenant = TenantId::default();
+        let engineering = Group::new(GroupName::parse("Engineering").unwrap());
+        user_store.add_group(&tenant, engineering.clone()).await.unwrap();
+
+        assert_eq!(
+            user_store.add_group(&tenant, Group::new(GroupName::parse("engineering").unwrap())).await,
+            Err(UserStoreError::GroupAlreadyExists)
+        );
+        assert_eq!(
+            user_store.add_group(&TenantId::parse("acme").unwrap(), Group::new(GroupName::parse("Engineering").unwrap())).await,
+            Ok(())
+        );
+
+        let sales = Group::new(GroupName::parse("Sales").unwrap());
+        user_store.add_group(&tenant, sales.clone()).await.unwrap();
+        assert_eq!(
+            user_store.rename_group(&tenant, &sales.id, GroupName::parse("ENGINEERING").unwrap()).await,
+            Err(UserStoreError::GroupAlreadyExists)
+        );
+        // Changing the case of a group's own name is fine
+        assert_eq!(user_store.rename_group(&tenant, &engineering.id, GroupName::parse("ENGINEERING").unwrap()).await, Ok(()));
+
+        let names: Vec<String> = user_store.list_groups(&tenant).await.unwrap()
+            .into_iter()
+            .map(|group| group.display_name.as_ref().to_owned())
+            .collect();
+        assert_eq!(names, vec!["ENGINEERING", "Sales"]);
+    }
+
+    #[tokio::test]
+    async fn test_group_members() {
+        let mut user_store = HashmapUserStore::default();
+        let tenant = TenantId::default();
+        for email in ["b@test.com", "a@test.com"] {
+            let user = User::new(Secret::new(email.to_string()), Secret::new("password".to_string()), false).unwrap();
+            user_store.add_user(&tenant, user).await.unwrap();
+        }
+        let group = Group::new(GroupName::parse("Engineering").unwrap());
+        user_store.add_group(&tenant, group.clone()).await.unwrap();
+
+        for email in ["b@test.com", "a@test.com", "a@test.com"] {
+            user_store.add_group_member(&tenant, &group.id, email).await.unwrap();
+        }
+        assert_eq!(
+            user_store.add_group_member(&tenant, &group.id, "missing@test.com").await,
+            Err(UserStoreError::UserNotFound)
+        );
+        assert_eq!(
+            user_store.add_group_member(&tenant, &GroupId::default(), "a@test.com").await,
+            Err(UserStoreError::GroupNotFound)
+        );
+
+        let members: Vec<String> = user_store.list_group_members(&tenant, &group.id).await.unwrap()
+            .into_iter()
+            .map(|user| user.email.as_ref().expose_secret().to_owned())
+            .collect();
+        assert_eq!(members, vec!["a@test.com", "b@test.com"]);
+        assert_eq!(user_store.get_user_groups(&tenant, "a@test.com").await, Ok(vec![group.clone()]));
+
+        user_store.remove_group_member(&tenant, &group.id, "a@test.com").await.unwrap();
+        user_store.delete_user(&tenant, "b@test.com").await.unwrap();
+        assert_eq!(user_store.list_group_members(&tenant, &group.id).await, Ok(vec![]));
+        assert_eq!(user_store.get_user_groups(&tenant, "a@test.com").await, Ok(vec![]));
+
+        user_store.delete_group(&tenant, &group.id).await.unwrap();
+        assert_eq!(user_store.get_group(&tenant, &group.id).await, Err(UserStoreError::GroupNotFound));
+    }
+}
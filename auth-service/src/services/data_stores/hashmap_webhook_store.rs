@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::{
+    IntoShared, TenantId, WebhookDelivery, WebhookDeliveryId, WebhookEndpoint, WebhookEndpointId, WebhookStore,
+    WebhookStoreError,
+};
+
+#[derive(Default)]
+pub struct HashmapWebhookStore {
+    endpoints: HashMap<WebhookEndpointId, WebhookEndpoint>,
+    deliveries: HashMap<WebhookDeliveryId, WebhookDelivery>,
+}
+
+#[async_trait::async_trait]
+impl WebhookStore for HashmapWebhookStore {
+    async fn add_endpoint(&mut self, endpoint: WebhookEndpoint) -> Result<(), WebhookStoreError> {
+        self.endpoints.insert(endpoint.id.clone(), endpoint);
+        Ok(())
+    }
+
+    async fn get_endpoint(&self, id: &WebhookEndpointId) -> Result<WebhookEndpoint, WebhookStoreError> {
+        self.endpoints.get(id).cloned().ok_or(WebhookStoreError::EndpointNotFound)
+    }
+
+    async fn list_endpoints(&self, tenant: &TenantId) -> Result<Vec<WebhookEndpoint>, WebhookStoreError> {
+        let mut endpoints: Vec<WebhookEndpoint> = self.endpoints
+            .values()
+            .filter(|endpoint| &endpoint.tenant == tenant)
+            .cloned()
+            .collect();
+        endpoints.sort_by_key(|endpoint| endpoint.created_at);
+        Ok(endpoints)
+    }
+
+    async fn remove_endpoint(&mut self, id: &WebhookEndpointId) -> Result<(), WebhookStoreError> {
+        self.endpoints.remove(id).ok_or(WebhookStoreError::EndpointNotFound)?;
+        self.deliveries.retain(|_, delivery| &delivery.endpoint_id != id);
+        Ok(())
+    }
+
+    async fn add_delivery(&mut self, delivery: WebhookDelivery) -> Result<(), WebhookStoreError> {
+        if !self.endpoints.contains_key(&delivery.endpoint_id) {
+            return Err(WebhookStoreError::EndpointNotFound);
+        }
+
+        self.deliveries.insert(delivery.id.clone(), delivery);
+        Ok(())
+    }
+
+    async fn update_delivery(&mut self, delivery: &WebhookDelivery) -> Result<(), WebhookStoreError> {
+        let stored = self.deliveries.get_mut(&delivery.id).ok_or(WebhookStoreError::DeliveryNotFound)?;
+        *stored = delivery.clone();
+        Ok(())
+    }
+
+    async fn list_due_deliveries(&self, now: DateTime<Utc>, limit: u32) -> Result<Vec<WebhookDelivery>, WebhookStoreError> {
+        let mut due: Vec<WebhookDelivery> = self.deliveries
+            .values()
+            .filter(|delivery| delivery.is_due(now))
+            .cloned()
+            .collect();
+        due.sort_by_key(|delivery| delivery.next_attempt_at);
+        due.truncate(limit as usize);
+        Ok(due)
+    }
+
+    async fn list_deliveries(&self, endpoint_id: &WebhookEndpointId, limit: u32) -> Result<Vec<WebhookDelivery>, WebhookStoreError> {
+        let mut deliveries: Vec<WebhookDelivery> = self.deliveries
+            .values()
+            .filter(|delivery| &delivery.endpoint_id == endpoint_id)
+            .cloned()
+            .collect();
+        deliveries.sort_by_key(|delivery| std::cmp::Reverse(delivery.created_at));
+        deliveries.truncate(limit as usize);
+        Ok(deliveries)
+    }
+}
+
+impl IntoShared for HashmapWebhookStore {}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::domain::{WebhookEvent, WebhookEventType};
+
+    fn endpoint() -> WebhookEndpoint {
+        WebhookEndpoint::new(
+            TenantId::default(),
+            "https://example.com/hooks".to_owned(),
+            vec![WebhookEventType::UserSignedUp],
+        )
+    }
+
+    fn delivery(endpoint: &WebhookEndpoint) -> WebhookDelivery {
+        let event = WebhookEvent::new(&endpoint.tenant, WebhookEventType::UserSignedUp, serde_json::json!({}));
+        WebhookDelivery::new(endpoint, &event).unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_only_list_due_deliveries() {
+        let mut store = HashmapWebhookStore::default();
+        let endpoint = endpoint();
+        store.add_endpoint(endpoint.clone()).await.unwrap();
+
+        let due = delivery(&endpoint);
+        let mut retried = delivery(&endpoint);
+        retried.record_failure("HTTP 500", Utc::now());
+        store.add_delivery(due.clone()).await.unwrap();
+        store.add_delivery(retried.clone()).await.unwrap();
+
+        assert_eq!(store.list_due_deliveries(Utc::now(), 10).await, Ok(vec![due]));
+        assert_eq!(store.list_due_deliveries(Utc::now() + Duration::hours(1), 10).await.unwrap().len(), 2);
+        assert_eq!(store.list_deliveries(&endpoint.id, 10).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn should_remove_deliveries_with_their_endpoint() {
+        let mut store = HashmapWebhookStore::default();
+        let endpoint = endpoint();
+        store.add_endpoint(endpoint.clone()).await.unwrap();
+        store.add_delivery(delivery(&endpoint)).await.unwrap();
+
+        assert_eq!(store.remove_endpoint(&endpoint.id).await, Ok(()));
+        assert_eq!(store.list_due_deliveries(Utc::now(), 10).await, Ok(vec![]));
+        assert_eq!(store.remove_endpoint(&endpoint.id).await, Err(WebhookStoreError::EndpointNotFound));
+        assert_eq!(store.add_delivery(delivery(&endpoint)).await, Err(WebhookStoreError::EndpointNotFound));
+    }
+}
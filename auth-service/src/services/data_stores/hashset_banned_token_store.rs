@@ -1,11 +1,14 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use chrono::Utc;
 use secrecy::{ExposeSecret, Secret};
 
-use crate::domain::{BannedTokenStore, IntoShared};
+use crate::domain::{BannedTokenStore, IntoShared, TenantId};
 
 #[derive(Default, Debug)]
 pub struct HashSetBannedTokenStore {
     pub banned_tokens: HashSet<String>,
+    // When each user's sessions were last banned, keyed by tenant and email
+    pub banned_sessions: HashMap<(TenantId, String), usize>,
 }
 
 #[async_trait::async_trait]
@@ -17,6 +20,23 @@ impl BannedTokenStore for HashSetBannedTokenStore {
     async fn is_token_banned(&self, token: &Secret<String>) -> bool {
         self.banned_tokens.contains(token.expose_secret())
     }
+
+    // Bans here never expire, so the TTL isn't needed
+    async fn ban_token_once(&mut self, token: &Secret<String>, _ttl_seconds: u64) -> bool {
+        self.banned_tokens.insert(token.expose_secret().to_owned())
+    }
+
+    async fn ban_sessions(&mut self, tenant: &TenantId, email: &str) -> bool {
+        let now = Utc::now().timestamp() as usize;
+        self.banned_sessions.insert((tenant.clone(), email.to_owned()), now);
+        true
+    }
+
+    async fn is_session_banned(&self, tenant: &TenantId, email: &str, issued_at: usize) -> bool {
+        self.banned_sessions
+            .get(&(tenant.clone(), email.to_owned()))
+            .is_some_and(|banned_at| issued_at <= *banned_at)
+    }
 }
 
 impl IntoShared for HashSetBannedTokenStore {}
@@ -37,7 +57,30 @@ mod tests {
         let token = Secret::new("token".to_string());
         let banned_token_store = HashSetBannedTokenStore {
             banned_tokens: vec!["token".to_string()].into_iter().collect(),
+            ..Default::default()
         };
         assert!(banned_token_store.is_token_banned(&token).await);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_ban_token_once() {
+        let mut banned_token_store = HashSetBannedTokenStore::default();
+        let token = Secret::new("token".to_string());
+        assert!(banned_token_store.ban_token_once(&token, 60).await);
+        assert!(!banned_token_store.ban_token_once(&token, 60).await);
+        assert!(banned_token_store.is_token_banned(&token).await);
+    }
+
+    #[tokio::test]
+    async fn test_is_session_banned() {
+        let mut banned_token_store = HashSetBannedTokenStore::default();
+        let tenant = TenantId::default();
+        let issued_at = Utc::now().timestamp() as usize;
+        assert!(!banned_token_store.is_session_banned(&tenant, "test@example.com", issued_at).await);
+
+        banned_token_store.ban_sessions(&tenant, "test@example.com").await;
+        assert!(banned_token_store.is_session_banned(&tenant, "test@example.com", issued_at).await);
+        assert!(!banned_token_store.is_session_banned(&tenant, "test@example.com", issued_at + 60).await);
+        assert!(!banned_token_store.is_session_banned(&tenant, "other@example.com", issued_at).await);
+    }
+}
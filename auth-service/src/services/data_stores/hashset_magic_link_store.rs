@@ -0,0 +1,51 @@
+use std::collections::HashSet;
+
+use secrecy::ExposeSecret;
+
+use crate::domain::{IntoShared, MagicLinkId, MagicLinkStore, MagicLinkStoreError};
+
+#[derive(Default)]
+pub struct HashSetMagicLinkStore {
+    links: HashSet<String>,
+}
+
+#[async_trait::async_trait]
+impl MagicLinkStore for HashSetMagicLinkStore {
+    async fn add_link(&mut self, id: &MagicLinkId) -> Result<(), MagicLinkStoreError> {
+        self.links.insert(id.as_ref().expose_secret().to_owned());
+        Ok(())
+    }
+
+    async fn consume_link(&mut self, id: &MagicLinkId) -> Result<(), MagicLinkStoreError> {
+        match self.links.remove(id.as_ref().expose_secret()) {
+            true => Ok(()),
+            false => Err(MagicLinkStoreError::LinkNotFound),
+        }
+    }
+}
+
+impl IntoShared for HashSetMagicLinkStore {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn should_consume_a_link_only_once() {
+        let mut store = HashSetMagicLinkStore::default();
+        let id = MagicLinkId::default();
+        store.add_link(&id).await.unwrap();
+
+        assert_eq!(store.consume_link(&id).await, Ok(()));
+        assert_eq!(store.consume_link(&id).await, Err(MagicLinkStoreError::LinkNotFound));
+    }
+
+    #[tokio::test]
+    async fn should_not_consume_an_unknown_link() {
+        let mut store = HashSetMagicLinkStore::default();
+        assert_eq!(
+            store.consume_link(&MagicLinkId::default()).await,
+            Err(MagicLinkStoreError::LinkNotFound)
+        );
+    }
+}
@@ -1,7 +1,29 @@
 pub mod hashmap_user_store;
 pub mod hashset_banned_token_store;
 pub mod hashmap_two_fa_code_store;
+pub mod hashmap_oidc_state_store;
+pub mod hashset_magic_link_store;
+pub mod hashmap_tenant_store;
+pub mod hashmap_invitation_store;
+pub mod vec_audit_log_store;
+pub mod hashmap_webhook_store;
+pub mod hashmap_known_device_store;
+pub mod hashmap_trusted_device_store;
+pub mod hashmap_data_export_store;
+pub mod hashmap_profile_store;
+pub mod hashmap_consent_store;
 pub mod mock_email_client;
 pub mod my_sql_user_store;
+pub mod my_sql_tenant_store;
+pub mod my_sql_invitation_store;
+pub mod my_sql_audit_log_store;
+pub mod my_sql_webhook_store;
+pub mod my_sql_known_device_store;
+pub mod my_sql_trusted_device_store;
+pub mod my_sql_data_export_store;
+pub mod my_sql_profile_store;
+pub mod my_sql_consent_store;
 pub mod redis_banned_token_store;
-pub mod redis_two_fa_code_store;
\ No newline at end of file
+pub mod redis_two_fa_code_store;
+pub mod redis_oidc_state_store;
+pub mod redis_magic_link_store;
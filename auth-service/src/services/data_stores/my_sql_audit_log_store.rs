@@ -0,0 +1,87 @@
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::eyre;
+use sqlx::{mysql::MySqlRow, MySqlPool, Row};
+
+use crate::{
+    domain::{
+        AuditEvent, AuditEventKind, AuditLogStore, AuditLogStoreError, AuditOutcome, ClientInfo, IntoShared, TenantId,
+    },
+    utils::parsable::Parsable,
+};
+
+pub struct MySqlAuditLogStore {
+    pool: MySqlPool,
+}
+
+impl MySqlAuditLogStore {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+const AUDIT_EVENT_COLUMNS: &str = "tenant_id, actor, event, outcome, detail, ip, user_agent, created_at";
+
+fn parse_event(row: MySqlRow) -> Result<AuditEvent, AuditLogStoreError> {
+    let unexpected = |e: sqlx::Error| AuditLogStoreError::UnexpectedError(e.into());
+    let invalid = |e: color_eyre::eyre::Error| AuditLogStoreError::UnexpectedError(eyre!(e));
+
+    let tenant: String = row.try_get("tenant_id").map_err(unexpected)?;
+    let kind: String = row.try_get("event").map_err(unexpected)?;
+    let outcome: String = row.try_get("outcome").map_err(unexpected)?;
+
+    Ok(AuditEvent {
+        tenant: TenantId::parse_or_error(&tenant, invalid)?,
+        actor: row.try_get("actor").map_err(unexpected)?,
+        kind: AuditEventKind::parse_or_error(&kind, invalid)?,
+        outcome: AuditOutcome::parse_or_error(&outcome, invalid)?,
+        detail: row.try_get("detail").map_err(unexpected)?,
+        client: ClientInfo {
+            ip: row.try_get("ip").map_err(unexpected)?,
+            user_agent: row.try_get("user_agent").map_err(unexpected)?,
+        },
+        created_at: row.try_get::<DateTime<Utc>, _>("created_at").map_err(unexpected)?,
+    })
+}
+
+#[async_trait::async_trait]
+impl AuditLogStore for MySqlAuditLogStore {
+    #[tracing::instrument(name = "Recording audit event in Database", skip_all)]
+    async fn record_event(&mut self, event: AuditEvent) -> Result<(), AuditLogStoreError> {
+        sqlx::query(&format!(
+            "INSERT INTO audit_events ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            AUDIT_EVENT_COLUMNS
+        ))
+            .bind(event.tenant.as_ref())
+            .bind(&event.actor)
+            .bind(event.kind.as_ref())
+            .bind(event.outcome.as_ref())
+            .bind(&event.detail)
+            .bind(&event.client.ip)
+            .bind(&event.client.user_agent)
+            .bind(event.created_at)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AuditLogStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Listing audit events from Database", skip_all)]
+    async fn list_events(&self, tenant: &TenantId, actor: &str, limit: u32) -> Result<Vec<AuditEvent>, AuditLogStoreError> {
+        sqlx::query(&format!(
+            "SELECT {} FROM audit_events WHERE tenant_id = ? AND actor = ? ORDER BY created_at DESC, id DESC LIMIT ?",
+            AUDIT_EVENT_COLUMNS
+        ))
+            .bind(tenant.as_ref())
+            .bind(actor)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AuditLogStoreError::UnexpectedError(e.into()))?
+            .into_iter()
+            .map(parse_event)
+            .collect()
+    }
+}
+
+impl IntoShared for MySqlAuditLogStore {}
@@ -0,0 +1,148 @@
+use sqlx::{mysql::MySqlRow, MySqlPool, Row};
+
+use crate::{
+    domain::{Consent, ConsentStore, ConsentStoreError, IntoShared, PolicyDocument, PolicyKind, PolicyVersion, TenantId},
+    utils::parsable::Parsable,
+};
+
+pub struct MySqlConsentStore {
+    pool: MySqlPool,
+}
+
+impl MySqlConsentStore {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl ConsentStore for MySqlConsentStore {
+    #[tracing::instrument(name = "Adding policy document to Database", skip_all)]
+    async fn add_policy(&mut self, tenant: &TenantId, document: PolicyDocument) -> Result<(), ConsentStoreError> {
+        sqlx::query("INSERT INTO policy_documents (tenant_id, kind, version, url, published_at) VALUES (?, ?, ?, ?, ?)")
+            .bind(tenant.as_ref())
+            .bind(document.kind.as_ref())
+            .bind(document.version.as_ref())
+            .bind(&document.url)
+            .bind(document.published_at)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(db_error) if db_error.is_unique_violation() => {
+                    ConsentStoreError::PolicyAlreadyExists
+                }
+                e => ConsentStoreError::UnexpectedError(e.into()),
+            })?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Retrieving current policy documents from Database", skip_all)]
+    async fn get_current_policies(&self, tenant: &TenantId) -> Result<Vec<PolicyDocument>, ConsentStoreError> {
+        let documents = sqlx::query(
+            "SELECT kind, version, url, published_at FROM policy_documents \
+             WHERE tenant_id = ? ORDER BY published_at DESC"
+        )
+            .bind(tenant.as_ref())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| ConsentStoreError::UnexpectedError(e.into()))?
+            .iter()
+            .map(document_from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut current: Vec<PolicyDocument> = Vec::new();
+        for document in documents {
+            if !current.iter().any(|policy| policy.kind == document.kind) {
+                current.push(document);
+            }
+        }
+
+        Ok(current)
+    }
+
+    #[tracing::instrument(name = "Retrieving policy document from Database", skip_all)]
+    async fn get_policy(
+        &self,
+        tenant: &TenantId,
+        kind: PolicyKind,
+        version: &PolicyVersion,
+    ) -> Result<PolicyDocument, ConsentStoreError> {
+        let row = sqlx::query(
+            "SELECT kind, version, url, published_at FROM policy_documents \
+             WHERE tenant_id = ? AND kind = ? AND version = ?"
+        )
+            .bind(tenant.as_ref())
+            .bind(kind.as_ref())
+            .bind(version.as_ref())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| ConsentStoreError::UnexpectedError(e.into()))?
+            .ok_or(ConsentStoreError::PolicyNotFound)?;
+
+        document_from_row(&row)
+    }
+
+    #[tracing::instrument(name = "Adding consent to Database", skip_all)]
+    async fn add_consent(&mut self, tenant: &TenantId, email: &str, consent: Consent) -> Result<(), ConsentStoreError> {
+        sqlx::query(
+            "INSERT IGNORE INTO user_consents (tenant_id, email, kind, version, accepted_at) VALUES (?, ?, ?, ?, ?)"
+        )
+            .bind(tenant.as_ref())
+            .bind(email)
+            .bind(consent.kind.as_ref())
+            .bind(consent.version.as_ref())
+            .bind(consent.accepted_at)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| ConsentStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Listing consents from Database", skip_all)]
+    async fn list_consents(&self, tenant: &TenantId, email: &str) -> Result<Vec<Consent>, ConsentStoreError> {
+        sqlx::query(
+            "SELECT kind, version, accepted_at FROM user_consents \
+             WHERE tenant_id = ? AND email = ? ORDER BY accepted_at DESC"
+        )
+            .bind(tenant.as_ref())
+            .bind(email)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| ConsentStoreError::UnexpectedError(e.into()))?
+            .iter()
+            .map(|row| {
+                let (kind, version) = kind_and_version(row)?;
+                Ok(Consent {
+                    kind,
+                    version,
+                    accepted_at: row.try_get("accepted_at").map_err(|e| ConsentStoreError::UnexpectedError(e.into()))?,
+                })
+            })
+            .collect()
+    }
+}
+
+fn kind_and_version(row: &MySqlRow) -> Result<(PolicyKind, PolicyVersion), ConsentStoreError> {
+    let kind: String = row.try_get("kind").map_err(|e| ConsentStoreError::UnexpectedError(e.into()))?;
+    let version: String = row.try_get("version").map_err(|e| ConsentStoreError::UnexpectedError(e.into()))?;
+
+    Ok((
+        PolicyKind::parse(kind).map_err(ConsentStoreError::UnexpectedError)?,
+        PolicyVersion::parse(version).map_err(ConsentStoreError::UnexpectedError)?,
+    ))
+}
+
+fn document_from_row(row: &MySqlRow) -> Result<PolicyDocument, ConsentStoreError> {
+    let (kind, version) = kind_and_version(row)?;
+
+    Ok(PolicyDocument {
+        kind,
+        version,
+        url: row.try_get("url").map_err(|e| ConsentStoreError::UnexpectedError(e.into()))?,
+        published_at: row.try_get("published_at").map_err(|e| ConsentStoreError::UnexpectedError(e.into()))?,
+    })
+}
+
+impl IntoShared for MySqlConsentStore {}
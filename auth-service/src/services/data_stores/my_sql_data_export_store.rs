@@ -0,0 +1,102 @@
+use color_eyre::eyre::eyre;
+use sqlx::{mysql::MySqlRow, MySqlPool, Row};
+
+use crate::{
+    domain::{DataExport, DataExportId, DataExportStatus, DataExportStore, DataExportStoreError, IntoShared, TenantId},
+    utils::parsable::Parsable,
+};
+
+pub struct MySqlDataExportStore {
+    pool: MySqlPool,
+}
+
+impl MySqlDataExportStore {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl DataExportStore for MySqlDataExportStore {
+    #[tracing::instrument(name = "Adding data export to Database", skip_all)]
+    async fn add_export(&mut self, tenant: &TenantId, email: &str, export: DataExport) -> Result<(), DataExportStoreError> {
+        let mut transaction = self.pool.begin().await.map_err(|e| DataExportStoreError::UnexpectedError(e.into()))?;
+
+        sqlx::query("DELETE FROM data_exports WHERE tenant_id = ? AND email = ?")
+            .bind(tenant.as_ref())
+            .bind(email)
+            .execute(&mut *transaction)
+            .await
+            .map_err(|e| DataExportStoreError::UnexpectedError(e.into()))?;
+
+        sqlx::query(
+            "INSERT INTO data_exports (id, tenant_id, email, status, archive, requested_at, completed_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?)"
+        )
+            .bind(export.id.as_ref())
+            .bind(tenant.as_ref())
+            .bind(email)
+            .bind(export.status.as_ref())
+            .bind(&export.archive)
+            .bind(export.requested_at)
+            .bind(export.completed_at)
+            .execute(&mut *transaction)
+            .await
+            .map_err(|e| DataExportStoreError::UnexpectedError(e.into()))?;
+
+        transaction.commit().await.map_err(|e| DataExportStoreError::UnexpectedError(e.into()))
+    }
+
+    #[tracing::instrument(name = "Retrieving data export from Database", skip_all)]
+    async fn get_latest_export(&self, tenant: &TenantId, email: &str) -> Result<DataExport, DataExportStoreError> {
+        let row = sqlx::query(
+            "SELECT id, status, archive, requested_at, completed_at FROM data_exports \
+             WHERE tenant_id = ? AND email = ? ORDER BY requested_at DESC LIMIT 1"
+        )
+            .bind(tenant.as_ref())
+            .bind(email)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| DataExportStoreError::UnexpectedError(e.into()))?
+            .ok_or(DataExportStoreError::ExportNotFound)?;
+
+        export_from_row(&row)
+    }
+
+    #[tracing::instrument(name = "Updating data export in Database", skip_all)]
+    async fn update_export(&mut self, tenant: &TenantId, email: &str, export: DataExport) -> Result<(), DataExportStoreError> {
+        let result = sqlx::query(
+            "UPDATE data_exports SET status = ?, archive = ?, completed_at = ? \
+             WHERE id = ? AND tenant_id = ? AND email = ?"
+        )
+            .bind(export.status.as_ref())
+            .bind(&export.archive)
+            .bind(export.completed_at)
+            .bind(export.id.as_ref())
+            .bind(tenant.as_ref())
+            .bind(email)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DataExportStoreError::UnexpectedError(e.into()))?;
+
+        match result.rows_affected() {
+            0 => Err(DataExportStoreError::ExportNotFound),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn export_from_row(row: &MySqlRow) -> Result<DataExport, DataExportStoreError> {
+    let id: String = row.try_get("id").map_err(|e| DataExportStoreError::UnexpectedError(e.into()))?;
+    let status: String = row.try_get("status").map_err(|e| DataExportStoreError::UnexpectedError(e.into()))?;
+
+    Ok(DataExport {
+        id: DataExportId::parse_or_error(&id, |e| DataExportStoreError::UnexpectedError(eyre!(e)))?,
+        status: DataExportStatus::parse_or_error(&status, |e| DataExportStoreError::UnexpectedError(eyre!(e)))?,
+        archive: row.try_get("archive").map_err(|e| DataExportStoreError::UnexpectedError(e.into()))?,
+        requested_at: row.try_get("requested_at").map_err(|e| DataExportStoreError::UnexpectedError(e.into()))?,
+        completed_at: row.try_get("completed_at").map_err(|e| DataExportStoreError::UnexpectedError(e.into()))?,
+    })
+}
+
+impl IntoShared for MySqlDataExportStore {}
@@ -0,0 +1,106 @@
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::eyre;
+use secrecy::ExposeSecret;
+use sqlx::{mysql::MySqlRow, MySqlPool, Row};
+
+use crate::{
+    domain::{Email, IntoShared, Invitation, InvitationId, InvitationStore, InvitationStoreError, Role, TenantId},
+    utils::parsable::Parsable,
+};
+
+pub struct MySqlInvitationStore {
+    pool: MySqlPool,
+}
+
+impl MySqlInvitationStore {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+const INVITATION_COLUMNS: &str = "id, tenant_id, email, role, invited_by, created_at, expires_at";
+
+fn parse_invitation(row: MySqlRow) -> Result<Invitation, InvitationStoreError> {
+    let unexpected = |e: sqlx::Error| InvitationStoreError::UnexpectedError(e.into());
+    let invalid = |e: color_eyre::eyre::Error| InvitationStoreError::UnexpectedError(eyre!(e));
+
+    let id: String = row.try_get("id").map_err(unexpected)?;
+    let tenant: String = row.try_get("tenant_id").map_err(unexpected)?;
+    let email: String = row.try_get("email").map_err(unexpected)?;
+    let role: Option<String> = row.try_get("role").map_err(unexpected)?;
+
+    Ok(Invitation {
+        id: InvitationId::parse_or_error(&id, invalid)?,
+        tenant: TenantId::parse_or_error(&tenant, invalid)?,
+        email: Email::parse_or_error(&email, invalid)?,
+        role: role.map(|role| Role::parse_or_error(&role, invalid)).transpose()?,
+        invited_by: row.try_get("invited_by").map_err(unexpected)?,
+        created_at: row.try_get::<DateTime<Utc>, _>("created_at").map_err(unexpected)?,
+        expires_at: row.try_get::<DateTime<Utc>, _>("expires_at").map_err(unexpected)?,
+    })
+}
+
+#[async_trait::async_trait]
+impl InvitationStore for MySqlInvitationStore {
+    #[tracing::instrument(name = "Adding invitation to Database", skip_all)]
+    async fn add_invitation(&mut self, invitation: Invitation) -> Result<(), InvitationStoreError> {
+        sqlx::query(
+            "INSERT INTO invitations (id, tenant_id, email, role, invited_by, created_at, expires_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?)"
+        )
+            .bind(invitation.id.as_ref())
+            .bind(invitation.tenant.as_ref())
+            .bind(invitation.email.as_ref().expose_secret())
+            .bind(invitation.role.as_ref().map(|role| role.as_ref()))
+            .bind(&invitation.invited_by)
+            .bind(invitation.created_at)
+            .bind(invitation.expires_at)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| InvitationStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Retrieving invitation from Database", skip_all)]
+    async fn get_invitation(&self, id: &InvitationId) -> Result<Invitation, InvitationStoreError> {
+        sqlx::query(&format!("SELECT {} FROM invitations WHERE id = ?", INVITATION_COLUMNS))
+            .bind(id.as_ref())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| InvitationStoreError::UnexpectedError(e.into()))?
+            .map(parse_invitation)
+            .ok_or(InvitationStoreError::InvitationNotFound)?
+    }
+
+    #[tracing::instrument(name = "Listing invitations from Database", skip_all)]
+    async fn list_invitations(&self, tenant: &TenantId) -> Result<Vec<Invitation>, InvitationStoreError> {
+        sqlx::query(&format!(
+            "SELECT {} FROM invitations WHERE tenant_id = ? ORDER BY created_at",
+            INVITATION_COLUMNS
+        ))
+            .bind(tenant.as_ref())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| InvitationStoreError::UnexpectedError(e.into()))?
+            .into_iter()
+            .map(parse_invitation)
+            .collect()
+    }
+
+    #[tracing::instrument(name = "Removing invitation from Database", skip_all)]
+    async fn remove_invitation(&mut self, id: &InvitationId) -> Result<(), InvitationStoreError> {
+        let result = sqlx::query("DELETE FROM invitations WHERE id = ?")
+            .bind(id.as_ref())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| InvitationStoreError::UnexpectedError(e.into()))?;
+
+        match result.rows_affected() {
+            0 => Err(InvitationStoreError::InvitationNotFound),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl IntoShared for MySqlInvitationStore {}
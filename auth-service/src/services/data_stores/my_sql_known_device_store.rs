@@ -0,0 +1,98 @@
+use sqlx::{MySqlPool, Row};
+
+use crate::{
+    domain::{
+        DeviceFingerprint, DeviceSighting, IntoShared, KnownDevice, KnownDeviceStore, KnownDeviceStoreError, TenantId,
+    },
+    utils::parsable::Parsable,
+};
+
+pub struct MySqlKnownDeviceStore {
+    pool: MySqlPool,
+}
+
+impl MySqlKnownDeviceStore {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl KnownDeviceStore for MySqlKnownDeviceStore {
+    #[tracing::instrument(name = "Recording sign-in device in Database", skip_all)]
+    async fn record_sign_in(&mut self, tenant: &TenantId, email: &str, device: KnownDevice) -> Result<DeviceSighting, KnownDeviceStoreError> {
+        let fingerprints = sqlx::query("SELECT fingerprint FROM known_devices WHERE tenant_id = ? AND email = ?")
+            .bind(tenant.as_ref())
+            .bind(email)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| KnownDeviceStoreError::UnexpectedError(e.into()))?
+            .into_iter()
+            .map(|row| row.try_get::<String, _>("fingerprint"))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| KnownDeviceStoreError::UnexpectedError(e.into()))?;
+
+        let sighting = match (fingerprints.is_empty(), fingerprints.iter().any(|f| f == device.fingerprint.as_ref())) {
+            (true, _) => DeviceSighting::First,
+            (false, true) => DeviceSighting::Known,
+            (false, false) => DeviceSighting::New,
+        };
+
+        sqlx::query(
+            "INSERT INTO known_devices (tenant_id, email, fingerprint, user_agent, ip, last_seen_at) \
+             VALUES (?, ?, ?, ?, ?, ?) \
+             ON DUPLICATE KEY UPDATE user_agent = VALUES(user_agent), ip = VALUES(ip), last_seen_at = VALUES(last_seen_at)"
+        )
+            .bind(tenant.as_ref())
+            .bind(email)
+            .bind(device.fingerprint.as_ref())
+            .bind(&device.user_agent)
+            .bind(&device.ip)
+            .bind(device.last_seen_at)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| KnownDeviceStoreError::UnexpectedError(e.into()))?;
+
+        Ok(sighting)
+    }
+
+    #[tracing::instrument(name = "Forgetting sign-in device in Database", skip_all)]
+    async fn forget_device(&mut self, tenant: &TenantId, email: &str, fingerprint: &DeviceFingerprint) -> Result<(), KnownDeviceStoreError> {
+        sqlx::query("DELETE FROM known_devices WHERE tenant_id = ? AND email = ? AND fingerprint = ?")
+            .bind(tenant.as_ref())
+            .bind(email)
+            .bind(fingerprint.as_ref())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| KnownDeviceStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Listing sign-in devices from Database", skip_all)]
+    async fn list_devices(&self, tenant: &TenantId, email: &str) -> Result<Vec<KnownDevice>, KnownDeviceStoreError> {
+        sqlx::query(
+            "SELECT fingerprint, user_agent, ip, last_seen_at FROM known_devices \
+             WHERE tenant_id = ? AND email = ? ORDER BY last_seen_at DESC"
+        )
+            .bind(tenant.as_ref())
+            .bind(email)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| KnownDeviceStoreError::UnexpectedError(e.into()))?
+            .iter()
+            .map(|row| {
+                let fingerprint: String = row.try_get("fingerprint")?;
+                Ok(KnownDevice {
+                    fingerprint: DeviceFingerprint::parse(&fingerprint)?,
+                    user_agent: row.try_get("user_agent")?,
+                    ip: row.try_get("ip")?,
+                    last_seen_at: row.try_get("last_seen_at")?,
+                })
+            })
+            .collect::<color_eyre::eyre::Result<_>>()
+            .map_err(KnownDeviceStoreError::UnexpectedError)
+    }
+}
+
+impl IntoShared for MySqlKnownDeviceStore {}
@@ -0,0 +1,77 @@
+use chrono::Utc;
+use color_eyre::eyre::eyre;
+use sqlx::{MySqlPool, Row};
+
+use crate::{
+    domain::{AvatarUrl, DisplayName, IntoShared, Locale, Profile, ProfileStore, ProfileStoreError, TenantId, TimeZone},
+    utils::parsable::Parsable,
+};
+
+pub struct MySqlProfileStore {
+    pool: MySqlPool,
+}
+
+impl MySqlProfileStore {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+fn parse_column<T: Parsable>(value: Option<String>) -> Result<Option<T>, ProfileStoreError> {
+    value
+        .map(|value| T::parse_or_error(&value, |e| ProfileStoreError::UnexpectedError(eyre!(e))))
+        .transpose()
+}
+
+#[async_trait::async_trait]
+impl ProfileStore for MySqlProfileStore {
+    #[tracing::instrument(name = "Retrieving profile from Database", skip_all)]
+    async fn get_profile(&self, tenant: &TenantId, email: &str) -> Result<Profile, ProfileStoreError> {
+        let row = sqlx::query(
+            "SELECT display_name, locale, timezone, avatar_url FROM user_profiles WHERE tenant_id = ? AND email = ?"
+        )
+            .bind(tenant.as_ref())
+            .bind(email)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| ProfileStoreError::UnexpectedError(e.into()))?;
+
+        let Some(row) = row else {
+            return Ok(Profile::default());
+        };
+        let column = |name: &str| {
+            row.try_get::<Option<String>, _>(name).map_err(|e| ProfileStoreError::UnexpectedError(e.into()))
+        };
+
+        Ok(Profile {
+            display_name: parse_column::<DisplayName>(column("display_name")?)?,
+            locale: parse_column::<Locale>(column("locale")?)?,
+            timezone: parse_column::<TimeZone>(column("timezone")?)?,
+            avatar_url: parse_column::<AvatarUrl>(column("avatar_url")?)?,
+        })
+    }
+
+    #[tracing::instrument(name = "Updating profile in Database", skip_all)]
+    async fn set_profile(&mut self, tenant: &TenantId, email: &str, profile: Profile) -> Result<(), ProfileStoreError> {
+        sqlx::query(
+            "INSERT INTO user_profiles (tenant_id, email, display_name, locale, timezone, avatar_url, updated_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?) \
+             ON DUPLICATE KEY UPDATE display_name = VALUES(display_name), locale = VALUES(locale), \
+             timezone = VALUES(timezone), avatar_url = VALUES(avatar_url), updated_at = VALUES(updated_at)"
+        )
+            .bind(tenant.as_ref())
+            .bind(email)
+            .bind(profile.display_name.as_ref().map(|name| name.as_ref()))
+            .bind(profile.locale.as_ref().map(|locale| locale.as_ref()))
+            .bind(profile.timezone.as_ref().map(|timezone| timezone.as_ref()))
+            .bind(profile.avatar_url.as_ref().map(|url| url.as_ref()))
+            .bind(Utc::now())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| ProfileStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+}
+
+impl IntoShared for MySqlProfileStore {}
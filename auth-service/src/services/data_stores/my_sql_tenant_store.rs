@@ -0,0 +1,99 @@
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::eyre;
+use sqlx::{mysql::MySqlRow, MySqlPool, Row};
+
+use crate::{
+    domain::{IntoShared, ScimToken, Tenant, TenantId, TenantStore, TenantStoreError},
+    utils::parsable::Parsable,
+};
+
+pub struct MySqlTenantStore {
+    pool: MySqlPool,
+}
+
+impl MySqlTenantStore {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+fn parse_tenant(row: MySqlRow) -> Result<Tenant, TenantStoreError> {
+    let id: String = row.try_get("id").map_err(|e| TenantStoreError::UnexpectedError(e.into()))?;
+
+    Ok(Tenant {
+        id: TenantId::parse_or_error(&id, |e| TenantStoreError::UnexpectedError(eyre!(e)))?,
+        name: row.try_get("name").map_err(|e| TenantStoreError::UnexpectedError(e.into()))?,
+        created_at: row.try_get::<DateTime<Utc>, _>("created_at")
+            .map_err(|e| TenantStoreError::UnexpectedError(e.into()))?,
+    })
+}
+
+#[async_trait::async_trait]
+impl TenantStore for MySqlTenantStore {
+    #[tracing::instrument(name = "Adding tenant to Database", skip_all)]
+    async fn add_tenant(&mut self, tenant: Tenant) -> Result<(), TenantStoreError> {
+        sqlx::query("INSERT INTO tenants (id, name, created_at) VALUES (?, ?, ?)")
+            .bind(tenant.id.as_ref())
+            .bind(&tenant.name)
+            .bind(tenant.created_at)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(db_error) if db_error.is_unique_violation() => {
+                    TenantStoreError::TenantAlreadyExists
+                }
+                e => TenantStoreError::UnexpectedError(e.into()),
+            })?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Retrieving tenant from Database", skip_all)]
+    async fn get_tenant(&self, id: &TenantId) -> Result<Tenant, TenantStoreError> {
+        sqlx::query("SELECT id, name, created_at FROM tenants WHERE id = ?")
+            .bind(id.as_ref())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| TenantStoreError::UnexpectedError(e.into()))?
+            .map(parse_tenant)
+            .ok_or(TenantStoreError::TenantNotFound)?
+    }
+
+    #[tracing::instrument(name = "Listing tenants from Database", skip_all)]
+    async fn list_tenants(&self) -> Result<Vec<Tenant>, TenantStoreError> {
+        sqlx::query("SELECT id, name, created_at FROM tenants ORDER BY id")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| TenantStoreError::UnexpectedError(e.into()))?
+            .into_iter()
+            .map(parse_tenant)
+            .collect()
+    }
+
+    #[tracing::instrument(name = "Setting SCIM token in Database", skip_all)]
+    async fn set_scim_token(&mut self, id: &TenantId, token: &ScimToken) -> Result<(), TenantStoreError> {
+        self.get_tenant(id).await?;
+
+        sqlx::query("UPDATE tenants SET scim_token_hash = ? WHERE id = ?")
+            .bind(token.hash())
+            .bind(id.as_ref())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| TenantStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Retrieving tenant by SCIM token from Database", skip_all)]
+    async fn get_tenant_by_scim_token(&self, token: &ScimToken) -> Result<Tenant, TenantStoreError> {
+        sqlx::query("SELECT id, name, created_at FROM tenants WHERE scim_token_hash = ?")
+            .bind(token.hash())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| TenantStoreError::UnexpectedError(e.into()))?
+            .map(parse_tenant)
+            .ok_or(TenantStoreError::TenantNotFound)?
+    }
+}
+
+impl IntoShared for MySqlTenantStore {}
@@ -0,0 +1,114 @@
+use sqlx::{mysql::MySqlRow, MySqlPool, Row};
+
+use crate::{
+    domain::{IntoShared, TenantId, TrustedDevice, TrustedDeviceId, TrustedDeviceStore, TrustedDeviceStoreError},
+    utils::parsable::Parsable,
+};
+
+pub struct MySqlTrustedDeviceStore {
+    pool: MySqlPool,
+}
+
+impl MySqlTrustedDeviceStore {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl TrustedDeviceStore for MySqlTrustedDeviceStore {
+    #[tracing::instrument(name = "Adding trusted device to Database", skip_all)]
+    async fn add_device(&mut self, tenant: &TenantId, email: &str, device: TrustedDevice) -> Result<(), TrustedDeviceStoreError> {
+        sqlx::query(
+            "INSERT INTO trusted_devices (id, tenant_id, email, user_agent, ip, created_at, expires_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?)"
+        )
+            .bind(device.id.as_ref())
+            .bind(tenant.as_ref())
+            .bind(email)
+            .bind(&device.user_agent)
+            .bind(&device.ip)
+            .bind(device.created_at)
+            .bind(device.expires_at)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| TrustedDeviceStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Retrieving trusted device from Database", skip_all)]
+    async fn get_device(&self, tenant: &TenantId, email: &str, id: &TrustedDeviceId) -> Result<TrustedDevice, TrustedDeviceStoreError> {
+        let row = sqlx::query(
+            "SELECT id, user_agent, ip, created_at, expires_at FROM trusted_devices \
+             WHERE id = ? AND tenant_id = ? AND email = ?"
+        )
+            .bind(id.as_ref())
+            .bind(tenant.as_ref())
+            .bind(email)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| TrustedDeviceStoreError::UnexpectedError(e.into()))?
+            .ok_or(TrustedDeviceStoreError::DeviceNotFound)?;
+
+        device_from_row(&row)
+    }
+
+    #[tracing::instrument(name = "Listing trusted devices from Database", skip_all)]
+    async fn list_devices(&self, tenant: &TenantId, email: &str) -> Result<Vec<TrustedDevice>, TrustedDeviceStoreError> {
+        sqlx::query(
+            "SELECT id, user_agent, ip, created_at, expires_at FROM trusted_devices \
+             WHERE tenant_id = ? AND email = ? ORDER BY created_at DESC"
+        )
+            .bind(tenant.as_ref())
+            .bind(email)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| TrustedDeviceStoreError::UnexpectedError(e.into()))?
+            .iter()
+            .map(device_from_row)
+            .collect()
+    }
+
+    #[tracing::instrument(name = "Removing trusted device from Database", skip_all)]
+    async fn remove_device(&mut self, tenant: &TenantId, email: &str, id: &TrustedDeviceId) -> Result<(), TrustedDeviceStoreError> {
+        let result = sqlx::query("DELETE FROM trusted_devices WHERE id = ? AND tenant_id = ? AND email = ?")
+            .bind(id.as_ref())
+            .bind(tenant.as_ref())
+            .bind(email)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| TrustedDeviceStoreError::UnexpectedError(e.into()))?;
+
+        match result.rows_affected() {
+            0 => Err(TrustedDeviceStoreError::DeviceNotFound),
+            _ => Ok(()),
+        }
+    }
+
+    #[tracing::instrument(name = "Removing all trusted devices from Database", skip_all)]
+    async fn remove_all_devices(&mut self, tenant: &TenantId, email: &str) -> Result<(), TrustedDeviceStoreError> {
+        sqlx::query("DELETE FROM trusted_devices WHERE tenant_id = ? AND email = ?")
+            .bind(tenant.as_ref())
+            .bind(email)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| TrustedDeviceStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+}
+
+fn device_from_row(row: &MySqlRow) -> Result<TrustedDevice, TrustedDeviceStoreError> {
+    let id: String = row.try_get("id").map_err(|e| TrustedDeviceStoreError::UnexpectedError(e.into()))?;
+
+    Ok(TrustedDevice {
+        id: TrustedDeviceId::parse(id).map_err(TrustedDeviceStoreError::UnexpectedError)?,
+        user_agent: row.try_get("user_agent").map_err(|e| TrustedDeviceStoreError::UnexpectedError(e.into()))?,
+        ip: row.try_get("ip").map_err(|e| TrustedDeviceStoreError::UnexpectedError(e.into()))?,
+        created_at: row.try_get("created_at").map_err(|e| TrustedDeviceStoreError::UnexpectedError(e.into()))?,
+        expires_at: row.try_get("expires_at").map_err(|e| TrustedDeviceStoreError::UnexpectedError(e.into()))?,
+    })
+}
+
+impl IntoShared for MySqlTrustedDeviceStore {}
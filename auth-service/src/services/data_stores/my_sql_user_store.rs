@@ -1,90 +1,281 @@
-use color_eyre::eyre::{eyre, Result};
+use color_eyre::eyre::eyre;
 use secrecy::Secret;
 
-use argon2::{
-    password_hash::SaltString,
-    Algorithm,
-    Argon2,
-    Params,
-    PasswordHash,
-    PasswordHasher,
-    PasswordVerifier,
-    Version,
-};
-
+use chrono::{DateTime, Utc};
 use secrecy::ExposeSecret;
-use sqlx::MySqlPool;
+use sqlx::{
+    mysql::{MySqlArguments, MySqlRow},
+    query::Query,
+    MySql, MySqlPool, Row,
+};
 
 use crate::{
     domain::{
-        Email, IntoShared, Password, User, UserStore, UserStoreError
+        AccountStatus, Email, Group, GroupId, GroupName, IntoShared, LinkedIdentity, Password, Permission, Role, TenantId,
+        User, UserAuthorization, UserId, UserPage, UserQuery, UserStore, UserStoreError, Username,
+    },
+    services::password_hashing::{
+        compute_password_hash,
+        is_supported_hash,
+        simulate_password_verification,
+        verify_password_hash,
+        HashingParams,
+        PasswordMatch,
     },
     utils::parsable::Parsable,
 };
 
 pub struct MySqlUserStore {
     pool: MySqlPool,
+    hashing: HashingParams,
+}
+
+const USER_COLUMNS: &str = "id, email, username, password_hash, requires_2fa, status, suspension_reason, purge_at";
+
+fn parse_user(row: MySqlRow) -> Result<User, UserStoreError> {
+    let id: String = row.try_get("id").map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+    let email: String = row.try_get("email").map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+    let username: Option<String> = row.try_get("username").map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+    let password_hash: Option<String> = row.try_get("password_hash")
+        .map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+    let status: String = row.try_get("status").map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+
+    Ok(User {
+        id: UserId::parse_or_error(&id, |e| UserStoreError::UnexpectedError(eyre!(e)))?,
+        email: Email::parse_or_error(&email, |e| UserStoreError::UnexpectedError(eyre!(e)))?,
+        username: username
+            .map(|username| Username::parse_or_error(&username, |e| UserStoreError::UnexpectedError(eyre!(e))))
+            .transpose()?,
+        password: password_hash
+            .map(|hash| Password::parse_or_error(&hash, |e| UserStoreError::UnexpectedError(eyre!(e))))
+            .transpose()?,
+        requires_2fa: row.try_get("requires_2fa").map_err(|e| UserStoreError::UnexpectedError(e.into()))?,
+        status: AccountStatus::parse_or_error(&status, |e| UserStoreError::UnexpectedError(eyre!(e)))?,
+        suspension_reason: row.try_get("suspension_reason").map_err(|e| UserStoreError::UnexpectedError(e.into()))?,
+        purge_at: row.try_get::<Option<DateTime<Utc>>, _>("purge_at").map_err(|e| UserStoreError::UnexpectedError(e.into()))?,
+    })
+}
+
+fn parse_group(row: MySqlRow) -> Result<Group, UserStoreError> {
+    let id: String = row.try_get("id").map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+    let display_name: String = row.try_get("display_name").map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+
+    Ok(Group {
+        id: GroupId::parse_or_error(&id, |e| UserStoreError::UnexpectedError(eyre!(e)))?,
+        display_name: GroupName::parse_or_error(&display_name, |e| UserStoreError::UnexpectedError(eyre!(e)))?,
+        created_at: row.try_get::<DateTime<Utc>, _>("created_at").map_err(|e| UserStoreError::UnexpectedError(e.into()))?,
+    })
+}
+
+// The username's unique key is the only one a user can break besides the
+// primary key on their email.
+fn map_user_write_error(e: sqlx::Error) -> UserStoreError {
+    match e {
+        sqlx::Error::Database(db_error) if db_error.is_unique_violation() => match db_error.message().contains("users_username") {
+            true => UserStoreError::UsernameTaken,
+            false => UserStoreError::UserAlreadyExists,
+        },
+        e => UserStoreError::UnexpectedError(e.into()),
+    }
+}
+
+fn map_group_name_error(e: sqlx::Error) -> UserStoreError {
+    match e {
+        sqlx::Error::Database(db_error) if db_error.is_unique_violation() => UserStoreError::GroupAlreadyExists,
+        e => UserStoreError::UnexpectedError(e.into()),
+    }
+}
+
+// Escapes the LIKE wildcards in a search term, so it only matches literally.
+fn like_pattern(search: &str) -> String {
+    let escaped = search.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    format!("%{}%", escaped)
 }
 
 impl MySqlUserStore {
     pub fn new(pool: MySqlPool) -> Self {
         Self {
-            pool
+            pool,
+            hashing: HashingParams::default(),
         }
     }
-}
 
-#[async_trait::async_trait]
-impl UserStore for MySqlUserStore {
-    #[tracing::instrument(name="Adding user to Database", skip_all)]
-    async fn add_user(&mut self, user: User) -> Result<(), UserStoreError>{
-        let password_hash = compute_password_hash(user.password.as_ref().to_owned())
-            .await
-            .map_err(UserStoreError::UnexpectedError)?;
+    pub fn with_hashing_params(mut self, hashing: HashingParams) -> Self {
+        self.hashing = hashing;
+        self
+    }
 
-        sqlx::query("Insert INTO users (email, password_hash, requires_2fa) VALUES (?, ?, ?)")
+    async fn insert_user(&self, tenant: &TenantId, user: &User, password_hash: Option<Secret<String>>) -> Result<(), UserStoreError> {
+        sqlx::query("INSERT INTO users (id, tenant_id, email, username, password_hash, requires_2fa) VALUES (?, ?, ?, ?, ?, ?)")
+            .bind(user.id.as_ref())
+            .bind(tenant.as_ref())
             .bind(user.email.as_ref().expose_secret())
-            .bind(password_hash.expose_secret())
+            .bind(user.username.as_ref().map(|username| username.as_ref()))
+            .bind(password_hash.as_ref().map(|hash| hash.expose_secret()))
             .bind(user.requires_2fa)
             .execute(&self.pool)
             .await
             .map_err(|err| {
                 tracing::error!("{:#?}", err);
-                UserStoreError::UnexpectedError(err.into())
+                map_user_write_error(err)
             })?;
 
         Ok(())
     }
 
+    // Replaces a hash made with outdated parameters or another algorithm,
+    // unless the password changed meanwhile. Failing to is only logged, the
+    // user gets another chance on their next sign in.
+    async fn rehash_password(&self, tenant: &TenantId, email: &str, old_hash: &Secret<String>, password: &str) {
+        let result = async {
+            let new_hash = compute_password_hash(Secret::new(password.to_owned()), self.hashing).await?;
+
+            sqlx::query("UPDATE users SET password_hash = ? WHERE tenant_id = ? AND email = ? AND password_hash = ?")
+                .bind(new_hash.expose_secret())
+                .bind(tenant.as_ref())
+                .bind(email)
+                .bind(old_hash.expose_secret())
+                .execute(&self.pool)
+                .await?;
+
+            Ok::<_, color_eyre::eyre::Report>(())
+        }.await;
+
+        if let Err(e) = result {
+            tracing::warn!("Failed to rehash password: {:?}", e);
+        }
+    }
+
+    async fn ensure_role_exists(&self, role: &Role) -> Result<(), UserStoreError> {
+        sqlx::query("SELECT name FROM roles WHERE name = ?")
+            .bind(role.as_ref())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?
+            .ok_or(UserStoreError::RoleNotFound)
+            .map(|_| ())
+    }
+
+    // Checks both the group and the user exist before changing a membership.
+    async fn ensure_group_member_exists(&self, tenant: &TenantId, id: &GroupId, email: &str) -> Result<(), UserStoreError> {
+        self.get_group(tenant, id).await?;
+        self.get_user(tenant, email).await.map(|_| ())
+    }
+
+    // Runs an `UPDATE ... WHERE tenant_id = ? AND email = ?` on a single user.
+    async fn update_user<'q>(
+        &self,
+        tenant: &'q TenantId,
+        email: &'q str,
+        query: Query<'q, MySql, MySqlArguments>,
+    ) -> Result<(), UserStoreError> {
+        let result = query
+            .bind(tenant.as_ref())
+            .bind(email)
+            .execute(&self.pool)
+            .await
+            .map_err(map_user_write_error)?;
+
+        match result.rows_affected() {
+            // MySQL doesn't count rows that already had the new value, so
+            // tell those apart from missing users.
+            0 => self.get_user(tenant, email).await.map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl UserStore for MySqlUserStore {
+    #[tracing::instrument(name="Adding user to Database", skip_all)]
+    async fn add_user(&mut self, tenant: &TenantId, user: User) -> Result<(), UserStoreError> {
+        let password_hash = match &user.password {
+            Some(password) => Some(
+                compute_password_hash(password.as_ref().to_owned(), self.hashing)
+                    .await
+                    .map_err(UserStoreError::UnexpectedError)?
+            ),
+            None => None,
+        };
+
+        self.insert_user(tenant, &user, password_hash).await
+    }
+
+    #[tracing::instrument(name="Importing user into Database", skip_all)]
+    async fn add_user_with_password_hash(&mut self, tenant: &TenantId, user: User, password_hash: Secret<String>) -> Result<(), UserStoreError> {
+        if !is_supported_hash(password_hash.expose_secret()) {
+            return Err(UserStoreError::UnsupportedPasswordHash);
+        }
+
+        self.insert_user(tenant, &user, Some(password_hash)).await
+    }
+
     #[tracing::instrument(name="Retrieving user from Database", skip_all)]
-    async fn get_user(&self, email: &str) -> Result<User, UserStoreError> {
-        sqlx::query!("select email, password_hash, requires_2fa from users where email = ?", email)
+    async fn get_user(&self, tenant: &TenantId, email: &str) -> Result<User, UserStoreError> {
+        sqlx::query(&format!("SELECT {} FROM users WHERE tenant_id = ? AND email = ?", USER_COLUMNS))
+            .bind(tenant.as_ref())
+            .bind(email)
             .fetch_optional(&self.pool)
             .await
             .map_err(|e| UserStoreError::UnexpectedError(e.into()))?
-            .map(|row| {
-                Ok(User {
-                    email: Email::parse_or_error(&row.email, |e| UserStoreError::UnexpectedError(eyre!(e)))?,
-                    password: Password::parse_or_error(&row.password_hash, |e| UserStoreError::UnexpectedError(eyre!(e)))?,
-                    requires_2fa: row.requires_2fa == 1,
-                })
-            }).ok_or(UserStoreError::UserNotFound)?
+            .map(parse_user)
+            .ok_or(UserStoreError::UserNotFound)?
+    }
+
+    #[tracing::instrument(name="Retrieving user by id from Database", skip_all)]
+    async fn get_user_by_id(&self, tenant: &TenantId, id: &UserId) -> Result<User, UserStoreError> {
+        sqlx::query(&format!("SELECT {} FROM users WHERE tenant_id = ? AND id = ?", USER_COLUMNS))
+            .bind(tenant.as_ref())
+            .bind(id.as_ref())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?
+            .map(parse_user)
+            .ok_or(UserStoreError::UserNotFound)?
+    }
+
+    // The username column's collation ignores case.
+    #[tracing::instrument(name="Retrieving user by username from Database", skip_all)]
+    async fn get_user_by_username(&self, tenant: &TenantId, username: &Username) -> Result<User, UserStoreError> {
+        sqlx::query(&format!("SELECT {} FROM users WHERE tenant_id = ? AND username = ?", USER_COLUMNS))
+            .bind(tenant.as_ref())
+            .bind(username.as_ref())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?
+            .map(parse_user)
+            .ok_or(UserStoreError::UserNotFound)?
     }
 
     #[tracing::instrument(name="Validating user credentials in Database", skip_all)]
-    async fn validate_user(&self, email: &str, password: &str) -> Result<(), UserStoreError> {
-        let user = self.get_user(email).await;
-        let is_valid_password = verify_password_hash(
-            user?.password.as_ref().expose_secret().to_owned(),
-            password.to_string()
-        ).await;
+    async fn validate_user(&self, tenant: &TenantId, email: &str, password: &str) -> Result<(), UserStoreError> {
+        let password_hash = match self.get_user(tenant, email).await {
+            Ok(User { password: Some(password_hash), .. }) => password_hash,
+            result => {
+                simulate_password_verification(Secret::new(password.to_string()), self.hashing).await;
+                return Err(result.err().unwrap_or(UserStoreError::InvalidCredentials));
+            }
+        };
+
+        let password_match = verify_password_hash(
+            password_hash.as_ref().clone(),
+            Secret::new(password.to_string()),
+            self.hashing,
+        )
+            .await
+            .map_err(|_| UserStoreError::InvalidCredentials)?;
 
-        is_valid_password.map_err(|_| UserStoreError::InvalidCredentials)
+        if password_match == PasswordMatch::Outdated {
+            self.rehash_password(tenant, email, password_hash.as_ref(), password).await;
+        }
+
+        Ok(())
     }
      
     #[tracing::instrument(name="Deleting user from Database", skip_all)]
-    async fn delete_user(&mut self, email: &str) -> Result<(), UserStoreError> {
-        sqlx::query("DELETE FROM users where email = ?")
+    async fn delete_user(&mut self, tenant: &TenantId, email: &str) -> Result<(), UserStoreError> {
+        sqlx::query("DELETE FROM users WHERE tenant_id = ? AND email = ?")
+            .bind(tenant.as_ref())
             .bind(email)
             .execute(&self.pool)
             .await
@@ -92,47 +283,436 @@ impl UserStore for MySqlUserStore {
 
         Ok(())
     }
-}
 
-impl IntoShared for MySqlUserStore {}
+    #[tracing::instrument(name="Linking identity in Database", skip_all)]
+    async fn link_identity(&mut self, tenant: &TenantId, email: &str, identity: LinkedIdentity) -> Result<(), UserStoreError> {
+        self.get_user(tenant, email).await?;
+
+        let existing_owner = sqlx::query("SELECT email FROM linked_identities WHERE tenant_id = ? AND provider = ? AND subject = ?")
+            .bind(tenant.as_ref())
+            .bind(&identity.provider)
+            .bind(&identity.subject)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?
+            .map(|row| row.try_get::<String, _>("email"))
+            .transpose()
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+
+        match existing_owner {
+            Some(owner) if owner == email => return Ok(()),
+            Some(_) => return Err(UserStoreError::IdentityAlreadyLinked),
+            None => {}
+        }
+
+        sqlx::query("INSERT INTO linked_identities (tenant_id, provider, subject, email, linked_at) VALUES (?, ?, ?, ?, ?)")
+            .bind(tenant.as_ref())
+            .bind(&identity.provider)
+            .bind(&identity.subject)
+            .bind(email)
+            .bind(identity.linked_at)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(db_error) if db_error.is_unique_violation() => {
+                    UserStoreError::IdentityAlreadyLinked
+                }
+                e => UserStoreError::UnexpectedError(e.into()),
+            })?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name="Unlinking identity in Database", skip_all)]
+    async fn unlink_identity(&mut self, tenant: &TenantId, email: &str, provider: &str) -> Result<(), UserStoreError> {
+        let user = self.get_user(tenant, email).await?;
+        let identities = self.get_linked_identities(tenant, email).await?;
+
+        if !identities.iter().any(|identity| identity.provider == provider) {
+            return Err(UserStoreError::IdentityNotFound);
+        }
+
+        if user.password.is_none() && identities.len() == 1 {
+            return Err(UserStoreError::LastLoginMethod);
+        }
+
+        sqlx::query("DELETE FROM linked_identities WHERE tenant_id = ? AND email = ? AND provider = ?")
+            .bind(tenant.as_ref())
+            .bind(email)
+            .bind(provider)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name="Retrieving linked identities from Database", skip_all)]
+    async fn get_linked_identities(&self, tenant: &TenantId, email: &str) -> Result<Vec<LinkedIdentity>, UserStoreError> {
+        self.get_user(tenant, email).await?;
+
+        sqlx::query(
+            "SELECT provider, subject, linked_at FROM linked_identities \
+             WHERE tenant_id = ? AND email = ? ORDER BY linked_at"
+        )
+            .bind(tenant.as_ref())
+            .bind(email)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?
+            .into_iter()
+            .map(|row| {
+                Ok(LinkedIdentity {
+                    provider: row.try_get("provider")?,
+                    subject: row.try_get("subject")?,
+                    linked_at: row.try_get::<DateTime<Utc>, _>("linked_at")?,
+                })
+            })
+            .collect::<Result<Vec<_>, sqlx::Error>>()
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))
+    }
+
+    #[tracing::instrument(name="Retrieving user by identity from Database", skip_all)]
+    async fn get_user_by_identity(&self, tenant: &TenantId, provider: &str, subject: &str) -> Result<User, UserStoreError> {
+        let email: String = sqlx::query("SELECT email FROM linked_identities WHERE tenant_id = ? AND provider = ? AND subject = ?")
+            .bind(tenant.as_ref())
+            .bind(provider)
+            .bind(subject)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?
+            .ok_or(UserStoreError::UserNotFound)?
+            .try_get("email")
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+
+        self.get_user(tenant, &email).await
+    }
+
+    async fn assign_role(&mut self, tenant: &TenantId, email: &str, role: &Role) -> Result<(), UserStoreError> {
+        self.get_user(tenant, email).await?;
+        self.ensure_role_exists(role).await?;
 
-#[tracing::instrument(name="Verify password hash", skip_all)]
-async fn verify_password_hash(
-    expected_password_hash: String,
-    password_candidate: String,
-) -> Result<()> {
-    let current_span: tracing::Span = tracing::Span::current();
-    let result = tokio::task::spawn_blocking(move || {
-        current_span.in_scope(|| {
-            let expected_password_hash: PasswordHash<'_> = PasswordHash::new(&expected_password_hash)?;
-
-            Argon2::default()
-                .verify_password(password_candidate.as_bytes(), &expected_password_hash)
-                .map_err(|e| e.into())
-        })
-    }).await;
-
-    result?
+        sqlx::query("INSERT IGNORE INTO user_roles (tenant_id, email, role) VALUES (?, ?, ?)")
+            .bind(tenant.as_ref())
+            .bind(email)
+            .bind(role.as_ref())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+
+    async fn remove_role(&mut self, tenant: &TenantId, email: &str, role: &Role) -> Result<(), UserStoreError> {
+        self.get_user(tenant, email).await?;
+        self.ensure_role_exists(role).await?;
+
+        sqlx::query("DELETE FROM user_roles WHERE tenant_id = ? AND email = ? AND role = ?")
+            .bind(tenant.as_ref())
+            .bind(email)
+            .bind(role.as_ref())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+
+    async fn get_authorization(&self, tenant: &TenantId, email: &str) -> Result<UserAuthorization, UserStoreError> {
+        self.get_user(tenant, email).await?;
+
+        let roles = sqlx::query("SELECT role FROM user_roles WHERE tenant_id = ? AND email = ? ORDER BY role")
+            .bind(tenant.as_ref())
+            .bind(email)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?
+            .into_iter()
+            .map(|row| {
+                let role: String = row.try_get("role")?;
+                Role::parse(role).map_err(|e| sqlx::Error::Decode(e.into()))
+            })
+            .collect::<Result<Vec<_>, sqlx::Error>>()
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+
+        let permissions = sqlx::query(
+            "SELECT DISTINCT rp.permission FROM role_permissions rp \
+             JOIN user_roles ur ON ur.role = rp.role \
+             WHERE ur.tenant_id = ? AND ur.email = ? ORDER BY rp.permission"
+        )
+            .bind(tenant.as_ref())
+            .bind(email)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?
+            .into_iter()
+            .map(|row| {
+                let permission: String = row.try_get("permission")?;
+                Permission::parse(permission).map_err(|e| sqlx::Error::Decode(e.into()))
+            })
+            .collect::<Result<Vec<_>, sqlx::Error>>()
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+
+        Ok(UserAuthorization { roles, permissions })
+    }
+
+    #[tracing::instrument(name="Listing users from Database", skip_all)]
+    async fn list_users(&self, tenant: &TenantId, query: &UserQuery) -> Result<UserPage, UserStoreError> {
+        let pattern = like_pattern(query.search.as_deref().unwrap_or_default());
+
+        let total: i64 = sqlx::query("SELECT COUNT(*) AS total FROM users WHERE tenant_id = ? AND email LIKE ?")
+            .bind(tenant.as_ref())
+            .bind(&pattern)
+            .fetch_one(&self.pool)
+            .await
+            .and_then(|row| row.try_get("total"))
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+
+        let users = sqlx::query(&format!(
+            "SELECT {} FROM users WHERE tenant_id = ? AND email LIKE ? ORDER BY email LIMIT ? OFFSET ?",
+            USER_COLUMNS
+        ))
+            .bind(tenant.as_ref())
+            .bind(&pattern)
+            .bind(query.per_page)
+            .bind(query.offset())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?
+            .into_iter()
+            .map(parse_user)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(UserPage { users, total: total as u64 })
+    }
+
+    #[tracing::instrument(name="Updating account status in Database", skip_all)]
+    async fn set_status(
+        &mut self,
+        tenant: &TenantId,
+        email: &str,
+        status: AccountStatus,
+        reason: Option<String>,
+    ) -> Result<(), UserStoreError> {
+        let query = sqlx::query(
+            "UPDATE users SET status = ?, suspension_reason = ?, purge_at = NULL WHERE tenant_id = ? AND email = ?"
+        )
+            .bind(status.as_ref().to_owned())
+            .bind(reason.filter(|_| status == AccountStatus::Suspended));
+        self.update_user(tenant, email, query).await
+    }
+
+    #[tracing::instrument(name="Updating password in Database", skip_all)]
+    async fn set_password(&mut self, tenant: &TenantId, email: &str, password: Option<Password>) -> Result<(), UserStoreError> {
+        let password_hash = match password {
+            Some(password) => Some(
+                compute_password_hash(password.as_ref().to_owned(), self.hashing)
+                    .await
+                    .map_err(UserStoreError::UnexpectedError)?
+            ),
+            None => None,
+        };
+
+        let query = sqlx::query("UPDATE users SET password_hash = ? WHERE tenant_id = ? AND email = ?")
+            .bind(password_hash.map(|hash| hash.expose_secret().to_owned()));
+        self.update_user(tenant, email, query).await
+    }
+
+    #[tracing::instrument(name="Importing password hash into Database", skip_all)]
+    async fn set_password_hash(&mut self, tenant: &TenantId, email: &str, password_hash: Secret<String>) -> Result<(), UserStoreError> {
+        if !is_supported_hash(password_hash.expose_secret()) {
+            return Err(UserStoreError::UnsupportedPasswordHash);
+        }
+
+        let query = sqlx::query("UPDATE users SET password_hash = ? WHERE tenant_id = ? AND email = ?")
+            .bind(password_hash.expose_secret().to_owned());
+        self.update_user(tenant, email, query).await
+    }
+
+    #[tracing::instrument(name="Updating 2FA requirement in Database", skip_all)]
+    async fn set_requires_2fa(&mut self, tenant: &TenantId, email: &str, requires_2fa: bool) -> Result<(), UserStoreError> {
+        let query = sqlx::query("UPDATE users SET requires_2fa = ? WHERE tenant_id = ? AND email = ?").bind(requires_2fa);
+        self.update_user(tenant, email, query).await
+    }
+
+    #[tracing::instrument(name="Setting username in Database", skip_all)]
+    async fn set_username(&mut self, tenant: &TenantId, email: &str, username: Option<Username>) -> Result<(), UserStoreError> {
+        let query = sqlx::query("UPDATE users SET username = ? WHERE tenant_id = ? AND email = ?")
+            .bind(username.map(|username| username.as_ref().to_owned()));
+        self.update_user(tenant, email, query).await
+    }
+
+    #[tracing::instrument(name="Scheduling user deletion in Database", skip_all)]
+    async fn schedule_deletion(&mut self, tenant: &TenantId, email: &str, purge_at: DateTime<Utc>) -> Result<(), UserStoreError> {
+        let query = sqlx::query(
+            "UPDATE users SET status = ?, suspension_reason = NULL, purge_at = ? WHERE tenant_id = ? AND email = ?"
+        )
+            .bind(AccountStatus::Deleted.as_ref().to_owned())
+            .bind(purge_at);
+        self.update_user(tenant, email, query).await
+    }
+
+    #[tracing::instrument(name="Listing users due for purge from Database", skip_all)]
+    async fn list_users_due_for_purge(&self, now: DateTime<Utc>) -> Result<Vec<(TenantId, User)>, UserStoreError> {
+        sqlx::query(&format!(
+            "SELECT tenant_id, {} FROM users WHERE status = ? AND purge_at <= ? ORDER BY purge_at",
+            USER_COLUMNS
+        ))
+            .bind(AccountStatus::Deleted.as_ref().to_owned())
+            .bind(now)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?
+            .into_iter()
+            .map(|row| {
+                let tenant: String = row.try_get("tenant_id").map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+                let tenant = TenantId::parse_or_error(&tenant, |e| UserStoreError::UnexpectedError(eyre!(e)))?;
+                Ok((tenant, parse_user(row)?))
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(name="Adding group to Database", skip_all)]
+    async fn add_group(&mut self, tenant: &TenantId, group: Group) -> Result<(), UserStoreError> {
+        sqlx::query("INSERT INTO user_groups (tenant_id, id, display_name, created_at) VALUES (?, ?, ?, ?)")
+            .bind(tenant.as_ref())
+            .bind(group.id.as_ref())
+            .bind(group.display_name.as_ref())
+            .bind(group.created_at)
+            .execute(&self.pool)
+            .await
+            .map_err(map_group_name_error)?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name="Retrieving group from Database", skip_all)]
+    async fn get_group(&self, tenant: &TenantId, id: &GroupId) -> Result<Group, UserStoreError> {
+        sqlx::query("SELECT id, display_name, created_at FROM user_groups WHERE tenant_id = ? AND id = ?")
+            .bind(tenant.as_ref())
+            .bind(id.as_ref())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?
+            .map(parse_group)
+            .ok_or(UserStoreError::GroupNotFound)?
+    }
+
+    #[tracing::instrument(name="Listing groups from Database", skip_all)]
+    async fn list_groups(&self, tenant: &TenantId) -> Result<Vec<Group>, UserStoreError> {
+        sqlx::query("SELECT id, display_name, created_at FROM user_groups WHERE tenant_id = ? ORDER BY display_name")
+            .bind(tenant.as_ref())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?
+            .into_iter()
+            .map(parse_group)
+            .collect()
+    }
+
+    #[tracing::instrument(name="Renaming group in Database", skip_all)]
+    async fn rename_group(&mut self, tenant: &TenantId, id: &GroupId, display_name: GroupName) -> Result<(), UserStoreError> {
+        self.get_group(tenant, id).await?;
+
+        sqlx::query("UPDATE user_groups SET display_name = ? WHERE tenant_id = ? AND id = ?")
+            .bind(display_name.as_ref())
+            .bind(tenant.as_ref())
+            .bind(id.as_ref())
+            .execute(&self.pool)
+            .await
+            .map_err(map_group_name_error)?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name="Deleting group from Database", skip_all)]
+    async fn delete_group(&mut self, tenant: &TenantId, id: &GroupId) -> Result<(), UserStoreError> {
+        let result = sqlx::query("DELETE FROM user_groups WHERE tenant_id = ? AND id = ?")
+            .bind(tenant.as_ref())
+            .bind(id.as_ref())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+
+        match result.rows_affected() {
+            0 => Err(UserStoreError::GroupNotFound),
+            _ => Ok(()),
+        }
+    }
+
+    #[tracing::instrument(name="Adding group member to Database", skip_all)]
+    async fn add_group_member(&mut self, tenant: &TenantId, id: &GroupId, email: &str) -> Result<(), UserStoreError> {
+        self.ensure_group_member_exists(tenant, id, email).await?;
+
+        sqlx::query("INSERT IGNORE INTO user_group_members (tenant_id, group_id, email) VALUES (?, ?, ?)")
+            .bind(tenant.as_ref())
+            .bind(id.as_ref())
+            .bind(email)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name="Removing group member from Database", skip_all)]
+    async fn remove_group_member(&mut self, tenant: &TenantId, id: &GroupId, email: &str) -> Result<(), UserStoreError> {
+        self.ensure_group_member_exists(tenant, id, email).await?;
+
+        sqlx::query("DELETE FROM user_group_members WHERE tenant_id = ? AND group_id = ? AND email = ?")
+            .bind(tenant.as_ref())
+            .bind(id.as_ref())
+            .bind(email)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name="Listing group members from Database", skip_all)]
+    async fn list_group_members(&self, tenant: &TenantId, id: &GroupId) -> Result<Vec<User>, UserStoreError> {
+        self.get_group(tenant, id).await?;
+
+        let columns = USER_COLUMNS
+            .split(", ")
+            .map(|column| format!("u.{}", column))
+            .collect::<Vec<_>>()
+            .join(", ");
+        sqlx::query(&format!(
+            "SELECT {} FROM users u \
+             JOIN user_group_members m ON m.tenant_id = u.tenant_id AND m.email = u.email \
+             WHERE m.tenant_id = ? AND m.group_id = ? ORDER BY u.email",
+            columns
+        ))
+            .bind(tenant.as_ref())
+            .bind(id.as_ref())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?
+            .into_iter()
+            .map(parse_user)
+            .collect()
+    }
+
+    #[tracing::instrument(name="Listing user groups from Database", skip_all)]
+    async fn get_user_groups(&self, tenant: &TenantId, email: &str) -> Result<Vec<Group>, UserStoreError> {
+        self.get_user(tenant, email).await?;
+
+        sqlx::query(
+            "SELECT g.id, g.display_name, g.created_at FROM user_groups g \
+             JOIN user_group_members m ON m.tenant_id = g.tenant_id AND m.group_id = g.id \
+             WHERE m.tenant_id = ? AND m.email = ? ORDER BY g.display_name"
+        )
+            .bind(tenant.as_ref())
+            .bind(email)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(e.into()))?
+            .into_iter()
+            .map(parse_group)
+            .collect()
+    }
 }
 
-#[tracing::instrument(name="Computing password hash", skip_all)]
-async fn compute_password_hash(password: Secret<String>) -> Result<Secret<String>> {
-    let current_span: tracing::Span = tracing::Span::current();
-
-    let result = tokio::task::spawn_blocking(move || {
-        current_span.in_scope(|| {
-            let salt: SaltString = SaltString::generate(&mut rand::thread_rng());
-            let password_hash = Argon2::new(
-                Algorithm::Argon2id,
-                Version::V0x13,
-                Params::new(15000, 2, 1, None)?,
-            )
-            .hash_password(password.expose_secret().as_bytes(), &salt)?
-            .to_string();
-        
-            Ok(Secret::new(password_hash))
-        })
-    }).await;
-
-    result?
-}
\ No newline at end of file
+impl IntoShared for MySqlUserStore {}
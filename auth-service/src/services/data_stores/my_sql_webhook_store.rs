@@ -0,0 +1,214 @@
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::eyre;
+use secrecy::{ExposeSecret, Secret};
+use sqlx::{mysql::MySqlRow, MySqlPool, Row};
+
+use crate::{
+    domain::{
+        IntoShared, TenantId, WebhookDelivery, WebhookDeliveryId, WebhookDeliveryStatus, WebhookEndpoint,
+        WebhookEndpointId, WebhookEventType, WebhookStore, WebhookStoreError,
+    },
+    utils::parsable::Parsable,
+};
+
+pub struct MySqlWebhookStore {
+    pool: MySqlPool,
+}
+
+impl MySqlWebhookStore {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+const ENDPOINT_COLUMNS: &str = "id, tenant_id, url, secret, events, created_at";
+const DELIVERY_COLUMNS: &str = "id, endpoint_id, event, payload, status, attempts, next_attempt_at, last_error, created_at";
+
+fn unexpected(e: sqlx::Error) -> WebhookStoreError {
+    WebhookStoreError::UnexpectedError(e.into())
+}
+
+fn invalid(e: color_eyre::eyre::Error) -> WebhookStoreError {
+    WebhookStoreError::UnexpectedError(eyre!(e))
+}
+
+fn parse_endpoint(row: MySqlRow) -> Result<WebhookEndpoint, WebhookStoreError> {
+    let id: String = row.try_get("id").map_err(unexpected)?;
+    let tenant: String = row.try_get("tenant_id").map_err(unexpected)?;
+    let secret: String = row.try_get("secret").map_err(unexpected)?;
+    let events: String = row.try_get("events").map_err(unexpected)?;
+
+    Ok(WebhookEndpoint {
+        id: WebhookEndpointId::parse_or_error(&id, invalid)?,
+        tenant: TenantId::parse_or_error(&tenant, invalid)?,
+        url: row.try_get("url").map_err(unexpected)?,
+        secret: Secret::new(secret),
+        events: events
+            .split(',')
+            .filter(|event| !event.is_empty())
+            .map(|event| WebhookEventType::parse_or_error(event, invalid))
+            .collect::<Result<_, _>>()?,
+        created_at: row.try_get::<DateTime<Utc>, _>("created_at").map_err(unexpected)?,
+    })
+}
+
+fn parse_delivery(row: MySqlRow) -> Result<WebhookDelivery, WebhookStoreError> {
+    let id: String = row.try_get("id").map_err(unexpected)?;
+    let endpoint_id: String = row.try_get("endpoint_id").map_err(unexpected)?;
+    let event_type: String = row.try_get("event").map_err(unexpected)?;
+    let status: String = row.try_get("status").map_err(unexpected)?;
+
+    Ok(WebhookDelivery {
+        id: WebhookDeliveryId::parse_or_error(&id, invalid)?,
+        endpoint_id: WebhookEndpointId::parse_or_error(&endpoint_id, invalid)?,
+        event_type: WebhookEventType::parse_or_error(&event_type, invalid)?,
+        payload: row.try_get("payload").map_err(unexpected)?,
+        status: WebhookDeliveryStatus::parse_or_error(&status, invalid)?,
+        attempts: row.try_get("attempts").map_err(unexpected)?,
+        next_attempt_at: row.try_get::<DateTime<Utc>, _>("next_attempt_at").map_err(unexpected)?,
+        last_error: row.try_get("last_error").map_err(unexpected)?,
+        created_at: row.try_get::<DateTime<Utc>, _>("created_at").map_err(unexpected)?,
+    })
+}
+
+#[async_trait::async_trait]
+impl WebhookStore for MySqlWebhookStore {
+    #[tracing::instrument(name = "Adding webhook endpoint to Database", skip_all)]
+    async fn add_endpoint(&mut self, endpoint: WebhookEndpoint) -> Result<(), WebhookStoreError> {
+        let events: Vec<&str> = endpoint.events.iter().map(|event| event.as_ref()).collect();
+
+        sqlx::query(&format!("INSERT INTO webhook_endpoints ({}) VALUES (?, ?, ?, ?, ?, ?)", ENDPOINT_COLUMNS))
+            .bind(endpoint.id.as_ref())
+            .bind(endpoint.tenant.as_ref())
+            .bind(&endpoint.url)
+            .bind(endpoint.secret.expose_secret())
+            .bind(events.join(","))
+            .bind(endpoint.created_at)
+            .execute(&self.pool)
+            .await
+            .map_err(unexpected)?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Retrieving webhook endpoint from Database", skip_all)]
+    async fn get_endpoint(&self, id: &WebhookEndpointId) -> Result<WebhookEndpoint, WebhookStoreError> {
+        sqlx::query(&format!("SELECT {} FROM webhook_endpoints WHERE id = ?", ENDPOINT_COLUMNS))
+            .bind(id.as_ref())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(unexpected)?
+            .map(parse_endpoint)
+            .ok_or(WebhookStoreError::EndpointNotFound)?
+    }
+
+    #[tracing::instrument(name = "Listing webhook endpoints from Database", skip_all)]
+    async fn list_endpoints(&self, tenant: &TenantId) -> Result<Vec<WebhookEndpoint>, WebhookStoreError> {
+        sqlx::query(&format!(
+            "SELECT {} FROM webhook_endpoints WHERE tenant_id = ? ORDER BY created_at",
+            ENDPOINT_COLUMNS
+        ))
+            .bind(tenant.as_ref())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(unexpected)?
+            .into_iter()
+            .map(parse_endpoint)
+            .collect()
+    }
+
+    // Deliveries go with the endpoint through the foreign key.
+    #[tracing::instrument(name = "Removing webhook endpoint from Database", skip_all)]
+    async fn remove_endpoint(&mut self, id: &WebhookEndpointId) -> Result<(), WebhookStoreError> {
+        let result = sqlx::query("DELETE FROM webhook_endpoints WHERE id = ?")
+            .bind(id.as_ref())
+            .execute(&self.pool)
+            .await
+            .map_err(unexpected)?;
+
+        match result.rows_affected() {
+            0 => Err(WebhookStoreError::EndpointNotFound),
+            _ => Ok(()),
+        }
+    }
+
+    #[tracing::instrument(name = "Adding webhook delivery to Database", skip_all)]
+    async fn add_delivery(&mut self, delivery: WebhookDelivery) -> Result<(), WebhookStoreError> {
+        sqlx::query(&format!(
+            "INSERT INTO webhook_deliveries ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            DELIVERY_COLUMNS
+        ))
+            .bind(delivery.id.as_ref())
+            .bind(delivery.endpoint_id.as_ref())
+            .bind(delivery.event_type.as_ref())
+            .bind(&delivery.payload)
+            .bind(delivery.status.as_ref())
+            .bind(delivery.attempts)
+            .bind(delivery.next_attempt_at)
+            .bind(&delivery.last_error)
+            .bind(delivery.created_at)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(e) if e.is_foreign_key_violation() => WebhookStoreError::EndpointNotFound,
+                e => unexpected(e),
+            })?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Updating webhook delivery in Database", skip_all)]
+    async fn update_delivery(&mut self, delivery: &WebhookDelivery) -> Result<(), WebhookStoreError> {
+        let result = sqlx::query(
+            "UPDATE webhook_deliveries SET status = ?, attempts = ?, next_attempt_at = ?, last_error = ? WHERE id = ?"
+        )
+            .bind(delivery.status.as_ref())
+            .bind(delivery.attempts)
+            .bind(delivery.next_attempt_at)
+            .bind(&delivery.last_error)
+            .bind(delivery.id.as_ref())
+            .execute(&self.pool)
+            .await
+            .map_err(unexpected)?;
+
+        match result.rows_affected() {
+            0 => Err(WebhookStoreError::DeliveryNotFound),
+            _ => Ok(()),
+        }
+    }
+
+    #[tracing::instrument(name = "Listing due webhook deliveries from Database", skip_all)]
+    async fn list_due_deliveries(&self, now: DateTime<Utc>, limit: u32) -> Result<Vec<WebhookDelivery>, WebhookStoreError> {
+        sqlx::query(&format!(
+            "SELECT {} FROM webhook_deliveries WHERE status = ? AND next_attempt_at <= ? ORDER BY next_attempt_at LIMIT ?",
+            DELIVERY_COLUMNS
+        ))
+            .bind(WebhookDeliveryStatus::Pending.as_ref())
+            .bind(now)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(unexpected)?
+            .into_iter()
+            .map(parse_delivery)
+            .collect()
+    }
+
+    #[tracing::instrument(name = "Listing webhook deliveries from Database", skip_all)]
+    async fn list_deliveries(&self, endpoint_id: &WebhookEndpointId, limit: u32) -> Result<Vec<WebhookDelivery>, WebhookStoreError> {
+        sqlx::query(&format!(
+            "SELECT {} FROM webhook_deliveries WHERE endpoint_id = ? ORDER BY created_at DESC LIMIT ?",
+            DELIVERY_COLUMNS
+        ))
+            .bind(endpoint_id.as_ref())
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(unexpected)?
+            .into_iter()
+            .map(parse_delivery)
+            .collect()
+    }
+}
+
+impl IntoShared for MySqlWebhookStore {}
@@ -1,10 +1,11 @@
 use std::sync::Arc;
+use chrono::Utc;
 use redis::{Commands, Connection};
 use tokio::sync::RwLock;
 use secrecy::{ExposeSecret, Secret};
 
 use crate::{
-    domain::{BannedTokenStore, IntoShared},
+    domain::{BannedTokenStore, IntoShared, TenantId},
     utils::auth::TOKEN_TTL_SECONDS,
 };
 
@@ -39,16 +40,60 @@ impl BannedTokenStore for RedisBannedTokenStore {
             .await
             .exists(key).unwrap_or(false)
     }
+
+    // Checked and set in one command, so concurrent uses can't both succeed.
+    #[tracing::instrument(name = "Ban token once", skip_all)]
+    async fn ban_token_once(&mut self, token: &Secret<String>, ttl_seconds: u64) -> bool {
+        let key = get_key(token.expose_secret());
+        let set: Option<String> = redis::cmd("SET")
+            .arg(key)
+            .arg(true)
+            .arg("NX")
+            .arg("EX")
+            .arg(ttl_seconds.max(1))
+            .query(&mut *self.conn.write().await)
+            .unwrap_or(None);
+
+        set.is_some()
+    }
+
+    // Tokens live for `TOKEN_TTL_SECONDS`, so the ban can expire with them.
+    #[tracing::instrument(name = "Ban sessions", skip_all)]
+    async fn ban_sessions(&mut self, tenant: &TenantId, email: &str) -> bool {
+        let key = get_sessions_key(tenant, email);
+        let now = Utc::now().timestamp();
+        self.conn.write()
+            .await
+            .set_ex(key, now, TOKEN_TTL_SECONDS as u64)
+            .unwrap_or(false)
+    }
+
+    #[tracing::instrument(name = "is session banned", skip_all)]
+    async fn is_session_banned(&self, tenant: &TenantId, email: &str, issued_at: usize) -> bool {
+        let key = get_sessions_key(tenant, email);
+        let banned_at: Option<usize> = self.conn.write()
+            .await
+            .get(key)
+            .unwrap_or(None);
+
+        banned_at.is_some_and(|banned_at| issued_at <= banned_at)
+    }
 }
 
 impl IntoShared for RedisBannedTokenStore {}
 
 const BANNED_TOKEN_KEY_PREFIX: &str = "banned_token:";
 
+const BANNED_SESSIONS_KEY_PREFIX: &str = "banned_sessions:";
+
 fn get_key(token: &str) -> String {
     format!("{}{}", BANNED_TOKEN_KEY_PREFIX, token)
 }
 
+fn get_sessions_key(tenant: &TenantId, email: &str) -> String {
+    format!("{}{}:{}", BANNED_SESSIONS_KEY_PREFIX, tenant.as_ref(), email)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -75,4 +120,15 @@ mod tests {
         banned_token_store.store_token(&token).await;
         assert!(banned_token_store.is_token_banned(&token).await);
     }
+
+    #[tokio::test]
+    async fn test_ban_token_once() {
+        let conn = configure_redis(DEFAULT_REDIS_HOSTNAME.to_string());
+        let conn = Arc::new(RwLock::new(conn));
+        let mut banned_token_store = RedisBannedTokenStore::new(conn);
+        let token = Secret::new(format!("once-{}", uuid::Uuid::new_v4()));
+        assert!(banned_token_store.ban_token_once(&token, 60).await);
+        assert!(!banned_token_store.ban_token_once(&token, 60).await);
+        assert!(banned_token_store.is_token_banned(&token).await);
+    }
 }
\ No newline at end of file
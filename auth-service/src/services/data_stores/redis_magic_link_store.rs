@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+use color_eyre::eyre::Context;
+use redis::{Commands, Connection};
+use secrecy::ExposeSecret;
+use tokio::sync::RwLock;
+
+use crate::{
+    domain::{IntoShared, MagicLinkId, MagicLinkStore, MagicLinkStoreError},
+    utils::auth::MAGIC_LINK_TTL_SECONDS,
+};
+
+pub struct RedisMagicLinkStore {
+    conn: Arc<RwLock<Connection>>,
+}
+
+impl RedisMagicLinkStore {
+    pub fn new(conn: Arc<RwLock<Connection>>) -> Self {
+        Self { conn }
+    }
+}
+
+#[async_trait::async_trait]
+impl MagicLinkStore for RedisMagicLinkStore {
+    #[tracing::instrument(name = "Add magic link", skip_all)]
+    async fn add_link(&mut self, id: &MagicLinkId) -> Result<(), MagicLinkStoreError> {
+        self.conn
+            .write()
+            .await
+            .set_ex::<String, bool, ()>(get_key(id), true, MAGIC_LINK_TTL_SECONDS as u64)
+            .wrap_err("Failed to set magic link in Redis")
+            .map_err(MagicLinkStoreError::UnexpectedError)
+    }
+
+    #[tracing::instrument(name = "Consume magic link", skip_all)]
+    async fn consume_link(&mut self, id: &MagicLinkId) -> Result<(), MagicLinkStoreError> {
+        // GETDEL makes sure two concurrent requests can't both use the link
+        let link: Option<bool> = self.conn
+            .write()
+            .await
+            .get_del(get_key(id))
+            .wrap_err("Failed to get the magic link from Redis")
+            .map_err(MagicLinkStoreError::UnexpectedError)?;
+
+        link.map(|_| ()).ok_or(MagicLinkStoreError::LinkNotFound)
+    }
+}
+
+impl IntoShared for RedisMagicLinkStore {}
+
+const MAGIC_LINK_PREFIX: &str = "magic_link:";
+
+fn get_key(id: &MagicLinkId) -> String {
+    format!("{}{}", MAGIC_LINK_PREFIX, id.as_ref().expose_secret())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        configure_redis,
+        utils::constants::DEFAULT_REDIS_HOSTNAME,
+    };
+
+    #[tokio::test]
+    async fn should_consume_a_link_only_once() {
+        let conn = Arc::new(RwLock::new(configure_redis(DEFAULT_REDIS_HOSTNAME.to_string())));
+        let mut store = RedisMagicLinkStore::new(conn);
+        let id = MagicLinkId::default();
+        store.add_link(&id).await.unwrap();
+
+        assert_eq!(store.consume_link(&id).await, Ok(()));
+        assert_eq!(store.consume_link(&id).await, Err(MagicLinkStoreError::LinkNotFound));
+    }
+}
@@ -0,0 +1,137 @@
+use redis::{Commands, Connection};
+use secrecy::ExposeSecret;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use color_eyre::eyre::{eyre, Context};
+
+use crate::{
+    domain::{
+        Email,
+        IntoShared,
+        OidcNonce,
+        OidcState,
+        OidcStateStore,
+        OidcStateStoreError,
+        PendingOidcLogin,
+        PkceVerifier,
+    },
+    utils::parsable::Parsable,
+};
+
+pub struct RedisOidcStateStore {
+    conn: Arc<RwLock<Connection>>,
+}
+
+impl RedisOidcStateStore {
+    pub fn new(conn: Arc<RwLock<Connection>>) -> Self {
+        Self { conn }
+    }
+}
+
+#[async_trait::async_trait]
+impl OidcStateStore for RedisOidcStateStore {
+    #[tracing::instrument(name = "Add OIDC state", skip_all)]
+    async fn add_state(
+        &mut self,
+        state: &OidcState,
+        pending_login: PendingOidcLogin,
+    ) -> Result<(), OidcStateStoreError> {
+        let key = get_key(state);
+        let stored_login = StoredOidcLogin {
+            provider: pending_login.provider,
+            nonce: pending_login.nonce.as_ref().expose_secret().to_owned(),
+            pkce_verifier: pending_login.pkce_verifier.as_ref().expose_secret().to_owned(),
+            link_email: pending_login.link_email.map(|email| email.as_ref().expose_secret().to_owned()),
+        };
+
+        let serialized_login = serde_json::to_string(&stored_login)
+            .wrap_err("Failed to serialize pending OIDC login")
+            .map_err(OidcStateStoreError::UnexpectedError)?;
+
+        self.conn
+            .write()
+            .await
+            .set_ex::<String, String, ()>(key, serialized_login, TEN_MINUTES_IN_SECONDS)
+            .wrap_err("Failed to set OIDC state in Redis")
+            .map_err(OidcStateStoreError::UnexpectedError)?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Take OIDC state", skip_all)]
+    async fn take_state(
+        &mut self,
+        state: &OidcState,
+    ) -> Result<PendingOidcLogin, OidcStateStoreError> {
+        let key = get_key(state);
+        let serialized_login: Option<String> = self
+            .conn
+            .write()
+            .await
+            .get_del(key)
+            .wrap_err("Failed to get the OIDC state from Redis")
+            .map_err(OidcStateStoreError::UnexpectedError)?;
+
+        let serialized_login = serialized_login.ok_or(OidcStateStoreError::StateNotFound)?;
+
+        let stored_login: StoredOidcLogin = serde_json::from_str(&serialized_login)
+            .wrap_err("Failed to deserialize pending OIDC login")
+            .map_err(OidcStateStoreError::UnexpectedError)?;
+
+        Ok(PendingOidcLogin {
+            provider: stored_login.provider,
+            nonce: OidcNonce::parse_or_error(&stored_login.nonce, |e| OidcStateStoreError::UnexpectedError(eyre!(e)))?,
+            pkce_verifier: PkceVerifier::parse_or_error(&stored_login.pkce_verifier, |e| OidcStateStoreError::UnexpectedError(eyre!(e)))?,
+            link_email: stored_login.link_email
+                .map(|email| Email::parse_or_error(email, |e| OidcStateStoreError::UnexpectedError(eyre!(e))))
+                .transpose()?,
+        })
+    }
+}
+
+impl IntoShared for RedisOidcStateStore {}
+
+#[derive(Serialize, Deserialize)]
+struct StoredOidcLogin {
+    provider: String,
+    nonce: String,
+    pkce_verifier: String,
+    #[serde(default)]
+    link_email: Option<String>,
+}
+
+const TEN_MINUTES_IN_SECONDS: u64 = 600;
+const OIDC_STATE_PREFIX: &str = "oidc_state:";
+
+fn get_key(state: &OidcState) -> String {
+    format!("{}{}", OIDC_STATE_PREFIX, state.as_ref().expose_secret())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        configure_redis,
+        utils::constants::DEFAULT_REDIS_HOSTNAME,
+    };
+
+    #[tokio::test]
+    async fn should_take_a_state_only_once() {
+        let conn = Arc::new(RwLock::new(configure_redis(DEFAULT_REDIS_HOSTNAME.to_string())));
+        let mut store = RedisOidcStateStore::new(conn);
+        let state = OidcState::default();
+        let pending_login = PendingOidcLogin {
+            provider: "google".to_string(),
+            nonce: OidcNonce::default(),
+            pkce_verifier: PkceVerifier::default(),
+            link_email: None,
+        };
+        store.add_state(&state, pending_login.clone()).await.unwrap();
+
+        let taken = store.take_state(&state).await.unwrap();
+        assert_eq!(taken.nonce, pending_login.nonce);
+        assert_eq!(taken.pkce_verifier, pending_login.pkce_verifier);
+        assert!(store.take_state(&state).await.is_err());
+    }
+}
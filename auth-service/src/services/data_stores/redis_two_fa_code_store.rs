@@ -7,7 +7,7 @@ use color_eyre::eyre::{eyre, Context};
 
 
 use crate::{
-    domain::{Email, IntoShared, LoginAttemptId, TwoFACode, TwoFACodeStore, TwoFACodeStoreError},
+    domain::{Email, IntoShared, LoginAttemptId, TenantId, TwoFACode, TwoFACodeStore, TwoFACodeStoreError},
     utils::parsable::Parsable,
 };
 
@@ -26,11 +26,12 @@ impl TwoFACodeStore for RedisTwoFACodeStore {
     #[tracing::instrument(name = "Add code", skip(self))]
     async fn add_code(
         &mut self,
+        tenant: &TenantId,
         email: Email,
         login_attempt_id: &LoginAttemptId,
         code: TwoFACode,
     ) -> Result<(), TwoFACodeStoreError> {
-        let key = get_key(&email);
+        let key = get_key(tenant, &email);
         let two_fa_tuple = TwoFATuple(
             login_attempt_id.as_ref().expose_secret().to_owned(),
             code.as_ref().expose_secret().to_owned(),
@@ -51,8 +52,8 @@ impl TwoFACodeStore for RedisTwoFACodeStore {
     }
 
     #[tracing::instrument(name = "Remove code", skip(self))]
-    async fn remove_code(&mut self, email: Email) -> Result<(), TwoFACodeStoreError> {
-        let key = get_key(&email);
+    async fn remove_code(&mut self, tenant: &TenantId, email: Email) -> Result<(), TwoFACodeStoreError> {
+        let key = get_key(tenant, &email);
         let mut conn = self.conn.write().await;
         
         conn
@@ -66,9 +67,10 @@ impl TwoFACodeStore for RedisTwoFACodeStore {
     #[tracing::instrument(name = "Get code", skip_all)]
     async fn get_code(
         &self,
+        tenant: &TenantId,
         email: Email,
     ) -> Result<(LoginAttemptId, TwoFACode), TwoFACodeStoreError> {
-        let key = get_key(&email);
+        let key = get_key(tenant, &email);
         let serialized_tuple: String = self
             .conn
             .write()
@@ -98,8 +100,9 @@ struct TwoFATuple(pub String, pub String);
 const TEN_MINUTES_IN_SECONDS: u64 = 600;
 const TWO_FA_CODE_PREFIX: &str = "two_fa_code:";
 
-fn get_key(email: &Email) -> String {
-    format!("{}{}", TWO_FA_CODE_PREFIX, email.as_ref().expose_secret())
+// Tenant ids can't contain ':', so keys of different tenants never collide
+fn get_key(tenant: &TenantId, email: &Email) -> String {
+    format!("{}{}:{}", TWO_FA_CODE_PREFIX, tenant.as_ref(), email.as_ref().expose_secret())
 }
 
 #[cfg(test)]
@@ -120,7 +123,7 @@ mod tests {
         let code = TwoFACode::default();
         assert_eq!(
             store
-                .add_code(email.clone(), &login_attempt_id, code.clone())
+                .add_code(&TenantId::default(), email.clone(), &login_attempt_id, code.clone())
                 .await,
             Ok(())
         );
@@ -134,7 +137,7 @@ mod tests {
         let login_attempt_id = LoginAttemptId::default();
         let code = TwoFACode::default();
         store
-            .add_code(email.clone(), &login_attempt_id, code.clone())
+            .add_code(&TenantId::default(), email.clone(), &login_attempt_id, code.clone())
             .await
             .unwrap();
         let login_attempt_id = LoginAttemptId::default();
@@ -142,7 +145,7 @@ mod tests {
 
         assert_eq!(
             store
-                .add_code(email.clone(), &login_attempt_id, code.clone())
+                .add_code(&TenantId::default(), email.clone(), &login_attempt_id, code.clone())
                 .await,
             Ok(())
         );
@@ -152,31 +155,31 @@ mod tests {
     async fn should_remove_a_code() {
         let conn: Arc<RwLock<Connection>> = get_redis_conn();
         let mut store = RedisTwoFACodeStore::new(conn);
-        let email = Email::parse(&get_random_email()).unwrap();
+        let email = Email::parse(get_random_email()).unwrap();
         let login_attempt_id = LoginAttemptId::default();
         let code = TwoFACode::default();
         store
-            .add_code(email.clone(), &login_attempt_id, code.clone())
+            .add_code(&TenantId::default(), email.clone(), &login_attempt_id, code.clone())
             .await
             .unwrap();
 
-        assert_eq!(store.remove_code(email.clone()).await, Ok(()));
+        assert_eq!(store.remove_code(&TenantId::default(), email.clone()).await, Ok(()));
     }
 
     #[tokio::test]
     async fn should_get_a_code() {
         let conn: Arc<RwLock<Connection>> = get_redis_conn();
         let mut store = RedisTwoFACodeStore::new(conn);
-        let email = Email::parse(&get_random_email()).unwrap();
+        let email = Email::parse(get_random_email()).unwrap();
         let login_attempt_id = LoginAttemptId::default();
         let code = TwoFACode::default();
         store
-            .add_code(email.clone(), &login_attempt_id, code.clone())
+            .add_code(&TenantId::default(), email.clone(), &login_attempt_id, code.clone())
             .await
             .unwrap();
 
         assert_eq!(
-            store.get_code(email.clone()).await,
+            store.get_code(&TenantId::default(), email.clone()).await,
             Ok((login_attempt_id, code))
         );
     }
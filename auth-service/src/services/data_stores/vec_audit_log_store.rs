@@ -0,0 +1,56 @@
+use crate::domain::{AuditEvent, AuditLogStore, AuditLogStoreError, IntoShared, TenantId};
+
+#[derive(Default)]
+pub struct VecAuditLogStore {
+    events: Vec<AuditEvent>,
+}
+
+#[async_trait::async_trait]
+impl AuditLogStore for VecAuditLogStore {
+    async fn record_event(&mut self, event: AuditEvent) -> Result<(), AuditLogStoreError> {
+        self.events.push(event);
+        Ok(())
+    }
+
+    async fn list_events(&self, tenant: &TenantId, actor: &str, limit: u32) -> Result<Vec<AuditEvent>, AuditLogStoreError> {
+        Ok(self.events
+            .iter()
+            .rev()
+            .filter(|event| &event.tenant == tenant && event.actor == actor)
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+}
+
+impl IntoShared for VecAuditLogStore {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{AuditEventKind, AuditOutcome, ClientInfo},
+        utils::parsable::Parsable,
+    };
+
+    fn event(tenant: &str, actor: &str, kind: AuditEventKind) -> AuditEvent {
+        AuditEvent::new(TenantId::parse(tenant).unwrap(), actor.to_owned(), kind, AuditOutcome::Success, ClientInfo::default())
+    }
+
+    #[tokio::test]
+    async fn should_list_an_actors_latest_events_first() {
+        let mut store = VecAuditLogStore::default();
+        store.record_event(event("default", "jane@example.com", AuditEventKind::Signup)).await.unwrap();
+        store.record_event(event("default", "john@example.com", AuditEventKind::Signup)).await.unwrap();
+        store.record_event(event("acme", "jane@example.com", AuditEventKind::Login)).await.unwrap();
+        store.record_event(event("default", "jane@example.com", AuditEventKind::Login)).await.unwrap();
+        store.record_event(event("default", "jane@example.com", AuditEventKind::Logout)).await.unwrap();
+
+        let events = store.list_events(&TenantId::default(), "jane@example.com", 2).await.unwrap();
+        let kinds: Vec<_> = events.iter().map(|event| event.kind).collect();
+        assert_eq!(kinds, vec![AuditEventKind::Logout, AuditEventKind::Login]);
+
+        let events = store.list_events(&TenantId::default(), "jane@example.com", 10).await.unwrap();
+        assert_eq!(events.len(), 3);
+    }
+}
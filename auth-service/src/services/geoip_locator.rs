@@ -0,0 +1,36 @@
+use std::{net::IpAddr, path::Path};
+
+use color_eyre::eyre::{Context, Result};
+use maxminddb::{geoip2, Reader};
+
+const LANGUAGE: &str = "en";
+
+// Looks up roughly where an IP address is from a local MaxMind GeoIP2 or
+// GeoLite2 City database, so no address leaves the service.
+pub struct GeoIpLocator {
+    reader: Reader<Vec<u8>>,
+}
+
+impl GeoIpLocator {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let reader = Reader::open_readfile(path).wrap_err("Failed to open GeoIP database")?;
+        Ok(Self { reader })
+    }
+
+    // "City, Country", just the country when the city isn't known, or none
+    // for private addresses and ones missing from the database.
+    pub fn locate(&self, ip: IpAddr) -> Option<String> {
+        let record: geoip2::City = self.reader.lookup(ip).ok()?;
+        let name = |names: Option<std::collections::BTreeMap<&str, &str>>| {
+            names.and_then(|names| names.get(LANGUAGE).map(|name| name.to_string()))
+        };
+
+        let city = record.city.and_then(|city| name(city.names));
+        let country = record.country.and_then(|country| name(country.names));
+
+        match (city, country) {
+            (Some(city), Some(country)) => Some(format!("{}, {}", city, country)),
+            (city, country) => country.or(city),
+        }
+    }
+}
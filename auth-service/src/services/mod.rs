@@ -1,2 +1,12 @@
+pub mod account_purger;
+pub mod geoip_locator;
+pub mod password_hashing;
+pub mod password_policy;
+pub mod user_transfer;
 pub mod data_stores;
-pub mod mailgun_email_client;
\ No newline at end of file
+pub mod mailgun_email_client;
+pub mod oidc_client;
+pub mod saml_service_provider;
+pub mod scim;
+pub mod webhook_address_policy;
+pub mod webhook_dispatcher;
\ No newline at end of file
@@ -0,0 +1,360 @@
+use color_eyre::eyre::{eyre, Context, ContextCompat, Result};
+use jsonwebtoken::{decode, decode_header, jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use reqwest::{Client, Url};
+use secrecy::{ExposeSecret, Secret};
+use serde::Deserialize;
+
+use crate::domain::{OidcNonce, OidcProviderConfig, OidcState, PendingOidcLogin, PkceVerifier};
+
+// Relying-party side of the OpenID Connect authorization code flow with PKCE.
+pub struct OidcClient {
+    http_client: Client,
+    config: OidcProviderConfig,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdTokenClaims {
+    pub sub: String,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: bool,
+    pub nonce: Option<String>,
+}
+
+impl OidcClient {
+    pub fn new(config: OidcProviderConfig, http_client: Client) -> Self {
+        Self {
+            http_client,
+            config,
+        }
+    }
+
+    #[tracing::instrument(name = "Building OIDC authorization URL", skip_all)]
+    pub async fn authorization_url(
+        &self,
+        state: &OidcState,
+        nonce: &OidcNonce,
+        pkce_verifier: &PkceVerifier,
+    ) -> Result<Url> {
+        let metadata = self.metadata().await?;
+
+        let url = Url::parse_with_params(
+            &metadata.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", &self.config.client_id),
+                ("redirect_uri", &self.config.redirect_uri),
+                ("scope", &self.config.scopes.join(" ")),
+                ("state", state.as_ref().expose_secret()),
+                ("nonce", nonce.as_ref().expose_secret()),
+                ("code_challenge", &pkce_verifier.challenge()),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .wrap_err("Invalid authorization endpoint")?;
+
+        Ok(url)
+    }
+
+    #[tracing::instrument(name = "Authenticating with OIDC provider", skip_all)]
+    pub async fn authenticate(&self, code: &str, pending_login: &PendingOidcLogin) -> Result<IdTokenClaims> {
+        let metadata = self.metadata().await?;
+        let id_token = self.exchange_code(&metadata, code, &pending_login.pkce_verifier).await?;
+        self.validate_id_token(&metadata, &id_token, &pending_login.nonce).await
+    }
+
+    async fn metadata(&self) -> Result<ProviderMetadata> {
+        let discovery_url = format!(
+            "{}/.well-known/openid-configuration",
+            self.config.issuer.trim_end_matches('/')
+        );
+
+        let metadata: ProviderMetadata = self
+            .http_client
+            .get(&discovery_url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .wrap_err("Failed to parse OIDC discovery document")?;
+
+        if metadata.issuer.trim_end_matches('/') != self.config.issuer.trim_end_matches('/') {
+            return Err(eyre!("OIDC discovery document issuer does not match the configured issuer"));
+        }
+
+        Ok(metadata)
+    }
+
+    async fn exchange_code(
+        &self,
+        metadata: &ProviderMetadata,
+        code: &str,
+        pkce_verifier: &PkceVerifier,
+    ) -> Result<Secret<String>> {
+        let form = [
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &self.config.redirect_uri),
+            ("client_id", &self.config.client_id),
+            ("client_secret", self.config.client_secret.expose_secret()),
+            ("code_verifier", pkce_verifier.as_ref().expose_secret()),
+        ];
+
+        let token_response: TokenResponse = self
+            .http_client
+            .post(&metadata.token_endpoint)
+            .form(&form)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .wrap_err("Failed to parse OIDC token response")?;
+
+        Ok(Secret::new(token_response.id_token))
+    }
+
+    async fn validate_id_token(
+        &self,
+        metadata: &ProviderMetadata,
+        id_token: &Secret<String>,
+        nonce: &OidcNonce,
+    ) -> Result<IdTokenClaims> {
+        let header = decode_header(id_token.expose_secret()).wrap_err("Invalid ID token header")?;
+        if header.alg != Algorithm::RS256 {
+            return Err(eyre!("Unsupported ID token algorithm: {:?}", header.alg));
+        }
+
+        let jwks: JwkSet = self
+            .http_client
+            .get(&metadata.jwks_uri)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .wrap_err("Failed to parse OIDC JWKS")?;
+
+        let jwk = match header.kid {
+            Some(kid) => jwks.find(&kid),
+            None => jwks.keys.first(),
+        }
+        .wrap_err("No matching key found for the ID token")?;
+
+        let decoding_key = DecodingKey::from_jwk(jwk).wrap_err("Invalid JWK")?;
+
+        let mut validation = Validation::new(Algorithm::RS256);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_issuer(&[&metadata.issuer]);
+
+        let claims = decode::<IdTokenClaims>(id_token.expose_secret(), &decoding_key, &validation)
+            .wrap_err("Invalid ID token")?
+            .claims;
+
+        if claims.nonce.as_deref() != Some(nonce.as_ref().expose_secret()) {
+            return Err(eyre!("ID token nonce does not match"));
+        }
+
+        Ok(claims)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::utils::constants::test;
+
+    const PRIVATE_KEY: &str = include_str!("../../tests/fixtures/oidc/private_key.pem");
+    const JWKS: &str = include_str!("../../tests/fixtures/oidc/jwks.json");
+    const CLIENT_ID: &str = "auth-service";
+
+    fn oidc_client(issuer: String) -> OidcClient {
+        let http_client = Client::builder()
+            .timeout(test::oidc::TIMEOUT)
+            .build()
+            .unwrap();
+
+        OidcClient::new(
+            OidcProviderConfig {
+                name: "mock".to_string(),
+                issuer,
+                client_id: CLIENT_ID.to_string(),
+                client_secret: Secret::new("client-secret".to_string()),
+                redirect_uri: "http://localhost/oidc/mock/callback".to_string(),
+                scopes: vec!["openid".to_string(), "email".to_string()],
+            },
+            http_client,
+        )
+    }
+
+    fn pending_login() -> PendingOidcLogin {
+        PendingOidcLogin {
+            provider: "mock".to_string(),
+            nonce: OidcNonce::default(),
+            pkce_verifier: PkceVerifier::default(),
+            link_email: None,
+        }
+    }
+
+    fn id_token(claims: serde_json::Value) -> String {
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = Some("test-key".to_string());
+        encode(&header, &claims, &EncodingKey::from_rsa_pem(PRIVATE_KEY.as_bytes()).unwrap()).unwrap()
+    }
+
+    fn valid_claims(issuer: &str, nonce: &OidcNonce) -> serde_json::Value {
+        serde_json::json!({
+            "iss": issuer,
+            "aud": CLIENT_ID,
+            "sub": "248289761001",
+            "email": "jane@example.com",
+            "email_verified": true,
+            "nonce": nonce.as_ref().expose_secret(),
+            "exp": Utc::now().timestamp() + 300,
+        })
+    }
+
+    async fn mock_identity_provider() -> MockServer {
+        let mock_server = MockServer::start().await;
+        let issuer = mock_server.uri();
+
+        Mock::given(method("GET"))
+            .and(path("/.well-known/openid-configuration"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "issuer": issuer,
+                "authorization_endpoint": format!("{}/authorize", issuer),
+                "token_endpoint": format!("{}/token", issuer),
+                "jwks_uri": format!("{}/jwks", issuer),
+            })))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/jwks"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(JWKS, "application/json"))
+            .mount(&mock_server)
+            .await;
+
+        mock_server
+    }
+
+    async fn mount_token_endpoint(mock_server: &MockServer, id_token: String) {
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .and(body_string_contains("grant_type=authorization_code"))
+            .and(body_string_contains("code_verifier="))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "access-token",
+                "token_type": "Bearer",
+                "id_token": id_token,
+            })))
+            .expect(1)
+            .mount(mock_server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn authorization_url_includes_state_nonce_and_pkce_challenge() {
+        let mock_server = mock_identity_provider().await;
+        let client = oidc_client(mock_server.uri());
+        let state = OidcState::default();
+        let pending_login = pending_login();
+
+        let url = client
+            .authorization_url(&state, &pending_login.nonce, &pending_login.pkce_verifier)
+            .await
+            .unwrap();
+
+        let params: std::collections::HashMap<_, _> = url.query_pairs().into_owned().collect();
+        assert_eq!(url.path(), "/authorize");
+        assert_eq!(params["client_id"], CLIENT_ID);
+        assert_eq!(params["state"], *state.as_ref().expose_secret());
+        assert_eq!(params["nonce"], *pending_login.nonce.as_ref().expose_secret());
+        assert_eq!(params["code_challenge"], pending_login.pkce_verifier.challenge());
+        assert_eq!(params["code_challenge_method"], "S256");
+    }
+
+    #[tokio::test]
+    async fn authenticate_returns_claims_for_a_valid_id_token() {
+        let pending_login = pending_login();
+        let mock_server = mock_identity_provider().await;
+        let claims = valid_claims(&mock_server.uri(), &pending_login.nonce);
+        mount_token_endpoint(&mock_server, id_token(claims)).await;
+        let client = oidc_client(mock_server.uri());
+
+        let claims = client.authenticate("code", &pending_login).await.unwrap();
+        assert_eq!(claims.sub, "248289761001");
+        assert_eq!(claims.email.as_deref(), Some("jane@example.com"));
+        assert!(claims.email_verified);
+    }
+
+    #[tokio::test]
+    async fn authenticate_fails_if_nonce_does_not_match() {
+        let pending_login = pending_login();
+        let mock_server = mock_identity_provider().await;
+        let claims = valid_claims(&mock_server.uri(), &OidcNonce::default());
+        mount_token_endpoint(&mock_server, id_token(claims)).await;
+        let client = oidc_client(mock_server.uri());
+
+        assert!(client.authenticate("code", &pending_login).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn authenticate_fails_if_audience_does_not_match() {
+        let pending_login = pending_login();
+        let mock_server = mock_identity_provider().await;
+        let mut claims = valid_claims(&mock_server.uri(), &pending_login.nonce);
+        claims["aud"] = serde_json::json!("another-client");
+        mount_token_endpoint(&mock_server, id_token(claims)).await;
+        let client = oidc_client(mock_server.uri());
+
+        assert!(client.authenticate("code", &pending_login).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn authenticate_fails_if_id_token_is_expired() {
+        let pending_login = pending_login();
+        let mock_server = mock_identity_provider().await;
+        let mut claims = valid_claims(&mock_server.uri(), &pending_login.nonce);
+        claims["exp"] = serde_json::json!(Utc::now().timestamp() - 3600);
+        mount_token_endpoint(&mock_server, id_token(claims)).await;
+        let client = oidc_client(mock_server.uri());
+
+        assert!(client.authenticate("code", &pending_login).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn authenticate_fails_if_id_token_is_not_signed_by_the_provider() {
+        let pending_login = pending_login();
+        let mock_server = mock_identity_provider().await;
+        let claims = valid_claims(&mock_server.uri(), &pending_login.nonce);
+        let forged_token = encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(b"not-the-provider-key"),
+        ).unwrap();
+        mount_token_endpoint(&mock_server, forged_token).await;
+        let client = oidc_client(mock_server.uri());
+
+        assert!(client.authenticate("code", &pending_login).await.is_err());
+    }
+}
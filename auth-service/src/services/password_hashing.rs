@@ -0,0 +1,273 @@
+use argon2::{
+    password_hash::SaltString,
+    Algorithm,
+    Argon2,
+    Params,
+    PasswordHash,
+    PasswordHasher,
+    PasswordVerifier,
+    Version,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use color_eyre::eyre::{eyre, Context, Result};
+use pbkdf2::Pbkdf2;
+use secrecy::{ExposeSecret, Secret};
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+
+const DJANGO_PBKDF2_PREFIX: &str = "pbkdf2_sha256$";
+// Django derives 32 bytes. A shorter hash would be matched by guessing just
+// as many bytes, and an empty one by any password.
+const MIN_PBKDF2_HASH_LENGTH: usize = 32;
+
+// Argon2id cost new password hashes are made with. Hashes made with anything
+// else still verify, and are replaced the next time the user signs in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashingParams {
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+impl HashingParams {
+    pub fn new(memory_kib: u32, iterations: u32, parallelism: u32) -> Result<Self> {
+        Params::new(memory_kib, iterations, parallelism, None).wrap_err("Invalid Argon2 parameters")?;
+        Ok(Self { memory_kib, iterations, parallelism })
+    }
+
+    fn argon2(&self) -> Result<Argon2<'static>> {
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, None)?;
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+}
+
+impl Default for HashingParams {
+    fn default() -> Self {
+        Self { memory_kib: 15000, iterations: 2, parallelism: 1 }
+    }
+}
+
+// How a password that matched its hash was hashed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordMatch {
+    Current,
+    // Made with other parameters or another algorithm, so it should be
+    // hashed again
+    Outdated,
+}
+
+#[tracing::instrument(name="Computing password hash", skip_all)]
+pub async fn compute_password_hash(password: Secret<String>, params: HashingParams) -> Result<Secret<String>> {
+    let current_span: tracing::Span = tracing::Span::current();
+
+    let result = tokio::task::spawn_blocking(move || {
+        current_span.in_scope(|| {
+            let salt: SaltString = SaltString::generate(&mut rand::thread_rng());
+            let password_hash = params.argon2()?
+                .hash_password(password.expose_secret().as_bytes(), &salt)?
+                .to_string();
+
+            Ok(Secret::new(password_hash))
+        })
+    }).await;
+
+    result?
+}
+
+// Takes as long as verifying a password against a current hash, without
+// checking anything. Run when there's no hash to verify against, so failing
+// to sign in as a user who doesn't exist takes as long as with a wrong
+// password.
+#[tracing::instrument(name="Simulate password verification", skip_all)]
+pub async fn simulate_password_verification(password_candidate: Secret<String>, params: HashingParams) {
+    // Verifying a hash computes it again with the same parameters
+    let _ = compute_password_hash(password_candidate, params).await;
+}
+
+// Besides Argon2, accepts the bcrypt and PBKDF2 hashes other systems leave
+// behind when their users are imported. Fails when the password doesn't
+// match.
+#[tracing::instrument(name="Verify password hash", skip_all)]
+pub async fn verify_password_hash(
+    expected_password_hash: Secret<String>,
+    password_candidate: Secret<String>,
+    params: HashingParams,
+) -> Result<PasswordMatch> {
+    let current_span: tracing::Span = tracing::Span::current();
+    let result = tokio::task::spawn_blocking(move || {
+        current_span.in_scope(|| {
+            verify(expected_password_hash.expose_secret(), password_candidate.expose_secret(), params)
+        })
+    }).await;
+
+    result?
+}
+
+// Whether a hash from another system can be verified, and so imported.
+pub fn is_supported_hash(hash: &str) -> bool {
+    match hash_kind(hash) {
+        Some(HashKind::Phc) => PasswordHash::new(hash).is_ok_and(|hash| {
+            Algorithm::try_from(hash.algorithm).is_ok() || hash.algorithm.as_str().starts_with("pbkdf2")
+        }),
+        Some(HashKind::Bcrypt) => hash.parse::<bcrypt::HashParts>().is_ok(),
+        Some(HashKind::DjangoPbkdf2) => parse_django_pbkdf2(hash).is_ok(),
+        None => false,
+    }
+}
+
+enum HashKind {
+    // Argon2 or PBKDF2 in the PHC string format, like `$argon2id$...`
+    Phc,
+    // `$2a$`, `$2b$` or `$2y$`
+    Bcrypt,
+    // Django's `pbkdf2_sha256$<iterations>$<salt>$<hash>`
+    DjangoPbkdf2,
+}
+
+fn hash_kind(hash: &str) -> Option<HashKind> {
+    if ["$2a$", "$2b$", "$2y$"].iter().any(|prefix| hash.starts_with(prefix)) {
+        Some(HashKind::Bcrypt)
+    } else if hash.starts_with(DJANGO_PBKDF2_PREFIX) {
+        Some(HashKind::DjangoPbkdf2)
+    } else if hash.starts_with('$') {
+        Some(HashKind::Phc)
+    } else {
+        None
+    }
+}
+
+fn verify(expected: &str, candidate: &str, params: HashingParams) -> Result<PasswordMatch> {
+    match hash_kind(expected).ok_or(eyre!("Unsupported password hash"))? {
+        HashKind::Phc => {
+            let hash = PasswordHash::new(expected)?;
+
+            match Algorithm::try_from(hash.algorithm) {
+                Ok(algorithm) => {
+                    Argon2::default().verify_password(candidate.as_bytes(), &hash)?;
+
+                    let hash_params = Params::try_from(&hash)?;
+                    let is_current = algorithm == Algorithm::Argon2id
+                        && hash.version == Some(Version::V0x13.into())
+                        && hash_params.m_cost() == params.memory_kib
+                        && hash_params.t_cost() == params.iterations
+                        && hash_params.p_cost() == params.parallelism;
+
+                    Ok(if is_current { PasswordMatch::Current } else { PasswordMatch::Outdated })
+                }
+                Err(_) => {
+                    Pbkdf2.verify_password(candidate.as_bytes(), &hash)?;
+                    Ok(PasswordMatch::Outdated)
+                }
+            }
+        }
+        HashKind::Bcrypt => match bcrypt::verify(candidate, expected)? {
+            true => Ok(PasswordMatch::Outdated),
+            false => Err(eyre!("Password doesn't match")),
+        },
+        HashKind::DjangoPbkdf2 => {
+            let (iterations, salt, hash) = parse_django_pbkdf2(expected)?;
+            let mut derived = vec![0u8; hash.len()];
+            pbkdf2::pbkdf2_hmac::<Sha256>(candidate.as_bytes(), salt.as_bytes(), iterations, &mut derived);
+
+            match bool::from(derived.ct_eq(&hash)) {
+                true => Ok(PasswordMatch::Outdated),
+                false => Err(eyre!("Password doesn't match")),
+            }
+        }
+    }
+}
+
+fn parse_django_pbkdf2(hash: &str) -> Result<(u32, &str, Vec<u8>)> {
+    let mut parts = hash.strip_prefix(DJANGO_PBKDF2_PREFIX).unwrap_or_default().splitn(3, '$');
+    let (Some(iterations), Some(salt), Some(hash)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(eyre!("Invalid PBKDF2 hash"));
+    };
+
+    let iterations = iterations.parse().wrap_err("Invalid PBKDF2 iterations")?;
+    if iterations == 0 {
+        return Err(eyre!("Invalid PBKDF2 iterations"));
+    }
+
+    let hash = STANDARD.decode(hash).wrap_err("Invalid PBKDF2 hash")?;
+    if hash.len() < MIN_PBKDF2_HASH_LENGTH {
+        return Err(eyre!("Invalid PBKDF2 hash"));
+    }
+    Ok((iterations, salt, hash))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSWORD: &str = "password123";
+
+    async fn verify_hash(hash: &str, password: &str) -> Result<PasswordMatch> {
+        verify_password_hash(Secret::new(hash.to_owned()), Secret::new(password.to_owned()), HashingParams::default()).await
+    }
+
+    #[tokio::test]
+    async fn should_tell_current_hashes_from_outdated_ones() {
+        let current = compute_password_hash(Secret::new(PASSWORD.to_owned()), HashingParams::default()).await.unwrap();
+        assert_eq!(verify_hash(current.expose_secret(), PASSWORD).await.unwrap(), PasswordMatch::Current);
+        assert!(verify_hash(current.expose_secret(), "password456").await.is_err());
+
+        let weaker = HashingParams::new(8192, 1, 1).unwrap();
+        let outdated = compute_password_hash(Secret::new(PASSWORD.to_owned()), weaker).await.unwrap();
+        assert!(outdated.expose_secret().starts_with("$argon2id$"));
+        assert_eq!(verify_hash(outdated.expose_secret(), PASSWORD).await.unwrap(), PasswordMatch::Outdated);
+    }
+
+    #[tokio::test]
+    async fn should_verify_imported_bcrypt_hashes() {
+        let hash = bcrypt::hash(PASSWORD, 4).unwrap();
+
+        assert!(is_supported_hash(&hash));
+        assert_eq!(verify_hash(&hash, PASSWORD).await.unwrap(), PasswordMatch::Outdated);
+        assert!(verify_hash(&hash, "password456").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn should_verify_imported_pbkdf2_hashes() {
+        let salt = SaltString::generate(&mut rand::thread_rng());
+        let phc = Pbkdf2.hash_password(PASSWORD.as_bytes(), &salt).unwrap().to_string();
+        assert!(phc.starts_with("$pbkdf2-sha256$"));
+        assert!(is_supported_hash(&phc));
+        assert_eq!(verify_hash(&phc, PASSWORD).await.unwrap(), PasswordMatch::Outdated);
+        assert!(verify_hash(&phc, "password456").await.is_err());
+
+        let mut derived = [0u8; 32];
+        pbkdf2::pbkdf2_hmac::<Sha256>(PASSWORD.as_bytes(), b"somesalt", 1000, &mut derived);
+        let django = format!("pbkdf2_sha256$1000$somesalt${}", STANDARD.encode(derived));
+        assert!(is_supported_hash(&django));
+        assert_eq!(verify_hash(&django, PASSWORD).await.unwrap(), PasswordMatch::Outdated);
+        assert!(verify_hash(&django, "password456").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn should_reject_pbkdf2_hashes_with_an_empty_hash() {
+        let hash = "pbkdf2_sha256$1000$somesalt$";
+
+        assert!(!is_supported_hash(hash));
+        assert!(verify_hash(hash, PASSWORD).await.is_err());
+        assert!(verify_hash(hash, "password456").await.is_err());
+
+        let truncated = format!("pbkdf2_sha256$1000$somesalt${}", STANDARD.encode([0u8; 4]));
+        assert!(!is_supported_hash(&truncated));
+    }
+
+    #[tokio::test]
+    async fn should_reject_pbkdf2_hashes_with_zero_iterations() {
+        let hash = format!("pbkdf2_sha256$0$somesalt${}", STANDARD.encode([0u8; 32]));
+
+        assert!(!is_supported_hash(&hash));
+        assert!(verify_hash(&hash, PASSWORD).await.is_err());
+    }
+
+    #[test]
+    fn should_reject_unknown_hashes() {
+        assert!(!is_supported_hash("password123"));
+        assert!(!is_supported_hash("$md5$abc"));
+        assert!(!is_supported_hash("pbkdf2_sha256$many$salt$hash"));
+        assert!(HashingParams::new(0, 0, 0).is_err());
+    }
+}
@@ -0,0 +1,342 @@
+use std::{
+    collections::HashSet,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use color_eyre::eyre::{Context, Result};
+use secrecy::ExposeSecret;
+use sha1::{Digest, Sha1};
+
+use crate::domain::{Email, Password, PasswordRejection};
+
+const COMMON_PASSWORDS: &str = include_str!("common_passwords.txt");
+pub const DEFAULT_MIN_PASSWORD_LENGTH: usize = 8;
+pub const DEFAULT_MIN_PASSWORD_STRENGTH: u8 = 3;
+pub const MAX_PASSWORD_STRENGTH: u8 = 4;
+// Shortest run of characters taken as a word or pattern rather than guessed
+// one character at a time
+const MIN_PATTERN_LENGTH: usize = 3;
+// Only this many characters are scored, as in zxcvbn, since scoring takes
+// time cubic in the length. The rest don't make the password any stronger.
+const MAX_SCORED_LENGTH: usize = 100;
+// log2 of the guesses needed to reach each strength score, as in zxcvbn
+const STRENGTH_THRESHOLDS: [f64; 4] = [10.0, 20.0, 26.6, 33.2];
+const KEYBOARD_ROWS: [&str; 4] = ["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+const KEYBOARD_KEYS: f64 = 36.0;
+const HIBP_PREFIX_LENGTH: usize = 5;
+
+// What passwords users choose are held to. Passwords already set are never
+// checked again, so tightening the policy only affects new ones.
+pub struct PasswordPolicy {
+    min_length: usize,
+    min_strength: u8,
+    denylist: HashSet<String>,
+    reject_email: bool,
+    breached_password_ranges: Option<PathBuf>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: DEFAULT_MIN_PASSWORD_LENGTH,
+            min_strength: DEFAULT_MIN_PASSWORD_STRENGTH,
+            denylist: password_list(COMMON_PASSWORDS).collect(),
+            reject_email: true,
+            breached_password_ranges: None,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    // Nothing beyond the minimum length `Password::parse` requires, as before
+    // there was a policy.
+    pub fn permissive() -> Self {
+        Self {
+            min_length: 0,
+            min_strength: 0,
+            denylist: HashSet::new(),
+            reject_email: false,
+            breached_password_ranges: None,
+        }
+    }
+
+    // Lengths under the 8 characters `Password::parse` requires have no effect
+    pub fn with_min_length(mut self, min_length: usize) -> Self {
+        self.min_length = min_length;
+        self
+    }
+
+    pub fn with_min_strength(mut self, min_strength: u8) -> Self {
+        self.min_strength = min_strength.min(MAX_PASSWORD_STRENGTH);
+        self
+    }
+
+    // One password per line, denied along with the built-in common ones
+    pub fn with_denylist_file(mut self, path: impl AsRef<Path>) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).wrap_err("Failed to read password denylist")?;
+        self.denylist.extend(password_list(&contents));
+        Ok(self)
+    }
+
+    // A directory of Have I Been Pwned range files as written by its
+    // downloader: one per 5 character SHA-1 prefix, named like `21BD1.txt`
+    // and holding `SUFFIX:COUNT` lines. Only the range of the prefix is read,
+    // as with the k-anonymity range API.
+    pub fn with_breached_password_ranges(mut self, path: impl Into<PathBuf>) -> Self {
+        self.breached_password_ranges = Some(path.into());
+        self
+    }
+
+    // Every rule the password breaks, none if it's acceptable. Scoring runs
+    // on a blocking thread so it doesn't hold up other requests.
+    pub async fn check(self: &Arc<Self>, password: &Password, email: &Email) -> Vec<PasswordRejection> {
+        let policy = Arc::clone(self);
+        let (candidate, email) = (password.clone(), email.clone());
+        let current_span: tracing::Span = tracing::Span::current();
+
+        let mut rejections = tokio::task::spawn_blocking(move || {
+            current_span.in_scope(|| policy.check_rules(&candidate, &email))
+        })
+        .await
+        .unwrap_or_else(|e| {
+            tracing::error!("Failed to score password: {:?}", e);
+            vec![PasswordRejection::TooWeak]
+        });
+
+        if self.is_breached(password.as_ref().expose_secret()).await {
+            rejections.push(PasswordRejection::Breached);
+        }
+
+        rejections
+    }
+
+    fn check_rules(&self, password: &Password, email: &Email) -> Vec<PasswordRejection> {
+        let password = password.as_ref().expose_secret();
+        let lowercase = password.to_lowercase();
+        let email_words = email_words(email);
+        let mut rejections = Vec::new();
+
+        if password.chars().count() < self.min_length {
+            rejections.push(PasswordRejection::TooShort);
+        }
+        if self.denylist.contains(&lowercase) {
+            rejections.push(PasswordRejection::Common);
+        }
+        if self.reject_email && email_words.iter().any(|word| lowercase.contains(word.as_str())) {
+            rejections.push(PasswordRejection::ContainsEmail);
+        }
+        if self.strength(password, &email_words) < self.min_strength {
+            rejections.push(PasswordRejection::TooWeak);
+        }
+
+        rejections
+    }
+
+    // A score from 0 to 4 of how hard the password is to guess for someone
+    // trying common passwords, the user's own details and simple patterns
+    // before anything else.
+    pub fn strength(&self, password: &str, user_inputs: &[String]) -> u8 {
+        let bits = guesses_log2(password, &self.denylist, user_inputs);
+        STRENGTH_THRESHOLDS.iter().take_while(|threshold| bits >= **threshold).count() as u8
+    }
+
+    // Failing to read a range lets the password through rather than keeping
+    // everyone from choosing one.
+    async fn is_breached(&self, password: &str) -> bool {
+        let Some(ranges) = &self.breached_password_ranges else {
+            return false;
+        };
+
+        let hash = hex::encode_upper(Sha1::digest(password.as_bytes()));
+        let (prefix, suffix) = hash.split_at(HIBP_PREFIX_LENGTH);
+
+        match tokio::fs::read_to_string(ranges.join(format!("{}.txt", prefix))).await {
+            // Padding entries have a count of 0
+            Ok(range) => range
+                .lines()
+                .filter_map(|line| line.trim().split_once(':'))
+                .any(|(candidate, count)| candidate.eq_ignore_ascii_case(suffix) && count.trim() != "0"),
+            Err(e) if e.kind() == ErrorKind::NotFound => false,
+            Err(e) => {
+                tracing::error!("Failed to read breached password range {}: {:?}", prefix, e);
+                false
+            }
+        }
+    }
+}
+
+fn password_list(contents: &str) -> impl Iterator<Item = String> + '_ {
+    contents.lines().map(str::trim).filter(|line| !line.is_empty()).map(str::to_lowercase)
+}
+
+// The email's local part and the words in it, e.g. "jane.doe", "jane" and "doe"
+fn email_words(email: &Email) -> Vec<String> {
+    let email = email.as_ref().expose_secret().to_lowercase();
+    let local_part = email.split('@').next().unwrap_or_default();
+
+    std::iter::once(local_part)
+        .chain(local_part.split(|c: char| !c.is_alphanumeric()))
+        .filter(|word| word.chars().count() >= MIN_PATTERN_LENGTH)
+        .map(str::to_owned)
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+// The fewest guesses (as log2) needed to find the password, splitting it into
+// runs that are each guessed either as a word or pattern or one character at
+// a time.
+fn guesses_log2(password: &str, dictionary: &HashSet<String>, user_inputs: &[String]) -> f64 {
+    let chars: Vec<char> = password.chars().take(MAX_SCORED_LENGTH).collect();
+    let mut best = vec![f64::INFINITY; chars.len() + 1];
+    best[0] = 0.0;
+
+    for start in 0..chars.len() {
+        let before = best[start];
+        best[start + 1] = best[start + 1].min(before + char_bits(chars[start]));
+
+        for end in (start + MIN_PATTERN_LENGTH)..=chars.len() {
+            if let Some(bits) = pattern_bits(&chars[start..end], dictionary, user_inputs) {
+                best[end] = best[end].min(before + bits);
+            }
+        }
+    }
+
+    best[chars.len()]
+}
+
+fn char_bits(c: char) -> f64 {
+    let cardinality: f64 = match c {
+        'a'..='z' | 'A'..='Z' => 26.0,
+        '0'..='9' => 10.0,
+        c if c.is_ascii() => 33.0,
+        _ => 100.0,
+    };
+    cardinality.log2()
+}
+
+fn pattern_bits(segment: &[char], dictionary: &HashSet<String>, user_inputs: &[String]) -> Option<f64> {
+    let length = (segment.len() as f64).log2();
+    let word = segment.iter().collect::<String>().to_lowercase();
+    let unleeted: String = word.chars().map(unleet).collect();
+    let case_bits = if segment.iter().any(|c| c.is_uppercase()) { 1.0 } else { 0.0 };
+    let mut candidates = Vec::new();
+
+    if user_inputs.iter().any(|input| *input == word || *input == unleeted) {
+        candidates.push(1.0 + case_bits);
+    }
+    if dictionary.contains(&word) {
+        candidates.push((dictionary.len() as f64).log2() + case_bits);
+    } else if dictionary.contains(&unleeted) {
+        candidates.push((dictionary.len() as f64).log2() + case_bits + 1.0);
+    }
+    if segment.iter().all(|c| *c == segment[0]) {
+        candidates.push(char_bits(segment[0]) + length);
+    }
+    if is_sequence(segment) {
+        candidates.push(char_bits(segment[0]) + length + 1.0);
+    }
+    if is_keyboard_run(&word) {
+        candidates.push(KEYBOARD_KEYS.log2() + length);
+    }
+
+    candidates.into_iter().reduce(f64::min)
+}
+
+fn unleet(c: char) -> char {
+    match c {
+        '4' | '@' => 'a',
+        '3' => 'e',
+        '1' | '!' => 'i',
+        '0' => 'o',
+        '5' | '$' => 's',
+        '7' => 't',
+        c => c,
+    }
+}
+
+// Like "abc", "123" or "987"
+fn is_sequence(segment: &[char]) -> bool {
+    let steps: Vec<i64> = segment.windows(2).map(|pair| pair[1] as i64 - pair[0] as i64).collect();
+    steps.iter().all(|step| *step == steps[0]) && steps[0].abs() == 1
+}
+
+// Like "qwerty" or "lkjh"
+fn is_keyboard_run(word: &str) -> bool {
+    let reversed: String = word.chars().rev().collect();
+    KEYBOARD_ROWS.iter().any(|row| row.contains(word) || row.contains(&reversed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::parsable::Parsable;
+
+    fn strength(password: &str) -> u8 {
+        PasswordPolicy::default().strength(password, &[])
+    }
+
+    async fn check(policy: &Arc<PasswordPolicy>, password: &str, email: &str) -> Vec<PasswordRejection> {
+        policy.check(&Password::parse(password).unwrap(), &Email::parse(email).unwrap()).await
+    }
+
+    #[test]
+    fn should_score_common_passwords_and_patterns_as_weak() {
+        for password in ["password", "password123", "12345678", "qwertyuiop", "aaaaaaaaaaaa", "P@ssw0rd2", "summer2024"] {
+            assert!(strength(password) < DEFAULT_MIN_PASSWORD_STRENGTH, "{} scored {}", password, strength(password));
+        }
+    }
+
+    #[test]
+    fn should_score_unpredictable_passwords_as_strong() {
+        for password in ["Tr0ub4dor&3", "correct horse battery staple", "kitten-lamp-42-violin"] {
+            assert_eq!(strength(password), MAX_PASSWORD_STRENGTH, "{}", password);
+        }
+    }
+
+    #[test]
+    fn should_only_score_the_start_of_long_passwords() {
+        let started = std::time::Instant::now();
+
+        assert_eq!(strength(&"a".repeat(100_000)), strength(&"a".repeat(MAX_SCORED_LENGTH)));
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn should_give_every_reason_a_password_is_rejected() {
+        let policy = Arc::new(PasswordPolicy::default());
+
+        assert_eq!(
+            check(&policy, "password123", "jane@example.com").await,
+            vec![PasswordRejection::Common, PasswordRejection::TooWeak],
+        );
+        assert_eq!(
+            check(&policy, "JaneDoe-1987!", "jane.doe@example.com").await,
+            vec![PasswordRejection::ContainsEmail, PasswordRejection::TooWeak],
+        );
+        assert_eq!(
+            check(&Arc::new(PasswordPolicy::default().with_min_length(16)), "kitten-lamp-42", "jane@example.com").await,
+            vec![PasswordRejection::TooShort],
+        );
+        assert!(check(&policy, "kitten-lamp-42", "jane@example.com").await.is_empty());
+        assert!(check(&Arc::new(PasswordPolicy::permissive()), "password123", "password@example.com").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_reject_passwords_in_breached_password_ranges() {
+        let ranges = std::env::temp_dir().join(format!("hibp-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&ranges).unwrap();
+        // SHA-1 of "kitten-lamp-42", split into prefix and suffix
+        let hash = hex::encode_upper(Sha1::digest(b"kitten-lamp-42"));
+        let (prefix, suffix) = hash.split_at(HIBP_PREFIX_LENGTH);
+        std::fs::write(ranges.join(format!("{}.txt", prefix)), format!("0000000000000000000000000000000000A:0\r\n{}:42\r\n", suffix)).unwrap();
+
+        let policy = Arc::new(PasswordPolicy::default().with_breached_password_ranges(&ranges));
+        assert_eq!(check(&policy, "kitten-lamp-42", "jane@example.com").await, vec![PasswordRejection::Breached]);
+        assert!(check(&policy, "violin-cloud-17", "jane@example.com").await.is_empty());
+
+        std::fs::remove_dir_all(&ranges).unwrap();
+    }
+}
@@ -0,0 +1,550 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Duration, Utc};
+use color_eyre::eyre::{eyre, Context, ContextCompat, Result};
+use roxmltree::{Document, Node};
+use rsa::{
+    pkcs1v15::{Signature, VerifyingKey},
+    pkcs8::DecodePublicKey,
+    signature::Verifier,
+    RsaPublicKey,
+};
+use sha2::{Digest, Sha256};
+use x509_cert::{
+    der::{DecodePem, Encode},
+    Certificate,
+};
+
+use crate::{
+    domain::{SamlAssertion, SamlIdpConfig, SamlServiceProviderConfig},
+    utils::xml_c14n::{canonicalize, escape_attribute},
+};
+
+const PROTOCOL_NS: &str = "urn:oasis:names:tc:SAML:2.0:protocol";
+const ASSERTION_NS: &str = "urn:oasis:names:tc:SAML:2.0:assertion";
+const DSIG_NS: &str = "http://www.w3.org/2000/09/xmldsig#";
+const EXC_C14N: &str = "http://www.w3.org/2001/10/xml-exc-c14n#";
+const ENVELOPED_SIGNATURE: &str = "http://www.w3.org/2000/09/xmldsig#enveloped-signature";
+const RSA_SHA256: &str = "http://www.w3.org/2001/04/xmldsig-more#rsa-sha256";
+const SHA256: &str = "http://www.w3.org/2001/04/xmlenc#sha256";
+const STATUS_SUCCESS: &str = "urn:oasis:names:tc:SAML:2.0:status:Success";
+const BEARER: &str = "urn:oasis:names:tc:SAML:2.0:cm:bearer";
+
+// Tolerated difference between our clock and the IdP's
+const CLOCK_SKEW_SECONDS: i64 = 60;
+
+// Service provider side of the SAML 2.0 Web Browser SSO profile, using the
+// HTTP-POST binding. Only signed assertions are accepted.
+pub struct SamlServiceProvider {
+    config: SamlServiceProviderConfig,
+    idps: Vec<SamlIdp>,
+}
+
+struct SamlIdp {
+    config: SamlIdpConfig,
+    keys: Vec<RsaPublicKey>,
+}
+
+impl SamlServiceProvider {
+    pub fn new(config: SamlServiceProviderConfig, idps: Vec<SamlIdpConfig>) -> Result<Self> {
+        let idps = idps
+            .into_iter()
+            .map(|config| {
+                let keys = config.certificates
+                    .iter()
+                    .map(|certificate| public_key_from_certificate(certificate))
+                    .collect::<Result<Vec<_>>>()
+                    .wrap_err(format!("Invalid certificate for SAML IdP {}", config.name))?;
+
+                if keys.is_empty() {
+                    return Err(eyre!("No certificates configured for SAML IdP {}", config.name));
+                }
+
+                Ok(SamlIdp { config, keys })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { config, idps })
+    }
+
+    pub fn metadata(&self) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<md:EntityDescriptor xmlns:md="urn:oasis:names:tc:SAML:2.0:metadata" entityID="{entity_id}">
+  <md:SPSSODescriptor AuthnRequestsSigned="false" WantAssertionsSigned="true" protocolSupportEnumeration="{protocol}">
+    <md:NameIDFormat>urn:oasis:names:tc:SAML:1.1:nameid-format:emailAddress</md:NameIDFormat>
+    <md:AssertionConsumerService Binding="urn:oasis:names:tc:SAML:2.0:bindings:HTTP-POST" Location="{acs_url}" index="0" isDefault="true"/>
+  </md:SPSSODescriptor>
+</md:EntityDescriptor>
+"#,
+            entity_id = escape_attribute(&self.config.entity_id),
+            protocol = PROTOCOL_NS,
+            acs_url = escape_attribute(&self.config.acs_url),
+        )
+    }
+
+    #[tracing::instrument(name = "Validating SAML response", skip_all)]
+    pub fn validate_response(&self, encoded_response: &str, now: DateTime<Utc>) -> Result<SamlAssertion> {
+        // IdPs are free to wrap the base64 payload over several lines
+        let encoded_response: String = encoded_response
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        let xml = STANDARD.decode(encoded_response).wrap_err("SAML response is not valid base64")?;
+        let xml = String::from_utf8(xml).wrap_err("SAML response is not valid UTF-8")?;
+        // roxmltree rejects DTDs, which rules out entity expansion attacks
+        let document = Document::parse(&xml).wrap_err("SAML response is not valid XML")?;
+
+        let response = document.root_element();
+        if !response.has_tag_name((PROTOCOL_NS, "Response")) {
+            return Err(eyre!("Expected a SAML response"));
+        }
+
+        if let Some(destination) = response.attribute("Destination") {
+            if destination != self.config.acs_url {
+                return Err(eyre!("SAML response destination does not match"));
+            }
+        }
+
+        let status = child(response, PROTOCOL_NS, "Status")?;
+        if child(status, PROTOCOL_NS, "StatusCode")?.attribute("Value") != Some(STATUS_SUCCESS) {
+            return Err(eyre!("SAML response status is not success"));
+        }
+
+        // With a single assertion, the one whose signature is checked is
+        // necessarily the one whose contents are used.
+        let mut assertions = document
+            .descendants()
+            .filter(|node| node.tag_name().namespace() == Some(ASSERTION_NS))
+            .filter(|node| matches!(node.tag_name().name(), "Assertion" | "EncryptedAssertion"));
+        let assertion = match (assertions.next(), assertions.next()) {
+            (Some(assertion), None) => assertion,
+            _ => return Err(eyre!("Expected exactly one assertion")),
+        };
+
+        if !assertion.has_tag_name((ASSERTION_NS, "Assertion")) {
+            return Err(eyre!("Encrypted assertions are not supported"));
+        }
+
+        if assertion.parent_element() != Some(response) {
+            return Err(eyre!("Assertion must be a child of the response"));
+        }
+
+        let issuer = text(child(assertion, ASSERTION_NS, "Issuer")?)?;
+        let idp = self.idps
+            .iter()
+            .find(|idp| idp.config.entity_id == issuer)
+            .wrap_err(format!("Unknown SAML issuer {}", issuer))?;
+
+        verify_signature(&document, assertion, &idp.keys)?;
+        let conditions_end = self.validate_conditions(assertion, now)?;
+
+        let subject = child(assertion, ASSERTION_NS, "Subject")?;
+        let confirmation_end = self.validate_subject_confirmation(subject, now)?;
+        let valid_until = conditions_end.map_or(confirmation_end, |end| end.min(confirmation_end))
+            + Duration::seconds(CLOCK_SKEW_SECONDS);
+
+        let name_id = text(child(subject, ASSERTION_NS, "NameID")?)?;
+        if name_id.is_empty() {
+            return Err(eyre!("Assertion has an empty NameID"));
+        }
+
+        let email = match &idp.config.email_attribute {
+            Some(name) => attribute_value(assertion, name)?,
+            None => name_id.clone(),
+        };
+
+        Ok(SamlAssertion {
+            id: assertion.attribute("ID").unwrap_or_default().to_owned(),
+            idp: idp.config.name.clone(),
+            name_id,
+            email,
+            valid_until,
+        })
+    }
+
+    // Returns when the conditions stop being met, if they ever do.
+    fn validate_conditions(&self, assertion: Node, now: DateTime<Utc>) -> Result<Option<DateTime<Utc>>> {
+        let conditions = child(assertion, ASSERTION_NS, "Conditions")?;
+        validate_time_window(conditions, now)?;
+
+        let restrictions: Vec<Node> = children(conditions, ASSERTION_NS, "AudienceRestriction").collect();
+        if restrictions.is_empty() {
+            return Err(eyre!("Assertion has no audience restriction"));
+        }
+
+        // Every restriction has to be met, and each is met when any of its
+        // audiences is us.
+        let is_audience = restrictions.iter().all(|restriction| {
+            children(*restriction, ASSERTION_NS, "Audience")
+                .any(|audience| text(audience).is_ok_and(|audience| audience == self.config.entity_id))
+        });
+
+        if !is_audience {
+            return Err(eyre!("Assertion is not intended for this service provider"));
+        }
+
+        conditions.attribute("NotOnOrAfter").map(parse_instant).transpose()
+    }
+
+    // Returns when the last of the valid confirmations expires.
+    fn validate_subject_confirmation(&self, subject: Node, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+        children(subject, ASSERTION_NS, "SubjectConfirmation")
+            .filter(|confirmation| confirmation.attribute("Method") == Some(BEARER))
+            .filter_map(|confirmation| child(confirmation, ASSERTION_NS, "SubjectConfirmationData").ok())
+            .filter(|data| {
+                data.attribute("Recipient") == Some(self.config.acs_url.as_str())
+                    && validate_time_window(*data, now).is_ok()
+            })
+            .filter_map(|data| data.attribute("NotOnOrAfter").and_then(|end| parse_instant(end).ok()))
+            .max()
+            .wrap_err("Assertion has no valid bearer subject confirmation")
+    }
+}
+
+fn public_key_from_certificate(pem: &str) -> Result<RsaPublicKey> {
+    let certificate = Certificate::from_pem(pem).map_err(|e| eyre!("Invalid certificate: {}", e))?;
+    let public_key = certificate.tbs_certificate.subject_public_key_info
+        .to_der()
+        .map_err(|e| eyre!("Invalid certificate public key: {}", e))?;
+
+    RsaPublicKey::from_public_key_der(&public_key).map_err(|e| eyre!("Certificate does not hold an RSA key: {}", e))
+}
+
+// Checks the enveloped signature on `element` against the configured keys.
+// Any certificate embedded in the signature's KeyInfo is deliberately ignored.
+fn verify_signature(document: &Document, element: Node, keys: &[RsaPublicKey]) -> Result<()> {
+    let id = element.attribute("ID").wrap_err("Signed element has no ID")?;
+    if document.descendants().filter(|node| node.attribute("ID") == Some(id)).count() != 1 {
+        return Err(eyre!("Signed element ID is not unique"));
+    }
+
+    let signature = child(element, DSIG_NS, "Signature")?;
+    let signed_info = child(signature, DSIG_NS, "SignedInfo")?;
+
+    let canonicalization_method = child(signed_info, DSIG_NS, "CanonicalizationMethod")?;
+    if canonicalization_method.attribute("Algorithm") != Some(EXC_C14N) {
+        return Err(eyre!("Unsupported canonicalization method"));
+    }
+
+    if child(signed_info, DSIG_NS, "SignatureMethod")?.attribute("Algorithm") != Some(RSA_SHA256) {
+        return Err(eyre!("Unsupported signature method"));
+    }
+
+    let mut references = children(signed_info, DSIG_NS, "Reference");
+    let reference = match (references.next(), references.next()) {
+        (Some(reference), None) => reference,
+        _ => return Err(eyre!("Expected exactly one signature reference")),
+    };
+
+    if reference.attribute("URI") != Some(format!("#{}", id).as_str()) {
+        return Err(eyre!("Signature does not reference the signed element"));
+    }
+
+    let mut inclusive_prefixes = Vec::new();
+    for transform in children(child(reference, DSIG_NS, "Transforms")?, DSIG_NS, "Transform") {
+        match transform.attribute("Algorithm") {
+            Some(ENVELOPED_SIGNATURE) => {}
+            Some(EXC_C14N) => inclusive_prefixes = inclusive_namespace_prefixes(transform),
+            _ => return Err(eyre!("Unsupported signature transform")),
+        }
+    }
+
+    if child(reference, DSIG_NS, "DigestMethod")?.attribute("Algorithm") != Some(SHA256) {
+        return Err(eyre!("Unsupported digest method"));
+    }
+
+    let expected_digest = decode_base64(text(child(reference, DSIG_NS, "DigestValue")?)?)?;
+    let canonical_element = canonicalize(element, Some(signature.id()), &inclusive_prefixes)?;
+    if Sha256::digest(canonical_element.as_bytes()).as_slice() != expected_digest.as_slice() {
+        return Err(eyre!("Signed element digest does not match"));
+    }
+
+    let canonical_signed_info = canonicalize(
+        signed_info,
+        None,
+        &inclusive_namespace_prefixes(canonicalization_method),
+    )?;
+    let signature_value = decode_base64(text(child(signature, DSIG_NS, "SignatureValue")?)?)?;
+    let signature_value = Signature::try_from(signature_value.as_slice())
+        .map_err(|e| eyre!("Invalid signature value: {}", e))?;
+
+    let is_valid = keys.iter().any(|key| {
+        VerifyingKey::<Sha256>::new(key.clone())
+            .verify(canonical_signed_info.as_bytes(), &signature_value)
+            .is_ok()
+    });
+
+    if !is_valid {
+        return Err(eyre!("Invalid signature"));
+    }
+
+    Ok(())
+}
+
+fn inclusive_namespace_prefixes(node: Node) -> Vec<String> {
+    children(node, EXC_C14N, "InclusiveNamespaces")
+        .filter_map(|namespaces| namespaces.attribute("PrefixList"))
+        .flat_map(|prefixes| prefixes.split_whitespace().map(str::to_owned))
+        .collect()
+}
+
+fn attribute_value(assertion: Node, name: &str) -> Result<String> {
+    children(assertion, ASSERTION_NS, "AttributeStatement")
+        .flat_map(|statement| children(statement, ASSERTION_NS, "Attribute"))
+        .filter(|attribute| attribute.attribute("Name") == Some(name))
+        .flat_map(|attribute| children(attribute, ASSERTION_NS, "AttributeValue"))
+        .map(text)
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .find(|value| !value.is_empty())
+        .wrap_err(format!("Assertion has no {} attribute", name))
+}
+
+fn validate_time_window(node: Node, now: DateTime<Utc>) -> Result<()> {
+    let skew = Duration::seconds(CLOCK_SKEW_SECONDS);
+
+    if let Some(not_before) = node.attribute("NotBefore") {
+        if now + skew < parse_instant(not_before)? {
+            return Err(eyre!("Assertion is not yet valid"));
+        }
+    }
+
+    if let Some(not_on_or_after) = node.attribute("NotOnOrAfter") {
+        if now - skew >= parse_instant(not_on_or_after)? {
+            return Err(eyre!("Assertion has expired"));
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_instant(value: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|instant| instant.with_timezone(&Utc))
+        .wrap_err(format!("Invalid SAML timestamp {}", value))
+}
+
+fn decode_base64(value: String) -> Result<Vec<u8>> {
+    let value: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+    STANDARD.decode(value).wrap_err("Invalid base64 value")
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, namespace: &'a str, name: &'a str) -> Result<Node<'a, 'input>> {
+    children(node, namespace, name)
+        .next()
+        .wrap_err(format!("Missing {} element", name))
+}
+
+fn children<'a, 'input>(
+    node: Node<'a, 'input>,
+    namespace: &'a str,
+    name: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children().filter(move |child| child.has_tag_name((namespace, name)))
+}
+
+// Values are only read from elements that hold nothing but text. The digest
+// is computed without comments, so a comment splitting a signed value, as in
+// `mallory@evil.com<!---->.example.com`, would keep the signature valid while
+// `Node::text` only returns the text before it.
+fn text(node: Node) -> Result<String> {
+    if node.children().any(|child| !child.is_text()) {
+        return Err(eyre!("Unexpected content in {} element", node.tag_name().name()));
+    }
+
+    let value: String = node.children().filter_map(|child| child.text()).collect();
+    Ok(value.trim().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIGNED_RESPONSE: &str = include_str!("../../tests/fixtures/saml/signed_response.xml");
+    const IDP_CERTIFICATE: &str = include_str!("../../tests/fixtures/saml/idp_cert.pem");
+    const OTHER_CERTIFICATE: &str = include_str!("../../tests/fixtures/saml/other_cert.pem");
+
+    const SP_ENTITY_ID: &str = "https://auth.example.com/saml/metadata";
+    const ACS_URL: &str = "https://auth.example.com/saml/acs";
+    const IDP_ENTITY_ID: &str = "https://idp.example.com/metadata";
+
+    fn idp_config(certificates: &[&str]) -> SamlIdpConfig {
+        SamlIdpConfig {
+            name: "acme".to_string(),
+            entity_id: IDP_ENTITY_ID.to_string(),
+            certificates: certificates.iter().map(|c| c.to_string()).collect(),
+            email_attribute: Some("mail".to_string()),
+        }
+    }
+
+    fn service_provider(entity_id: &str, idp: SamlIdpConfig) -> SamlServiceProvider {
+        SamlServiceProvider::new(
+            SamlServiceProviderConfig {
+                entity_id: entity_id.to_string(),
+                acs_url: ACS_URL.to_string(),
+            },
+            vec![idp],
+        )
+        .unwrap()
+    }
+
+    fn now() -> DateTime<Utc> {
+        parse_instant("2026-10-19T09:01:00Z").unwrap()
+    }
+
+    fn encode(response: &str) -> String {
+        STANDARD.encode(response)
+    }
+
+    #[test]
+    fn test_validates_signed_response() {
+        let sp = service_provider(SP_ENTITY_ID, idp_config(&[IDP_CERTIFICATE]));
+
+        let assertion = sp.validate_response(&encode(SIGNED_RESPONSE), now()).unwrap();
+
+        assert_eq!(
+            assertion,
+            SamlAssertion {
+                id: "_assertion-1".to_string(),
+                idp: "acme".to_string(),
+                name_id: "jane.doe".to_string(),
+                email: "jane.doe@example.com".to_string(),
+                valid_until: parse_instant("2026-10-19T09:06:00Z").unwrap(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_accepts_any_configured_certificate() {
+        let sp = service_provider(SP_ENTITY_ID, idp_config(&[OTHER_CERTIFICATE, IDP_CERTIFICATE]));
+        assert!(sp.validate_response(&encode(SIGNED_RESPONSE), now()).is_ok());
+    }
+
+    #[test]
+    fn test_falls_back_to_name_id_without_email_attribute() {
+        let mut idp = idp_config(&[IDP_CERTIFICATE]);
+        idp.email_attribute = None;
+        let sp = service_provider(SP_ENTITY_ID, idp);
+
+        let assertion = sp.validate_response(&encode(SIGNED_RESPONSE), now()).unwrap();
+        assert_eq!(assertion.email, "jane.doe");
+    }
+
+    #[test]
+    fn test_rejects_untrusted_certificate() {
+        let sp = service_provider(SP_ENTITY_ID, idp_config(&[OTHER_CERTIFICATE]));
+        assert!(sp.validate_response(&encode(SIGNED_RESPONSE), now()).is_err());
+    }
+
+    #[test]
+    fn test_rejects_tampered_assertion() {
+        let sp = service_provider(SP_ENTITY_ID, idp_config(&[IDP_CERTIFICATE]));
+        let tampered = SIGNED_RESPONSE.replace("jane.doe@example.com", "mallory@example.com");
+
+        assert!(sp.validate_response(&encode(&tampered), now()).is_err());
+    }
+
+    #[test]
+    fn test_rejects_injected_unsigned_assertion() {
+        let sp = service_provider(SP_ENTITY_ID, idp_config(&[IDP_CERTIFICATE]));
+        let injected = SIGNED_RESPONSE.replacen(
+            "<saml:Assertion",
+            "<saml:Assertion ID=\"_evil\"><saml:Issuer>https://idp.example.com/metadata</saml:Issuer></saml:Assertion><saml:Assertion",
+            1,
+        );
+
+        assert!(sp.validate_response(&encode(&injected), now()).is_err());
+    }
+
+    #[test]
+    fn test_rejects_values_split_by_comments() {
+        let sp = service_provider(SP_ENTITY_ID, idp_config(&[IDP_CERTIFICATE]));
+        let split_values = [
+            SIGNED_RESPONSE.replace(">jane.doe</saml:NameID>", ">jane<!---->.doe</saml:NameID>"),
+            SIGNED_RESPONSE.replace(">jane.doe@example.com<", ">jane.doe@example<!---->.com<"),
+        ];
+
+        for response in split_values {
+            let error = sp.validate_response(&encode(&response), now()).unwrap_err();
+            // The signature still holds, only the value is refused
+            assert!(error.to_string().starts_with("Unexpected content"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_rejects_expired_assertion() {
+        let sp = service_provider(SP_ENTITY_ID, idp_config(&[IDP_CERTIFICATE]));
+        let later = parse_instant("2026-10-19T09:10:00Z").unwrap();
+
+        assert!(sp.validate_response(&encode(SIGNED_RESPONSE), later).is_err());
+    }
+
+    #[test]
+    fn test_rejects_assertion_not_yet_valid() {
+        let sp = service_provider(SP_ENTITY_ID, idp_config(&[IDP_CERTIFICATE]));
+        let earlier = parse_instant("2026-10-19T08:50:00Z").unwrap();
+
+        assert!(sp.validate_response(&encode(SIGNED_RESPONSE), earlier).is_err());
+    }
+
+    #[test]
+    fn test_rejects_other_audience() {
+        let sp = service_provider("https://other.example.com/saml/metadata", idp_config(&[IDP_CERTIFICATE]));
+        assert!(sp.validate_response(&encode(SIGNED_RESPONSE), now()).is_err());
+    }
+
+    #[test]
+    fn test_rejects_unknown_issuer() {
+        let mut idp = idp_config(&[IDP_CERTIFICATE]);
+        idp.entity_id = "https://other-idp.example.com/metadata".to_string();
+        let sp = service_provider(SP_ENTITY_ID, idp);
+
+        assert!(sp.validate_response(&encode(SIGNED_RESPONSE), now()).is_err());
+    }
+
+    #[test]
+    fn test_rejects_unsuccessful_status() {
+        let sp = service_provider(SP_ENTITY_ID, idp_config(&[IDP_CERTIFICATE]));
+        let failed = SIGNED_RESPONSE.replace(
+            "urn:oasis:names:tc:SAML:2.0:status:Success",
+            "urn:oasis:names:tc:SAML:2.0:status:Requester",
+        );
+
+        assert!(sp.validate_response(&encode(&failed), now()).is_err());
+    }
+
+    #[test]
+    fn test_rejects_invalid_encoding() {
+        let sp = service_provider(SP_ENTITY_ID, idp_config(&[IDP_CERTIFICATE]));
+        assert!(sp.validate_response("not base64!", now()).is_err());
+        assert!(sp.validate_response(&encode("<not-saml/>"), now()).is_err());
+    }
+
+    #[test]
+    fn test_metadata() {
+        let sp = service_provider(SP_ENTITY_ID, idp_config(&[IDP_CERTIFICATE]));
+        let metadata = sp.metadata();
+        let document = Document::parse(&metadata).unwrap();
+
+        let root = document.root_element();
+        assert_eq!(root.attribute("entityID"), Some(SP_ENTITY_ID));
+        let acs = document
+            .descendants()
+            .find(|node| node.has_tag_name("AssertionConsumerService"))
+            .unwrap();
+        assert_eq!(acs.attribute("Location"), Some(ACS_URL));
+    }
+
+    #[test]
+    fn test_rejects_idp_without_certificates() {
+        let result = SamlServiceProvider::new(
+            SamlServiceProviderConfig {
+                entity_id: SP_ENTITY_ID.to_string(),
+                acs_url: ACS_URL.to_string(),
+            },
+            vec![idp_config(&[])],
+        );
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,904 @@
+// SCIM 2.0 filters and PATCH operations (RFC 7644, sections 3.4.2.2 and
+// 3.5.2), evaluated over the JSON form of a resource. Attribute names are
+// matched ignoring case, and so are string values except for ids.
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::domain::ScimError;
+
+pub const CONTENT_TYPE: &str = "application/scim+json";
+
+pub const USER_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:User";
+pub const GROUP_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:Group";
+pub const LIST_RESPONSE_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:ListResponse";
+pub const PATCH_OP_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:PatchOp";
+pub const ERROR_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:Error";
+
+const CASE_EXACT_ATTRIBUTES: [&str; 2] = ["id", "externalId"];
+
+// An attribute like `userName`, or a sub-attribute like `name.givenName`.
+// Names qualified with their schema URN are accepted too.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttrPath {
+    pub attr: String,
+    pub sub_attr: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Co,
+    Sw,
+    Ew,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    Present(AttrPath),
+    Compare(AttrPath, CompareOp, Value),
+    // Matches when one of the values of a multi-valued attribute matches the
+    // inner filter, like `emails[type eq "work"]`.
+    ValuePath(String, Box<Filter>),
+    Not(Box<Filter>),
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    OpenParen,
+    CloseParen,
+    OpenBracket,
+    CloseBracket,
+    Word(String),
+    Str(String),
+}
+
+impl AttrPath {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let path = match input.get(..4).is_some_and(|prefix| prefix.eq_ignore_ascii_case("urn:")) {
+            true => input.rsplit(':').next().unwrap_or_default(),
+            false => input,
+        };
+
+        let (attr, sub_attr) = match path.split_once('.') {
+            Some((attr, sub_attr)) => (attr, Some(sub_attr)),
+            None => (path, None),
+        };
+        if !is_attr_name(attr) || sub_attr.is_some_and(|sub_attr| !is_attr_name(sub_attr)) {
+            return Err(format!("Invalid attribute path: {}", input));
+        }
+
+        Ok(Self { attr: attr.to_owned(), sub_attr: sub_attr.map(str::to_owned) })
+    }
+
+    fn is_case_exact(&self) -> bool {
+        self.sub_attr.is_none() && CASE_EXACT_ATTRIBUTES.iter().any(|attr| attr.eq_ignore_ascii_case(&self.attr))
+    }
+
+    // Every value the path points to in the resource, looking into each value
+    // of multi-valued attributes. Complex values without a sub-attribute
+    // stand for their `value`.
+    fn values<'a>(&self, resource: &'a Value) -> Vec<&'a Value> {
+        let Some(value) = resource.as_object().and_then(|object| get_attribute(object, &self.attr)) else {
+            return Vec::new();
+        };
+
+        let items: Vec<&Value> = match value {
+            Value::Array(items) => items.iter().collect(),
+            value => vec![value],
+        };
+
+        let mut values = Vec::new();
+        for item in items {
+            let value = match (&self.sub_attr, item) {
+                (Some(sub_attr), Value::Object(object)) => get_attribute(object, sub_attr),
+                (Some(_), _) => None,
+                (None, Value::Object(object)) => get_attribute(object, "value"),
+                (None, item) => Some(item),
+            };
+            match value {
+                Some(Value::Array(nested)) => values.extend(nested.iter()),
+                Some(Value::Null) | None => {}
+                Some(value) => values.push(value),
+            }
+        }
+        values
+    }
+}
+
+fn is_attr_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+impl CompareOp {
+    fn parse(input: &str) -> Option<Self> {
+        match input.to_ascii_lowercase().as_str() {
+            "eq" => Some(Self::Eq),
+            "ne" => Some(Self::Ne),
+            "co" => Some(Self::Co),
+            "sw" => Some(Self::Sw),
+            "ew" => Some(Self::Ew),
+            "gt" => Some(Self::Gt),
+            "ge" => Some(Self::Ge),
+            "lt" => Some(Self::Lt),
+            "le" => Some(Self::Le),
+            _ => None,
+        }
+    }
+}
+
+impl Filter {
+    pub fn parse(input: &str) -> Result<Self, ScimError> {
+        let tokens = tokenize(input).map_err(ScimError::InvalidFilter)?;
+        let mut parser = Parser { tokens, position: 0 };
+
+        let filter = parser.parse_or().map_err(ScimError::InvalidFilter)?;
+        match parser.next() {
+            None => Ok(filter),
+            Some(token) => Err(ScimError::InvalidFilter(format!("Unexpected {:?} in filter", token))),
+        }
+    }
+
+    pub fn matches(&self, resource: &Value) -> bool {
+        match self {
+            Self::Present(path) => path.values(resource).iter().any(|value| match value {
+                Value::String(value) => !value.is_empty(),
+                _ => true,
+            }),
+            Self::Compare(path, op, expected) => {
+                let values = path.values(resource);
+                let case_exact = path.is_case_exact();
+                match (op, expected) {
+                    (CompareOp::Eq, Value::Null) => values.is_empty(),
+                    (CompareOp::Ne, Value::Null) => !values.is_empty(),
+                    (CompareOp::Ne, expected) => !values.iter().any(|value| compare(value, CompareOp::Eq, expected, case_exact)),
+                    (op, expected) => values.iter().any(|value| compare(value, *op, expected, case_exact)),
+                }
+            }
+            Self::ValuePath(attr, filter) => {
+                let Some(value) = resource.as_object().and_then(|object| get_attribute(object, attr)) else {
+                    return false;
+                };
+                match value {
+                    Value::Array(items) => items.iter().any(|item| filter.matches(item)),
+                    value => filter.matches(value),
+                }
+            }
+            Self::Not(filter) => !filter.matches(resource),
+            Self::And(left, right) => left.matches(resource) && right.matches(resource),
+            Self::Or(left, right) => left.matches(resource) || right.matches(resource),
+        }
+    }
+
+    // The value the filter asks `attr` to equal, when that's all it asks.
+    // Lets the most common filters, like `userName eq "jane@example.com"`,
+    // skip scanning every resource.
+    pub fn equality_on(&self, attr: &str) -> Option<&str> {
+        match self {
+            Self::Compare(path, CompareOp::Eq, Value::String(value))
+                if path.sub_attr.is_none() && path.attr.eq_ignore_ascii_case(attr) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+fn compare(value: &Value, op: CompareOp, expected: &Value, case_exact: bool) -> bool {
+    match (value, expected) {
+        (Value::String(value), Value::String(expected)) => {
+            let (value, expected) = match case_exact {
+                true => (value.clone(), expected.clone()),
+                false => (value.to_lowercase(), expected.to_lowercase()),
+            };
+            match op {
+                CompareOp::Eq => value == expected,
+                CompareOp::Ne => value != expected,
+                CompareOp::Co => value.contains(&expected),
+                CompareOp::Sw => value.starts_with(&expected),
+                CompareOp::Ew => value.ends_with(&expected),
+                // Timestamps are ISO 8601, so they order like their text
+                CompareOp::Gt => value > expected,
+                CompareOp::Ge => value >= expected,
+                CompareOp::Lt => value < expected,
+                CompareOp::Le => value <= expected,
+            }
+        }
+        (Value::Number(value), Value::Number(expected)) => {
+            let (Some(value), Some(expected)) = (value.as_f64(), expected.as_f64()) else {
+                return false;
+            };
+            match op {
+                CompareOp::Eq => value == expected,
+                CompareOp::Ne => value != expected,
+                CompareOp::Gt => value > expected,
+                CompareOp::Ge => value >= expected,
+                CompareOp::Lt => value < expected,
+                CompareOp::Le => value <= expected,
+                CompareOp::Co | CompareOp::Sw | CompareOp::Ew => false,
+            }
+        }
+        (Value::Bool(value), Value::Bool(expected)) => match op {
+            CompareOp::Eq => value == expected,
+            CompareOp::Ne => value != expected,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' => tokens.push(Token::OpenParen),
+            ')' => tokens.push(Token::CloseParen),
+            '[' => tokens.push(Token::OpenBracket),
+            ']' => tokens.push(Token::CloseBracket),
+            '"' => {
+                let mut end = None;
+                let mut escaped = false;
+                for (index, c) in chars.by_ref() {
+                    match c {
+                        '\\' if !escaped => escaped = true,
+                        '"' if !escaped => {
+                            end = Some(index);
+                            break;
+                        }
+                        _ => escaped = false,
+                    }
+                }
+                let end = end.ok_or("Unterminated string in filter")?;
+                let value: String = serde_json::from_str(&input[start..=end])
+                    .map_err(|_| "Invalid string in filter")?;
+                tokens.push(Token::Str(value));
+            }
+            _ => {
+                let mut end = start + c.len_utf8();
+                while let Some(&(index, c)) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '(' | ')' | '[' | ']' | '"') {
+                        break;
+                    }
+                    end = index + c.len_utf8();
+                    chars.next();
+                }
+                tokens.push(Token::Word(input[start..end].to_owned()));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+// Recursive descent over the filter grammar, where `not` binds tighter than
+// `and`, and `and` tighter than `or`.
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.tokens.get(self.position), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            token => Err(format!("Expected {:?} in filter, found {:?}", expected, token)),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Filter, String> {
+        let mut filter = self.parse_and()?;
+        while self.peek_keyword("or") {
+            self.position += 1;
+            filter = Filter::Or(Box::new(filter), Box::new(self.parse_and()?));
+        }
+        Ok(filter)
+    }
+
+    fn parse_and(&mut self) -> Result<Filter, String> {
+        let mut filter = self.parse_not()?;
+        while self.peek_keyword("and") {
+            self.position += 1;
+            filter = Filter::And(Box::new(filter), Box::new(self.parse_not()?));
+        }
+        Ok(filter)
+    }
+
+    fn parse_not(&mut self) -> Result<Filter, String> {
+        if !self.peek_keyword("not") {
+            return self.parse_expression();
+        }
+
+        self.position += 1;
+        self.expect(Token::OpenParen)?;
+        let filter = self.parse_or()?;
+        self.expect(Token::CloseParen)?;
+        Ok(Filter::Not(Box::new(filter)))
+    }
+
+    fn parse_expression(&mut self) -> Result<Filter, String> {
+        let path = match self.next() {
+            Some(Token::OpenParen) => {
+                let filter = self.parse_or()?;
+                self.expect(Token::CloseParen)?;
+                return Ok(filter);
+            }
+            Some(Token::Word(word)) => AttrPath::parse(&word)?,
+            token => return Err(format!("Expected an attribute in filter, found {:?}", token)),
+        };
+
+        if self.tokens.get(self.position) == Some(&Token::OpenBracket) {
+            self.position += 1;
+            let filter = self.parse_or()?;
+            self.expect(Token::CloseBracket)?;
+            return match path.sub_attr {
+                None => Ok(Filter::ValuePath(path.attr, Box::new(filter))),
+                Some(_) => Err("Value filters can't follow a sub-attribute".to_owned()),
+            };
+        }
+
+        let op = match self.next() {
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("pr") => return Ok(Filter::Present(path)),
+            Some(Token::Word(word)) => CompareOp::parse(&word).ok_or(format!("Unknown operator {}", word))?,
+            token => return Err(format!("Expected an operator in filter, found {:?}", token)),
+        };
+
+        let value = match self.next() {
+            Some(Token::Str(value)) => Value::String(value),
+            Some(Token::Word(word)) => match word.as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                "null" => Value::Null,
+                number => serde_json::from_str::<serde_json::Number>(number)
+                    .map(Value::Number)
+                    .map_err(|_| format!("Invalid value {} in filter", number))?,
+            },
+            token => return Err(format!("Expected a value in filter, found {:?}", token)),
+        };
+
+        Ok(Filter::Compare(path, op, value))
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PatchRequest {
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    #[serde(rename = "Operations", alias = "operations")]
+    pub operations: Vec<PatchOperation>,
+}
+
+// Some identity systems capitalize `op`, so it's only parsed once applied.
+#[derive(Deserialize, Debug, Clone)]
+pub struct PatchOperation {
+    pub op: String,
+    pub path: Option<String>,
+    pub value: Option<Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PatchOpKind {
+    Add,
+    Replace,
+    Remove,
+}
+
+// The target of a PATCH operation, like `displayName`, `name.givenName`,
+// `members[value eq "..."]` or `emails[type eq "work"].value`.
+#[derive(Debug, Clone, PartialEq)]
+struct PatchPath {
+    attr: String,
+    filter: Option<Filter>,
+    sub_attr: Option<String>,
+}
+
+impl PatchPath {
+    fn parse(input: &str) -> Result<Self, ScimError> {
+        let Some((head, rest)) = input.split_once('[') else {
+            let path = AttrPath::parse(input).map_err(ScimError::InvalidPath)?;
+            return Ok(Self { attr: path.attr, filter: None, sub_attr: path.sub_attr });
+        };
+
+        let head = AttrPath::parse(head).map_err(ScimError::InvalidPath)?;
+        let (filter, sub_attr) = rest
+            .rsplit_once(']')
+            .ok_or_else(|| ScimError::InvalidPath(format!("Invalid path: {}", input)))?;
+        let sub_attr = match sub_attr {
+            "" => None,
+            sub_attr => match sub_attr.strip_prefix('.') {
+                Some(sub_attr) if is_attr_name(sub_attr) => Some(sub_attr.to_owned()),
+                _ => return Err(ScimError::InvalidPath(format!("Invalid path: {}", input))),
+            },
+        };
+        if head.sub_attr.is_some() {
+            return Err(ScimError::InvalidPath(format!("Invalid path: {}", input)));
+        }
+
+        let filter = Filter::parse(filter).map_err(|e| ScimError::InvalidPath(e.to_string()))?;
+        Ok(Self { attr: head.attr, filter: Some(filter), sub_attr })
+    }
+}
+
+impl PatchRequest {
+    pub fn validate(&self) -> Result<(), ScimError> {
+        match self.schemas.iter().any(|schema| schema == PATCH_OP_SCHEMA) {
+            true => Ok(()),
+            false => Err(ScimError::InvalidSyntax(format!("PATCH requests must use the {} schema", PATCH_OP_SCHEMA))),
+        }
+    }
+
+    // Applies every operation in order. Nothing is kept when one of them
+    // fails, as the caller only saves the resource once they all succeed.
+    pub fn apply(&self, resource: &mut Value) -> Result<(), ScimError> {
+        let object = resource
+            .as_object_mut()
+            .ok_or_else(|| ScimError::InvalidSyntax("Only objects can be patched".to_owned()))?;
+
+        for operation in &self.operations {
+            operation.apply(object)?;
+        }
+        Ok(())
+    }
+}
+
+impl PatchOperation {
+    fn apply(&self, resource: &mut Map<String, Value>) -> Result<(), ScimError> {
+        let kind = match self.op.to_ascii_lowercase().as_str() {
+            "add" => PatchOpKind::Add,
+            "replace" => PatchOpKind::Replace,
+            "remove" => PatchOpKind::Remove,
+            op => return Err(ScimError::InvalidSyntax(format!("Unknown PATCH operation {}", op))),
+        };
+
+        match (kind, &self.path) {
+            (PatchOpKind::Remove, None) => Err(ScimError::NoTarget("Remove operations need a path".to_owned())),
+            (PatchOpKind::Remove, Some(path)) => remove(resource, &PatchPath::parse(path)?, self.value.as_ref()),
+            (kind, None) => {
+                let Some(Value::Object(values)) = &self.value else {
+                    return Err(ScimError::InvalidValue("Operations without a path need an object value".to_owned()));
+                };
+                for (name, value) in values {
+                    set(resource, kind, &PatchPath::parse(name)?, value.clone())?;
+                }
+                Ok(())
+            }
+            (kind, Some(path)) => {
+                let value = self.value
+                    .clone()
+                    .ok_or_else(|| ScimError::InvalidValue(format!("Missing value for {}", path)))?;
+                set(resource, kind, &PatchPath::parse(path)?, value)
+            }
+        }
+    }
+}
+
+fn set(resource: &mut Map<String, Value>, kind: PatchOpKind, path: &PatchPath, value: Value) -> Result<(), ScimError> {
+    let key = attribute_key(resource, &path.attr);
+
+    let Some(filter) = &path.filter else {
+        let target = resource.entry(key).or_insert(Value::Null);
+        return match &path.sub_attr {
+            None => {
+                set_value(target, kind, value);
+                Ok(())
+            }
+            Some(sub_attr) => {
+                if target.is_null() {
+                    *target = Value::Object(Map::new());
+                }
+                match target {
+                    Value::Object(object) => set_attribute(object, sub_attr, value),
+                    Value::Array(items) => {
+                        for item in items.iter_mut().filter_map(Value::as_object_mut) {
+                            set_attribute(item, sub_attr, value.clone());
+                        }
+                    }
+                    _ => return Err(ScimError::InvalidPath(format!("{} has no sub-attributes", path.attr))),
+                }
+                Ok(())
+            }
+        };
+    };
+
+    let target = resource.entry(key).or_insert_with(|| Value::Array(Vec::new()));
+    let Value::Array(items) = target else {
+        return Err(ScimError::InvalidPath(format!("{} isn't multi-valued", path.attr)));
+    };
+
+    let mut matched = false;
+    for item in items.iter_mut() {
+        if !filter.matches(item) {
+            continue;
+        }
+        matched = true;
+        match &path.sub_attr {
+            Some(sub_attr) => match item {
+                Value::Object(object) => set_attribute(object, sub_attr, value.clone()),
+                _ => return Err(ScimError::InvalidPath(format!("{} has no sub-attributes", path.attr))),
+            },
+            None => set_value(item, kind, value.clone()),
+        }
+    }
+    if matched {
+        return Ok(());
+    }
+
+    // Adding to a value that doesn't exist yet, like `emails[type eq "work"].value`,
+    // creates it when the filter says what it looks like.
+    match (kind, filter) {
+        (PatchOpKind::Add, Filter::Compare(filter_path, CompareOp::Eq, expected)) if filter_path.sub_attr.is_none() => {
+            let mut item = Map::from_iter([(filter_path.attr.clone(), expected.clone())]);
+            match (&path.sub_attr, value) {
+                (Some(sub_attr), value) => set_attribute(&mut item, sub_attr, value),
+                (None, Value::Object(values)) => values.into_iter().for_each(|(name, value)| set_attribute(&mut item, &name, value)),
+                (None, _) => return Err(ScimError::InvalidValue(format!("Values of {} are objects", path.attr))),
+            }
+            items.push(Value::Object(item));
+            Ok(())
+        }
+        _ => Err(ScimError::NoTarget(format!("No value of {} matches the filter", path.attr))),
+    }
+}
+
+// Adding to a multi-valued attribute appends the new values, and both adding
+// and replacing a complex one only change the sub-attributes given.
+fn set_value(target: &mut Value, kind: PatchOpKind, value: Value) {
+    match (target, value) {
+        (Value::Array(items), value) if kind == PatchOpKind::Add => {
+            for item in into_items(value) {
+                if !items.contains(&item) {
+                    items.push(item);
+                }
+            }
+        }
+        (Value::Object(object), Value::Object(values)) => {
+            values.into_iter().for_each(|(name, value)| set_attribute(object, &name, value));
+        }
+        (target, value) => *target = value,
+    }
+}
+
+fn remove(resource: &mut Map<String, Value>, path: &PatchPath, value: Option<&Value>) -> Result<(), ScimError> {
+    let key = attribute_key(resource, &path.attr);
+    let Some(target) = resource.get_mut(&key) else {
+        return match path.filter {
+            Some(_) => Err(ScimError::NoTarget(format!("No value of {} matches the filter", path.attr))),
+            None => Ok(()),
+        };
+    };
+
+    match (&path.filter, &path.sub_attr, target) {
+        // Removing the values given, like members by their `value`
+        (None, None, Value::Array(items)) if value.is_some() => {
+            let removed = value.cloned().map(into_items).unwrap_or_default();
+            items.retain(|item| !removed.iter().any(|removed| is_same_value(item, removed)));
+        }
+        (None, None, _) => {
+            resource.remove(&key);
+            return Ok(());
+        }
+        (None, Some(sub_attr), Value::Object(object)) => remove_attribute(object, sub_attr),
+        (None, Some(sub_attr), Value::Array(items)) => {
+            items.iter_mut().filter_map(Value::as_object_mut).for_each(|item| remove_attribute(item, sub_attr));
+        }
+        (None, Some(_), _) => return Err(ScimError::InvalidPath(format!("{} has no sub-attributes", path.attr))),
+        (Some(filter), sub_attr, Value::Array(items)) => {
+            if !items.iter().any(|item| filter.matches(item)) {
+                return Err(ScimError::NoTarget(format!("No value of {} matches the filter", path.attr)));
+            }
+            match sub_attr {
+                None => items.retain(|item| !filter.matches(item)),
+                Some(sub_attr) => items
+                    .iter_mut()
+                    .filter(|item| filter.matches(item))
+                    .filter_map(Value::as_object_mut)
+                    .for_each(|item| remove_attribute(item, sub_attr)),
+            }
+        }
+        (Some(_), _, _) => return Err(ScimError::InvalidPath(format!("{} isn't multi-valued", path.attr))),
+    }
+
+    // Attributes left without values are removed altogether
+    if resource.get(&key).is_some_and(|value| value.as_array().is_some_and(Vec::is_empty)) {
+        resource.remove(&key);
+    }
+    Ok(())
+}
+
+fn into_items(value: Value) -> Vec<Value> {
+    match value {
+        Value::Array(items) => items,
+        value => vec![value],
+    }
+}
+
+fn is_same_value(item: &Value, removed: &Value) -> bool {
+    let value_of = |value: &Value| value.as_object().and_then(|object| get_attribute(object, "value")).cloned();
+    item == removed || value_of(item).is_some_and(|value| Some(value) == value_of(removed))
+}
+
+// The key the resource already uses for the attribute, whatever its case.
+fn attribute_key(object: &Map<String, Value>, name: &str) -> String {
+    object
+        .keys()
+        .find(|key| key.eq_ignore_ascii_case(name))
+        .cloned()
+        .unwrap_or_else(|| name.to_owned())
+}
+
+fn set_attribute(object: &mut Map<String, Value>, name: &str, value: Value) {
+    let key = attribute_key(object, name);
+    object.insert(key, value);
+}
+
+fn remove_attribute(object: &mut Map<String, Value>, name: &str) {
+    let key = attribute_key(object, name);
+    object.remove(&key);
+}
+
+pub fn get_attribute<'a>(object: &'a Map<String, Value>, name: &str) -> Option<&'a Value> {
+    object.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value)
+}
+
+// Booleans as sent by identity systems, some of which quote them.
+pub fn as_bool(value: &Value) -> Option<bool> {
+    match value {
+        Value::Bool(value) => Some(*value),
+        Value::String(value) if value.eq_ignore_ascii_case("true") => Some(true),
+        Value::String(value) if value.eq_ignore_ascii_case("false") => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn user() -> Value {
+        json!({
+            "schemas": [USER_SCHEMA],
+            "id": "2819c223-7f76-453a-919d-413861904646",
+            "userName": "Jane@Example.com",
+            "displayName": "Jane Doe",
+            "active": true,
+            "emails": [
+                { "value": "jane@example.com", "type": "work", "primary": true },
+                { "value": "jane@home.example", "type": "home" }
+            ],
+            "meta": { "resourceType": "User", "lastModified": "2026-10-19T09:00:00Z" }
+        })
+    }
+
+    fn matches(filter: &str) -> bool {
+        Filter::parse(filter).unwrap().matches(&user())
+    }
+
+    fn patch(resource: &mut Value, operations: Value) -> Result<(), ScimError> {
+        let request: PatchRequest = serde_json::from_value(json!({
+            "schemas": [PATCH_OP_SCHEMA],
+            "Operations": operations,
+        })).unwrap();
+        request.validate()?;
+        request.apply(resource)
+    }
+
+    #[test]
+    fn test_compare_operators() {
+        assert!(matches(r#"userName eq "jane@example.com""#));
+        assert!(matches(r#"userName ne "john@example.com""#));
+        assert!(matches(r#"displayName co "doe""#));
+        assert!(matches(r#"displayName sw "Jane""#));
+        assert!(matches(r#"userName ew "EXAMPLE.COM""#));
+        assert!(matches(r#"meta.lastModified gt "2026-01-01T00:00:00Z""#));
+        assert!(matches(r#"meta.lastModified le "2026-10-19T09:00:00Z""#));
+        assert!(!matches(r#"meta.lastModified lt "2026-10-19T09:00:00Z""#));
+        assert!(matches("active eq true"));
+        assert!(!matches("active eq false"));
+        assert!(!matches(r#"displayName sw "Doe""#));
+    }
+
+    #[test]
+    fn test_ids_are_case_exact() {
+        assert!(matches(r#"id eq "2819c223-7f76-453a-919d-413861904646""#));
+        assert!(!matches(r#"id eq "2819C223-7F76-453A-919D-413861904646""#));
+    }
+
+    #[test]
+    fn test_attribute_names_ignore_case_and_schema() {
+        assert!(matches(r#"USERNAME eq "jane@example.com""#));
+        assert!(matches(r#"urn:ietf:params:scim:schemas:core:2.0:User:userName eq "jane@example.com""#));
+        assert!(matches(r#"name.givenName pr or urn:ietf:params:scim:schemas:core:2.0:User:meta.resourceType eq "User""#));
+    }
+
+    #[test]
+    fn test_presence_and_null() {
+        assert!(matches("displayName pr"));
+        assert!(!matches("nickName pr"));
+        assert!(matches("nickName eq null"));
+        assert!(matches("displayName ne null"));
+    }
+
+    #[test]
+    fn test_multi_valued_attributes() {
+        assert!(matches(r#"emails co "home.example""#));
+        assert!(matches(r#"emails.type eq "home""#));
+        assert!(matches(r#"emails[type eq "work" and value co "@example.com"]"#));
+        assert!(!matches(r#"emails[type eq "home" and value co "@example.com"]"#));
+    }
+
+    #[test]
+    fn test_logical_operators_and_precedence() {
+        assert!(matches(r#"userName eq "nobody" or displayName pr and active eq true"#));
+        assert!(!matches(r#"(userName eq "nobody" or displayName pr) and active eq false"#));
+        assert!(matches(r#"not (userName eq "nobody")"#));
+        assert!(!matches(r#"not(displayName pr)"#));
+        assert!(matches(r#"userName eq "jane@example.com" AND NOT (active eq false)"#));
+    }
+
+    #[test]
+    fn test_escaped_strings() {
+        let filter = Filter::parse(r#"displayName eq "Jane \"JD\" Doe""#).unwrap();
+        assert!(filter.matches(&json!({ "displayName": "Jane \"JD\" Doe" })));
+    }
+
+    #[test]
+    fn test_invalid_filters() {
+        for filter in [
+            "",
+            "userName",
+            r#"userName xx "jane""#,
+            r#"userName eq"#,
+            r#"userName eq "jane"#,
+            r#"(userName eq "jane""#,
+            r#"userName eq "jane" and"#,
+            r#"userName eq "jane" extra"#,
+            r#"emails[type eq "work""#,
+            r#"1userName eq "jane""#,
+            "userName eq jane",
+        ] {
+            assert!(matches!(Filter::parse(filter), Err(ScimError::InvalidFilter(_))), "{}", filter);
+        }
+    }
+
+    #[test]
+    fn test_equality_on() {
+        let filter = Filter::parse(r#"UserName eq "jane@example.com""#).unwrap();
+        assert_eq!(filter.equality_on("userName"), Some("jane@example.com"));
+        assert_eq!(filter.equality_on("displayName"), None);
+        assert_eq!(Filter::parse(r#"userName co "jane""#).unwrap().equality_on("userName"), None);
+    }
+
+    #[test]
+    fn test_patch_requires_the_patch_op_schema() {
+        let request: PatchRequest = serde_json::from_value(json!({
+            "schemas": [USER_SCHEMA],
+            "Operations": [],
+        })).unwrap();
+        assert!(matches!(request.validate(), Err(ScimError::InvalidSyntax(_))));
+    }
+
+    #[test]
+    fn test_replace_with_and_without_path() {
+        let mut resource = user();
+        patch(&mut resource, json!([
+            { "op": "Replace", "path": "active", "value": "False" },
+            { "op": "replace", "value": { "displayName": "Jane Smith", "name": { "givenName": "Jane" } } },
+            { "op": "replace", "value": { "name": { "familyName": "Smith" } } },
+        ])).unwrap();
+
+        assert_eq!(resource["active"], json!("False"));
+        assert_eq!(as_bool(&resource["active"]), Some(false));
+        assert_eq!(resource["displayName"], json!("Jane Smith"));
+        assert_eq!(resource["name"], json!({ "givenName": "Jane", "familyName": "Smith" }));
+    }
+
+    #[test]
+    fn test_add_appends_to_multi_valued_attributes() {
+        let mut resource = json!({ "members": [{ "value": "a" }] });
+        patch(&mut resource, json!([
+            { "op": "add", "path": "members", "value": [{ "value": "b" }, { "value": "a" }] },
+            { "op": "add", "path": "displayName", "value": "Engineering" },
+        ])).unwrap();
+
+        assert_eq!(resource["members"], json!([{ "value": "a" }, { "value": "b" }]));
+        assert_eq!(resource["displayName"], json!("Engineering"));
+    }
+
+    #[test]
+    fn test_replace_overwrites_multi_valued_attributes() {
+        let mut resource = json!({ "members": [{ "value": "a" }, { "value": "b" }] });
+        patch(&mut resource, json!([{ "op": "replace", "path": "members", "value": [{ "value": "c" }] }])).unwrap();
+        assert_eq!(resource["members"], json!([{ "value": "c" }]));
+    }
+
+    #[test]
+    fn test_value_filters_in_paths() {
+        let mut resource = user();
+        patch(&mut resource, json!([
+            { "op": "replace", "path": r#"emails[type eq "work"].value"#, "value": "jane@work.example" },
+            { "op": "add", "path": r#"phoneNumbers[type eq "mobile"].value"#, "value": "+1 555 0100" },
+        ])).unwrap();
+
+        assert_eq!(resource["emails"][0]["value"], json!("jane@work.example"));
+        assert_eq!(resource["emails"][1]["value"], json!("jane@home.example"));
+        assert_eq!(resource["phoneNumbers"], json!([{ "type": "mobile", "value": "+1 555 0100" }]));
+
+        let result = patch(&mut resource, json!([
+            { "op": "replace", "path": r#"emails[type eq "other"].value"#, "value": "x@example.com" },
+        ]));
+        assert!(matches!(result, Err(ScimError::NoTarget(_))));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut resource = json!({
+            "displayName": "Engineering",
+            "members": [{ "value": "a" }, { "value": "b" }, { "value": "c" }],
+            "name": { "givenName": "Jane", "familyName": "Doe" },
+        });
+        patch(&mut resource, json!([
+            { "op": "remove", "path": r#"members[value eq "a"]"# },
+            // As sent by Azure AD, with the members in the value
+            { "op": "Remove", "path": "members", "value": [{ "value": "b" }] },
+            { "op": "remove", "path": "name.givenName" },
+            { "op": "remove", "path": "nickName" },
+        ])).unwrap();
+        assert_eq!(resource["members"], json!([{ "value": "c" }]));
+        assert_eq!(resource["name"], json!({ "familyName": "Doe" }));
+
+        patch(&mut resource, json!([
+            { "op": "remove", "path": "members" },
+            { "op": "remove", "path": "DISPLAYNAME" },
+        ])).unwrap();
+        assert_eq!(resource, json!({ "name": { "familyName": "Doe" } }));
+    }
+
+    #[test]
+    fn test_removing_the_last_value_removes_the_attribute() {
+        let mut resource = json!({ "members": [{ "value": "a" }] });
+        patch(&mut resource, json!([{ "op": "remove", "path": r#"members[value eq "a"]"# }])).unwrap();
+        assert_eq!(resource, json!({}));
+    }
+
+    #[test]
+    fn test_invalid_operations() {
+        let mut resource = user();
+        assert!(matches!(patch(&mut resource, json!([{ "op": "remove" }])), Err(ScimError::NoTarget(_))));
+        assert!(matches!(
+            patch(&mut resource, json!([{ "op": "move", "path": "active", "value": true }])),
+            Err(ScimError::InvalidSyntax(_))
+        ));
+        assert!(matches!(
+            patch(&mut resource, json!([{ "op": "replace", "value": "Jane" }])),
+            Err(ScimError::InvalidValue(_))
+        ));
+        assert!(matches!(
+            patch(&mut resource, json!([{ "op": "replace", "path": "emails[type eq]", "value": "x" }])),
+            Err(ScimError::InvalidPath(_))
+        ));
+        assert!(matches!(
+            patch(&mut resource, json!([{ "op": "remove", "path": r#"displayName[value eq "x"]"# }])),
+            Err(ScimError::InvalidPath(_))
+        ));
+    }
+}
@@ -0,0 +1,337 @@
+use color_eyre::eyre::{eyre, Result};
+use secrecy::{ExposeSecret, Secret};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::{
+    domain::{Email, TenantId, User, UserQuery, UserStoreError},
+    services::password_hashing::is_supported_hash,
+    utils::parsable::Parsable,
+    UserStoreType,
+};
+
+const EXPORT_PAGE_SIZE: u32 = 500;
+
+// Moves users in and out in bulk, one user per line of JSON Lines or CSV. CSV
+// files start with a header naming their columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferFormat {
+    JsonLines,
+    Csv,
+}
+
+impl Parsable for TransferFormat {
+    fn parse<S>(input: S) -> Result<Self>
+    where
+        S: AsRef<str>
+    {
+        match input.as_ref() {
+            "jsonl" => Ok(Self::JsonLines),
+            "csv" => Ok(Self::Csv),
+            other => Err(eyre!("Unknown format: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ImportRow {
+    email: String,
+    // Argon2, bcrypt or PBKDF2, as `is_supported_hash` accepts. Users
+    // without one can only sign in through a reset, a magic link or an
+    // identity provider.
+    #[serde(default)]
+    password_hash: Option<String>,
+    #[serde(default)]
+    requires_2fa: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportRow<'a> {
+    email: &'a str,
+    requires_2fa: bool,
+    status: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    password_hash: Option<&'a str>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportReport {
+    pub created: Vec<String>,
+    // Users that already existed are left as they are, so an import can be
+    // run again after fixing the rows it turned down.
+    pub skipped: usize,
+    pub errors: Vec<ImportError>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ImportError {
+    pub line: usize,
+    pub error: String,
+}
+
+// Turned-down rows are reported and the import carries on with the next one.
+#[tracing::instrument(name = "Import users", skip_all)]
+pub async fn import_users<R>(
+    user_store: &UserStoreType,
+    tenant: &TenantId,
+    format: TransferFormat,
+    reader: R,
+) -> Result<ImportReport>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut lines = reader.lines();
+    let mut report = ImportReport::default();
+    let mut columns = None;
+    let mut line_number = 0;
+
+    while let Some(line) = lines.next_line().await? {
+        line_number += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let row = match (format, &columns) {
+            (TransferFormat::JsonLines, _) => serde_json::from_str::<ImportRow>(&line).map_err(|e| e.to_string()),
+            (TransferFormat::Csv, None) => {
+                columns = Some(split_csv_line(&line)?);
+                continue;
+            }
+            (TransferFormat::Csv, Some(columns)) => parse_csv_row(columns, &line),
+        };
+
+        let result = match row {
+            Ok(row) => import_user(user_store, tenant, row).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(Some(email)) => report.created.push(email),
+            Ok(None) => report.skipped += 1,
+            Err(error) => report.errors.push(ImportError { line: line_number, error }),
+        }
+    }
+
+    Ok(report)
+}
+
+// The email of the user when it was created, `None` when it already existed.
+async fn import_user(user_store: &UserStoreType, tenant: &TenantId, row: ImportRow) -> Result<Option<String>, String> {
+    let email = Email::parse_or_error(row.email.trim(), |_| "Invalid email".to_owned())?;
+    let password_hash = row.password_hash.filter(|hash| !hash.is_empty());
+    if password_hash.as_ref().is_some_and(|hash| !is_supported_hash(hash)) {
+        return Err(UserStoreError::UnsupportedPasswordHash.to_string());
+    }
+
+    let mut user = User::new_passwordless(email.clone());
+    user.requires_2fa = row.requires_2fa;
+
+    let email = email.as_ref().expose_secret().to_owned();
+    let mut user_store = user_store.write().await;
+    let result = match password_hash {
+        Some(password_hash) => user_store.add_user_with_password_hash(tenant, user, Secret::new(password_hash)).await,
+        None => user_store.add_user(tenant, user).await,
+    };
+    match result {
+        Ok(()) => Ok(Some(email)),
+        Err(UserStoreError::UserAlreadyExists) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn parse_csv_row(columns: &[String], line: &str) -> Result<ImportRow, String> {
+    let fields = split_csv_line(line).map_err(|e| e.to_string())?;
+    let field = |name: &str| {
+        columns.iter().position(|column| column == name).and_then(|i| fields.get(i)).map(String::as_str)
+    };
+
+    Ok(ImportRow {
+        email: field("email").ok_or("Missing email")?.to_owned(),
+        password_hash: field("password_hash").map(str::to_owned),
+        requires_2fa: match field("requires_2fa").unwrap_or_default() {
+            "" | "false" => false,
+            "true" => true,
+            _ => return Err("Invalid requires_2fa".to_owned()),
+        },
+    })
+}
+
+// Fields may be quoted, with `""` standing for a quote inside them.
+fn split_csv_line(line: &str) -> Result<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(eyre!("Unterminated quoted field"));
+    }
+    fields.push(field);
+
+    Ok(fields.into_iter().map(|field| field.trim().to_owned()).collect())
+}
+
+fn csv_field(value: &str) -> String {
+    match value.contains([',', '"']) {
+        true => format!("\"{}\"", value.replace('"', "\"\"")),
+        false => value.to_owned(),
+    }
+}
+
+// Writes every user of the tenant, a page at a time, and returns how many
+// were written. Password hashes are left out unless asked for.
+#[tracing::instrument(name = "Export users", skip_all)]
+pub async fn export_users<W>(
+    user_store: &UserStoreType,
+    tenant: &TenantId,
+    format: TransferFormat,
+    include_password_hashes: bool,
+    mut writer: W,
+) -> Result<usize>
+where
+    W: AsyncWrite + Unpin,
+{
+    if format == TransferFormat::Csv {
+        let header = match include_password_hashes {
+            true => "email,requires_2fa,status,password_hash\n",
+            false => "email,requires_2fa,status\n",
+        };
+        writer.write_all(header.as_bytes()).await?;
+    }
+
+    let mut query = UserQuery { search: None, page: 1, per_page: EXPORT_PAGE_SIZE };
+    let mut exported = 0;
+    loop {
+        let page = user_store.read().await.list_users(tenant, &query).await?;
+        if page.users.is_empty() {
+            break;
+        }
+
+        for user in &page.users {
+            let row = ExportRow {
+                email: user.email.as_ref().expose_secret(),
+                requires_2fa: user.requires_2fa,
+                status: user.status.as_ref(),
+                password_hash: user.password
+                    .as_ref()
+                    .filter(|_| include_password_hashes)
+                    .map(|password| password.as_ref().expose_secret().as_str()),
+            };
+
+            let line = match format {
+                TransferFormat::JsonLines => serde_json::to_string(&row)?,
+                TransferFormat::Csv => {
+                    let mut fields = vec![csv_field(row.email), row.requires_2fa.to_string(), row.status.to_owned()];
+                    if include_password_hashes {
+                        fields.push(csv_field(row.password_hash.unwrap_or_default()));
+                    }
+                    fields.join(",")
+                }
+            };
+            writer.write_all(line.as_bytes()).await?;
+            writer.write_all(b"\n").await?;
+        }
+
+        exported += page.users.len();
+        if exported as u64 >= page.total {
+            break;
+        }
+        query.page += 1;
+    }
+
+    writer.flush().await?;
+    Ok(exported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{domain::IntoShared, services::data_stores::hashmap_user_store::HashmapUserStore};
+
+    const CSV: &str = "email,password_hash,requires_2fa
+jane@example.com,$2b$04$Lv4E0aE0m2rZ8VnEWtQYF.zI7dGSaLXIxc0mRBlCmMN0Gfb0yTz3W,true
+not-an-email,,false
+\"john@example.com\",,
+
+bob@example.com,md5:abc,false
+";
+
+    #[tokio::test]
+    async fn should_import_csv_and_report_bad_rows() {
+        let user_store: UserStoreType = HashmapUserStore::default().into_shared();
+        let tenant = TenantId::default();
+
+        let report = import_users(&user_store, &tenant, TransferFormat::Csv, CSV.as_bytes()).await.unwrap();
+        assert_eq!(report.created, vec!["jane@example.com".to_owned(), "john@example.com".to_owned()]);
+        assert_eq!(report.skipped, 0);
+        assert_eq!(report.errors, vec![
+            ImportError { line: 3, error: "Invalid email".to_owned() },
+            ImportError { line: 6, error: "Unsupported password hash".to_owned() },
+        ]);
+
+        let jane = user_store.read().await.get_user(&tenant, "jane@example.com").await.unwrap();
+        assert!(jane.requires_2fa);
+        assert!(jane.password.is_some());
+        let john = user_store.read().await.get_user(&tenant, "john@example.com").await.unwrap();
+        assert!(john.password.is_none());
+
+        // Running it again changes nothing
+        let report = import_users(&user_store, &tenant, TransferFormat::Csv, CSV.as_bytes()).await.unwrap();
+        assert!(report.created.is_empty());
+        assert_eq!(report.skipped, 2);
+        assert_eq!(report.errors.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn should_round_trip_through_json_lines() {
+        let user_store: UserStoreType = HashmapUserStore::default().into_shared();
+        let tenant = TenantId::default();
+        let input = "{\"email\": \"jane@example.com\", \"requires_2fa\": true}\n{\"email\": 42}\n";
+
+        let report = import_users(&user_store, &tenant, TransferFormat::JsonLines, input.as_bytes()).await.unwrap();
+        assert_eq!(report.created, vec!["jane@example.com".to_owned()]);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].line, 2);
+
+        let mut output = Vec::new();
+        let exported = export_users(&user_store, &tenant, TransferFormat::JsonLines, false, &mut output).await.unwrap();
+        assert_eq!(exported, 1);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\"email\":\"jane@example.com\",\"requires_2fa\":true,\"status\":\"active\"}\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_only_export_password_hashes_when_asked() {
+        let user_store: UserStoreType = HashmapUserStore::default().into_shared();
+        let tenant = TenantId::default();
+        let user = User::new(Secret::new("jane@example.com".to_owned()), Secret::new("password123".to_owned()), false).unwrap();
+        user_store.write().await.add_user(&tenant, user).await.unwrap();
+
+        let mut output = Vec::new();
+        export_users(&user_store, &tenant, TransferFormat::Csv, false, &mut output).await.unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "email,requires_2fa,status\njane@example.com,false,active\n");
+
+        let mut output = Vec::new();
+        export_users(&user_store, &tenant, TransferFormat::Csv, true, &mut output).await.unwrap();
+        assert!(String::from_utf8(output).unwrap().ends_with("jane@example.com,false,active,password123\n"));
+    }
+
+    #[test]
+    fn should_split_quoted_csv_fields() {
+        assert_eq!(split_csv_line("a,\"b,c\",\"say \"\"hi\"\"\"").unwrap(), vec!["a", "b,c", "say \"hi\""]);
+        assert!(split_csv_line("\"open").is_err());
+    }
+}
@@ -0,0 +1,148 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use color_eyre::eyre::{eyre, Result};
+use reqwest::{
+    dns::{Addrs, Name, Resolve, Resolving},
+    Url,
+};
+
+// Which addresses webhooks may be sent to. By default only public ones, so
+// admins can't have the service make requests into its own network, like to
+// the database or the cloud metadata endpoint at 169.254.169.254.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WebhookAddressPolicy {
+    allow_private_addresses: bool,
+}
+
+impl WebhookAddressPolicy {
+    // For receivers running on the same machine, as in tests
+    pub fn allowing_private_addresses() -> Self {
+        Self { allow_private_addresses: true }
+    }
+
+    // Fails unless every address the URL's host resolves to is allowed.
+    pub async fn check_url(&self, url: &Url) -> Result<()> {
+        if self.allow_private_addresses {
+            return Ok(());
+        }
+
+        let host = url.host_str().ok_or(eyre!("Webhook URL has no host"))?;
+        let port = url.port_or_known_default().unwrap_or_default();
+        self.resolve_allowed(host, port).await.map(|_| ())
+    }
+
+    async fn resolve_allowed(&self, host: &str, port: u16) -> Result<Vec<SocketAddr>> {
+        // IPv6 hosts of URLs are bracketed
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port)).await?.collect();
+
+        if addrs.is_empty() {
+            return Err(eyre!("{} doesn't resolve to any address", host));
+        }
+        if !self.allow_private_addresses && !addrs.iter().all(|addr| is_public_address(addr.ip())) {
+            return Err(eyre!("{} resolves to a private address", host));
+        }
+        Ok(addrs)
+    }
+}
+
+// Used as the DNS resolver of the webhook HTTP client, so a host can't pass
+// the check and then resolve to a private address when it's connected to.
+impl Resolve for WebhookAddressPolicy {
+    fn resolve(&self, name: Name) -> Resolving {
+        let policy = *self;
+        let host = name.as_str().to_owned();
+
+        Box::pin(async move {
+            let addrs = policy.resolve_allowed(&host, 0).await?;
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_ipv4(ip),
+            None => is_public_ipv6(ip),
+        },
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        // "This network", carrier-grade NAT, IETF protocol assignments,
+        // benchmarking and reserved
+        || a == 0
+        || (a == 100 && (64..128).contains(&b))
+        || (a == 192 && b == 0 && c == 0)
+        || (a == 198 && (18..20).contains(&b))
+        || a >= 240)
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    let segments = ip.segments();
+    // NAT64 addresses reach the IPv4 address they embed
+    if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+        let [.., a, b, c, d] = ip.octets();
+        return is_public_ipv4(Ipv4Addr::new(a, b, c, d));
+    }
+
+    !(ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        // Unique local, link-local and documentation
+        || (segments[0] & 0xfe00) == 0xfc00
+        || (segments[0] & 0xffc0) == 0xfe80
+        || (segments[0] == 0x2001 && segments[1] == 0xdb8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_only_take_public_addresses_as_public() {
+        for ip in ["93.184.215.14", "8.8.8.8", "2606:2800:21f:cb07:6820:80da:af6b:8b2c"] {
+            assert!(is_public_address(ip.parse().unwrap()), "{ip}");
+        }
+
+        for ip in [
+            "127.0.0.1", "10.0.0.1", "172.16.5.4", "192.168.1.1", "169.254.169.254", "0.0.0.0", "100.64.0.1",
+            "255.255.255.255", "::1", "::", "fc00::1", "fd12:3456::1", "fe80::1", "::ffff:127.0.0.1",
+            "::ffff:169.254.169.254", "64:ff9b::a9fe:a9fe",
+        ] {
+            assert!(!is_public_address(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[tokio::test]
+    async fn should_reject_urls_of_private_hosts() {
+        let policy = WebhookAddressPolicy::default();
+
+        for url in [
+            "http://127.0.0.1:8080/hooks",
+            "http://169.254.169.254/latest/meta-data",
+            "http://[::1]/hooks",
+            "https://10.1.2.3/hooks",
+            "http://localhost/hooks",
+        ] {
+            assert!(policy.check_url(&Url::parse(url).unwrap()).await.is_err(), "{url}");
+        }
+        assert!(policy.check_url(&Url::parse("https://93.184.215.14/hooks").unwrap()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn should_allow_private_hosts_when_configured_to() {
+        let policy = WebhookAddressPolicy::allowing_private_addresses();
+        assert!(policy.check_url(&Url::parse("http://127.0.0.1:8080/hooks").unwrap()).await.is_ok());
+    }
+}
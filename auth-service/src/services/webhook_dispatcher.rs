@@ -0,0 +1,255 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::Result;
+use hmac::{Hmac, Mac};
+use reqwest::{header::CONTENT_TYPE, Client, Url};
+use secrecy::{ExposeSecret, Secret};
+use sha2::Sha256;
+use tokio::task::JoinHandle;
+
+use crate::{
+    domain::{WebhookDelivery, WebhookEndpoint, WebhookStoreError},
+    services::webhook_address_policy::WebhookAddressPolicy,
+    WebhookStoreType,
+};
+
+pub const WEBHOOK_ID_HEADER: &str = "X-Webhook-Id";
+pub const WEBHOOK_TIMESTAMP_HEADER: &str = "X-Webhook-Timestamp";
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+// Deliveries attempted per run, so a backlog is worked through gradually
+const BATCH_SIZE: u32 = 100;
+
+// Sends due webhook deliveries to their endpoints, retrying failed ones with
+// backoff. Deliveries are at least once: an endpoint may get the same event
+// again if saving the outcome of an attempt fails.
+pub struct WebhookDispatcher {
+    webhook_store: WebhookStoreType,
+    http_client: Client,
+    address_policy: WebhookAddressPolicy,
+}
+
+impl WebhookDispatcher {
+    pub fn new(webhook_store: WebhookStoreType, http_client: Client) -> Self {
+        Self { webhook_store, http_client, address_policy: WebhookAddressPolicy::default() }
+    }
+
+    pub fn with_address_policy(mut self, address_policy: WebhookAddressPolicy) -> Self {
+        self.address_policy = address_policy;
+        self
+    }
+
+    // Runs a dispatch every `interval` until the process exits.
+    pub fn spawn(self, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.dispatch(Utc::now()).await {
+                    tracing::error!("Failed to dispatch webhooks: {:?}", e);
+                }
+            }
+        })
+    }
+
+    // Attempts every delivery due by `now` and returns how many were
+    // delivered.
+    #[tracing::instrument(name = "Dispatch webhooks", skip_all)]
+    pub async fn dispatch(&self, now: DateTime<Utc>) -> Result<usize> {
+        let due = self.webhook_store.read().await.list_due_deliveries(now, BATCH_SIZE).await?;
+        let mut delivered = 0;
+
+        for mut delivery in due {
+            // The endpoint may have been removed since the deliveries were listed.
+            let endpoint = match self.webhook_store.read().await.get_endpoint(&delivery.endpoint_id).await {
+                Ok(endpoint) => endpoint,
+                Err(WebhookStoreError::EndpointNotFound) => continue,
+                Err(e) => return Err(e.into()),
+            };
+
+            match self.send(&endpoint, &delivery, now).await {
+                Ok(()) => {
+                    delivery.record_success();
+                    delivered += 1;
+                }
+                Err(error) => {
+                    tracing::warn!("Failed to deliver webhook {}: {}", delivery.id.as_ref(), error);
+                    delivery.record_failure(&error, now);
+                }
+            }
+
+            match self.webhook_store.write().await.update_delivery(&delivery).await {
+                Ok(()) | Err(WebhookStoreError::DeliveryNotFound) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(delivered)
+    }
+
+    // Failures are described to the admins who can list deliveries, so they
+    // don't include the details of transport errors.
+    async fn send(&self, endpoint: &WebhookEndpoint, delivery: &WebhookDelivery, now: DateTime<Utc>) -> Result<(), String> {
+        // Checked again on each attempt, as where the host resolves to can change.
+        let url = Url::parse(&endpoint.url).map_err(|_| "Invalid URL".to_owned())?;
+        if let Err(e) = self.address_policy.check_url(&url).await {
+            tracing::warn!("Refused to deliver webhook {}: {}", delivery.id.as_ref(), e);
+            return Err("Address not allowed".to_owned());
+        }
+
+        let timestamp = now.timestamp();
+        let signature = sign_webhook_payload(&endpoint.secret, timestamp, &delivery.payload);
+
+        let response = self.http_client
+            .post(url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(WEBHOOK_ID_HEADER, delivery.id.as_ref())
+            .header(WEBHOOK_TIMESTAMP_HEADER, timestamp.to_string())
+            .header(WEBHOOK_SIGNATURE_HEADER, signature)
+            .body(delivery.payload.clone())
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::warn!("Failed to send webhook {}: {:?}", delivery.id.as_ref(), e);
+                describe_send_error(&e)
+            })?;
+
+        match response.status().is_success() {
+            true => Ok(()),
+            false => Err(format!("HTTP {}", response.status())),
+        }
+    }
+}
+
+fn describe_send_error(e: &reqwest::Error) -> String {
+    let description = if e.is_timeout() {
+        "Timed out"
+    } else if e.is_connect() {
+        "Connection failed"
+    } else {
+        "Request failed"
+    };
+    description.to_owned()
+}
+
+// Receivers check a payload by computing the HMAC-SHA256 of
+// "{timestamp}.{payload}" with the endpoint's secret. Signing the timestamp
+// too lets them reject replays of old payloads.
+pub fn sign_webhook_payload(secret: &Secret<String>, timestamp: i64, payload: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.expose_secret().as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(format!("{}.{}", timestamp, payload).as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        matchers::{header, header_exists, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+    use crate::{
+        domain::{IntoShared, TenantId, WebhookDeliveryStatus, WebhookEvent, WebhookEventType, WebhookStore},
+        services::data_stores::hashmap_webhook_store::HashmapWebhookStore,
+    };
+
+    async fn dispatcher_with_delivery(url: String) -> (WebhookDispatcher, WebhookStoreType, WebhookDelivery) {
+        let endpoint = WebhookEndpoint::new(TenantId::default(), url, vec![WebhookEventType::UserSignedUp]);
+        let event = WebhookEvent::new(
+            &TenantId::default(),
+            WebhookEventType::UserSignedUp,
+            serde_json::json!({ "email": "test@example.com" }),
+        );
+        let delivery = WebhookDelivery::new(&endpoint, &event).unwrap();
+
+        let mut store = HashmapWebhookStore::default();
+        store.add_endpoint(endpoint).await.unwrap();
+        store.add_delivery(delivery.clone()).await.unwrap();
+        let store: WebhookStoreType = store.into_shared();
+
+        let dispatcher = WebhookDispatcher::new(store.clone(), Client::new())
+            .with_address_policy(WebhookAddressPolicy::allowing_private_addresses());
+        (dispatcher, store, delivery)
+    }
+
+    async fn stored_delivery(store: &WebhookStoreType, delivery: &WebhookDelivery) -> WebhookDelivery {
+        store.read().await.list_deliveries(&delivery.endpoint_id, 1).await.unwrap().remove(0)
+    }
+
+    #[test]
+    fn should_sign_timestamp_and_payload() {
+        let secret = Secret::new("whsec_test".to_owned());
+        let signature = sign_webhook_payload(&secret, 1700000000, "{}");
+
+        assert!(signature.starts_with("sha256="));
+        assert_eq!(signature.len(), "sha256=".len() + 64);
+        assert_eq!(signature, sign_webhook_payload(&secret, 1700000000, "{}"));
+        assert_ne!(signature, sign_webhook_payload(&secret, 1700000001, "{}"));
+    }
+
+    #[tokio::test]
+    async fn should_deliver_signed_payload() {
+        let server = MockServer::start().await;
+        let (dispatcher, store, delivery) = dispatcher_with_delivery(format!("{}/hooks", server.uri())).await;
+
+        Mock::given(method("POST"))
+            .and(path("/hooks"))
+            .and(header(WEBHOOK_ID_HEADER, delivery.id.as_ref()))
+            .and(header_exists(WEBHOOK_SIGNATURE_HEADER))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        assert_eq!(dispatcher.dispatch(Utc::now()).await.unwrap(), 1);
+        assert_eq!(stored_delivery(&store, &delivery).await.status, WebhookDeliveryStatus::Delivered);
+
+        // Delivered ones aren't sent again.
+        assert_eq!(dispatcher.dispatch(Utc::now()).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn should_schedule_retry_when_endpoint_fails() {
+        let server = MockServer::start().await;
+        let (dispatcher, store, delivery) = dispatcher_with_delivery(server.uri()).await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let now = Utc::now();
+        assert_eq!(dispatcher.dispatch(now).await.unwrap(), 0);
+
+        let stored = stored_delivery(&store, &delivery).await;
+        assert_eq!(stored.status, WebhookDeliveryStatus::Pending);
+        assert_eq!(stored.attempts, 1);
+        assert_eq!(stored.last_error.as_deref(), Some("HTTP 500 Internal Server Error"));
+        assert!(stored.next_attempt_at > now);
+
+        // Not retried before the backoff is over.
+        assert_eq!(dispatcher.dispatch(now).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn should_refuse_to_deliver_to_private_addresses() {
+        let server = MockServer::start().await;
+        let (dispatcher, store, delivery) = dispatcher_with_delivery(server.uri()).await;
+        let dispatcher = dispatcher.with_address_policy(WebhookAddressPolicy::default());
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        assert_eq!(dispatcher.dispatch(Utc::now()).await.unwrap(), 0);
+
+        let stored = stored_delivery(&store, &delivery).await;
+        assert_eq!(stored.status, WebhookDeliveryStatus::Pending);
+        assert_eq!(stored.last_error.as_deref(), Some("Address not allowed"));
+    }
+}
@@ -0,0 +1,185 @@
+use std::{
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+};
+
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{header::USER_AGENT, request::Parts},
+};
+
+use crate::{
+    AppState,
+    domain::{AuditEvent, AuditEventKind, AuditOutcome, AuthAPIError, ClientInfo, TenantId},
+    utils::constants::TRUSTED_PROXIES,
+};
+
+// Set by the nginx reverse proxy in front of the service. Only read from
+// peers listed in TRUSTED_PROXIES, since anyone else can send it too.
+const REAL_IP_HEADER: &str = "x-real-ip";
+const MAX_USER_AGENT_LENGTH: usize = 512;
+const MAX_FIELD_LENGTH: usize = 255;
+
+impl<S> FromRequestParts<S> for ClientInfo
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let header = |name| {
+            parts.headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+        };
+
+        let peer = parts.extensions.get::<ConnectInfo<SocketAddr>>().map(|info| info.0.ip());
+        let ip = client_ip(peer, header(REAL_IP_HEADER), &TRUSTED_PROXIES).map(|ip| ip.to_string());
+        let user_agent = header(USER_AGENT.as_str()).map(|user_agent| truncate(user_agent, MAX_USER_AGENT_LENGTH));
+
+        Ok(Self { ip, user_agent })
+    }
+}
+
+// The address the request came from: the peer's own, unless the peer is a
+// trusted proxy that says who it forwarded the request for.
+fn client_ip(peer: Option<IpAddr>, real_ip: Option<&str>, trusted_proxies: &str) -> Option<IpAddr> {
+    match peer {
+        Some(peer) if is_trusted_proxy(peer, trusted_proxies) => real_ip.and_then(|ip| ip.parse().ok()).or(Some(peer)),
+        peer => peer,
+    }
+}
+
+// `trusted_proxies` is a comma separated list of addresses and CIDR networks,
+// e.g. `10.0.0.1,172.16.0.0/12`.
+fn is_trusted_proxy(peer: IpAddr, trusted_proxies: &str) -> bool {
+    trusted_proxies
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| {
+            let (network, prefix) = entry.split_once('/').unwrap_or((entry, ""));
+            let Ok(network) = network.parse::<IpAddr>() else {
+                return false;
+            };
+            match (network, peer, prefix.parse::<u32>().ok()) {
+                (IpAddr::V4(network), IpAddr::V4(peer), prefix) => {
+                    prefix_matches(u32::from(network).into(), u32::from(peer).into(), prefix.unwrap_or(32), 32)
+                }
+                (IpAddr::V6(network), IpAddr::V6(peer), prefix) => {
+                    prefix_matches(network.into(), peer.into(), prefix.unwrap_or(128), 128)
+                }
+                _ => false,
+            }
+        })
+}
+
+fn prefix_matches(network: u128, peer: u128, prefix: u32, bits: u32) -> bool {
+    if prefix > bits {
+        return false;
+    }
+    let shift = bits - prefix;
+    shift == bits || network >> shift == peer >> shift
+}
+
+// Builds the event for the outcome of a request. Failures keep the error
+// message as their detail.
+pub fn audit_event<T>(
+    tenant: &TenantId,
+    actor: &str,
+    kind: AuditEventKind,
+    client: ClientInfo,
+    result: &Result<T, AuthAPIError>,
+) -> AuditEvent {
+    let actor = truncate(actor, MAX_FIELD_LENGTH);
+    match result {
+        Ok(_) => AuditEvent::new(tenant.clone(), actor, kind, AuditOutcome::Success, client),
+        Err(e) => AuditEvent::new(tenant.clone(), actor, kind, AuditOutcome::Failure, client)
+            .with_detail(truncate(&e.to_string(), MAX_FIELD_LENGTH)),
+    }
+}
+
+// A failure to record an event is logged, but doesn't fail the request
+// being audited.
+#[tracing::instrument(name = "Record audit event", skip_all)]
+pub async fn record_audit_event(state: &AppState, event: AuditEvent) {
+    if let Err(e) = state.audit_log_store.write().await.record_event(event).await {
+        tracing::error!("Failed to record audit event: {:?}", e);
+    }
+}
+
+fn truncate(value: &str, max_chars: usize) -> String {
+    value.chars().take(max_chars).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Request;
+
+    use super::*;
+
+    async fn client_info(request: Request<()>) -> ClientInfo {
+        let (mut parts, _) = request.into_parts();
+        ClientInfo::from_request_parts(&mut parts, &()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_read_client_info_from_request() {
+        let mut request = Request::builder()
+            .header(REAL_IP_HEADER, "203.0.113.7")
+            .header(USER_AGENT, "x".repeat(600))
+            .body(())
+            .unwrap();
+        request.extensions_mut().insert(ConnectInfo(SocketAddr::from(([192, 0, 2, 1], 4000))));
+
+        let client = client_info(request).await;
+        assert_eq!(client.ip.as_deref(), Some("192.0.2.1"));
+        assert_eq!(client.user_agent.map(|user_agent| user_agent.len()), Some(MAX_USER_AGENT_LENGTH));
+    }
+
+    #[tokio::test]
+    async fn should_ignore_real_ip_header_without_peer_address() {
+        let request = Request::builder().header(REAL_IP_HEADER, "203.0.113.7").body(()).unwrap();
+
+        let client = client_info(request).await;
+        assert_eq!(client.ip, None);
+        assert_eq!(client.user_agent, None);
+    }
+
+    #[test]
+    fn should_only_trust_real_ip_from_trusted_proxies() {
+        let peer = Some("172.18.0.5".parse().unwrap());
+        let real_ip = Some("203.0.113.7");
+        let forwarded = Some("203.0.113.7".parse().unwrap());
+
+        assert_eq!(client_ip(peer, real_ip, ""), peer);
+        assert_eq!(client_ip(peer, real_ip, "10.0.0.1"), peer);
+        assert_eq!(client_ip(peer, real_ip, "172.18.0.5"), forwarded);
+        assert_eq!(client_ip(peer, real_ip, "10.0.0.1, 172.16.0.0/12"), forwarded);
+        assert_eq!(client_ip(peer, Some("not an address"), "172.16.0.0/12"), peer);
+        assert_eq!(client_ip(peer, None, "172.16.0.0/12"), peer);
+    }
+
+    #[test]
+    fn should_match_trusted_proxy_networks() {
+        let peer = |ip: &str| ip.parse().unwrap();
+
+        assert!(is_trusted_proxy(peer("10.1.2.3"), "0.0.0.0/0"));
+        assert!(is_trusted_proxy(peer("192.168.1.200"), "192.168.1.0/24"));
+        assert!(!is_trusted_proxy(peer("192.168.2.1"), "192.168.1.0/24"));
+        assert!(!is_trusted_proxy(peer("192.168.1.1"), "192.168.1.0/33"));
+        assert!(is_trusted_proxy(peer("::1"), "::1"));
+        assert!(is_trusted_proxy(peer("2001:db8::7"), "2001:db8::/32"));
+        assert!(!is_trusted_proxy(peer("2001:db8::7"), "10.0.0.0/8"));
+    }
+
+    #[test]
+    fn should_keep_error_as_detail() {
+        let result: Result<(), _> = Err(AuthAPIError::IncorrectCredentials);
+        let event = audit_event(&TenantId::default(), "jane@example.com", AuditEventKind::Login, ClientInfo::default(), &result);
+        assert_eq!(event.outcome, AuditOutcome::Failure);
+        assert_eq!(event.detail.as_deref(), Some("Incorrect credentials"));
+    }
+}
@@ -1,4 +1,13 @@
-use axum_extra::extract::cookie::{Cookie, SameSite};
+use std::marker::PhantomData;
+
+use axum::{
+    extract::FromRequestParts,
+    http::{header::AUTHORIZATION, request::Parts},
+};
+use axum_extra::extract::{
+    cookie::{Cookie, SameSite},
+    CookieJar,
+};
 use chrono::Utc;
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Validation};
 use secrecy::ExposeSecret;
@@ -7,20 +16,113 @@ use color_eyre::eyre::{eyre, Context, ContextCompat, Result};
 use secrecy::Secret;
 
 use crate::{
+    AppState,
     BannedTokenStoreType,
-    domain::Email,
+    UserStoreType,
+    domain::{AccountStatus, AuthAPIError, DeviceFingerprint, Email, Invitation, TrustedDevice, User, UserStoreError, MagicLinkBinding, MagicLinkId, OidcState, ScimError, ScimToken, TenantId, TenantStoreError, UserAuthorization, ADMIN_ROLE, DEFAULT_TENANT},
+    utils::parsable::Parsable,
 };
 
-use super::constants::{JWT_COOKIE_NAME, JWT_SECRET};
+use super::constants::{
+    IMPERSONATOR_COOKIE_NAME, JWT_COOKIE_NAME, JWT_SECRET, MAGIC_LINK_BINDING_COOKIE_NAME, OIDC_STATE_COOKIE_NAME,
+    TRUSTED_DEVICE_COOKIE_NAME,
+};
 
 #[tracing::instrument(name = "Generate authentication cookie", skip_all)]
-pub fn generate_auth_cookie(email: &Email) -> Result<Cookie<'static>> {
-    let token = generate_auth_token(email)?;
+pub fn generate_auth_cookie(
+    tenant: &TenantId,
+    email: &Email,
+    authorization: &UserAuthorization,
+) -> Result<Cookie<'static>> {
+    let token = generate_auth_token(tenant, email, authorization)?;
     Ok(create_auth_cookie(token.to_string()))
 }
 
+// Issues an auth cookie carrying the user's current roles and permissions.
+// Every way of signing in ends here, so inactive accounts are turned away
+// here, except deleted ones still in their grace period, which are restored.
+#[tracing::instrument(name = "Issue authentication cookie", skip_all)]
+pub async fn issue_auth_cookie(
+    user_store: UserStoreType,
+    tenant: &TenantId,
+    email: &Email,
+) -> Result<Cookie<'static>, AuthAPIError> {
+    let email_str = email.as_ref().expose_secret();
+
+    let user = user_store
+        .read()
+        .await
+        .get_user(tenant, email_str)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    if user.is_restorable(Utc::now()) {
+        user_store
+            .write()
+            .await
+            .set_status(tenant, email_str, AccountStatus::Active, None)
+            .await
+            .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+        tracing::info!("Restored account scheduled for deletion");
+    } else {
+        ensure_active(&user)?;
+    }
+
+    let authorization = user_store
+        .read()
+        .await
+        .get_authorization(tenant, email_str)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    generate_auth_cookie(tenant, email, &authorization).map_err(AuthAPIError::UnexpectedError)
+}
+
+pub fn ensure_active(user: &User) -> Result<(), AuthAPIError> {
+    match user.status.is_active() {
+        true => Ok(()),
+        false => Err(AuthAPIError::AccountDisabled(user.status)),
+    }
+}
+
+// Like `ensure_active`, but lets deleted accounts that can still be restored
+// through, for checks made before `issue_auth_cookie`.
+pub fn ensure_can_sign_in(user: &User) -> Result<(), AuthAPIError> {
+    match user.is_restorable(Utc::now()) {
+        true => Ok(()),
+        false => ensure_active(user),
+    }
+}
+
+// Lets an admin see the service as the user does. The token names the admin
+// in its `act` claim and expires sooner than a regular one.
+#[tracing::instrument(name = "Generate impersonation cookie", skip_all)]
+pub fn generate_impersonation_cookie(
+    tenant: &TenantId,
+    email: &Email,
+    authorization: &UserAuthorization,
+    actor: &str,
+) -> Result<Cookie<'static>> {
+    let mut claims = auth_claims(tenant, email, authorization, IMPERSONATION_TTL_SECONDS)?;
+    // The admin never proved the user's credentials
+    claims.auth_time = 0;
+    claims.act = Some(ActorClaim { sub: actor.to_owned() });
+
+    Ok(create_auth_cookie(create_token(&claims)?))
+}
+
+// Holds the admin's own token while they impersonate someone, to be restored
+// when they stop.
+pub fn create_impersonator_cookie(token: String) -> Cookie<'static> {
+    Cookie::build((IMPERSONATOR_COOKIE_NAME, token))
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .build()
+}
+
 #[tracing::instrument(name = "Create authentication token", skip_all)]
-fn create_auth_cookie(token: String) -> Cookie<'static> {
+pub fn create_auth_cookie(token: String) -> Cookie<'static> {
     let cookie = Cookie::build((JWT_COOKIE_NAME, token))
         .path("/")
         .http_only(true)
@@ -37,40 +139,406 @@ pub enum GenerateTokenError {
 }
 
 pub const TOKEN_TTL_SECONDS: i64 = 600; // 10 minutes
+pub const IMPERSONATION_TTL_SECONDS: i64 = 300; // 5 minutes
+pub const MAGIC_LINK_TTL_SECONDS: i64 = 600; // 10 minutes
+pub const PASSWORD_RESET_TTL_SECONDS: i64 = 3600; // 1 hour
+pub const LOGIN_REPORT_TTL_SECONDS: i64 = 7 * 24 * 3600; // 7 days
+// Tokens are still accepted this long after they expire, as by the default
+// `Validation`, to allow for clock differences.
+pub const TOKEN_LEEWAY_SECONDS: u64 = 60;
+// How long after signing in sensitive account changes are allowed
+pub const REAUTHENTICATION_MAX_AGE_SECONDS: i64 = 300; // 5 minutes
+
+const MAGIC_LINK_AUDIENCE: &str = "magic-link";
+const INVITATION_AUDIENCE: &str = "invitation";
+const PASSWORD_RESET_AUDIENCE: &str = "password-reset";
+const LOGIN_REPORT_AUDIENCE: &str = "login-report";
+const TRUSTED_DEVICE_AUDIENCE: &str = "trusted-device";
 
 #[tracing::instrument(name = "Generate authentication token", skip_all)]
-fn generate_auth_token(email: &Email) -> Result<String> {
-    let delta = chrono::Duration::try_seconds(TOKEN_TTL_SECONDS)
-        .wrap_err("Failed to create 10 minute time delta")?;
+fn generate_auth_token(tenant: &TenantId, email: &Email, authorization: &UserAuthorization) -> Result<String> {
+    create_token(&auth_claims(tenant, email, authorization, TOKEN_TTL_SECONDS)?)
+}
+
+fn auth_claims(tenant: &TenantId, email: &Email, authorization: &UserAuthorization, ttl_seconds: i64) -> Result<Claims> {
+    let exp = expiration_time(ttl_seconds)?;
+
+    let sub = email.as_ref().to_owned();
+
+    // Auth tokens are only issued when the user signs in, so they were
+    // authenticated just now.
+    let now: usize = Utc::now().timestamp().try_into().wrap_err("Failed to cast iat time to usize")?;
+    let claims = Claims {
+        sub: sub.expose_secret().to_owned(),
+        exp,
+        iat: now,
+        auth_time: now,
+        tenant: tenant.as_ref().to_owned(),
+        roles: authorization.roles.iter().map(|role| role.as_ref().to_owned()).collect(),
+        permissions: authorization.permissions.iter().map(|permission| permission.as_ref().to_owned()).collect(),
+        act: None,
+    };
+
+    Ok(claims)
+}
+
+fn expiration_time(ttl_seconds: i64) -> Result<usize> {
+    let delta = chrono::Duration::try_seconds(ttl_seconds)
+        .wrap_err(format!("Failed to create {} second time delta", ttl_seconds))?;
 
     let exp = Utc::now()
         .checked_add_signed(delta)
-        .ok_or(eyre!("Failed to add 10 minutes to current time"))?
+        .ok_or(eyre!("Failed to add {} seconds to current time", ttl_seconds))?
         .timestamp();
 
-    let exp: usize = exp.try_into()
-        .wrap_err(format!("Failed to cast exp time to usize. exp time: {}", exp))?;
+    exp.try_into()
+        .wrap_err(format!("Failed to cast exp time to usize. exp time: {}", exp))
+}
+
+// Magic link tokens are signed with the same secret as auth tokens, but carry
+// an audience. `validate_token` rejects any token with an audience, so one
+// can never be used in place of the other.
+#[tracing::instrument(name = "Generate magic link token", skip_all)]
+pub fn generate_magic_link_token(
+    email: &Email,
+    id: &MagicLinkId,
+    binding: Option<&MagicLinkBinding>,
+) -> Result<Secret<String>> {
+    let claims = MagicLinkClaims {
+        sub: email.as_ref().expose_secret().to_owned(),
+        aud: MAGIC_LINK_AUDIENCE.to_owned(),
+        exp: expiration_time(MAGIC_LINK_TTL_SECONDS)?,
+        jti: id.as_ref().expose_secret().to_owned(),
+        bnd: binding.map(MagicLinkBinding::fingerprint),
+    };
 
-    let sub = email.as_ref().to_owned();
+    encode(
+        &jsonwebtoken::Header::default(),
+        &claims,
+        &EncodingKey::from_secret(JWT_SECRET.expose_secret().as_bytes()),
+    )
+    .map(Secret::new)
+    .wrap_err("Failed to create magic link token")
+}
+
+#[tracing::instrument(name = "Validate magic link token", skip_all)]
+pub fn validate_magic_link_token(token: &Secret<String>) -> Result<MagicLinkClaims, jsonwebtoken::errors::Error> {
+    let mut validation = Validation::default();
+    validation.set_audience(&[MAGIC_LINK_AUDIENCE]);
+
+    decode::<MagicLinkClaims>(
+        token.expose_secret(),
+        &DecodingKey::from_secret(JWT_SECRET.expose_secret().as_bytes()),
+        &validation,
+    )
+    .map(|data| data.claims)
+}
+
+// Invitation tokens expire with the invitation they point to. Like magic
+// links they carry an audience, so they can't be used as auth tokens.
+#[tracing::instrument(name = "Generate invitation token", skip_all)]
+pub fn generate_invitation_token(invitation: &Invitation) -> Result<Secret<String>> {
+    let exp = invitation.expires_at.timestamp();
+
+    let claims = InvitationClaims {
+        sub: invitation.email.as_ref().expose_secret().to_owned(),
+        aud: INVITATION_AUDIENCE.to_owned(),
+        exp: exp.try_into().wrap_err(format!("Failed to cast exp time to usize. exp time: {}", exp))?,
+        jti: invitation.id.as_ref().to_owned(),
+        tenant: invitation.tenant.as_ref().to_owned(),
+    };
 
-    let claims = Claims { sub: sub.expose_secret().to_owned(), exp };
+    encode(
+        &jsonwebtoken::Header::default(),
+        &claims,
+        &EncodingKey::from_secret(JWT_SECRET.expose_secret().as_bytes()),
+    )
+    .map(Secret::new)
+    .wrap_err("Failed to create invitation token")
+}
+
+#[tracing::instrument(name = "Validate invitation token", skip_all)]
+pub fn validate_invitation_token(token: &Secret<String>) -> Result<InvitationClaims, jsonwebtoken::errors::Error> {
+    let mut validation = Validation::default();
+    validation.set_audience(&[INVITATION_AUDIENCE]);
 
-    create_token(&claims)
+    decode::<InvitationClaims>(
+        token.expose_secret(),
+        &DecodingKey::from_secret(JWT_SECRET.expose_secret().as_bytes()),
+        &validation,
+    )
+    .map(|data| data.claims)
 }
 
+#[tracing::instrument(name = "Generate password reset token", skip_all)]
+pub fn generate_password_reset_token(tenant: &TenantId, email: &Email) -> Result<Secret<String>> {
+    let claims = PasswordResetClaims {
+        sub: email.as_ref().expose_secret().to_owned(),
+        aud: PASSWORD_RESET_AUDIENCE.to_owned(),
+        exp: expiration_time(PASSWORD_RESET_TTL_SECONDS)?,
+        tenant: tenant.as_ref().to_owned(),
+    };
+
+    encode(
+        &jsonwebtoken::Header::default(),
+        &claims,
+        &EncodingKey::from_secret(JWT_SECRET.expose_secret().as_bytes()),
+    )
+    .map(Secret::new)
+    .wrap_err("Failed to create password reset token")
+}
+
+#[tracing::instrument(name = "Validate password reset token", skip_all)]
+pub fn validate_password_reset_token(token: &Secret<String>) -> Result<PasswordResetClaims, jsonwebtoken::errors::Error> {
+    let mut validation = Validation::default();
+    validation.set_audience(&[PASSWORD_RESET_AUDIENCE]);
+
+    decode::<PasswordResetClaims>(
+        token.expose_secret(),
+        &DecodingKey::from_secret(JWT_SECRET.expose_secret().as_bytes()),
+        &validation,
+    )
+    .map(|data| data.claims)
+}
+
+// Sent with new-device sign-in emails, for the user to report a sign-in
+// that wasn't them. Each one can only be used once, through its `jti`.
+#[tracing::instrument(name = "Generate login report token", skip_all)]
+pub fn generate_login_report_token(tenant: &TenantId, email: &str, device: &DeviceFingerprint) -> Result<Secret<String>> {
+    let claims = LoginReportClaims {
+        sub: email.to_owned(),
+        aud: LOGIN_REPORT_AUDIENCE.to_owned(),
+        exp: expiration_time(LOGIN_REPORT_TTL_SECONDS)?,
+        tenant: tenant.as_ref().to_owned(),
+        dev: device.as_ref().to_owned(),
+        jti: uuid::Uuid::new_v4().to_string(),
+    };
+
+    encode(
+        &jsonwebtoken::Header::default(),
+        &claims,
+        &EncodingKey::from_secret(JWT_SECRET.expose_secret().as_bytes()),
+    )
+    .map(Secret::new)
+    .wrap_err("Failed to create login report token")
+}
+
+// How much longer a token expiring at `exp` will be accepted
+pub fn remaining_validity_seconds(exp: usize) -> u64 {
+    let now = Utc::now().timestamp().max(0) as u64;
+    (exp as u64).saturating_sub(now) + TOKEN_LEEWAY_SECONDS
+}
+
+#[tracing::instrument(name = "Validate login report token", skip_all)]
+pub fn validate_login_report_token(token: &Secret<String>) -> Result<LoginReportClaims, jsonwebtoken::errors::Error> {
+    let mut validation = Validation::default();
+    validation.set_audience(&[LOGIN_REPORT_AUDIENCE]);
+
+    decode::<LoginReportClaims>(
+        token.expose_secret(),
+        &DecodingKey::from_secret(JWT_SECRET.expose_secret().as_bytes()),
+        &validation,
+    )
+    .map(|data| data.claims)
+}
+
+// Names a trusted device and the user it was trusted by, so it only skips
+// 2FA for that user. The device itself is checked on every use, so revoking
+// it takes effect before the cookie expires.
+#[tracing::instrument(name = "Create trusted device cookie", skip_all)]
+pub fn create_trusted_device_cookie(tenant: &TenantId, email: &Email, device: &TrustedDevice) -> Result<Cookie<'static>> {
+    let exp = device.expires_at.timestamp();
+
+    let claims = TrustedDeviceClaims {
+        sub: email.as_ref().expose_secret().to_owned(),
+        aud: TRUSTED_DEVICE_AUDIENCE.to_owned(),
+        exp: exp.try_into().wrap_err(format!("Failed to cast exp time to usize. exp time: {}", exp))?,
+        jti: device.id.as_ref().to_owned(),
+        tenant: tenant.as_ref().to_owned(),
+    };
+
+    let token = encode(
+        &jsonwebtoken::Header::default(),
+        &claims,
+        &EncodingKey::from_secret(JWT_SECRET.expose_secret().as_bytes()),
+    )
+    .wrap_err("Failed to create trusted device token")?;
+
+    let max_age = (device.expires_at - device.created_at).num_seconds();
+
+    Ok(Cookie::build((TRUSTED_DEVICE_COOKIE_NAME, token))
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .max_age(time::Duration::seconds(max_age))
+        .build())
+}
+
+#[tracing::instrument(name = "Validate trusted device cookie", skip_all)]
+pub fn validate_trusted_device_cookie(jar: &CookieJar) -> Option<TrustedDeviceClaims> {
+    let cookie = jar.get(TRUSTED_DEVICE_COOKIE_NAME)?;
+    let mut validation = Validation::default();
+    validation.set_audience(&[TRUSTED_DEVICE_AUDIENCE]);
+
+    decode::<TrustedDeviceClaims>(
+        cookie.value(),
+        &DecodingKey::from_secret(JWT_SECRET.expose_secret().as_bytes()),
+        &validation,
+    )
+    .map(|data| data.claims)
+    .ok()
+}
+
+#[tracing::instrument(name = "Create magic link binding cookie", skip_all)]
+pub fn create_magic_link_binding_cookie(binding: &MagicLinkBinding) -> Cookie<'static> {
+    Cookie::build((MAGIC_LINK_BINDING_COOKIE_NAME, binding.as_ref().expose_secret().to_owned()))
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .build()
+}
+
+#[tracing::instrument(name = "Create OIDC state cookie", skip_all)]
+pub fn create_oidc_state_cookie(state: &OidcState) -> Cookie<'static> {
+    Cookie::build((OIDC_STATE_COOKIE_NAME, state.fingerprint()))
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .build()
+}
+
+// Besides checking the signature and bans, looks the user up so tokens stop
+// working as soon as the account is suspended or removed.
 #[tracing::instrument(name = "Validate token", skip_all)]
-pub async fn validate_token(banned_token_store: BannedTokenStoreType, token: &Secret<String>) -> Result<Claims, jsonwebtoken::errors::Error> {
+pub async fn validate_token(
+    banned_token_store: BannedTokenStoreType,
+    user_store: UserStoreType,
+    token: &Secret<String>,
+) -> Result<Claims, AuthAPIError> {
     let banned_token_store = banned_token_store.read().await;
     if banned_token_store.is_token_banned(token).await {
-        return Err(jsonwebtoken::errors::Error::from(jsonwebtoken::errors::ErrorKind::InvalidToken));
+        return Err(AuthAPIError::InvalidToken);
     }
-    let token = token.expose_secret();
-    decode::<Claims>(
-        token,
+
+    let claims = decode::<Claims>(
+        token.expose_secret(),
         &DecodingKey::from_secret(JWT_SECRET.expose_secret().as_bytes()),
         &Validation::default(),
     )
     .map(|data| data.claims)
+    .map_err(|_| AuthAPIError::InvalidToken)?;
+
+    let tenant = claims.tenant_id()?;
+    if banned_token_store.is_session_banned(&tenant, &claims.sub, claims.iat).await {
+        return Err(AuthAPIError::InvalidToken);
+    }
+
+    let user = user_store
+        .read()
+        .await
+        .get_user(&tenant, &claims.sub)
+        .await
+        .map_err(|e| match e {
+            UserStoreError::UserNotFound => AuthAPIError::InvalidToken,
+            e => AuthAPIError::UnexpectedError(e.into()),
+        })?;
+    ensure_active(&user)?;
+
+    // Impersonation ends as soon as the admin's own sessions would
+    if let Some(actor) = &claims.act {
+        if banned_token_store.is_session_banned(&tenant, &actor.sub, claims.iat).await {
+            return Err(AuthAPIError::InvalidToken);
+        }
+
+        let actor = user_store
+            .read()
+            .await
+            .get_user(&tenant, &actor.sub)
+            .await
+            .map_err(|e| match e {
+                UserStoreError::UserNotFound => AuthAPIError::InvalidToken,
+                e => AuthAPIError::UnexpectedError(e.into()),
+            })?;
+        ensure_active(&actor).map_err(|_| AuthAPIError::InvalidToken)?;
+    }
+
+    Ok(claims)
+}
+
+#[tracing::instrument(name = "Validate auth cookie", skip_all)]
+pub async fn validate_auth_cookie(jar: &CookieJar, state: &AppState) -> Result<Claims, AuthAPIError> {
+    let cookie = jar.get(JWT_COOKIE_NAME).ok_or(AuthAPIError::MissingToken)?;
+    let token = Secret::new(cookie.value().to_owned());
+
+    validate_token(state.banned_token_store.clone(), state.user_store.clone(), &token).await
+}
+
+pub trait RequiredRole {
+    const ROLE: &'static str;
+}
+
+pub struct Admin;
+
+impl RequiredRole for Admin {
+    const ROLE: &'static str = ADMIN_ROLE;
+}
+
+// Extractor for routes that require the role `R`, checked against the roles
+// in the auth cookie. A whole router can be guarded with
+// `axum::middleware::from_extractor_with_state::<RequireRole<R>, _>`.
+pub struct RequireRole<R: RequiredRole> {
+    pub claims: Claims,
+    role: PhantomData<R>,
+}
+
+impl<R> FromRequestParts<AppState> for RequireRole<R>
+where
+    R: RequiredRole + Send + Sync,
+{
+    type Rejection = AuthAPIError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let jar = CookieJar::from_headers(&parts.headers);
+        let claims = validate_auth_cookie(&jar, state).await?;
+
+        if !claims.has_role(R::ROLE) {
+            return Err(AuthAPIError::Forbidden);
+        }
+
+        Ok(Self { claims, role: PhantomData })
+    }
+}
+
+// Extractor for the SCIM endpoints, called by a tenant's identity system with
+// the tenant's SCIM token as a bearer token.
+pub struct ScimClient {
+    pub tenant: TenantId,
+}
+
+impl FromRequestParts<AppState> for ScimClient {
+    type Rejection = ScimError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let token = parts.headers
+            .get(AUTHORIZATION)
+            .and_then(|header| header.to_str().ok())
+            .and_then(|header| header.split_once(' '))
+            .filter(|(scheme, token)| scheme.eq_ignore_ascii_case("bearer") && !token.trim().is_empty())
+            .map(|(_, token)| ScimToken::new(Secret::new(token.trim().to_owned())))
+            .ok_or(ScimError::InvalidToken)?;
+
+        let tenant = state.tenant_store
+            .read()
+            .await
+            .get_tenant_by_scim_token(&token)
+            .await
+            .map_err(|e| match e {
+                TenantStoreError::TenantNotFound => ScimError::InvalidToken,
+                e => ScimError::UnexpectedError(e.into()),
+            })?;
+
+        Ok(Self { tenant: tenant.id })
+    }
 }
 
 #[tracing::instrument(name = "Create token", skip_all)]
@@ -86,21 +554,149 @@ fn create_token(claims: &Claims) -> Result<String> {
 pub struct Claims {
     pub sub: String,
     pub exp: usize,
+    // Tokens issued before this claim existed count as issued at the epoch
+    #[serde(default)]
+    pub iat: usize,
+    // When the user last proved their credentials. Tokens issued before this
+    // claim existed count as authenticated at the epoch.
+    #[serde(default)]
+    pub auth_time: usize,
+    // Tokens issued before tenants existed belong to the default tenant
+    #[serde(default = "default_tenant")]
+    pub tenant: String,
+    // Roles and permissions as they were when the token was issued, so
+    // changes only apply once the user signs in again.
+    #[serde(default)]
+    pub roles: Vec<String>,
+    #[serde(default)]
+    pub permissions: Vec<String>,
+    // The admin impersonating the user, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<ActorClaim>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActorClaim {
+    pub sub: String,
+}
+
+fn default_tenant() -> String {
+    DEFAULT_TENANT.to_owned()
+}
+
+impl Claims {
+    pub fn tenant_id(&self) -> Result<TenantId, AuthAPIError> {
+        TenantId::parse_or_error(&self.tenant, |_| AuthAPIError::InvalidToken)
+    }
+
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r == role)
+    }
+
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.permissions.iter().any(|p| p == permission)
+    }
+
+    pub fn is_impersonated(&self) -> bool {
+        self.act.is_some()
+    }
+
+    // For actions only the user themself may take, even though an
+    // impersonating admin can see everything else.
+    pub fn forbid_impersonation(&self) -> Result<(), AuthAPIError> {
+        match self.is_impersonated() {
+            true => Err(AuthAPIError::ImpersonatedSession),
+            false => Ok(()),
+        }
+    }
+
+    // Sensitive account changes need a recent sign-in, so a session left open
+    // in someone else's hands can't be used for them. Impersonated sessions
+    // can never make them.
+    pub fn require_recent_auth(&self) -> Result<(), AuthAPIError> {
+        self.forbid_impersonation()?;
+
+        let age = Utc::now().timestamp() - self.auth_time as i64;
+        match age <= REAUTHENTICATION_MAX_AGE_SECONDS {
+            true => Ok(()),
+            false => Err(AuthAPIError::ReauthenticationRequired),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MagicLinkClaims {
+    pub sub: String,
+    pub aud: String,
+    pub exp: usize,
+    pub jti: String,
+    // Fingerprint of the browser binding, for links that must be opened in
+    // the browser that requested them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bnd: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvitationClaims {
+    pub sub: String,
+    pub aud: String,
+    pub exp: usize,
+    pub jti: String,
+    pub tenant: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PasswordResetClaims {
+    pub sub: String,
+    pub aud: String,
+    pub exp: usize,
+    pub tenant: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LoginReportClaims {
+    pub sub: String,
+    pub aud: String,
+    pub exp: usize,
+    pub tenant: String,
+    // Fingerprint of the device the reported sign-in was made from
+    pub dev: String,
+    pub jti: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TrustedDeviceClaims {
+    pub sub: String,
+    pub aud: String,
+    pub exp: usize,
+    pub jti: String,
+    pub tenant: String,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
-        services::data_stores::hashset_banned_token_store::HashSetBannedTokenStore,
-        domain::{IntoShared, BannedTokenStore},
-        utils::parsable::Parsable,
+        services::data_stores::{
+            hashmap_user_store::HashmapUserStore, hashset_banned_token_store::HashSetBannedTokenStore,
+        },
+        domain::{AccountStatus, IntoShared, BannedTokenStore, Permission, Role, UserStore},
     };
 
+    // A user store holding test@example.com in the default and acme tenants
+    async fn test_user_store() -> UserStoreType {
+        let mut user_store = HashmapUserStore::default();
+        for tenant in [TenantId::default(), TenantId::parse("acme").unwrap()] {
+            let user = User::new_passwordless(Email::parse("test@example.com").unwrap());
+            user_store.add_user(&tenant, user).await.unwrap();
+        }
+        user_store.into_shared()
+    }
+
     #[tokio::test]
     async fn test_generate_auth_cookie() {
         let email = Email::parse("test@example.com").unwrap();
-        let cookie = generate_auth_cookie(&email).unwrap();
+        let cookie = generate_auth_cookie(&TenantId::default(), &email, &UserAuthorization::default()).unwrap();
         assert_eq!(cookie.name(), JWT_COOKIE_NAME);
         assert_eq!(cookie.value().split('.').count(), 3);
         assert_eq!(cookie.path(), Some("/"));
@@ -122,7 +718,7 @@ mod tests {
     #[tokio::test]
     async fn test_generate_auth_token() {
         let email = Email::parse("test@example.com").unwrap();
-        let result = generate_auth_token(&email).unwrap();
+        let result = generate_auth_token(&TenantId::default(), &email, &UserAuthorization::default()).unwrap();
         assert_eq!(result.split('.').count(), 3);
     }
 
@@ -130,9 +726,9 @@ mod tests {
     async fn test_validate_token_with_valid_token() {
         let banned_token_store = HashSetBannedTokenStore::default().into_shared();
         let email = Email::parse("test@example.com").unwrap();
-        let token = generate_auth_token(&email).unwrap();
+        let token = generate_auth_token(&TenantId::default(), &email, &UserAuthorization::default()).unwrap();
         let token = Secret::new(token);
-        let result = validate_token(banned_token_store, &token).await.unwrap();
+        let result = validate_token(banned_token_store, test_user_store().await, &token).await.unwrap();
         assert_eq!(result.sub, "test@example.com");
 
         let exp = Utc::now()
@@ -147,16 +743,228 @@ mod tests {
     async fn test_validate_token_with_invalid_token() {
         let banned_token_store = HashSetBannedTokenStore::default().into_shared();
         let token = Secret::new("invalid_token".to_string());
-        let result = validate_token(banned_token_store, &token).await;
+        let result = validate_token(banned_token_store, test_user_store().await, &token).await;
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_validate_magic_link_token() {
+        let email = Email::parse("test@example.com").unwrap();
+        let id = MagicLinkId::default();
+        let binding = MagicLinkBinding::default();
+
+        let token = generate_magic_link_token(&email, &id, Some(&binding)).unwrap();
+        let claims = validate_magic_link_token(&token).unwrap();
+
+        assert_eq!(claims.sub, "test@example.com");
+        assert_eq!(&claims.jti, id.as_ref().expose_secret());
+        assert_eq!(claims.bnd, Some(binding.fingerprint()));
+    }
+
+    #[tokio::test]
+    async fn test_magic_link_token_is_not_an_auth_token() {
+        let banned_token_store = HashSetBannedTokenStore::default().into_shared();
+        let email = Email::parse("test@example.com").unwrap();
+
+        let magic_link_token = generate_magic_link_token(&email, &MagicLinkId::default(), None).unwrap();
+        assert!(validate_token(banned_token_store, test_user_store().await, &magic_link_token).await.is_err());
+
+        let auth_token = Secret::new(generate_auth_token(&TenantId::default(), &email, &UserAuthorization::default()).unwrap());
+        assert!(validate_magic_link_token(&auth_token).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_invitation_token_is_not_an_auth_token() {
+        let banned_token_store = HashSetBannedTokenStore::default().into_shared();
+        let invitation = Invitation::new(
+            TenantId::parse("acme").unwrap(),
+            Email::parse("test@example.com").unwrap(),
+            None,
+            "admin@example.com".to_owned(),
+            chrono::Duration::days(1),
+        );
+
+        let token = generate_invitation_token(&invitation).unwrap();
+        let claims = validate_invitation_token(&token).unwrap();
+        assert_eq!(claims.sub, "test@example.com");
+        assert_eq!(claims.jti, invitation.id.as_ref());
+        assert_eq!(claims.tenant, "acme");
+        assert!(validate_token(banned_token_store, test_user_store().await, &token).await.is_err());
+        assert!(validate_magic_link_token(&token).is_err());
+    }
+
     #[tokio::test]
     async fn test_validate_token_with_banned_token() {
         let banned_token_store = HashSetBannedTokenStore::default().into_shared();
         let token = Secret::new("banned_token".to_string());
         banned_token_store.write().await.store_token(&token).await;
-        let result = validate_token(banned_token_store, &token).await;
+        let result = validate_token(banned_token_store, test_user_store().await, &token).await;
         assert!(result.is_err());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_auth_token_carries_roles_and_permissions() {
+        let banned_token_store = HashSetBannedTokenStore::default().into_shared();
+        let email = Email::parse("test@example.com").unwrap();
+        let authorization = UserAuthorization {
+            roles: vec![Role::admin()],
+            permissions: vec![Permission::parse("users:read").unwrap()],
+        };
+
+        let token = Secret::new(generate_auth_token(&TenantId::default(), &email, &authorization).unwrap());
+        let claims = validate_token(banned_token_store, test_user_store().await, &token).await.unwrap();
+
+        assert!(claims.has_role("admin"));
+        assert!(claims.has_permission("users:read"));
+        assert!(!claims.has_permission("users:write"));
+    }
+
+    #[tokio::test]
+    async fn test_auth_token_carries_tenant() {
+        let banned_token_store = HashSetBannedTokenStore::default().into_shared();
+        let email = Email::parse("test@example.com").unwrap();
+        let tenant = TenantId::parse("acme").unwrap();
+
+        let token = Secret::new(generate_auth_token(&tenant, &email, &UserAuthorization::default()).unwrap());
+        let claims = validate_token(banned_token_store, test_user_store().await, &token).await.unwrap();
+
+        assert_eq!(claims.tenant_id().unwrap(), tenant);
+    }
+
+    #[tokio::test]
+    async fn test_validate_token_with_banned_session() {
+        let banned_token_store = HashSetBannedTokenStore::default().into_shared();
+        let email = Email::parse("test@example.com").unwrap();
+        let token = Secret::new(generate_auth_token(&TenantId::default(), &email, &UserAuthorization::default()).unwrap());
+
+        banned_token_store.write().await.ban_sessions(&TenantId::default(), "test@example.com").await;
+        assert!(validate_token(banned_token_store, test_user_store().await, &token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_auth_token_requires_recent_auth() {
+        let email = Email::parse("test@example.com").unwrap();
+        let token = Secret::new(generate_auth_token(&TenantId::default(), &email, &UserAuthorization::default()).unwrap());
+        let mut claims = validate_token(HashSetBannedTokenStore::default().into_shared(), test_user_store().await, &token)
+            .await
+            .unwrap();
+        assert_eq!(claims.auth_time, claims.iat);
+        assert!(claims.require_recent_auth().is_ok());
+
+        claims.auth_time -= REAUTHENTICATION_MAX_AGE_SECONDS as usize + 1;
+        assert!(matches!(claims.require_recent_auth(), Err(AuthAPIError::ReauthenticationRequired)));
+    }
+
+    #[tokio::test]
+    async fn test_validate_token_rejects_inactive_account() {
+        let email = Email::parse("test@example.com").unwrap();
+        let token = Secret::new(generate_auth_token(&TenantId::default(), &email, &UserAuthorization::default()).unwrap());
+
+        let user_store = test_user_store().await;
+        user_store
+            .write()
+            .await
+            .set_status(&TenantId::default(), "test@example.com", AccountStatus::Suspended, None)
+            .await
+            .unwrap();
+        let result = validate_token(HashSetBannedTokenStore::default().into_shared(), user_store.clone(), &token).await;
+        assert!(matches!(result, Err(AuthAPIError::AccountDisabled(AccountStatus::Suspended))));
+
+        user_store.write().await.delete_user(&TenantId::default(), "test@example.com").await.unwrap();
+        let result = validate_token(HashSetBannedTokenStore::default().into_shared(), user_store, &token).await;
+        assert!(matches!(result, Err(AuthAPIError::InvalidToken)));
+    }
+
+    #[tokio::test]
+    async fn test_password_reset_token_is_not_an_auth_token() {
+        let banned_token_store = HashSetBannedTokenStore::default().into_shared();
+        let email = Email::parse("test@example.com").unwrap();
+        let tenant = TenantId::parse("acme").unwrap();
+
+        let token = generate_password_reset_token(&tenant, &email).unwrap();
+        let claims = validate_password_reset_token(&token).unwrap();
+        assert_eq!(claims.sub, "test@example.com");
+        assert_eq!(claims.tenant, "acme");
+        assert!(validate_token(banned_token_store, test_user_store().await, &token).await.is_err());
+    }
+
+    #[test]
+    fn test_remaining_validity_includes_leeway() {
+        let now = Utc::now().timestamp() as usize;
+        assert!((3600 + TOKEN_LEEWAY_SECONDS - 1..=3600 + TOKEN_LEEWAY_SECONDS).contains(&remaining_validity_seconds(now + 3600)));
+        assert_eq!(remaining_validity_seconds(now - 3600), TOKEN_LEEWAY_SECONDS);
+    }
+
+    #[tokio::test]
+    async fn test_login_report_token_is_not_an_auth_token() {
+        let banned_token_store = HashSetBannedTokenStore::default().into_shared();
+        let tenant = TenantId::parse("acme").unwrap();
+        let device = DeviceFingerprint::from_client(&crate::domain::ClientInfo::default());
+
+        let token = generate_login_report_token(&tenant, "test@example.com", &device).unwrap();
+        let claims = validate_login_report_token(&token).unwrap();
+        assert_eq!(claims.sub, "test@example.com");
+        assert_eq!(claims.dev, device.as_ref());
+        let other = generate_login_report_token(&tenant, "test@example.com", &device).unwrap();
+        assert_ne!(validate_login_report_token(&other).unwrap().jti, claims.jti);
+        assert!(validate_password_reset_token(&token).is_err());
+        assert!(validate_token(banned_token_store, test_user_store().await, &token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_trusted_device_cookie_is_not_an_auth_token() {
+        let banned_token_store = HashSetBannedTokenStore::default().into_shared();
+        let tenant = TenantId::parse("acme").unwrap();
+        let email = Email::parse("test@example.com").unwrap();
+        let device = TrustedDevice::new(&crate::domain::ClientInfo::default());
+
+        let cookie = create_trusted_device_cookie(&tenant, &email, &device).unwrap();
+        assert_eq!(cookie.name(), TRUSTED_DEVICE_COOKIE_NAME);
+        assert_eq!(cookie.http_only(), Some(true));
+        assert_eq!(cookie.max_age(), Some(time::Duration::days(30)));
+
+        let claims = validate_trusted_device_cookie(&CookieJar::new().add(cookie.clone())).unwrap();
+        assert_eq!(claims.sub, "test@example.com");
+        assert_eq!(claims.tenant, "acme");
+        assert_eq!(claims.jti, device.id.as_ref());
+
+        let token = Secret::new(cookie.value().to_owned());
+        assert!(validate_token(banned_token_store, test_user_store().await, &token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_impersonation_token_names_the_admin() {
+        let banned_token_store = HashSetBannedTokenStore::default().into_shared();
+        let user_store = test_user_store().await;
+        let admin = User::new_passwordless(Email::parse("admin@example.com").unwrap());
+        user_store.write().await.add_user(&TenantId::default(), admin).await.unwrap();
+
+        let email = Email::parse("test@example.com").unwrap();
+        let cookie = generate_impersonation_cookie(
+            &TenantId::default(),
+            &email,
+            &UserAuthorization::default(),
+            "admin@example.com",
+        ).unwrap();
+        let token = Secret::new(cookie.value().to_owned());
+
+        let claims = validate_token(banned_token_store.clone(), user_store.clone(), &token).await.unwrap();
+        assert_eq!(claims.sub, "test@example.com");
+        assert_eq!(claims.act, Some(ActorClaim { sub: "admin@example.com".to_owned() }));
+        assert!(claims.exp <= Utc::now().timestamp() as usize + IMPERSONATION_TTL_SECONDS as usize);
+        assert!(matches!(claims.require_recent_auth(), Err(AuthAPIError::ImpersonatedSession)));
+
+        // Revoking the admin's sessions ends the impersonation too
+        banned_token_store.write().await.ban_sessions(&TenantId::default(), "admin@example.com").await;
+        assert!(validate_token(banned_token_store, user_store, &token).await.is_err());
+    }
+
+    #[test]
+    fn test_claims_without_roles_deserialize() {
+        let claims: Claims = serde_json::from_str(r#"{"sub":"test@example.com","exp":0}"#).unwrap();
+        assert_eq!(claims.tenant_id().unwrap(), TenantId::default());
+        assert!(claims.roles.is_empty());
+        assert!(claims.permissions.is_empty());
+        assert!(!claims.is_impersonated());
+    }
+}
@@ -9,6 +9,45 @@ lazy_static! {
     pub static ref DATABASE_NAME: String = init_env_var(env::DATABASE_NAME_ENV_VAR);
     pub static ref REDIS_HOST_NAME: String = init_env_var_or_default(env::REDIS_HOST_NAME_ENV_VAR, DEFAULT_REDIS_HOSTNAME);
     pub static ref MAIL_AUTH_TOKEN: Secret<String> = Secret::new(init_env_var(env::MAIL_AUTH_TOKEN_ENV_VAR));
+    pub static ref OIDC_PROVIDERS: String = init_env_var_or_default(env::OIDC_PROVIDERS_ENV_VAR, "");
+    pub static ref OIDC_POST_LOGIN_REDIRECT: String = init_env_var_or_default(env::OIDC_POST_LOGIN_REDIRECT_ENV_VAR, "/");
+    pub static ref AUTH_SERVICE_URL: String = init_env_var_or_default(env::AUTH_SERVICE_URL_ENV_VAR, DEFAULT_AUTH_SERVICE_URL);
+    pub static ref MAGIC_LINK_POST_LOGIN_REDIRECT: String = init_env_var_or_default(env::MAGIC_LINK_POST_LOGIN_REDIRECT_ENV_VAR, "/");
+    pub static ref INVITATION_URL: String = init_env_var_or_default(env::INVITATION_URL_ENV_VAR, DEFAULT_INVITATION_URL);
+    pub static ref PASSWORD_RESET_URL: String = init_env_var_or_default(env::PASSWORD_RESET_URL_ENV_VAR, DEFAULT_PASSWORD_RESET_URL);
+    pub static ref SAML_IDPS: String = init_env_var_or_default(env::SAML_IDPS_ENV_VAR, "");
+    pub static ref SAML_POST_LOGIN_REDIRECT: String = init_env_var_or_default(env::SAML_POST_LOGIN_REDIRECT_ENV_VAR, "/");
+    pub static ref GEOIP_DATABASE_PATH: String = init_env_var_or_default(env::GEOIP_DATABASE_PATH_ENV_VAR, "");
+    pub static ref TRUSTED_PROXIES: String = init_env_var_or_default(env::TRUSTED_PROXIES_ENV_VAR, "");
+    pub static ref ACCOUNT_DELETION_GRACE_DAYS: i64 = init_env_var_or_default(
+        env::ACCOUNT_DELETION_GRACE_DAYS_ENV_VAR,
+        DEFAULT_ACCOUNT_DELETION_GRACE_DAYS,
+    )
+    .parse()
+    .unwrap_or_else(|_| panic!("{} must be a number of days", env::ACCOUNT_DELETION_GRACE_DAYS_ENV_VAR));
+    pub static ref PASSWORD_MIN_LENGTH: usize = init_env_var_or_default(
+        env::PASSWORD_MIN_LENGTH_ENV_VAR,
+        DEFAULT_PASSWORD_MIN_LENGTH,
+    )
+    .parse()
+    .unwrap_or_else(|_| panic!("{} must be a number of characters", env::PASSWORD_MIN_LENGTH_ENV_VAR));
+    pub static ref PASSWORD_MIN_STRENGTH: u8 = init_env_var_or_default(
+        env::PASSWORD_MIN_STRENGTH_ENV_VAR,
+        DEFAULT_PASSWORD_MIN_STRENGTH,
+    )
+    .parse()
+    .unwrap_or_else(|_| panic!("{} must be a score from 0 to 4", env::PASSWORD_MIN_STRENGTH_ENV_VAR));
+    pub static ref PASSWORD_DENYLIST_PATH: String = init_env_var_or_default(env::PASSWORD_DENYLIST_PATH_ENV_VAR, "");
+    pub static ref BREACHED_PASSWORD_RANGES_PATH: String = init_env_var_or_default(env::BREACHED_PASSWORD_RANGES_PATH_ENV_VAR, "");
+    pub static ref ARGON2_MEMORY_KIB: u32 = init_env_var_or_default(env::ARGON2_MEMORY_KIB_ENV_VAR, DEFAULT_ARGON2_MEMORY_KIB)
+        .parse()
+        .unwrap_or_else(|_| panic!("{} must be a number of KiB", env::ARGON2_MEMORY_KIB_ENV_VAR));
+    pub static ref ARGON2_ITERATIONS: u32 = init_env_var_or_default(env::ARGON2_ITERATIONS_ENV_VAR, DEFAULT_ARGON2_ITERATIONS)
+        .parse()
+        .unwrap_or_else(|_| panic!("{} must be a number of passes", env::ARGON2_ITERATIONS_ENV_VAR));
+    pub static ref ARGON2_PARALLELISM: u32 = init_env_var_or_default(env::ARGON2_PARALLELISM_ENV_VAR, DEFAULT_ARGON2_PARALLELISM)
+        .parse()
+        .unwrap_or_else(|_| panic!("{} must be a number of lanes", env::ARGON2_PARALLELISM_ENV_VAR));
 }
 
 fn init_env_var(var_name: &str) -> String {
@@ -27,6 +66,32 @@ fn init_env_var_or_default(var_name: &str, default_value: &str) -> String {
     std_env::var(var_name).unwrap_or_else(|_| default_value.to_string())
 }
 
+// Each provider listed in OIDC_PROVIDERS is configured through
+// OIDC_<PROVIDER>_<SETTING> variables, e.g. OIDC_GOOGLE_CLIENT_ID.
+pub fn oidc_provider_env_var(provider: &str, setting: &str) -> String {
+    init_env_var(&format!("OIDC_{}_{}", provider.to_uppercase(), setting))
+}
+
+pub fn oidc_provider_env_var_or_default(provider: &str, setting: &str, default_value: &str) -> String {
+    init_env_var_or_default(&format!("OIDC_{}_{}", provider.to_uppercase(), setting), default_value)
+}
+
+// Likewise each IdP listed in SAML_IDPS is configured through
+// SAML_<IDP>_<SETTING> variables, e.g. SAML_OKTA_ENTITY_ID.
+pub fn saml_idp_env_var(idp: &str, setting: &str) -> String {
+    init_env_var(&format!("SAML_{}_{}", idp.to_uppercase(), setting))
+}
+
+pub fn saml_idp_env_var_or_none(idp: &str, setting: &str) -> Option<String> {
+    dotenv().ok();
+    std_env::var(format!("SAML_{}_{}", idp.to_uppercase(), setting))
+        .ok()
+        .filter(|value| !value.is_empty())
+}
+
+pub fn saml_sp_env_var(setting: &str) -> String {
+    init_env_var(&format!("SAML_SP_{}", setting))
+}
 
 pub mod env {
     pub const JWT_SECRET_ENV_VAR: &str = "JWT_SECRET";
@@ -34,10 +99,49 @@ pub mod env {
     pub const DATABASE_NAME_ENV_VAR: &str = "DATABASE_NAME";
     pub const REDIS_HOST_NAME_ENV_VAR: &str = "REDIS_HOST_NAME";
     pub const MAIL_AUTH_TOKEN_ENV_VAR: &str = "MAIL_AUTH_TOKEN";
+    pub const OIDC_PROVIDERS_ENV_VAR: &str = "OIDC_PROVIDERS";
+    pub const OIDC_POST_LOGIN_REDIRECT_ENV_VAR: &str = "OIDC_POST_LOGIN_REDIRECT";
+    pub const AUTH_SERVICE_URL_ENV_VAR: &str = "AUTH_SERVICE_URL";
+    pub const MAGIC_LINK_POST_LOGIN_REDIRECT_ENV_VAR: &str = "MAGIC_LINK_POST_LOGIN_REDIRECT";
+    pub const INVITATION_URL_ENV_VAR: &str = "INVITATION_URL";
+    pub const PASSWORD_RESET_URL_ENV_VAR: &str = "PASSWORD_RESET_URL";
+    pub const SAML_IDPS_ENV_VAR: &str = "SAML_IDPS";
+    pub const SAML_POST_LOGIN_REDIRECT_ENV_VAR: &str = "SAML_POST_LOGIN_REDIRECT";
+    pub const ACCOUNT_DELETION_GRACE_DAYS_ENV_VAR: &str = "ACCOUNT_DELETION_GRACE_DAYS";
+    pub const GEOIP_DATABASE_PATH_ENV_VAR: &str = "GEOIP_DATABASE_PATH";
+    pub const TRUSTED_PROXIES_ENV_VAR: &str = "TRUSTED_PROXIES";
+    pub const PASSWORD_MIN_LENGTH_ENV_VAR: &str = "PASSWORD_MIN_LENGTH";
+    pub const PASSWORD_MIN_STRENGTH_ENV_VAR: &str = "PASSWORD_MIN_STRENGTH";
+    pub const PASSWORD_DENYLIST_PATH_ENV_VAR: &str = "PASSWORD_DENYLIST_PATH";
+    pub const BREACHED_PASSWORD_RANGES_PATH_ENV_VAR: &str = "BREACHED_PASSWORD_RANGES_PATH";
+    pub const ARGON2_MEMORY_KIB_ENV_VAR: &str = "ARGON2_MEMORY_KIB";
+    pub const ARGON2_ITERATIONS_ENV_VAR: &str = "ARGON2_ITERATIONS";
+    pub const ARGON2_PARALLELISM_ENV_VAR: &str = "ARGON2_PARALLELISM";
 }
 
 pub const JWT_COOKIE_NAME: &str = "jwt";
+pub const MAGIC_LINK_BINDING_COOKIE_NAME: &str = "magic_link_binding";
+pub const OIDC_STATE_COOKIE_NAME: &str = "oidc_state";
+pub const TRUSTED_DEVICE_COOKIE_NAME: &str = "trusted_device";
+pub const IMPERSONATOR_COOKIE_NAME: &str = "impersonator";
 pub const DEFAULT_REDIS_HOSTNAME: &str = "127.0.0.1";
+// Public URL of this service, used to build links sent by email
+pub const DEFAULT_AUTH_SERVICE_URL: &str = "http://localhost/auth";
+// Page that lets an invitee accept or decline, reached with `?token=`
+pub const DEFAULT_INVITATION_URL: &str = "http://localhost/app/invitation";
+// Page where a user whose password was reset by an admin sets a new one
+pub const DEFAULT_PASSWORD_RESET_URL: &str = "http://localhost/app/password-reset";
+pub const DEFAULT_OIDC_SCOPES: &str = "openid email profile";
+// How long a deleted account can still be restored by signing in
+pub const DEFAULT_ACCOUNT_DELETION_GRACE_DAYS: &str = "30";
+pub const DEFAULT_PASSWORD_MIN_LENGTH: &str = "8";
+// zxcvbn-style score from 0 (guessable) to 4 (very hard to guess)
+pub const DEFAULT_PASSWORD_MIN_STRENGTH: &str = "3";
+// Argon2id cost of new password hashes. Raising it rehashes each password the
+// next time its user signs in.
+pub const DEFAULT_ARGON2_MEMORY_KIB: &str = "15000";
+pub const DEFAULT_ARGON2_ITERATIONS: &str = "2";
+pub const DEFAULT_ARGON2_PARALLELISM: &str = "1";
 
 
 pub mod prod {
@@ -51,6 +155,26 @@ pub mod prod {
         pub const SENDER: &str = "postmaster@sandbox57e9d90a8cc34de6b96f21e84108edbd.mailgun.org";
         pub const TIMEOUT: Duration = std::time::Duration::from_secs(10);
     }
+
+    pub mod oidc {
+        use std::time::Duration;
+
+        pub const TIMEOUT: Duration = std::time::Duration::from_secs(10);
+    }
+
+    pub mod account_purger {
+        use std::time::Duration;
+
+        pub const INTERVAL: Duration = std::time::Duration::from_secs(60 * 60);
+    }
+
+    pub mod webhooks {
+        use std::time::Duration;
+
+        // How often due deliveries are sent, which bounds how late one can be
+        pub const INTERVAL: Duration = std::time::Duration::from_secs(10);
+        pub const TIMEOUT: Duration = std::time::Duration::from_secs(10);
+    }
 }
 
 pub mod test {
@@ -62,4 +186,10 @@ pub mod test {
         pub const SENDER: &str = "dobleuber@gmail.com";
         pub const TIMEOUT: Duration = std::time::Duration::from_millis(200);
     }
+
+    pub mod oidc {
+        use std::time::Duration;
+
+        pub const TIMEOUT: Duration = std::time::Duration::from_secs(2);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,207 @@
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::Result;
+use secrecy::ExposeSecret;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState,
+    domain::{AuditEventKind, AuditOutcome, DataExport, PolicyKind, Email, LinkedIdentity, TenantId, DATA_EXPORT_TTL_DAYS},
+    utils::constants::AUTH_SERVICE_URL,
+};
+
+// Everything held about a user, as handed to them on a data-subject access
+// request.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PersonalDataArchive {
+    #[serde(rename = "generatedAt")]
+    pub generated_at: DateTime<Utc>,
+    pub account: ArchivedAccount,
+    pub roles: Vec<String>,
+    pub identities: Vec<LinkedIdentity>,
+    // Devices the user signed in from
+    pub devices: Vec<ArchivedDevice>,
+    #[serde(rename = "trustedDevices")]
+    pub trusted_devices: Vec<ArchivedTrustedDevice>,
+    #[serde(rename = "auditEvents")]
+    pub audit_events: Vec<ArchivedAuditEvent>,
+    // Policy versions the user accepted
+    pub consents: Vec<ArchivedConsent>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ArchivedAccount {
+    pub id: String,
+    pub email: String,
+    pub username: Option<String>,
+    pub status: String,
+    #[serde(rename = "requires2FA")]
+    pub requires_2fa: bool,
+    #[serde(rename = "hasPassword")]
+    pub has_password: bool,
+    #[serde(rename = "suspensionReason")]
+    pub suspension_reason: Option<String>,
+    #[serde(rename = "purgeAt")]
+    pub purge_at: Option<DateTime<Utc>>,
+    #[serde(rename = "displayName")]
+    pub display_name: Option<String>,
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+    #[serde(rename = "avatarUrl")]
+    pub avatar_url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ArchivedDevice {
+    #[serde(rename = "userAgent")]
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    #[serde(rename = "lastSeenAt")]
+    pub last_seen_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ArchivedTrustedDevice {
+    #[serde(rename = "userAgent")]
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ArchivedAuditEvent {
+    pub event: AuditEventKind,
+    pub outcome: AuditOutcome,
+    pub detail: Option<String>,
+    pub ip: Option<String>,
+    #[serde(rename = "userAgent")]
+    pub user_agent: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ArchivedConsent {
+    pub kind: PolicyKind,
+    pub version: String,
+    #[serde(rename = "acceptedAt")]
+    pub accepted_at: DateTime<Utc>,
+}
+
+// Builds the archive in the background, then emails the user that it's ready
+// to download. A failed export is recorded, so asking again starts over.
+pub fn spawn_data_export(state: AppState, tenant: TenantId, email: Email, mut export: DataExport) {
+    tokio::spawn(async move {
+        let address = email.as_ref().expose_secret().to_owned();
+        let archive = assemble_archive(&state, &tenant, &address)
+            .await
+            .and_then(|archive| Ok(serde_json::to_string(&archive)?));
+
+        match archive {
+            Ok(archive) => export.complete(archive),
+            Err(e) => {
+                tracing::error!("Failed to assemble personal data export: {:?}", e);
+                export.fail();
+            }
+        }
+        let is_ready = export.is_downloadable(Utc::now());
+
+        if let Err(e) = state.data_export_store.write().await.update_export(&tenant, &address, export).await {
+            tracing::error!("Failed to store personal data export: {:?}", e);
+            return;
+        }
+        if is_ready {
+            if let Err(e) = send_export_ready_email(&state, &email).await {
+                tracing::warn!("Failed to send data export email: {:?}", e);
+            }
+        }
+    });
+}
+
+#[tracing::instrument(name = "Assemble personal data archive", skip_all)]
+async fn assemble_archive(state: &AppState, tenant: &TenantId, email: &str) -> Result<PersonalDataArchive> {
+    let user_store = state.user_store.read().await;
+    let user = user_store.get_user(tenant, email).await?;
+    let authorization = user_store.get_authorization(tenant, email).await?;
+    let identities = user_store.get_linked_identities(tenant, email).await?;
+    drop(user_store);
+
+    let profile = state.profile_store.read().await.get_profile(tenant, email).await?;
+    let devices = state.known_device_store.read().await.list_devices(tenant, email).await?;
+    let trusted_devices = state.trusted_device_store.read().await.list_devices(tenant, email).await?;
+    let audit_events = state.audit_log_store.read().await.list_events(tenant, email, u32::MAX).await?;
+    let consents = state.consent_store.read().await.list_consents(tenant, email).await?;
+
+    Ok(PersonalDataArchive {
+        generated_at: Utc::now(),
+        account: ArchivedAccount {
+            id: user.id.as_ref().to_owned(),
+            email: email.to_owned(),
+            username: user.username.map(|username| username.as_ref().to_owned()),
+            status: user.status.as_ref().to_owned(),
+            requires_2fa: user.requires_2fa,
+            has_password: user.password.is_some(),
+            suspension_reason: user.suspension_reason,
+            purge_at: user.purge_at,
+            display_name: profile.display_name.map(|name| name.as_ref().to_owned()),
+            locale: profile.locale.map(|locale| locale.as_ref().to_owned()),
+            timezone: profile.timezone.map(|timezone| timezone.as_ref().to_owned()),
+            avatar_url: profile.avatar_url.map(|url| url.as_ref().to_owned()),
+        },
+        roles: authorization.roles.iter().map(|role| role.as_ref().to_owned()).collect(),
+        identities,
+        devices: devices
+            .into_iter()
+            .map(|device| ArchivedDevice {
+                user_agent: device.user_agent,
+                ip: device.ip,
+                last_seen_at: device.last_seen_at,
+            })
+            .collect(),
+        trusted_devices: trusted_devices
+            .into_iter()
+            .map(|device| ArchivedTrustedDevice {
+                user_agent: device.user_agent,
+                ip: device.ip,
+                created_at: device.created_at,
+                expires_at: device.expires_at,
+            })
+            .collect(),
+        audit_events: audit_events
+            .into_iter()
+            .map(|event| ArchivedAuditEvent {
+                event: event.kind,
+                outcome: event.outcome,
+                detail: event.detail,
+                ip: event.client.ip,
+                user_agent: event.client.user_agent,
+                created_at: event.created_at,
+            })
+            .collect(),
+        consents: consents
+            .into_iter()
+            .map(|consent| ArchivedConsent {
+                kind: consent.kind,
+                version: consent.version.as_ref().to_owned(),
+                accepted_at: consent.accepted_at,
+            })
+            .collect(),
+    })
+}
+
+async fn send_export_ready_email(state: &AppState, email: &Email) -> Result<()> {
+    let content = format!(
+        "The copy of your personal data you asked for is ready. While signed in, download it from {}/me/export \
+         within the next {} days.",
+        AUTH_SERVICE_URL.trim_end_matches('/'),
+        DATA_EXPORT_TTL_DAYS,
+    );
+
+    state.email_client
+        .read()
+        .await
+        .send_email(email, "Your personal data export is ready", &content)
+        .await
+}
@@ -0,0 +1,126 @@
+use std::net::IpAddr;
+
+use axum_extra::extract::{cookie::Cookie, CookieJar};
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::Result;
+use reqwest::Url;
+use secrecy::ExposeSecret;
+
+use crate::{
+    AppState,
+    domain::{AuthAPIError, ClientInfo, DeviceFingerprint, DeviceSighting, Email, KnownDevice, TenantId, TrustedDevice, TrustedDeviceId},
+    utils::{
+        auth::{create_trusted_device_cookie, generate_login_report_token, validate_trusted_device_cookie},
+        constants::AUTH_SERVICE_URL,
+        parsable::Parsable,
+    },
+};
+
+const UNKNOWN: &str = "Unknown";
+
+// Remembers the device of a successful sign-in and emails the user when it's
+// one they haven't signed in from before. Their very first device is
+// remembered silently. Neither failure to record nor to send fails the
+// sign-in.
+#[tracing::instrument(name = "Check sign-in device", skip_all)]
+pub async fn check_sign_in_device(state: &AppState, tenant: &TenantId, email: &Email, client: &ClientInfo) {
+    let device = KnownDevice::new(client);
+    let fingerprint = device.fingerprint.clone();
+    let signed_in_at = device.last_seen_at;
+
+    let sighting = state.known_device_store
+        .write()
+        .await
+        .record_sign_in(tenant, email.as_ref().expose_secret(), device)
+        .await;
+
+    match sighting {
+        Ok(DeviceSighting::New) => {
+            if let Err(e) = send_new_device_email(state, tenant, email, client, &fingerprint, signed_in_at).await {
+                tracing::warn!("Failed to send new device email: {:?}", e);
+            }
+        }
+        Ok(_) => {}
+        Err(e) => tracing::error!("Failed to record sign-in device: {:?}", e),
+    }
+}
+
+async fn send_new_device_email(
+    state: &AppState,
+    tenant: &TenantId,
+    email: &Email,
+    client: &ClientInfo,
+    fingerprint: &DeviceFingerprint,
+    signed_in_at: DateTime<Utc>,
+) -> Result<()> {
+    let token = generate_login_report_token(tenant, email.as_ref().expose_secret(), fingerprint)?;
+    let link = Url::parse_with_params(
+        &format!("{}/login/report", AUTH_SERVICE_URL.trim_end_matches('/')),
+        &[("token", token.expose_secret())],
+    )?;
+
+    let location = client.ip
+        .as_deref()
+        .and_then(|ip| ip.parse::<IpAddr>().ok())
+        .and_then(|ip| state.geoip_locator.as_ref()?.locate(ip));
+
+    let content = format!(
+        "Your account was just signed in to from a device you haven't used before.\n\n\
+         Time: {}\nApproximate location: {}\nIP address: {}\nDevice: {}\n\n\
+         If this was you, there's nothing to do. If it wasn't, follow this link to sign out everywhere and \
+         choose a new password: {}",
+        signed_in_at.format("%Y-%m-%d %H:%M UTC"),
+        location.as_deref().unwrap_or(UNKNOWN),
+        client.ip.as_deref().unwrap_or(UNKNOWN),
+        client.user_agent.as_deref().unwrap_or(UNKNOWN),
+        link,
+    );
+
+    state.email_client
+        .read()
+        .await
+        .send_email(email, "New sign-in to your account", &content)
+        .await
+}
+
+// Remembers the browser the user just completed 2FA on, returning the cookie
+// that lets it skip 2FA from now on.
+#[tracing::instrument(name = "Trust device", skip_all)]
+pub async fn trust_device(
+    state: &AppState,
+    tenant: &TenantId,
+    email: &Email,
+    client: &ClientInfo,
+) -> Result<Cookie<'static>, AuthAPIError> {
+    let device = TrustedDevice::new(client);
+    let cookie = create_trusted_device_cookie(tenant, email, &device).map_err(AuthAPIError::UnexpectedError)?;
+
+    state.trusted_device_store
+        .write()
+        .await
+        .add_device(tenant, email.as_ref().expose_secret(), device)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    Ok(cookie)
+}
+
+// Whether the request carries a trusted device cookie the user set up and
+// hasn't revoked. Cookies of other users of the same browser don't count.
+#[tracing::instrument(name = "Check trusted device", skip_all)]
+pub async fn is_trusted_device(state: &AppState, tenant: &TenantId, email: &Email, jar: &CookieJar) -> bool {
+    let Some(claims) = validate_trusted_device_cookie(jar) else {
+        return false;
+    };
+    if claims.sub != *email.as_ref().expose_secret() || claims.tenant != tenant.as_ref() {
+        return false;
+    }
+    let Ok(id) = TrustedDeviceId::parse(&claims.jti) else {
+        return false;
+    };
+
+    match state.trusted_device_store.read().await.get_device(tenant, &claims.sub, &id).await {
+        Ok(device) => !device.is_expired(Utc::now()),
+        Err(_) => false,
+    }
+}
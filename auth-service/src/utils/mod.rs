@@ -1,4 +1,9 @@
 pub mod constants;
 pub mod auth;
+pub mod audit;
 pub mod parsable;
-pub mod tracing;
\ No newline at end of file
+pub mod tracing;
+pub mod webhooks;
+pub mod devices;
+pub mod passwords;
+pub mod xml_c14n;pub mod data_export;
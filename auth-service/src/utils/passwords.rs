@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use color_eyre::eyre::Result;
+
+use crate::{
+    domain::{AuthAPIError, Email, Password},
+    services::{password_hashing::HashingParams, password_policy::PasswordPolicy},
+    utils::constants::{
+        ARGON2_ITERATIONS,
+        ARGON2_MEMORY_KIB,
+        ARGON2_PARALLELISM,
+        BREACHED_PASSWORD_RANGES_PATH,
+        PASSWORD_DENYLIST_PATH,
+        PASSWORD_MIN_LENGTH,
+        PASSWORD_MIN_STRENGTH,
+    },
+};
+
+// Holds a password the user is choosing to the policy, turning it down with
+// every reason it breaks it for.
+#[tracing::instrument(name = "Enforce password policy", skip_all)]
+pub async fn enforce_password_policy(policy: &Arc<PasswordPolicy>, email: &Email, password: &Password) -> Result<(), AuthAPIError> {
+    let rejections = policy.check(password, email).await;
+
+    match rejections.is_empty() {
+        true => Ok(()),
+        false => Err(AuthAPIError::WeakPassword(rejections)),
+    }
+}
+
+pub fn configure_password_policy() -> Result<PasswordPolicy> {
+    let mut policy = PasswordPolicy::default()
+        .with_min_length(*PASSWORD_MIN_LENGTH)
+        .with_min_strength(*PASSWORD_MIN_STRENGTH);
+
+    if !PASSWORD_DENYLIST_PATH.is_empty() {
+        policy = policy.with_denylist_file(PASSWORD_DENYLIST_PATH.as_str())?;
+    }
+    if !BREACHED_PASSWORD_RANGES_PATH.is_empty() {
+        policy = policy.with_breached_password_ranges(BREACHED_PASSWORD_RANGES_PATH.as_str());
+    }
+
+    Ok(policy)
+}
+
+pub fn configure_password_hashing() -> Result<HashingParams> {
+    HashingParams::new(*ARGON2_MEMORY_KIB, *ARGON2_ITERATIONS, *ARGON2_PARALLELISM)
+}
@@ -0,0 +1,80 @@
+use crate::{
+    domain::{TenantId, WebhookDelivery, WebhookEvent, WebhookEventType},
+    WebhookStoreType,
+};
+
+// Queues a delivery of the event to every endpoint of the tenant subscribed
+// to it. The dispatcher sends them later, so a slow or failing endpoint
+// never holds up the request. Failing to queue is logged, but doesn't fail
+// the request either.
+#[tracing::instrument(name = "Publish webhook event", skip_all)]
+pub async fn publish_webhook_event(
+    webhook_store: &WebhookStoreType,
+    tenant: &TenantId,
+    event_type: WebhookEventType,
+    data: serde_json::Value,
+) {
+    let event = WebhookEvent::new(tenant, event_type, data);
+    let mut webhook_store = webhook_store.write().await;
+
+    let endpoints = match webhook_store.list_endpoints(tenant).await {
+        Ok(endpoints) => endpoints,
+        Err(e) => {
+            tracing::error!("Failed to list webhook endpoints: {:?}", e);
+            return;
+        }
+    };
+
+    for endpoint in endpoints.iter().filter(|endpoint| endpoint.subscribes_to(event_type)) {
+        let queued = match WebhookDelivery::new(endpoint, &event) {
+            Ok(delivery) => webhook_store.add_delivery(delivery).await.map_err(Into::into),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = queued {
+            tracing::error!("Failed to queue webhook delivery: {:?}", e);
+        }
+    }
+}
+
+// The data of events about a user, who is identified by their email within
+// the event's tenant.
+pub fn user_event_data(email: &str) -> serde_json::Value {
+    serde_json::json!({ "email": email })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{IntoShared, WebhookEndpoint, WebhookStore},
+        services::data_stores::hashmap_webhook_store::HashmapWebhookStore,
+        utils::parsable::Parsable,
+    };
+
+    #[tokio::test]
+    async fn should_only_queue_for_subscribed_endpoints_of_the_tenant() {
+        let tenant = TenantId::default();
+        let subscribed = WebhookEndpoint::new(tenant.clone(), "https://a.example.com".to_owned(), vec![WebhookEventType::UserDeleted]);
+        let unsubscribed = WebhookEndpoint::new(tenant.clone(), "https://b.example.com".to_owned(), vec![WebhookEventType::UserSignedUp]);
+        let other_tenant = WebhookEndpoint::new(TenantId::parse("acme").unwrap(), "https://c.example.com".to_owned(), vec![WebhookEventType::UserDeleted]);
+
+        let mut store = HashmapWebhookStore::default();
+        for endpoint in [&subscribed, &unsubscribed, &other_tenant] {
+            store.add_endpoint(endpoint.clone()).await.unwrap();
+        }
+        let store: WebhookStoreType = store.into_shared();
+
+        publish_webhook_event(&store, &tenant, WebhookEventType::UserDeleted, user_event_data("test@example.com")).await;
+
+        let store = store.read().await;
+        let deliveries = store.list_deliveries(&subscribed.id, 10).await.unwrap();
+        assert_eq!(deliveries.len(), 1);
+        let payload: serde_json::Value = serde_json::from_str(&deliveries[0].payload).unwrap();
+        assert_eq!(payload["type"], "user.deleted");
+        assert_eq!(payload["tenant"], "default");
+        assert_eq!(payload["data"]["email"], "test@example.com");
+
+        assert!(store.list_deliveries(&unsubscribed.id, 10).await.unwrap().is_empty());
+        assert!(store.list_deliveries(&other_tenant.id, 10).await.unwrap().is_empty());
+    }
+}
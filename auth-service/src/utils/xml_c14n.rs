@@ -0,0 +1,221 @@
+use std::collections::BTreeMap;
+
+use color_eyre::eyre::{eyre, Result};
+use roxmltree::{Node, NodeId};
+
+const XML_PREFIX: &str = "xml";
+
+// Exclusive XML canonicalization 1.0, without comments, of the subtree rooted
+// at `node`. This is the only canonicalization XML signatures in SAML are
+// expected to use. The `excluded` element and its descendants are left out,
+// which is how the enveloped signature transform is applied.
+pub fn canonicalize(node: Node, excluded: Option<NodeId>, inclusive_prefixes: &[String]) -> Result<String> {
+    if !node.is_element() {
+        return Err(eyre!("Only elements can be canonicalized"));
+    }
+
+    let mut output = String::new();
+    write_element(node, excluded, inclusive_prefixes, &BTreeMap::new(), &mut output);
+    Ok(output)
+}
+
+// `rendered` holds the namespace declarations already written by output
+// ancestors, keyed by prefix with the default namespace under "".
+fn write_element(
+    node: Node,
+    excluded: Option<NodeId>,
+    inclusive_prefixes: &[String],
+    rendered: &BTreeMap<String, String>,
+    output: &mut String,
+) {
+    let input = node.document().input_text();
+    let qname = element_qname(node, input);
+
+    let mut utilized = BTreeMap::new();
+    utilized.insert(prefix(qname), node.tag_name().namespace().unwrap_or(""));
+
+    let mut attributes = Vec::new();
+    for attribute in node.attributes() {
+        let attribute_qname = &input[attribute.range_qname()];
+        let attribute_prefix = prefix(attribute_qname);
+        // Unprefixed attributes are never in the default namespace
+        if !attribute_prefix.is_empty() {
+            utilized.insert(attribute_prefix, attribute.namespace().unwrap_or(""));
+        }
+        attributes.push((
+            attribute.namespace().unwrap_or(""),
+            attribute.name(),
+            attribute_qname,
+            attribute.value(),
+        ));
+    }
+
+    // Prefixes from an InclusiveNamespaces PrefixList are treated as if they
+    // were utilized whenever they are in scope.
+    for inclusive_prefix in inclusive_prefixes {
+        let inclusive_prefix = match inclusive_prefix.as_str() {
+            "#default" => "",
+            inclusive_prefix => inclusive_prefix,
+        };
+        let uri = match inclusive_prefix {
+            "" => node.default_namespace(),
+            inclusive_prefix => node.lookup_namespace_uri(Some(inclusive_prefix)),
+        };
+        if let Some(uri) = uri {
+            utilized.insert(inclusive_prefix, uri);
+        }
+    }
+
+    utilized.remove(XML_PREFIX);
+
+    let mut in_scope = rendered.clone();
+    let mut declarations = String::new();
+    for (ns_prefix, uri) in utilized {
+        let already_rendered = rendered.get(ns_prefix).map(String::as_str).unwrap_or("");
+        if already_rendered == uri {
+            continue;
+        }
+
+        match ns_prefix {
+            "" => declarations.push_str(" xmlns=\""),
+            ns_prefix => {
+                declarations.push_str(" xmlns:");
+                declarations.push_str(ns_prefix);
+                declarations.push_str("=\"");
+            }
+        }
+        declarations.push_str(&escape_attribute(uri));
+        declarations.push('"');
+        in_scope.insert(ns_prefix.to_owned(), uri.to_owned());
+    }
+
+    attributes.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+
+    output.push('<');
+    output.push_str(qname);
+    output.push_str(&declarations);
+    for (_, _, attribute_qname, value) in attributes {
+        output.push(' ');
+        output.push_str(attribute_qname);
+        output.push_str("=\"");
+        output.push_str(&escape_attribute(value));
+        output.push('"');
+    }
+    output.push('>');
+
+    for child in node.children() {
+        if child.is_element() {
+            if Some(child.id()) != excluded {
+                write_element(child, excluded, inclusive_prefixes, &in_scope, output);
+            }
+        } else if child.is_text() {
+            output.push_str(&escape_text(child.text().unwrap_or("")));
+        } else if let Some(pi) = child.pi() {
+            output.push_str("<?");
+            output.push_str(pi.target);
+            if let Some(value) = pi.value {
+                output.push(' ');
+                output.push_str(value);
+            }
+            output.push_str("?>");
+        }
+    }
+
+    output.push_str("</");
+    output.push_str(qname);
+    output.push('>');
+}
+
+// roxmltree resolves names to namespace URIs, so the prefix the document
+// actually used is read back from the start tag.
+fn element_qname<'a>(node: Node, input: &'a str) -> &'a str {
+    let start_tag = &input[node.range().start + 1..];
+    let end = start_tag
+        .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .unwrap_or(start_tag.len());
+    &start_tag[..end]
+}
+
+fn prefix(qname: &str) -> &str {
+    qname.split_once(':').map(|(prefix, _)| prefix).unwrap_or("")
+}
+
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '\r' => escaped.push_str("&#xD;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+pub fn escape_attribute(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\t' => escaped.push_str("&#x9;"),
+            '\n' => escaped.push_str("&#xA;"),
+            '\r' => escaped.push_str("&#xD;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roxmltree::Document;
+
+    fn canonicalize_root(xml: &str) -> String {
+        let document = Document::parse(xml).unwrap();
+        canonicalize(document.root_element(), None, &[]).unwrap()
+    }
+
+    #[test]
+    fn test_sorts_attributes_and_expands_empty_elements() {
+        let canonical = canonicalize_root(r#"<a z='1' b="2" xmlns:n="urn:n" n:a="3"><b/></a>"#);
+        assert_eq!(canonical, r#"<a xmlns:n="urn:n" b="2" z="1" n:a="3"><b></b></a>"#);
+    }
+
+    #[test]
+    fn test_only_renders_utilized_namespaces() {
+        let xml = r#"<p:a xmlns:p="urn:p" xmlns:q="urn:q" xmlns="urn:default"><p:b><c/></p:b></p:a>"#;
+        let document = Document::parse(xml).unwrap();
+        let b = document.descendants().find(|n| n.has_tag_name("b")).unwrap();
+
+        assert_eq!(
+            canonicalize(b, None, &[]).unwrap(),
+            r#"<p:b xmlns:p="urn:p"><c xmlns="urn:default"></c></p:b>"#,
+        );
+        assert_eq!(
+            canonicalize(b, None, &["q".to_string()]).unwrap(),
+            r#"<p:b xmlns:p="urn:p" xmlns:q="urn:q"><c xmlns="urn:default"></c></p:b>"#,
+        );
+    }
+
+    #[test]
+    fn test_escapes_text_and_attributes() {
+        let canonical = canonicalize_root("<a b='&quot;&lt;&#9;'>&amp;&lt;&gt;&apos;<!-- gone --></a>");
+        assert_eq!(canonical, r#"<a b="&quot;&lt;&#x9;">&amp;&lt;&gt;'</a>"#);
+    }
+
+    #[test]
+    fn test_excludes_element() {
+        let document = Document::parse("<a><b>keep</b><c>drop</c></a>").unwrap();
+        let c = document.descendants().find(|n| n.has_tag_name("c")).unwrap();
+
+        assert_eq!(
+            canonicalize(document.root_element(), Some(c.id()), &[]).unwrap(),
+            "<a><b>keep</b></a>",
+        );
+    }
+}
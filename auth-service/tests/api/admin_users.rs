@@ -0,0 +1,178 @@
+use auth_service::{
+    domain::AccountStatus,
+    routes::{ListUsersResponse, UserDetailsResponse},
+};
+
+use crate::helpers::{auth_token, get_random_email, TestApp};
+
+async fn sign_up(app: &TestApp, email: &str) {
+    let response = app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    assert_eq!(response.status().as_u16(), 201);
+}
+
+async fn log_in(app: &TestApp, email: &str, password: &str) -> reqwest::Response {
+    app.post_login(&serde_json::json!({ "email": email, "password": password })).await
+}
+
+async fn sign_in_as_admin(app: &TestApp) -> String {
+    let admin = get_random_email();
+    sign_up(app, &admin).await;
+    app.grant_role(&admin, "admin").await;
+    assert_eq!(log_in(app, &admin, "password123").await.status().as_u16(), 200);
+    admin
+}
+
+#[tokio::test]
+async fn should_list_and_search_users() {
+    let mut app = TestApp::new().await;
+    let user = format!("search-{}", get_random_email());
+    sign_up(&app, &user).await;
+    sign_in_as_admin(&app).await;
+
+    let response = app.get_users(&[("search", "search-"), ("perPage", "10")]).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let page = response.json::<ListUsersResponse>().await.unwrap();
+    assert_eq!(page.total, 1);
+    assert_eq!(page.users[0].email, user);
+
+    let page = app.get_users(&[("perPage", "1"), ("page", "2")]).await.json::<ListUsersResponse>().await.unwrap();
+    assert_eq!(page.total, 2);
+    assert_eq!(page.users.len(), 1);
+
+    assert_eq!(app.get_users(&[("perPage", "1000")]).await.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_get_user_details() {
+    let mut app = TestApp::new().await;
+    let admin = sign_in_as_admin(&app).await;
+
+    let response = app.get_user(&admin).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let details = response.json::<UserDetailsResponse>().await.unwrap();
+    assert_eq!(details.user.email, admin);
+    assert!(details.has_password);
+    assert_eq!(details.user.status, AccountStatus::Active);
+    assert_eq!(details.roles, vec!["admin".to_owned()]);
+
+    assert_eq!(app.get_user(&get_random_email()).await.status().as_u16(), 404);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_prevent_suspended_user_from_signing_in() {
+    let mut app = TestApp::new().await;
+    let user = get_random_email();
+    sign_up(&app, &user).await;
+
+    log_in(&app, &user, "password123").await;
+    let token = auth_token(&app).expect("No auth cookie found");
+    sign_in_as_admin(&app).await;
+
+    let response = app.post_user_action_with("disable", &serde_json::json!({ "email": user, "reason": "Chargeback" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(log_in(&app, &user, "password123").await.status().as_u16(), 403);
+    assert_ne!(app.post_verify_token(&serde_json::json!({ "token": token })).await.status().as_u16(), 200);
+
+    let details = app.get_user(&user).await.json::<UserDetailsResponse>().await.unwrap();
+    assert_eq!(details.user.status, AccountStatus::Suspended);
+    assert_eq!(details.suspension_reason.as_deref(), Some("Chargeback"));
+
+    app.post_user_action("enable", &user).await;
+    assert_eq!(log_in(&app, &user, "password123").await.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_not_let_admin_disable_or_delete_themselves() {
+    let mut app = TestApp::new().await;
+    let admin = sign_in_as_admin(&app).await;
+
+    assert_eq!(app.post_user_action("disable", &admin).await.status().as_u16(), 403);
+    assert_eq!(app.post_user_action("delete", &admin).await.status().as_u16(), 403);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_revoke_sessions() {
+    let mut app = TestApp::new().await;
+    let user = get_random_email();
+    sign_up(&app, &user).await;
+    log_in(&app, &user, "password123").await;
+    let token = auth_token(&app).expect("No auth cookie found");
+
+    sign_in_as_admin(&app).await;
+    assert_eq!(app.post_user_action("revoke-sessions", &user).await.status().as_u16(), 200);
+
+    let response = app.post_verify_token(&serde_json::json!({ "token": token })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_force_password_reset() {
+    let mut app = TestApp::new().await;
+    let user = get_random_email();
+    sign_up(&app, &user).await;
+    sign_in_as_admin(&app).await;
+
+    assert_eq!(app.post_user_action("reset-password", &user).await.status().as_u16(), 200);
+    assert_eq!(log_in(&app, &user, "password123").await.status().as_u16(), 401);
+
+    let token = app
+        .last_email_to(&user)
+        .and_then(|email| email.link_param("token"))
+        .expect("No password reset link was sent");
+
+    let body = serde_json::json!({ "token": token, "password": "new-password123" });
+    assert_eq!(app.post_password_reset(&body).await.status().as_u16(), 200);
+    assert_eq!(app.post_password_reset(&body).await.status().as_u16(), 401);
+
+    assert_eq!(log_in(&app, &user, "new-password123").await.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_force_2fa_reset_and_delete_user() {
+    let mut app = TestApp::new().await;
+    let user = get_random_email();
+    app.post_signup(&serde_json::json!({
+        "email": user,
+        "password": "password123",
+        "requires2FA": true,
+    })).await;
+    sign_in_as_admin(&app).await;
+
+    assert_eq!(app.post_user_action("reset-2fa", &user).await.status().as_u16(), 200);
+    let details = app.get_user(&user).await.json::<UserDetailsResponse>().await.unwrap();
+    assert!(!details.user.requires_2fa);
+
+    assert_eq!(app.post_user_action("delete", &user).await.status().as_u16(), 200);
+    assert_eq!(app.post_user_action("delete", &user).await.status().as_u16(), 404);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_403_without_admin_role() {
+    let mut app = TestApp::new().await;
+    let user = get_random_email();
+    sign_up(&app, &user).await;
+    log_in(&app, &user, "password123").await;
+
+    assert_eq!(app.get_users(&[]).await.status().as_u16(), 403);
+    assert_eq!(app.post_user_action("revoke-sessions", &user).await.status().as_u16(), 403);
+
+    app.clean_up().await;
+}
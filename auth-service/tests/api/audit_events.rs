@@ -0,0 +1,44 @@
+use auth_service::{
+    domain::{AuditEventKind, AuditOutcome},
+    routes::ListAuditEventsResponse,
+};
+
+use crate::helpers::{get_random_email, TestApp};
+
+#[tokio::test]
+async fn should_list_own_authentication_history() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    app.post_login(&serde_json::json!({ "email": email, "password": "wrong-password" })).await;
+    app.post_login(&serde_json::json!({ "email": email, "password": "password123" })).await;
+
+    let response = app.get_audit_events().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let events = response.json::<ListAuditEventsResponse>().await.unwrap().events;
+
+    let history: Vec<_> = events.iter().map(|event| (event.event, event.outcome)).collect();
+    assert_eq!(history, vec![
+        (AuditEventKind::Login, AuditOutcome::Success),
+        (AuditEventKind::Login, AuditOutcome::Failure),
+        (AuditEventKind::Signup, AuditOutcome::Success),
+    ]);
+    assert_eq!(events[1].detail.as_deref(), Some("Incorrect credentials"));
+    assert!(events[0].ip.is_some());
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_listing_audit_events_without_session() {
+    let mut app = TestApp::new().await;
+
+    assert_eq!(app.get_audit_events().await.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
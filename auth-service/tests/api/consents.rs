@@ -0,0 +1,141 @@
+use auth_service::{
+    domain::PolicyKind,
+    routes::{ListConsentsResponse, ListPoliciesResponse, LoginResponse},
+};
+
+use crate::helpers::{get_random_email, TestApp};
+
+// Signs up an admin of the default tenant, before any policy is published
+async fn sign_up_admin(app: &TestApp) -> String {
+    let admin = get_random_email();
+
+    let response = app.post_signup(&serde_json::json!({
+        "email": admin,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    assert_eq!(response.status().as_u16(), 201);
+    app.grant_role(&admin, "admin").await;
+    admin
+}
+
+async fn publish_terms(app: &TestApp, admin: &str, version: &str) {
+    let response = app.post_login(&serde_json::json!({ "email": admin, "password": "password123" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_policy(&serde_json::json!({
+        "kind": "terms_of_service",
+        "version": version,
+        "url": format!("https://example.com/terms/{}", version),
+    })).await;
+    assert_eq!(response.status().as_u16(), 201);
+}
+
+fn accepting_terms(version: &str) -> serde_json::Value {
+    serde_json::json!([{ "kind": "terms_of_service", "version": version }])
+}
+
+#[tokio::test]
+async fn should_require_accepting_current_policies_at_signup() {
+    let mut app = TestApp::new().await;
+    let admin = sign_up_admin(&app).await;
+    publish_terms(&app, &admin, "1").await;
+
+    let policies = app.get_policies().await.json::<ListPoliciesResponse>().await.unwrap().policies;
+    assert_eq!(policies.len(), 1);
+    assert_eq!(policies[0].kind, PolicyKind::TermsOfService);
+    assert_eq!(policies[0].url, "https://example.com/terms/1");
+
+    let email = get_random_email();
+    let signup = |accepted: serde_json::Value| serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": false,
+        "acceptedPolicies": accepted,
+    });
+    assert_eq!(app.post_signup(&signup(serde_json::json!([]))).await.status().as_u16(), 400);
+    assert_eq!(app.post_signup(&signup(accepting_terms("0"))).await.status().as_u16(), 400);
+    assert_eq!(app.post_signup(&signup(accepting_terms("1"))).await.status().as_u16(), 201);
+
+    let response = app.post_login(&serde_json::json!({ "email": email, "password": "password123" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let consents = app.get_consents().await.json::<ListConsentsResponse>().await.unwrap().consents;
+    assert_eq!(consents.len(), 1);
+    assert_eq!(consents[0].version, "1");
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_gate_login_until_newer_version_is_accepted() {
+    let mut app = TestApp::new().await;
+    let admin = sign_up_admin(&app).await;
+    publish_terms(&app, &admin, "1").await;
+
+    let email = get_random_email();
+    app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": false,
+        "acceptedPolicies": accepting_terms("1"),
+    })).await;
+    publish_terms(&app, &admin, "2").await;
+
+    let response = app.post_login(&serde_json::json!({ "email": email, "password": "password123" })).await;
+    assert_eq!(response.status().as_u16(), 428);
+    let LoginResponse::PolicyAcceptanceRequired(body) = response.json::<LoginResponse>().await.unwrap() else {
+        panic!("Expected a policy acceptance response");
+    };
+    assert_eq!(body.policies.len(), 1);
+    assert_eq!(body.policies[0].version, "2");
+
+    let response = app.post_login(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "acceptedPolicies": accepting_terms("2"),
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let consents = app.get_consents().await.json::<ListConsentsResponse>().await.unwrap().consents;
+    let versions: Vec<_> = consents.iter().map(|consent| consent.version.as_str()).collect();
+    assert_eq!(versions, vec!["2", "1"]);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_let_signed_in_users_accept_a_new_version() {
+    let mut app = TestApp::new().await;
+    let admin = sign_up_admin(&app).await;
+    publish_terms(&app, &admin, "1").await;
+
+    // The admin is already signed in and accepts the version they published
+    let response = app.post_consents(&serde_json::json!({ "policies": accepting_terms("1") })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.json::<ListConsentsResponse>().await.unwrap().consents.len(), 1);
+
+    let response = app.post_login(&serde_json::json!({ "email": admin, "password": "password123" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_consents(&serde_json::json!({ "policies": accepting_terms("3") })).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_409_for_an_already_published_version() {
+    let mut app = TestApp::new().await;
+    let admin = sign_up_admin(&app).await;
+    publish_terms(&app, &admin, "1").await;
+
+    let response = app.post_policy(&serde_json::json!({
+        "kind": "terms_of_service",
+        "version": "1",
+        "url": "https://example.com/terms/1",
+    })).await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    app.clean_up().await;
+}
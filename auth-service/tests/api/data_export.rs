@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use auth_service::{
+    domain::AuditEventKind,
+    routes::DataExportPendingResponse,
+    utils::data_export::PersonalDataArchive,
+};
+
+use crate::helpers::{get_random_email, TestApp};
+
+#[tokio::test]
+async fn should_export_personal_data_in_the_background() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    app.post_login(&serde_json::json!({ "email": email, "password": "password123" })).await;
+
+    let response = app.get_data_export().await;
+    assert_eq!(response.status().as_u16(), 202);
+    assert_eq!(response.json::<DataExportPendingResponse>().await.unwrap().status, "pending");
+
+    let mut response = app.get_data_export().await;
+    for _ in 0..50 {
+        if response.status().as_u16() == 200 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        response = app.get_data_export().await;
+    }
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.headers()["content-disposition"].to_str().unwrap().starts_with("attachment"));
+
+    let archive = response.json::<PersonalDataArchive>().await.unwrap();
+    assert_eq!(archive.account.email, email);
+    assert!(archive.account.has_password);
+    assert_eq!(archive.devices.len(), 1);
+    let events: Vec<_> = archive.audit_events.iter().map(|event| event.event).collect();
+    assert_eq!(events, vec![AuditEventKind::Login, AuditEventKind::Signup]);
+    assert!(archive.consents.is_empty());
+
+    let emails = app.sent_emails.lock().unwrap().clone();
+    assert!(emails.iter().any(|sent| sent.recipient == email && sent.content.contains("/me/export")));
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_exporting_without_session() {
+    let mut app = TestApp::new().await;
+
+    assert_eq!(app.get_data_export().await.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
@@ -1,7 +1,8 @@
-use auth_service::utils::constants::JWT_COOKIE_NAME;
+use auth_service::{routes::DeleteAccountResponse, utils::constants::JWT_COOKIE_NAME};
+use chrono::Utc;
 use reqwest::Url;
 
-use crate::helpers::{get_random_email, TestApp}; 
+use crate::helpers::{auth_token, get_random_email, TestApp};
 
 #[tokio::test]
 async fn should_return_400_if_jwt_cookie_missing() {
@@ -83,3 +84,40 @@ async fn should_return_400_if_delete_account_called_twice_in_a_row() {
     assert_eq!(response.status().as_u16(), 400);
     app.clean_up().await;
 }
+
+#[tokio::test]
+async fn should_restore_deleted_account_by_logging_in() {
+    let random_email = get_random_email();
+    let user_credentials = serde_json::json!({
+        "email": random_email,
+        "password": "password123",
+    });
+
+    let mut app = TestApp::new().await;
+
+    app.post_signup(&serde_json::json!({
+        "email": random_email,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    app.post_login(&user_credentials).await;
+    let token = auth_token(&app).expect("No auth cookie found");
+
+    let response = app.delete_account().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let purge_at = response.json::<DeleteAccountResponse>().await.unwrap().purge_at;
+    assert!(purge_at > Utc::now());
+    assert_eq!(app.last_email_to(&random_email).unwrap().subject, "Your account will be deleted");
+
+    // The old session ended with the deletion
+    let response = app.post_verify_token(&serde_json::json!({ "token": token })).await;
+    assert_ne!(response.status().as_u16(), 200);
+
+    let response = app.post_login(&user_credentials).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let token = auth_token(&app).expect("No auth cookie found");
+    let response = app.post_verify_token(&serde_json::json!({ "token": token })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
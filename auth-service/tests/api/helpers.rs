@@ -1,51 +1,122 @@
+#![allow(dead_code)]
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rsa::{
+    pkcs1v15::SigningKey,
+    pkcs8::DecodePrivateKey,
+    signature::{SignatureEncoding, Signer},
+    RsaPrivateKey,
+};
 use secrecy::{Secret, ExposeSecret};
+use sha2::{Digest, Sha256};
 use sqlx::{
     mysql::{MySqlConnectOptions, MySqlConnection, MySqlPool, MySqlPoolOptions},
     Connection, Executor,
 };
 use tokio::sync::RwLock;
-use std::{str::FromStr, sync::Arc};
-use reqwest::cookie::Jar;
+use std::{collections::HashMap, str::FromStr, sync::{Arc, Mutex}};
+use chrono::Utc;
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use reqwest::{cookie::{CookieStore, Jar}, Url};
 use uuid::Uuid;
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, Request, Respond, ResponseTemplate,
+};
 
 use auth_service::{
-    domain::IntoShared,
+    domain::{Email, EmailClient, IntoShared, OidcProviderConfig, Role, SamlIdpConfig, SamlServiceProviderConfig, TenantId},
     get_mysql_pool,
     configure_redis,
     services::data_stores::{
         redis_two_fa_code_store::RedisTwoFACodeStore,
         redis_banned_token_store::RedisBannedTokenStore,
-        mock_email_client::MockEmailClient,
         my_sql_user_store::MySqlUserStore,
+        my_sql_tenant_store::MySqlTenantStore,
+        my_sql_invitation_store::MySqlInvitationStore,
+        my_sql_audit_log_store::MySqlAuditLogStore,
+        my_sql_webhook_store::MySqlWebhookStore,
+        my_sql_known_device_store::MySqlKnownDeviceStore,
+        my_sql_trusted_device_store::MySqlTrustedDeviceStore,
+        my_sql_data_export_store::MySqlDataExportStore,
+        my_sql_profile_store::MySqlProfileStore,
+        my_sql_consent_store::MySqlConsentStore,
+        redis_oidc_state_store::RedisOidcStateStore,
+        redis_magic_link_store::RedisMagicLinkStore,
+    },
+    services::{
+        oidc_client::OidcClient,
+        password_policy::PasswordPolicy,
+        saml_service_provider::SamlServiceProvider,
+        webhook_address_policy::WebhookAddressPolicy,
     },
-    utils::constants::{test, DATABASE_URL, DEFAULT_REDIS_HOSTNAME},
-    AppState, Application, BannedTokenStoreType, TwoFACodeStoreType,
+    utils::constants::{test, DATABASE_URL, DEFAULT_REDIS_HOSTNAME, JWT_COOKIE_NAME, JWT_SECRET},
+    utils::parsable::Parsable,
+    AppState, Application, BannedTokenStoreType, TwoFACodeStoreType, UserStoreType, WebhookStoreType,
 };
 
 pub struct TestApp {
     pub address: String,
     pub cookie_jar: Arc<Jar>,
     pub http_client: reqwest::Client,
+    pub user_store: UserStoreType,
     pub banned_token_store: BannedTokenStoreType,
     pub two_fa_code_store: TwoFACodeStoreType,
+    pub webhook_store: WebhookStoreType,
     pub db_name: String,
     pub clean_up_called: bool,
+    pub oidc_server: MockServer,
+    pub sent_emails: Arc<Mutex<Vec<SentEmail>>>,
 }
 
 impl TestApp {
+    // Most tests aren't about password strength, so any password `Password::parse`
+    // accepts will do.
     pub async fn new() -> Self {
+        Self::with_password_policy(PasswordPolicy::permissive()).await
+    }
+
+    pub async fn with_password_policy(password_policy: PasswordPolicy) -> Self {
         let (db_pool, db_name) = configure_my_sql().await;
         let redis_conn = Arc::new(RwLock::new(configure_redis(DEFAULT_REDIS_HOSTNAME.to_string())));
-        let user_store = MySqlUserStore::new(db_pool).into_shared();
+        let user_store = MySqlUserStore::new(db_pool.clone()).into_shared();
+        let tenant_store = MySqlTenantStore::new(db_pool.clone()).into_shared();
+        let invitation_store = MySqlInvitationStore::new(db_pool.clone()).into_shared();
+        let audit_log_store = MySqlAuditLogStore::new(db_pool.clone()).into_shared();
+        let webhook_store = MySqlWebhookStore::new(db_pool.clone()).into_shared();
+        let known_device_store = MySqlKnownDeviceStore::new(db_pool.clone()).into_shared();
+        let trusted_device_store = MySqlTrustedDeviceStore::new(db_pool.clone()).into_shared();
+        let data_export_store = MySqlDataExportStore::new(db_pool.clone()).into_shared();
+        let profile_store = MySqlProfileStore::new(db_pool.clone()).into_shared();
+        let consent_store = MySqlConsentStore::new(db_pool).into_shared();
         let banned_token_store = RedisBannedTokenStore::new(redis_conn.clone()).into_shared();
         let two_fa_code_store = RedisTwoFACodeStore::new(redis_conn.clone()).into_shared();
-        let mock_email_client = MockEmailClient.into_shared();
+        let email_client = RecordingEmailClient::default();
+        let sent_emails = email_client.sent_emails.clone();
+        let oidc_server = MockServer::start().await;
+        let oidc_state_store = RedisOidcStateStore::new(redis_conn.clone()).into_shared();
+        let magic_link_store = RedisMagicLinkStore::new(redis_conn.clone()).into_shared();
         let app_state = AppState::new(
-            user_store,
+            user_store.clone(),
             banned_token_store.clone(),
             two_fa_code_store.clone(),
-            mock_email_client,
-        );
+            email_client.into_shared(),
+        )
+        .with_oidc(Arc::new(configure_oidc_clients(&oidc_server)), oidc_state_store)
+        .with_saml(Arc::new(configure_saml_service_provider()))
+        .with_magic_link_store(magic_link_store)
+        .with_tenant_store(tenant_store)
+        .with_invitation_store(invitation_store)
+        .with_audit_log_store(audit_log_store)
+        .with_webhook_store(webhook_store.clone())
+        .with_known_device_store(known_device_store)
+        .with_trusted_device_store(trusted_device_store)
+        .with_data_export_store(data_export_store)
+        .with_profile_store(profile_store)
+        .with_consent_store(consent_store)
+        .with_password_policy(Arc::new(password_policy))
+        // Webhook receivers in tests run on this machine
+        .with_webhook_address_policy(WebhookAddressPolicy::allowing_private_addresses());
         let app = Application::build(app_state, test::APP_ADDRESS)
             .await
             .expect("Failed to build the app");
@@ -64,16 +135,20 @@ impl TestApp {
             address,
             cookie_jar,
             http_client,
+            user_store,
             banned_token_store,
             two_fa_code_store,
+            webhook_store,
             db_name,
             clean_up_called: false,
+            oidc_server,
+            sent_emails,
         }
     }
 
     pub async fn get_root(&self) -> reqwest::Response {
         self.http_client
-            .get(&format!("{}/", &self.address))
+            .get(format!("{}/", &self.address))
             .send()
             .await
             .expect("Failed to execute request.")
@@ -84,7 +159,7 @@ impl TestApp {
         Body: serde::Serialize,
     {
         self.http_client
-            .post(&format!("{}/signup", &self.address))
+            .post(format!("{}/signup", &self.address))
             .json(body)
             .send()
             .await
@@ -96,7 +171,62 @@ impl TestApp {
         Body: serde::Serialize,
     {
         self.http_client
-            .post(&format!("{}/login", &self.address))
+            .post(format!("{}/login", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_login_from<Body>(&self, body: &Body, user_agent: &str) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/login", &self.address))
+            .header(reqwest::header::USER_AGENT, user_agent)
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_login_report(&self, token: &str) -> reqwest::Response {
+        self.http_client
+            .get(Url::parse_with_params(&format!("{}/login/report", &self.address), &[("token", token)]).unwrap())
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    // Submits the form, like the confirmation page does. Doesn't follow the
+    // redirect, which leaves the app.
+    pub async fn post_login_report(&self, token: &str) -> reqwest::Response {
+        reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("Failed to build HTTP client")
+            .post(format!("{}/login/report", &self.address))
+            .form(&[("token", token)])
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_trusted_devices(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/trusted-devices", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_revoke_trusted_device<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/trusted-devices/revoke", &self.address))
             .json(body)
             .send()
             .await
@@ -105,7 +235,7 @@ impl TestApp {
 
     pub async fn post_logout(&self) -> reqwest::Response {
         self.http_client
-            .post(&format!("{}/logout", &self.address))
+            .post(format!("{}/logout", &self.address))
             .send()
             .await
             .expect("Failed to execute request.")
@@ -113,7 +243,7 @@ impl TestApp {
 
     pub async fn get_verify_2fa(&self) -> reqwest::Response {
         self.http_client
-            .post(&format!("{}/verify-2fa", &self.address))
+            .post(format!("{}/verify-2fa", &self.address))
             .send()
             .await
             .expect("Failed to execute request.")
@@ -124,16 +254,424 @@ impl TestApp {
         Body: serde::Serialize,
     {
         self.http_client
-            .post(&format!("{}/verify-token", &self.address))
+            .post(format!("{}/verify-token", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    // Grants a role to a user of the default tenant directly through the
+    // store, for tests that need an admin to begin with.
+    pub async fn grant_role(&self, email: &str, role: &str) {
+        self.user_store
+            .write()
+            .await
+            .assign_role(&TenantId::default(), email, &Role::parse(role).expect("Invalid role"))
+            .await
+            .expect("Failed to assign role");
+    }
+
+    pub async fn post_assign_role<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/admin/roles/assign", &self.address))
             .json(body)
             .send()
             .await
             .expect("Failed to execute request.")
     }
 
+    pub async fn post_remove_role<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/admin/roles/remove", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_tenant_signup<Body>(&self, tenant: &str, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/tenants/{}/signup", &self.address, tenant))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_tenant_login<Body>(&self, tenant: &str, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/tenants/{}/login", &self.address, tenant))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_tenant<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/admin/tenants", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_tenants(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/admin/tenants", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_invitation<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/admin/invitations", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_invitations(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/admin/invitations", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_revoke_invitation<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/admin/invitations/revoke", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_accept_invitation<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/invitations/accept", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_decline_invitation<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/invitations/decline", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_users(&self, query: &[(&str, &str)]) -> reqwest::Response {
+        let url = Url::parse_with_params(&format!("{}/admin/users", &self.address), query)
+            .expect("Failed to parse URL");
+
+        self.http_client
+            .get(url)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_user(&self, email: &str) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/admin/users/{}", &self.address, email))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    // Posts `{ "email": email }` to one of the `/admin/users/<action>` routes
+    pub async fn post_user_action(&self, action: &str, email: &str) -> reqwest::Response {
+        self.post_user_action_with(action, &serde_json::json!({ "email": email })).await
+    }
+
+    pub async fn post_user_action_with<Body>(&self, action: &str, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/admin/users/{}", &self.address, action))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_password_reset<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/password-reset", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_reauthenticate<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/reauthenticate", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_change_password<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/change-password", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_disable_2fa(&self) -> reqwest::Response {
+        self.http_client
+            .post(format!("{}/disable-2fa", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_audit_events(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/me/audit-events", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_me(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/me", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn patch_me<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .patch(format!("{}/me", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_data_export(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/me/export", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_webhook<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/admin/webhooks", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_policy<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/admin/policies", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_policies(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/policies", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_consents(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/me/consents", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_consents<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/me/consents", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_impersonate<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/admin/impersonate", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_stop_impersonation(&self) -> reqwest::Response {
+        self.http_client
+            .post(format!("{}/impersonate/stop", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_scim_token(&self) -> reqwest::Response {
+        self.http_client
+            .post(format!("{}/admin/scim/token", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    // The SCIM endpoints take the tenant's token instead of the auth cookie.
+    // `resource` is relative to `/scim/v2`, like `Users` or `Groups/{id}`.
+    pub async fn get_scim(&self, token: &str, resource: &str, query: &[(&str, &str)]) -> reqwest::Response {
+        let url = Url::parse_with_params(&format!("{}/scim/v2/{}", &self.address, resource), query)
+            .expect("Failed to parse URL");
+
+        self.http_client
+            .get(url)
+            .bearer_auth(token)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_scim<Body>(&self, token: &str, resource: &str, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/scim/v2/{}", &self.address, resource))
+            .bearer_auth(token)
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn patch_scim<Body>(&self, token: &str, resource: &str, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .patch(format!("{}/scim/v2/{}", &self.address, resource))
+            .bearer_auth(token)
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn delete_scim(&self, token: &str, resource: &str) -> reqwest::Response {
+        self.http_client
+            .delete(format!("{}/scim/v2/{}", &self.address, resource))
+            .bearer_auth(token)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_webhooks(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/admin/webhooks", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_delete_webhook<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/admin/webhooks/delete", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_webhook_deliveries(&self, id: &str) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/admin/webhooks/{}/deliveries", &self.address, id))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
     pub async fn delete_account(&self) -> reqwest::Response {
         self.http_client
-            .post(&format!("{}/delete-account", &self.address))
+            .post(format!("{}/delete-account", &self.address))
             .send()
             .await
             .expect("Failed to execute request.")
@@ -144,13 +682,118 @@ impl TestApp {
         Body: serde::Serialize,
     {
         self.http_client
-            .post(&format!("{}/verify-2fa", &self.address))
+            .post(format!("{}/verify-2fa", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_oidc_login(&self, provider: &str) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/oidc/{}/login", &self.address, provider))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_oidc_link(&self, provider: &str) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/oidc/{}/link", &self.address, provider))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_identities(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/identities", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_unlink_identity<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/identities/unlink", &self.address))
             .json(body)
             .send()
             .await
             .expect("Failed to execute request.")
     }
 
+    pub async fn get_oidc_callback(&self, provider: &str, query: &[(&str, &str)]) -> reqwest::Response {
+        let url = reqwest::Url::parse_with_params(
+            &format!("{}/oidc/{}/callback", &self.address, provider),
+            query,
+        )
+        .expect("Failed to parse URL");
+
+        self.http_client
+            .get(url)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_saml_metadata(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/saml/metadata", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_saml_acs(&self, form: &[(&str, &str)]) -> reqwest::Response {
+        self.http_client
+            .post(format!("{}/saml/acs", &self.address))
+            .form(form)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_magic_link<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/login/magic-link", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_verify_magic_link(&self, token: &str) -> reqwest::Response {
+        self.http_client
+            .get(self.verify_magic_link_url(token))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub fn verify_magic_link_url(&self, token: &str) -> Url {
+        Url::parse_with_params(
+            &format!("{}/login/magic-link/verify", &self.address),
+            &[("token", token)],
+        )
+        .expect("Failed to parse URL")
+    }
+
+    pub fn last_email_to(&self, recipient: &str) -> Option<SentEmail> {
+        self.sent_emails
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|email| email.recipient == recipient)
+            .cloned()
+    }
+
     pub async fn clean_up(&mut self) {
         if self.clean_up_called {
             return;
@@ -170,6 +813,303 @@ impl Drop for TestApp {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct SentEmail {
+    pub recipient: String,
+    pub subject: String,
+    pub content: String,
+}
+
+impl SentEmail {
+    // Finds the first link in the email carrying the given query parameter
+    pub fn link_param(&self, name: &str) -> Option<String> {
+        self.content
+            .split_whitespace()
+            .filter_map(|word| Url::parse(word).ok())
+            .find_map(|url| url.query_pairs().find(|(key, _)| key == name).map(|(_, value)| value.into_owned()))
+    }
+}
+
+// Keeps every email the app sends so tests can follow the links in them
+#[derive(Default)]
+pub struct RecordingEmailClient {
+    pub sent_emails: Arc<Mutex<Vec<SentEmail>>>,
+}
+
+#[async_trait::async_trait]
+impl EmailClient for RecordingEmailClient {
+    async fn send_email(&self, recipient: &Email, subject: &str, content: &str) -> color_eyre::Result<()> {
+        self.sent_emails.lock().unwrap().push(SentEmail {
+            recipient: recipient.as_ref().expose_secret().to_owned(),
+            subject: subject.to_owned(),
+            content: content.to_owned(),
+        });
+
+        Ok(())
+    }
+}
+
+impl IntoShared for RecordingEmailClient {}
+
+pub const OIDC_PROVIDER: &str = "mock";
+pub const OIDC_CLIENT_ID: &str = "auth-service";
+
+fn configure_oidc_clients(oidc_server: &MockServer) -> HashMap<String, OidcClient> {
+    let http_client = reqwest::Client::builder()
+        .timeout(test::oidc::TIMEOUT)
+        .build()
+        .expect("Failed to build HTTP client");
+
+    let config = OidcProviderConfig {
+        name: OIDC_PROVIDER.to_owned(),
+        issuer: oidc_server.uri(),
+        client_id: OIDC_CLIENT_ID.to_owned(),
+        client_secret: Secret::new("client-secret".to_owned()),
+        redirect_uri: format!("http://127.0.0.1/oidc/{}/callback", OIDC_PROVIDER),
+        scopes: vec!["openid".to_owned(), "email".to_owned()],
+    };
+
+    HashMap::from([(OIDC_PROVIDER.to_owned(), OidcClient::new(config, http_client))])
+}
+
+pub const SAML_IDP: &str = "acme";
+pub const SAML_IDP_ENTITY_ID: &str = "https://idp.example.com/metadata";
+pub const SAML_SP_ENTITY_ID: &str = "http://127.0.0.1/saml/metadata";
+pub const SAML_ACS_URL: &str = "http://127.0.0.1/saml/acs";
+
+const SAML_IDP_KEY: &str = include_str!("../fixtures/saml/idp_key.pem");
+const SAML_IDP_CERTIFICATE: &str = include_str!("../fixtures/saml/idp_cert.pem");
+
+fn configure_saml_service_provider() -> SamlServiceProvider {
+    SamlServiceProvider::new(
+        SamlServiceProviderConfig {
+            entity_id: SAML_SP_ENTITY_ID.to_owned(),
+            acs_url: SAML_ACS_URL.to_owned(),
+        },
+        vec![SamlIdpConfig {
+            name: SAML_IDP.to_owned(),
+            entity_id: SAML_IDP_ENTITY_ID.to_owned(),
+            certificates: vec![SAML_IDP_CERTIFICATE.to_owned()],
+            email_attribute: Some("mail".to_owned()),
+        }],
+    )
+    .expect("Failed to configure SAML service provider")
+}
+
+// Builds a base64 encoded SAML response as the IdP would post it, with the
+// assertion signed by the fixture IdP key. Every response gets fresh IDs so
+// replay protection doesn't interfere between tests. The assertion and
+// SignedInfo are written in canonical form so they can be digested and
+// signed as they are.
+pub fn signed_saml_response(name_id: &str, email: &str) -> String {
+    let now = Utc::now();
+    let issue_instant = now.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let not_on_or_after = (now + chrono::Duration::minutes(5)).format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let assertion_id = format!("_{}", Uuid::new_v4());
+
+    let assertion = |signature: &str| format!(
+        concat!(
+            r#"<saml:Assertion xmlns:saml="urn:oasis:names:tc:SAML:2.0:assertion" ID="{id}" IssueInstant="{issue_instant}" Version="2.0">"#,
+            r#"<saml:Issuer>{issuer}</saml:Issuer>{signature}"#,
+            r#"<saml:Subject><saml:NameID>{name_id}</saml:NameID>"#,
+            r#"<saml:SubjectConfirmation Method="urn:oasis:names:tc:SAML:2.0:cm:bearer">"#,
+            r#"<saml:SubjectConfirmationData NotOnOrAfter="{not_on_or_after}" Recipient="{acs_url}"></saml:SubjectConfirmationData>"#,
+            r#"</saml:SubjectConfirmation></saml:Subject>"#,
+            r#"<saml:Conditions NotBefore="{issue_instant}" NotOnOrAfter="{not_on_or_after}">"#,
+            r#"<saml:AudienceRestriction><saml:Audience>{audience}</saml:Audience></saml:AudienceRestriction>"#,
+            r#"</saml:Conditions>"#,
+            r#"<saml:AttributeStatement><saml:Attribute Name="mail"><saml:AttributeValue>{email}</saml:AttributeValue></saml:Attribute></saml:AttributeStatement>"#,
+            r#"</saml:Assertion>"#,
+        ),
+        id = assertion_id,
+        issue_instant = issue_instant,
+        issuer = SAML_IDP_ENTITY_ID,
+        signature = signature,
+        name_id = name_id,
+        not_on_or_after = not_on_or_after,
+        acs_url = SAML_ACS_URL,
+        audience = SAML_SP_ENTITY_ID,
+        email = email,
+    );
+
+    let digest = STANDARD.encode(Sha256::digest(assertion("").as_bytes()));
+    let signed_info = format!(
+        concat!(
+            r#"<ds:SignedInfo xmlns:ds="http://www.w3.org/2000/09/xmldsig#">"#,
+            r#"<ds:CanonicalizationMethod Algorithm="http://www.w3.org/2001/10/xml-exc-c14n#"></ds:CanonicalizationMethod>"#,
+            r#"<ds:SignatureMethod Algorithm="http://www.w3.org/2001/04/xmldsig-more#rsa-sha256"></ds:SignatureMethod>"#,
+            r##"<ds:Reference URI="#{id}"><ds:Transforms>"##,
+            r#"<ds:Transform Algorithm="http://www.w3.org/2000/09/xmldsig#enveloped-signature"></ds:Transform>"#,
+            r#"<ds:Transform Algorithm="http://www.w3.org/2001/10/xml-exc-c14n#"></ds:Transform>"#,
+            r#"</ds:Transforms><ds:DigestMethod Algorithm="http://www.w3.org/2001/04/xmlenc#sha256"></ds:DigestMethod>"#,
+            r#"<ds:DigestValue>{digest}</ds:DigestValue></ds:Reference></ds:SignedInfo>"#,
+        ),
+        id = assertion_id,
+        digest = digest,
+    );
+
+    let private_key = RsaPrivateKey::from_pkcs8_pem(SAML_IDP_KEY).expect("Invalid SAML IdP key");
+    let signature_value = SigningKey::<Sha256>::new(private_key).sign(signed_info.as_bytes());
+    let signature = format!(
+        r#"<ds:Signature xmlns:ds="http://www.w3.org/2000/09/xmldsig#">{}<ds:SignatureValue>{}</ds:SignatureValue></ds:Signature>"#,
+        signed_info,
+        STANDARD.encode(signature_value.to_bytes()),
+    );
+
+    let response = format!(
+        concat!(
+            r#"<samlp:Response xmlns:samlp="urn:oasis:names:tc:SAML:2.0:protocol" Destination="{acs_url}" ID="_{id}" IssueInstant="{issue_instant}" Version="2.0">"#,
+            r#"<samlp:Status><samlp:StatusCode Value="urn:oasis:names:tc:SAML:2.0:status:Success"/></samlp:Status>"#,
+            "{assertion}</samlp:Response>",
+        ),
+        acs_url = SAML_ACS_URL,
+        id = Uuid::new_v4(),
+        issue_instant = issue_instant,
+        assertion = assertion(&signature),
+    );
+
+    STANDARD.encode(response)
+}
+
+const PRIVATE_KEY: &str = include_str!("../fixtures/oidc/private_key.pem");
+const JWKS: &str = include_str!("../fixtures/oidc/jwks.json");
+
+// Sends the browser straight back to our callback, remembering the nonce so
+// the token endpoint can embed it in the ID token.
+struct AuthorizeResponder {
+    app_address: String,
+    nonce: Arc<Mutex<Option<String>>>,
+}
+
+impl Respond for AuthorizeResponder {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let params: HashMap<String, String> = request.url.query_pairs().into_owned().collect();
+        *self.nonce.lock().unwrap() = params.get("nonce").cloned();
+
+        let mut location = Url::parse(&format!("{}/oidc/{}/callback", self.app_address, OIDC_PROVIDER))
+            .expect("Failed to parse callback URL");
+        location
+            .query_pairs_mut()
+            .append_pair("code", "authorization-code")
+            .append_pair("state", &params["state"]);
+
+        ResponseTemplate::new(302).insert_header("Location", location.as_str())
+    }
+}
+
+struct TokenResponder {
+    issuer: String,
+    email: String,
+    email_verified: bool,
+    nonce: Arc<Mutex<Option<String>>>,
+}
+
+impl Respond for TokenResponder {
+    fn respond(&self, _request: &Request) -> ResponseTemplate {
+        let claims = serde_json::json!({
+            "iss": self.issuer,
+            "aud": OIDC_CLIENT_ID,
+            "sub": "mock-subject",
+            "email": self.email,
+            "email_verified": self.email_verified,
+            "nonce": self.nonce.lock().unwrap().clone(),
+            "exp": Utc::now().timestamp() + 300,
+        });
+
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = Some("test-key".to_owned());
+        let id_token = encode(
+            &header,
+            &claims,
+            &EncodingKey::from_rsa_pem(PRIVATE_KEY.as_bytes()).expect("Invalid test key"),
+        )
+        .expect("Failed to sign ID token");
+
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "access_token": "access-token",
+            "token_type": "Bearer",
+            "id_token": id_token,
+        }))
+    }
+}
+
+pub async fn mount_identity_provider(app: &TestApp, email: &str, email_verified: bool) {
+    let issuer = app.oidc_server.uri();
+    let nonce = Arc::new(Mutex::new(None));
+
+    Mock::given(method("GET"))
+        .and(path("/.well-known/openid-configuration"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "issuer": issuer,
+            "authorization_endpoint": format!("{}/authorize", issuer),
+            "token_endpoint": format!("{}/token", issuer),
+            "jwks_uri": format!("{}/jwks", issuer),
+        })))
+        .mount(&app.oidc_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/jwks"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(JWKS, "application/json"))
+        .mount(&app.oidc_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/authorize"))
+        .respond_with(AuthorizeResponder {
+            app_address: app.address.clone(),
+            nonce: nonce.clone(),
+        })
+        .mount(&app.oidc_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/token"))
+        .respond_with(TokenResponder {
+            issuer,
+            email: email.to_owned(),
+            email_verified,
+            nonce,
+        })
+        .mount(&app.oidc_server)
+        .await;
+}
+
+pub fn auth_token(app: &TestApp) -> Option<String> {
+    let url = Url::parse(&app.address).expect("Failed to parse URL");
+    let cookies = app.cookie_jar.cookies(&url)?;
+    cookies
+        .to_str()
+        .ok()?
+        .split("; ")
+        .find_map(|cookie| cookie.strip_prefix(&format!("{}=", JWT_COOKIE_NAME)))
+        .map(str::to_owned)
+}
+
+// Replaces the auth cookie with a valid token for `email` whose sign-in was
+// an hour ago, as if the session had been left open.
+pub fn set_stale_auth_cookie(app: &TestApp, email: &str) {
+    let now = Utc::now().timestamp();
+    let claims = serde_json::json!({
+        "sub": email,
+        "exp": now + 300,
+        "iat": now,
+        "auth_time": now - 3600,
+        "tenant": "default",
+    });
+    let token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(JWT_SECRET.expose_secret().as_bytes()),
+    )
+    .expect("Failed to sign auth token");
+
+    app.cookie_jar.add_cookie_str(
+        &format!("{}={}; HttpOnly; SameSite=Lax; Path=/", JWT_COOKIE_NAME, token),
+        &Url::parse(&app.address).expect("Failed to parse URL"),
+    );
+}
+
 pub fn get_random_email() -> String {
     format!("{}@example.com", Uuid::new_v4())
 }
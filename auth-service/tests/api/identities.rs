@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use auth_service::{routes::ListIdentitiesResponse, ErrorResponse};
+use wiremock::{
+    matchers::{method, path},
+    Mock, ResponseTemplate,
+};
+
+use crate::helpers::{get_random_email, mount_identity_provider, TestApp, OIDC_PROVIDER};
+
+#[tokio::test]
+async fn should_return_400_if_jwt_cookie_missing() {
+    let mut app = TestApp::new().await;
+
+    let response = app.get_identities().await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app.post_unlink_identity(&serde_json::json!({ "provider": OIDC_PROVIDER })).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_list_identity_used_to_sign_in() {
+    let mut app = TestApp::new().await;
+    mount_identity_provider(&app, &get_random_email(), true).await;
+
+    let _response = app.get_oidc_login(OIDC_PROVIDER).await;
+
+    let response = app.get_identities().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body = response
+        .json::<ListIdentitiesResponse>()
+        .await
+        .expect("Could not deserialize response body to ListIdentitiesResponse");
+    assert_eq!(body.identities.len(), 1);
+    assert_eq!(body.identities[0].provider, OIDC_PROVIDER);
+    assert_eq!(body.identities[0].subject, "mock-subject");
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_409_if_unlinking_last_login_method() {
+    let mut app = TestApp::new().await;
+    mount_identity_provider(&app, &get_random_email(), true).await;
+
+    let _response = app.get_oidc_login(OIDC_PROVIDER).await;
+
+    let response = app.post_unlink_identity(&serde_json::json!({ "provider": OIDC_PROVIDER })).await;
+    assert_eq!(response.status().as_u16(), 409);
+    assert_eq!(
+        response
+            .json::<ErrorResponse>()
+            .await
+            .expect("Could not deserialize response body to ErrorResponse")
+            .error,
+        "Cannot unlink the last login method".to_string()
+    );
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_link_and_unlink_identity_for_password_user() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    let _response = app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    let _response = app.post_login(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+    })).await;
+
+    // The provider reports a different address; linking must not depend on it.
+    mount_identity_provider(&app, &get_random_email(), true).await;
+
+    let response = app.get_oidc_link(OIDC_PROVIDER).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body = app.get_identities().await
+        .json::<ListIdentitiesResponse>()
+        .await
+        .expect("Could not deserialize response body to ListIdentitiesResponse");
+    assert_eq!(body.identities.len(), 1);
+
+    let response = app.post_unlink_identity(&serde_json::json!({ "provider": OIDC_PROVIDER })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body = app.get_identities().await
+        .json::<ListIdentitiesResponse>()
+        .await
+        .expect("Could not deserialize response body to ListIdentitiesResponse");
+    assert!(body.identities.is_empty());
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_not_link_identity_once_signed_out() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    let _response = app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    let _response = app.post_login(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+    })).await;
+
+    mount_identity_provider(&app, &get_random_email(), true).await;
+    Mock::given(method("GET"))
+        .and(path("/authorize"))
+        .respond_with(ResponseTemplate::new(200))
+        .with_priority(1)
+        .mount(&app.oidc_server)
+        .await;
+
+    let response = app.get_oidc_link(OIDC_PROVIDER).await;
+    let params: HashMap<String, String> = response.url().query_pairs().into_owned().collect();
+    app.post_logout().await;
+
+    let response = app
+        .get_oidc_callback(OIDC_PROVIDER, &[("state", params["state"].as_str()), ("code", "authorization-code")])
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_404_if_identity_not_linked() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    let _response = app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    let _response = app.post_login(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+    })).await;
+
+    let response = app.post_unlink_identity(&serde_json::json!({ "provider": OIDC_PROVIDER })).await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.clean_up().await;
+}
@@ -0,0 +1,138 @@
+use auth_service::{
+    domain::AuditEventKind,
+    routes::{ImpersonationResponse, ListAuditEventsResponse, MeResponse},
+};
+
+use crate::helpers::{get_random_email, TestApp};
+
+async fn sign_up(app: &TestApp) -> String {
+    let email = get_random_email();
+    let response = app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    assert_eq!(response.status().as_u16(), 201);
+    email
+}
+
+async fn log_in(app: &TestApp, email: &str) {
+    let response = app.post_login(&serde_json::json!({ "email": email, "password": "password123" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+// Signs in an admin and starts impersonating a new user
+async fn impersonate_new_user(app: &TestApp) -> (String, String) {
+    let admin = sign_up(app).await;
+    app.grant_role(&admin, "admin").await;
+    let user = sign_up(app).await;
+    log_in(app, &admin).await;
+
+    let response = app.post_impersonate(&serde_json::json!({ "email": user })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.json::<ImpersonationResponse>().await.unwrap().email, user);
+
+    (admin, user)
+}
+
+async fn audit_events(app: &TestApp) -> Vec<(AuditEventKind, Option<String>)> {
+    app.get_audit_events()
+        .await
+        .json::<ListAuditEventsResponse>()
+        .await
+        .unwrap()
+        .events
+        .into_iter()
+        .map(|event| (event.event, event.detail))
+        .collect()
+}
+
+#[tokio::test]
+async fn should_see_the_service_as_the_user() {
+    let mut app = TestApp::new().await;
+    let (_, user) = impersonate_new_user(&app).await;
+
+    let response = app.get_me().await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.json::<MeResponse>().await.unwrap().email, user);
+
+    // The user's session has none of the admin's roles
+    assert_eq!(app.get_users(&[]).await.status().as_u16(), 403);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_not_allow_sensitive_actions_while_impersonating() {
+    let mut app = TestApp::new().await;
+    impersonate_new_user(&app).await;
+
+    let response = app.post_change_password(&serde_json::json!({ "newPassword": "password456" })).await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(app.delete_account().await.status().as_u16(), 403);
+    assert_eq!(app.post_reauthenticate(&serde_json::json!({ "password": "password123" })).await.status().as_u16(), 403);
+    assert_eq!(app.get_data_export().await.status().as_u16(), 403);
+    assert_eq!(app.patch_me(&serde_json::json!({ "username": "jane.doe" })).await.status().as_u16(), 403);
+    assert_eq!(app.patch_me(&serde_json::json!({ "locale": "pt-BR" })).await.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_record_start_and_stop_for_both_admin_and_user() {
+    let mut app = TestApp::new().await;
+    let (admin, user) = impersonate_new_user(&app).await;
+
+    let response = app.post_stop_impersonation().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // The admin's own session is back
+    let response = app.get_me().await;
+    assert_eq!(response.json::<MeResponse>().await.unwrap().email, admin);
+    let events = audit_events(&app).await;
+    assert_eq!(events[0], (AuditEventKind::ImpersonationStop, Some(format!("Stopped impersonating {}", user))));
+    assert_eq!(events[1], (AuditEventKind::ImpersonationStart, Some(format!("Impersonating {}", user))));
+
+    log_in(&app, &user).await;
+    let events = audit_events(&app).await;
+    assert_eq!(events[1], (AuditEventKind::ImpersonationStop, Some(format!("No longer impersonated by {}", admin))));
+    assert_eq!(events[2], (AuditEventKind::ImpersonationStart, Some(format!("Impersonated by {}", admin))));
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_not_impersonate_admins_or_unknown_users() {
+    let mut app = TestApp::new().await;
+    let admin = sign_up(&app).await;
+    let other_admin = sign_up(&app).await;
+    app.grant_role(&admin, "admin").await;
+    app.grant_role(&other_admin, "admin").await;
+    log_in(&app, &admin).await;
+
+    let test_cases = [
+        (other_admin.as_str(), 403),
+        (admin.as_str(), 403),
+        ("nobody@example.com", 404),
+        ("not-an-email", 400),
+    ];
+    for (email, status) in test_cases {
+        let response = app.post_impersonate(&serde_json::json!({ "email": email })).await;
+        assert_eq!(response.status().as_u16(), status, "Impersonating {}", email);
+    }
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_403_for_non_admins_and_400_when_not_impersonating() {
+    let mut app = TestApp::new().await;
+    let user = sign_up(&app).await;
+    let other = sign_up(&app).await;
+    log_in(&app, &user).await;
+
+    assert_eq!(app.post_impersonate(&serde_json::json!({ "email": other })).await.status().as_u16(), 403);
+    assert_eq!(app.post_stop_impersonation().await.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
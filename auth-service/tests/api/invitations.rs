@@ -0,0 +1,158 @@
+use auth_service::routes::{AcceptInvitationResponse, InvitationResponse, ListInvitationsResponse, VerifyTokenResponse};
+
+use crate::helpers::{auth_token, get_random_email, TestApp};
+
+// Signs up an admin of the default tenant and signs them in
+async fn sign_in_as_admin(app: &TestApp) -> String {
+    let admin = get_random_email();
+
+    let response = app.post_signup(&serde_json::json!({
+        "email": admin,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    assert_eq!(response.status().as_u16(), 201);
+    app.grant_role(&admin, "admin").await;
+
+    let response = app.post_login(&serde_json::json!({ "email": admin, "password": "password123" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    admin
+}
+
+async fn invite(app: &TestApp, email: &str, role: Option<&str>) -> (InvitationResponse, String) {
+    let response = app.post_invitation(&serde_json::json!({ "email": email, "role": role })).await;
+    assert_eq!(response.status().as_u16(), 201);
+    let invitation = response.json::<InvitationResponse>().await.unwrap();
+
+    let token = app
+        .last_email_to(email)
+        .and_then(|email| email.link_param("token"))
+        .expect("No invitation link was sent");
+
+    (invitation, token)
+}
+
+#[tokio::test]
+async fn should_create_account_when_accepting_invitation() {
+    let mut app = TestApp::new().await;
+    let admin = sign_in_as_admin(&app).await;
+    let invitee = get_random_email();
+
+    let (invitation, token) = invite(&app, &invitee, Some("admin")).await;
+    assert_eq!(invitation.email, invitee);
+    assert_eq!(invitation.invited_by, admin);
+
+    let invitations = app.get_invitations().await.json::<ListInvitationsResponse>().await.unwrap();
+    assert_eq!(invitations.invitations, vec![invitation]);
+
+    let response = app.post_accept_invitation(&serde_json::json!({ "token": token, "password": "password123" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response.json::<AcceptInvitationResponse>().await.unwrap(),
+        AcceptInvitationResponse { tenant: "default".to_owned(), created: true }
+    );
+
+    let token = auth_token(&app).expect("No auth cookie found");
+    let response = app.post_verify_token(&serde_json::json!({ "token": token })).await;
+    let verified = response.json::<VerifyTokenResponse>().await.unwrap();
+    assert_eq!(verified.email, invitee);
+    assert!(verified.roles.contains(&"admin".to_owned()));
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_attach_existing_user_without_signing_in() {
+    let mut app = TestApp::new().await;
+    let invitee = get_random_email();
+    app.post_signup(&serde_json::json!({
+        "email": invitee,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    sign_in_as_admin(&app).await;
+
+    let (_, token) = invite(&app, &invitee, None).await;
+    let response = app.post_accept_invitation(&serde_json::json!({ "token": token })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(!response.json::<AcceptInvitationResponse>().await.unwrap().created);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_only_accept_invitation_once() {
+    let mut app = TestApp::new().await;
+    sign_in_as_admin(&app).await;
+    let invitee = get_random_email();
+
+    let (_, token) = invite(&app, &invitee, None).await;
+    let body = serde_json::json!({ "token": token, "password": "password123" });
+
+    assert_eq!(app.post_accept_invitation(&body).await.status().as_u16(), 200);
+    assert_eq!(app.post_accept_invitation(&body).await.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_new_user_accepts_without_password() {
+    let mut app = TestApp::new().await;
+    sign_in_as_admin(&app).await;
+    let invitee = get_random_email();
+
+    let (_, token) = invite(&app, &invitee, None).await;
+    let response = app.post_accept_invitation(&serde_json::json!({ "token": token })).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    // The invitation is still pending
+    let response = app.post_accept_invitation(&serde_json::json!({ "token": token, "password": "password123" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_not_accept_declined_or_revoked_invitation() {
+    let mut app = TestApp::new().await;
+    sign_in_as_admin(&app).await;
+
+    let (_, declined) = invite(&app, &get_random_email(), None).await;
+    let response = app.post_decline_invitation(&serde_json::json!({ "token": declined })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let (revoked_invitation, revoked) = invite(&app, &get_random_email(), None).await;
+    let response = app.post_revoke_invitation(&serde_json::json!({ "id": revoked_invitation.id })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let response = app.post_revoke_invitation(&serde_json::json!({ "id": revoked_invitation.id })).await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    for token in [declined, revoked] {
+        let response = app.post_accept_invitation(&serde_json::json!({ "token": token, "password": "password123" })).await;
+        assert_eq!(response.status().as_u16(), 401);
+    }
+
+    let invitations = app.get_invitations().await.json::<ListInvitationsResponse>().await.unwrap();
+    assert!(invitations.invitations.is_empty());
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_403_if_inviting_without_admin_role() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    app.post_login(&serde_json::json!({ "email": email, "password": "password123" })).await;
+
+    let response = app.post_invitation(&serde_json::json!({ "email": get_random_email() })).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.clean_up().await;
+}
@@ -1,6 +1,6 @@
 use crate::helpers::{TestApp, get_random_email};
 use auth_service::{
-    domain::Email,
+    domain::{Email, TenantId},
     routes::{LoginResponse, TwoFactorAuthResponse},
     utils::{
         constants::JWT_COOKIE_NAME,
@@ -22,6 +22,11 @@ async fn should_return_422_if_malformed_credentials() {
         serde_json::json!({
             "email": email,
         }),
+        serde_json::json!({
+            "email": email,
+            "username": "jane.doe",
+            "password": "password123",
+        }),
     ];
 
     for test_case in test_cases {
@@ -179,7 +184,7 @@ async fn should_return_206_if_valid_credentials_and_2fa_enabled() {
     let (login_attempt_id, _) = app.two_fa_code_store
         .read()
         .await
-        .get_code(email)
+        .get_code(&TenantId::default(), email)
         .await
         .expect("The code was not added");
 
@@ -198,3 +203,55 @@ async fn should_return_206_if_valid_credentials_and_2fa_enabled() {
 
     app.clean_up().await;
 }
+
+#[tokio::test]
+async fn should_return_200_if_valid_username_credentials() {
+    let mut app = TestApp::new().await;
+
+    let _response = app.post_signup(&serde_json::json!({
+        "email": get_random_email(),
+        "password": "password123",
+        "requires2FA": false,
+        "username": "Jane.Doe",
+    })).await;
+
+    // Usernames match ignoring case
+    let response = app.post_login(&serde_json::json!({
+        "username": "jane.doe",
+        "password": "password123",
+    })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.cookies().any(|cookie| cookie.name() == JWT_COOKIE_NAME));
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_unknown_username_or_wrong_password() {
+    let mut app = TestApp::new().await;
+
+    let _response = app.post_signup(&serde_json::json!({
+        "email": get_random_email(),
+        "password": "password123",
+        "requires2FA": false,
+        "username": "jane.doe",
+    })).await;
+
+    let test_cases = [
+        serde_json::json!({ "username": "john.doe", "password": "password123" }),
+        serde_json::json!({ "username": "jane.doe", "password": "wrong-password" }),
+    ];
+
+    for test_case in test_cases {
+        let response = app.post_login(&test_case).await;
+        assert_eq!(response.status().as_u16(), 401, "Failed for input: {:?}", test_case);
+    }
+
+    assert_eq!(
+        app.post_login(&serde_json::json!({ "username": "jane doe", "password": "password123" })).await.status().as_u16(),
+        400
+    );
+
+    app.clean_up().await;
+}
@@ -0,0 +1,124 @@
+use auth_service::routes::MagicLinkResponse;
+
+use crate::helpers::{auth_token, get_random_email, TestApp};
+
+async fn signup(app: &TestApp, email: &str) {
+    let response = app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+
+    assert_eq!(response.status().as_u16(), 201);
+}
+
+#[tokio::test]
+async fn should_return_400_if_invalid_email() {
+    let mut app = TestApp::new().await;
+
+    let response = app.post_magic_link(&serde_json::json!({ "email": "not-an-email" })).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_200_without_sending_email_if_user_does_not_exist() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    let response = app.post_magic_link(&serde_json::json!({ "email": email })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.json::<MagicLinkResponse>().await.is_ok());
+    assert!(app.last_email_to(&email).is_none());
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_sign_in_with_magic_link() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    signup(&app, &email).await;
+
+    let response = app.post_magic_link(&serde_json::json!({ "email": email })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let token = app.last_email_to(&email)
+        .and_then(|email| email.link_param("token"))
+        .expect("No magic link sent");
+
+    let response = app.get_verify_magic_link(&token).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.url().path(), "/");
+
+    let auth_token = auth_token(&app).expect("No auth cookie found");
+    let response = app.post_verify_token(&serde_json::json!({ "token": auth_token })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_magic_link_is_reused() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    signup(&app, &email).await;
+
+    app.post_magic_link(&serde_json::json!({ "email": email })).await;
+    let token = app.last_email_to(&email)
+        .and_then(|email| email.link_param("token"))
+        .expect("No magic link sent");
+
+    let response = app.get_verify_magic_link(&token).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.get_verify_magic_link(&token).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_token_is_invalid() {
+    let mut app = TestApp::new().await;
+
+    let response = app.get_verify_magic_link("invalid-token").await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_only_accept_bound_link_in_requesting_browser() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    signup(&app, &email).await;
+
+    let response = app.post_magic_link(&serde_json::json!({
+        "email": email,
+        "requireSameBrowser": true,
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let token = app.last_email_to(&email)
+        .and_then(|email| email.link_param("token"))
+        .expect("No magic link sent");
+
+    let other_browser = reqwest::Client::builder()
+        .cookie_store(true)
+        .build()
+        .unwrap();
+    let response = other_browser
+        .get(app.verify_magic_link_url(&token))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.get_verify_magic_link(&token).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(auth_token(&app).is_some());
+
+    app.clean_up().await;
+}
@@ -6,3 +6,23 @@ mod signup;
 // mod verify_2fa;
 mod verify_token;
 mod delete_account;
+mod oidc;
+mod identities;
+mod saml;
+mod magic_link;
+mod roles;
+mod tenants;
+mod invitations;
+mod admin_users;
+mod reauthenticate;
+mod audit_events;
+mod webhooks;
+mod new_device;
+mod trusted_devices;
+mod password_policy;
+mod password_hashing;
+mod data_export;
+mod me;
+mod consents;
+mod impersonation;
+mod scim;
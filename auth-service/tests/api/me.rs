@@ -0,0 +1,114 @@
+use auth_service::{routes::{MeResponse, ProfileResponse}, ErrorResponse};
+
+use crate::helpers::{get_random_email, TestApp};
+
+async fn sign_up_and_log_in(app: &TestApp) -> String {
+    let email = get_random_email();
+    app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    app.post_login(&serde_json::json!({ "email": email, "password": "password123" })).await;
+    email
+}
+
+#[tokio::test]
+async fn should_return_the_current_user() {
+    let mut app = TestApp::new().await;
+    let email = sign_up_and_log_in(&app).await;
+
+    let response = app.get_me().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let me = response.json::<MeResponse>().await.unwrap();
+    assert_eq!(me.email, email);
+    assert!(!me.requires_2fa);
+    assert_eq!(me.username, None);
+    assert!(uuid::Uuid::parse_str(&me.id).is_ok());
+    assert_eq!(me.profile, ProfileResponse { display_name: None, locale: None, timezone: None, avatar_url: None });
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_update_only_the_given_profile_fields() {
+    let mut app = TestApp::new().await;
+    sign_up_and_log_in(&app).await;
+
+    let response = app.patch_me(&serde_json::json!({
+        "displayName": "Jane Doe",
+        "locale": "pt_br",
+        "timezone": "America/Sao_Paulo",
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let profile = response.json::<MeResponse>().await.unwrap().profile;
+    assert_eq!(profile.locale.as_deref(), Some("pt-BR"));
+
+    let response = app.patch_me(&serde_json::json!({
+        "displayName": null,
+        "avatarUrl": "https://cdn.example.com/jane.png",
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let profile = app.get_me().await.json::<MeResponse>().await.unwrap().profile;
+    assert_eq!(profile, ProfileResponse {
+        display_name: None,
+        locale: Some("pt-BR".to_owned()),
+        timezone: Some("America/Sao_Paulo".to_owned()),
+        avatar_url: Some("https://cdn.example.com/jane.png".to_owned()),
+    });
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_for_invalid_profile_fields() {
+    let mut app = TestApp::new().await;
+    sign_up_and_log_in(&app).await;
+
+    let invalid_changes = [
+        serde_json::json!({ "displayName": "   " }),
+        serde_json::json!({ "locale": "english" }),
+        serde_json::json!({ "timezone": "Mars/Olympus_Mons" }),
+        serde_json::json!({ "avatarUrl": "http://cdn.example.com/jane.png" }),
+    ];
+    for change in invalid_changes {
+        assert_eq!(app.patch_me(&change).await.status().as_u16(), 400, "Accepted {}", change);
+    }
+
+    let response = app.patch_me(&serde_json::json!({ "timezone": "Mars/Olympus_Mons" })).await;
+    assert_eq!(response.json::<ErrorResponse>().await.unwrap().error, "Invalid time zone");
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_set_a_username_to_log_in_with() {
+    let mut app = TestApp::new().await;
+    let email = sign_up_and_log_in(&app).await;
+
+    let response = app.patch_me(&serde_json::json!({ "username": "jane.doe" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.json::<MeResponse>().await.unwrap().username.as_deref(), Some("jane.doe"));
+
+    let response = app.post_login(&serde_json::json!({ "username": "jane.doe", "password": "password123" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(app.get_me().await.json::<MeResponse>().await.unwrap().email, email);
+
+    // Another user can't take it, whatever the case
+    sign_up_and_log_in(&app).await;
+    assert_eq!(app.patch_me(&serde_json::json!({ "username": "Jane.Doe" })).await.status().as_u16(), 409);
+    assert_eq!(app.patch_me(&serde_json::json!({ "username": "jd" })).await.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_without_session() {
+    let mut app = TestApp::new().await;
+
+    assert_eq!(app.get_me().await.status().as_u16(), 400);
+    assert_eq!(app.patch_me(&serde_json::json!({ "locale": "en" })).await.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
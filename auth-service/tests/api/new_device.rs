@@ -0,0 +1,104 @@
+use reqwest::Url;
+
+use crate::helpers::{auth_token, get_random_email, TestApp};
+
+const NEW_DEVICE_SUBJECT: &str = "New sign-in to your account";
+
+async fn sign_up(app: &TestApp, email: &str) {
+    let response = app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    assert_eq!(response.status().as_u16(), 201);
+}
+
+fn credentials(email: &str, password: &str) -> serde_json::Value {
+    serde_json::json!({ "email": email, "password": password })
+}
+
+#[tokio::test]
+async fn should_only_email_about_unseen_devices() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    sign_up(&app, &email).await;
+    let new_device_emails = || app.sent_emails
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|sent| sent.recipient == email && sent.subject == NEW_DEVICE_SUBJECT)
+        .count();
+
+    let response = app.post_login_from(&credentials(&email, "password123"), "Firefox").await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(new_device_emails(), 0);
+
+    app.post_login_from(&credentials(&email, "password123"), "Firefox").await;
+    assert_eq!(new_device_emails(), 0);
+
+    app.post_login_from(&credentials(&email, "wrong-password"), "Chrome").await;
+    assert_eq!(new_device_emails(), 0);
+
+    app.post_login_from(&credentials(&email, "password123"), "Chrome").await;
+    assert_eq!(new_device_emails(), 1);
+    let sent = app.last_email_to(&email).unwrap();
+    assert!(sent.content.contains("Device: Chrome"));
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_revoke_access_when_sign_in_is_reported() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    sign_up(&app, &email).await;
+    app.post_login_from(&credentials(&email, "password123"), "Firefox").await;
+    app.post_login_from(&credentials(&email, "password123"), "Chrome").await;
+    let session = auth_token(&app).expect("No auth cookie found");
+
+    let token = app
+        .last_email_to(&email)
+        .and_then(|sent| sent.link_param("token"))
+        .expect("No new device email was sent");
+
+    // Opening the link only asks to confirm.
+    let response = app.get_login_report(&token).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.text().await.unwrap().contains(&token));
+    assert_eq!(app.post_verify_token(&serde_json::json!({ "token": session })).await.status().as_u16(), 200);
+
+    let response = app.post_login_report(&token).await;
+    assert_eq!(response.status().as_u16(), 303);
+    let verify_response = app.post_verify_token(&serde_json::json!({ "token": session })).await;
+    assert_eq!(verify_response.status().as_u16(), 401);
+    assert_eq!(app.post_login(&credentials(&email, "password123")).await.status().as_u16(), 401);
+
+    let location = response.headers()["location"].to_str().unwrap();
+    let reset_token = Url::parse(location)
+        .unwrap()
+        .query_pairs()
+        .find(|(key, _)| key == "token")
+        .map(|(_, value)| value.into_owned())
+        .expect("No password reset token in the redirect");
+
+    let body = serde_json::json!({ "token": reset_token, "password": "new-password123" });
+    assert_eq!(app.post_password_reset(&body).await.status().as_u16(), 200);
+    assert_eq!(app.post_login(&credentials(&email, "new-password123")).await.status().as_u16(), 200);
+
+    // The link can't be used again to take away the new password.
+    assert_eq!(app.get_login_report(&token).await.status().as_u16(), 401);
+    assert_eq!(app.post_login_report(&token).await.status().as_u16(), 401);
+    assert_eq!(app.post_login(&credentials(&email, "new-password123")).await.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_login_report_token_is_invalid() {
+    let mut app = TestApp::new().await;
+
+    assert_eq!(app.get_login_report("invalid").await.status().as_u16(), 401);
+    assert_eq!(app.post_login_report("invalid").await.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
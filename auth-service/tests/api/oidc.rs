@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use auth_service::{
+    domain::{Email, TenantId},
+    utils::parsable::Parsable,
+};
+use secrecy::ExposeSecret;
+use wiremock::{
+    matchers::{method, path},
+    Mock, ResponseTemplate,
+};
+
+use crate::helpers::{
+    auth_token, get_random_email, mount_identity_provider, TestApp, OIDC_CLIENT_ID, OIDC_PROVIDER,
+};
+
+#[tokio::test]
+async fn should_return_404_if_unknown_provider() {
+    let mut app = TestApp::new().await;
+
+    let response = app.get_oidc_login("unknown").await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    let response = app.get_oidc_callback("unknown", &[("state", "state"), ("code", "code")]).await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_redirect_to_provider_with_state_nonce_and_pkce() {
+    let mut app = TestApp::new().await;
+    mount_identity_provider(&app, &get_random_email(), true).await;
+
+    Mock::given(method("GET"))
+        .and(path("/authorize"))
+        .respond_with(ResponseTemplate::new(200))
+        .with_priority(1)
+        .mount(&app.oidc_server)
+        .await;
+
+    let response = app.get_oidc_login(OIDC_PROVIDER).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let params: HashMap<String, String> = response.url().query_pairs().into_owned().collect();
+    assert_eq!(response.url().path(), "/authorize");
+    assert_eq!(params["client_id"], OIDC_CLIENT_ID);
+    assert_eq!(params["code_challenge_method"], "S256");
+    assert!(!params["state"].is_empty());
+    assert!(!params["nonce"].is_empty());
+    assert!(!params["code_challenge"].is_empty());
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_state_is_unknown() {
+    let mut app = TestApp::new().await;
+
+    let response = app
+        .get_oidc_callback(OIDC_PROVIDER, &[("state", "unknown-state"), ("code", "code")])
+        .await;
+
+    assert_eq!(response.status().as_u16(), 401);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_callback_is_opened_in_another_browser() {
+    let mut app = TestApp::new().await;
+    mount_identity_provider(&app, &get_random_email(), true).await;
+
+    Mock::given(method("GET"))
+        .and(path("/authorize"))
+        .respond_with(ResponseTemplate::new(200))
+        .with_priority(1)
+        .mount(&app.oidc_server)
+        .await;
+
+    let response = app.get_oidc_login(OIDC_PROVIDER).await;
+    let params: HashMap<String, String> = response.url().query_pairs().into_owned().collect();
+
+    // A browser without the state cookie, like a victim sent the attacker's callback
+    let url = reqwest::Url::parse_with_params(
+        &format!("{}/oidc/{}/callback", &app.address, OIDC_PROVIDER),
+        &[("state", params["state"].as_str()), ("code", "authorization-code")],
+    )
+    .unwrap();
+    let response = reqwest::get(url).await.unwrap();
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_create_passwordless_user_and_set_auth_cookie() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    mount_identity_provider(&app, &email, true).await;
+
+    let response = app.get_oidc_login(OIDC_PROVIDER).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.url().path(), "/");
+
+    let token = auth_token(&app).expect("No auth cookie found");
+    let response = app.post_verify_token(&serde_json::json!({ "token": token })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_login(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+    })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_sign_in_existing_user_with_the_same_verified_email() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    let response = app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    mount_identity_provider(&app, &email, true).await;
+
+    let response = app.get_oidc_login(OIDC_PROVIDER).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(auth_token(&app).is_some());
+
+    let response = app.post_login(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_require_2fa_from_existing_user_who_has_it_enabled() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    let response = app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": true,
+    })).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    mount_identity_provider(&app, &email, true).await;
+
+    let response = app.get_oidc_login(OIDC_PROVIDER).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(auth_token(&app).is_none());
+    assert!(app.last_email_to(&email).is_some_and(|sent| sent.subject == "2FA code"));
+
+    let (login_attempt_id, code) = app.two_fa_code_store
+        .read()
+        .await
+        .get_code(&TenantId::default(), Email::parse(&email).unwrap())
+        .await
+        .expect("The code was not added");
+
+    let response = app.post_verify_2fa(&serde_json::json!({
+        "email": email,
+        "loginAttemptId": login_attempt_id.as_ref().expose_secret(),
+        "2FACode": code.as_ref().expose_secret(),
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(auth_token(&app).is_some());
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_email_is_not_verified() {
+    let mut app = TestApp::new().await;
+    mount_identity_provider(&app, &get_random_email(), false).await;
+
+    let response = app.get_oidc_login(OIDC_PROVIDER).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+    assert!(auth_token(&app).is_none());
+    app.clean_up().await;
+}
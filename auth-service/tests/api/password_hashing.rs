@@ -0,0 +1,53 @@
+use auth_service::{
+    domain::{Email, TenantId, User, UserStoreError},
+    utils::parsable::Parsable,
+};
+use secrecy::{ExposeSecret, Secret};
+
+use crate::helpers::{get_random_email, TestApp};
+
+#[tokio::test]
+async fn should_rehash_imported_bcrypt_passwords_on_login() {
+    let mut app = TestApp::new().await;
+    let tenant = TenantId::default();
+    let email = get_random_email();
+
+    let mut user_store = app.user_store.write().await;
+    user_store.add_user(&tenant, User::new_passwordless(Email::parse(&email).unwrap())).await.unwrap();
+    let bcrypt_hash = bcrypt::hash("imported123", 4).unwrap();
+    user_store.set_password_hash(&tenant, &email, Secret::new(bcrypt_hash)).await.unwrap();
+    drop(user_store);
+
+    let response = app.post_login(&serde_json::json!({
+        "email": email,
+        "password": "imported123",
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let user = app.user_store.read().await.get_user(&tenant, &email).await.unwrap();
+    assert!(user.password.unwrap().as_ref().expose_secret().starts_with("$argon2id$"));
+
+    // Still the same password
+    let response = app.post_login(&serde_json::json!({
+        "email": email,
+        "password": "imported123",
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_reject_unsupported_password_hashes() {
+    let mut app = TestApp::new().await;
+    let tenant = TenantId::default();
+    let email = get_random_email();
+
+    let mut user_store = app.user_store.write().await;
+    user_store.add_user(&tenant, User::new_passwordless(Email::parse(&email).unwrap())).await.unwrap();
+    let result = user_store.set_password_hash(&tenant, &email, Secret::new("5f4dcc3b5aa765d61d8327deb882cf99".to_owned())).await;
+    assert_eq!(result, Err(UserStoreError::UnsupportedPasswordHash));
+    drop(user_store);
+
+    app.clean_up().await;
+}
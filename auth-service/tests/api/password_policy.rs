@@ -0,0 +1,66 @@
+use auth_service::{services::password_policy::PasswordPolicy, ErrorResponse};
+
+use crate::helpers::{get_random_email, TestApp};
+
+#[tokio::test]
+async fn should_reject_weak_passwords_with_reasons() {
+    let mut app = TestApp::with_password_policy(PasswordPolicy::default()).await;
+
+    let response = app.post_signup(&serde_json::json!({
+        "email": "jane.doe@example.com",
+        "password": "janedoe123",
+        "requires2FA": false,
+    })).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let body = response.json::<ErrorResponse>().await.unwrap();
+    assert_eq!(body.error, "Password rejected");
+    assert_eq!(body.reasons, vec![
+        "Password contains the email address".to_string(),
+        "Password is too easy to guess".to_string(),
+    ]);
+
+    let response = app.post_signup(&serde_json::json!({
+        "email": "jane.doe@example.com",
+        "password": "password",
+        "requires2FA": false,
+    })).await;
+    let body = response.json::<ErrorResponse>().await.unwrap();
+    assert!(body.reasons.contains(&"Password is too common".to_string()));
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_accept_strong_passwords() {
+    let mut app = TestApp::with_password_policy(PasswordPolicy::default()).await;
+
+    let response = app.post_signup(&serde_json::json!({
+        "email": get_random_email(),
+        "password": "kitten-lamp-42-violin",
+        "requires2FA": false,
+    })).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_hold_password_changes_to_the_policy() {
+    let mut app = TestApp::with_password_policy(PasswordPolicy::default()).await;
+    let email = get_random_email();
+    app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": "kitten-lamp-42-violin",
+        "requires2FA": false,
+    })).await;
+    app.post_login(&serde_json::json!({ "email": email, "password": "kitten-lamp-42-violin" })).await;
+
+    let response = app.post_change_password(&serde_json::json!({ "newPassword": "qwerty123" })).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app.post_change_password(&serde_json::json!({ "newPassword": "violin-cloud-17-harbor" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
@@ -0,0 +1,86 @@
+use auth_service::{
+    domain::{Email, TenantId},
+    routes::LoginResponse,
+    utils::parsable::Parsable,
+};
+use secrecy::ExposeSecret;
+
+use crate::helpers::{get_random_email, set_stale_auth_cookie, TestApp};
+
+async fn sign_up_and_log_in(app: &TestApp, email: &str, requires_2fa: bool) {
+    app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": requires_2fa,
+    })).await;
+    app.post_login(&serde_json::json!({ "email": email, "password": "password123" })).await;
+}
+
+#[tokio::test]
+async fn should_require_recent_auth_for_sensitive_actions() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    sign_up_and_log_in(&app, &email, false).await;
+    set_stale_auth_cookie(&app, &email);
+
+    let response = app.post_change_password(&serde_json::json!({ "newPassword": "password456" })).await;
+    assert_eq!(response.status().as_u16(), 401);
+    assert_eq!(app.post_disable_2fa().await.status().as_u16(), 401);
+    assert_eq!(app.delete_account().await.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_allow_sensitive_actions_after_reauthenticating() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    sign_up_and_log_in(&app, &email, false).await;
+    set_stale_auth_cookie(&app, &email);
+
+    let response = app.post_reauthenticate(&serde_json::json!({ "password": "wrong-password" })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_reauthenticate(&serde_json::json!({ "password": "password123" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_change_password(&serde_json::json!({ "newPassword": "password456" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_login(&serde_json::json!({ "email": email, "password": "password456" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_complete_reauthentication_with_2fa() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    sign_up_and_log_in(&app, &email, true).await;
+    set_stale_auth_cookie(&app, &email);
+
+    let response = app.post_reauthenticate(&serde_json::json!({ "password": "password123" })).await;
+    assert_eq!(response.status().as_u16(), 206);
+    let login_attempt_id = match response.json::<LoginResponse>().await.unwrap() {
+        LoginResponse::TwoFactorAuth(response) => response.login_attempt_id,
+        _ => panic!("Expected a 2FA response"),
+    };
+
+    let (_, code) = app.two_fa_code_store
+        .read()
+        .await
+        .get_code(&TenantId::default(), Email::parse(&email).unwrap())
+        .await
+        .unwrap();
+    let response = app.post_verify_2fa(&serde_json::json!({
+        "email": email,
+        "loginAttemptId": login_attempt_id,
+        "2FACode": code.as_ref().expose_secret(),
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    assert_eq!(app.post_disable_2fa().await.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
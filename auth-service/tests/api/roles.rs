@@ -0,0 +1,135 @@
+use auth_service::{domain::TenantId, routes::VerifyTokenResponse};
+
+use crate::helpers::{auth_token, get_random_email, TestApp};
+
+async fn signup_and_login(app: &TestApp, email: &str) {
+    let response = app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    login(app, email).await;
+}
+
+// Roles are read when the token is issued, so granting one needs a new login
+async fn login(app: &TestApp, email: &str) {
+    let response = app.post_login(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+async fn verified_roles(app: &TestApp) -> VerifyTokenResponse {
+    let token = auth_token(app).expect("No auth cookie found");
+    let response = app.post_verify_token(&serde_json::json!({ "token": token })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    response.json::<VerifyTokenResponse>().await.unwrap()
+}
+
+#[tokio::test]
+async fn should_include_roles_and_permissions_in_token() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    signup_and_login(&app, &email).await;
+    let claims = verified_roles(&app).await;
+    assert_eq!(claims.email, email);
+    assert!(claims.roles.is_empty());
+
+    app.grant_role(&email, "admin").await;
+    login(&app, &email).await;
+
+    let claims = verified_roles(&app).await;
+    assert_eq!(claims.roles, vec!["admin".to_owned()]);
+    assert!(claims.permissions.contains(&"roles:manage".to_owned()));
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_not_signed_in() {
+    let mut app = TestApp::new().await;
+
+    let response = app.post_assign_role(&serde_json::json!({
+        "email": get_random_email(),
+        "role": "admin",
+    })).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_403_if_not_admin() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    signup_and_login(&app, &email).await;
+
+    let response = app.post_assign_role(&serde_json::json!({
+        "email": email,
+        "role": "admin",
+    })).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_assign_and_remove_roles_as_admin() {
+    let mut app = TestApp::new().await;
+    let admin = get_random_email();
+    let user = get_random_email();
+
+    let response = app.post_signup(&serde_json::json!({
+        "email": user,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    signup_and_login(&app, &admin).await;
+    app.grant_role(&admin, "admin").await;
+    login(&app, &admin).await;
+
+    let body = serde_json::json!({ "email": user, "role": "admin" });
+    let response = app.post_assign_role(&body).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let authorization = app.user_store.read().await.get_authorization(&TenantId::default(), &user).await.unwrap();
+    assert_eq!(authorization.roles.len(), 1);
+
+    let response = app.post_remove_role(&body).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let authorization = app.user_store.read().await.get_authorization(&TenantId::default(), &user).await.unwrap();
+    assert!(authorization.roles.is_empty());
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_404_for_unknown_user_or_role() {
+    let mut app = TestApp::new().await;
+    let admin = get_random_email();
+
+    signup_and_login(&app, &admin).await;
+    app.grant_role(&admin, "admin").await;
+    login(&app, &admin).await;
+
+    let response = app.post_assign_role(&serde_json::json!({
+        "email": get_random_email(),
+        "role": "admin",
+    })).await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    let response = app.post_assign_role(&serde_json::json!({
+        "email": admin,
+        "role": "auditor",
+    })).await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.clean_up().await;
+}
@@ -0,0 +1,118 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use auth_service::routes::ListIdentitiesResponse;
+
+use crate::helpers::{
+    auth_token, get_random_email, signed_saml_response, TestApp, SAML_ACS_URL, SAML_IDP, SAML_SP_ENTITY_ID,
+};
+
+#[tokio::test]
+async fn should_return_service_provider_metadata() {
+    let mut app = TestApp::new().await;
+
+    let response = app.get_saml_metadata().await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "application/samlmetadata+xml"
+    );
+
+    let metadata = response.text().await.unwrap();
+    assert!(metadata.contains(&format!(r#"entityID="{}""#, SAML_SP_ENTITY_ID)));
+    assert!(metadata.contains(&format!(r#"Location="{}""#, SAML_ACS_URL)));
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_create_passwordless_user_and_set_auth_cookie() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    let saml_response = signed_saml_response("jane.doe", &email);
+    let response = app.post_saml_acs(&[("SAMLResponse", &saml_response)]).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.url().path(), "/");
+
+    let token = auth_token(&app).expect("No auth cookie found");
+    let response = app.post_verify_token(&serde_json::json!({ "token": token })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let identities = app.get_identities().await.json::<ListIdentitiesResponse>().await.unwrap();
+    assert_eq!(identities.identities.len(), 1);
+    assert_eq!(identities.identities[0].provider, format!("saml:{}", SAML_IDP));
+    assert_eq!(identities.identities[0].subject, "jane.doe");
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_sign_in_existing_user_with_matching_email() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    let response = app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let saml_response = signed_saml_response("existing.user", &email);
+    let response = app.post_saml_acs(&[("SAMLResponse", &saml_response)]).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(auth_token(&app).is_some());
+
+    let response = app.post_login(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_only_follow_local_relay_state() {
+    let mut app = TestApp::new().await;
+
+    let saml_response = signed_saml_response("jane.doe", &get_random_email());
+    let response = app
+        .post_saml_acs(&[("SAMLResponse", &saml_response), ("RelayState", "//evil.example.com/")])
+        .await;
+
+    assert_eq!(response.url().host_str(), Some("127.0.0.1"));
+    assert_eq!(response.url().path(), "/");
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_assertion_is_replayed() {
+    let mut app = TestApp::new().await;
+
+    let saml_response = signed_saml_response("jane.doe", &get_random_email());
+    let response = app.post_saml_acs(&[("SAMLResponse", &saml_response)]).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_saml_acs(&[("SAMLResponse", &saml_response)]).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_assertion_is_tampered_with() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    let saml_response = signed_saml_response("jane.doe", &email);
+    let xml = String::from_utf8(STANDARD.decode(saml_response).unwrap()).unwrap();
+    let tampered = STANDARD.encode(xml.replace(&email, &get_random_email()));
+
+    let response = app.post_saml_acs(&[("SAMLResponse", &tampered)]).await;
+    assert_eq!(response.status().as_u16(), 401);
+    assert!(auth_token(&app).is_none());
+
+    app.clean_up().await;
+}
@@ -0,0 +1,243 @@
+use auth_service::routes::{ListResponse, ScimGroup, ScimReference, ScimTokenResponse, ScimUser};
+
+use crate::helpers::{get_random_email, TestApp};
+
+const USER_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:User";
+const GROUP_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:Group";
+const PATCH_OP_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:PatchOp";
+
+async fn sign_up(app: &TestApp) -> String {
+    let email = get_random_email();
+    let response = app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    assert_eq!(response.status().as_u16(), 201);
+    email
+}
+
+async fn log_in(app: &TestApp, email: &str) -> reqwest::Response {
+    app.post_login(&serde_json::json!({ "email": email, "password": "password123" })).await
+}
+
+// Signs in an admin and issues a SCIM token for the default tenant
+async fn scim_token(app: &TestApp) -> String {
+    let admin = sign_up(app).await;
+    app.grant_role(&admin, "admin").await;
+    assert_eq!(log_in(app, &admin).await.status().as_u16(), 200);
+
+    let response = app.post_scim_token().await;
+    assert_eq!(response.status().as_u16(), 201);
+    response.json::<ScimTokenResponse>().await.unwrap().token
+}
+
+async fn create_user(app: &TestApp, token: &str, email: &str) -> ScimUser {
+    let response = app.post_scim(token, "Users", &serde_json::json!({
+        "schemas": [USER_SCHEMA],
+        "userName": email,
+        "displayName": "Jane Doe",
+        "active": true,
+    })).await;
+    assert_eq!(response.status().as_u16(), 201);
+    response.json::<ScimUser>().await.unwrap()
+}
+
+async fn find_user(app: &TestApp, token: &str, email: &str) -> ListResponse {
+    let filter = format!("userName eq \"{}\"", email);
+    let response = app.get_scim(token, "Users", &[("filter", &filter)]).await;
+    assert_eq!(response.status().as_u16(), 200);
+    response.json::<ListResponse>().await.unwrap()
+}
+
+fn patch(operations: serde_json::Value) -> serde_json::Value {
+    serde_json::json!({ "schemas": [PATCH_OP_SCHEMA], "Operations": operations })
+}
+
+#[tokio::test]
+async fn should_return_401_without_a_valid_token() {
+    let mut app = TestApp::new().await;
+    scim_token(&app).await;
+
+    let response = app.get_scim("scim_wrong", "Users", &[]).await;
+    assert_eq!(response.status().as_u16(), 401);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "application/scim+json"
+    );
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_invalidate_the_previous_token() {
+    let mut app = TestApp::new().await;
+    let old_token = scim_token(&app).await;
+    let response = app.post_scim_token().await;
+    let new_token = response.json::<ScimTokenResponse>().await.unwrap().token;
+
+    assert_eq!(app.get_scim(&old_token, "Users", &[]).await.status().as_u16(), 401);
+    assert_eq!(app.get_scim(&new_token, "Users", &[]).await.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_create_and_find_a_user() {
+    let mut app = TestApp::new().await;
+    let token = scim_token(&app).await;
+    let email = get_random_email();
+
+    let created = create_user(&app, &token, &email).await;
+    assert_eq!(created.user_name, email);
+    assert_eq!(created.display_name.as_deref(), Some("Jane Doe"));
+    assert!(created.active);
+
+    let response = app.get_scim(&token, &format!("Users/{}", created.id), &[]).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.json::<ScimUser>().await.unwrap(), created);
+
+    let found = find_user(&app, &token, &email).await;
+    assert_eq!(found.total_results, 1);
+    assert_eq!(found.resources[0]["id"], created.id);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_409_for_an_existing_user_name() {
+    let mut app = TestApp::new().await;
+    let token = scim_token(&app).await;
+    let email = get_random_email();
+    create_user(&app, &token, &email).await;
+
+    let response = app.post_scim(&token, "Users", &serde_json::json!({
+        "schemas": [USER_SCHEMA],
+        "userName": email,
+    })).await;
+    assert_eq!(response.status().as_u16(), 409);
+    let body = response.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(body["scimType"], "uniqueness");
+    assert_eq!(body["status"], "409");
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_for_an_invalid_filter() {
+    let mut app = TestApp::new().await;
+    let token = scim_token(&app).await;
+
+    let response = app.get_scim(&token, "Users", &[("filter", "userName eq")]).await;
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(response.json::<serde_json::Value>().await.unwrap()["scimType"], "invalidFilter");
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_prevent_a_deactivated_user_from_signing_in() {
+    let mut app = TestApp::new().await;
+    let token = scim_token(&app).await;
+    let user = sign_up(&app).await;
+    let id = find_user(&app, &token, &user).await.resources[0]["id"].as_str().unwrap().to_owned();
+
+    let response = app.patch_scim(&token, &format!("Users/{}", id), &patch(serde_json::json!([
+        { "op": "Replace", "path": "active", "value": "False" },
+    ]))).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(!response.json::<ScimUser>().await.unwrap().active);
+    assert_eq!(log_in(&app, &user).await.status().as_u16(), 403);
+
+    let response = app.patch_scim(&token, &format!("Users/{}", id), &patch(serde_json::json!([
+        { "op": "replace", "value": { "active": true } },
+    ]))).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(log_in(&app, &user).await.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_not_change_the_user_name() {
+    let mut app = TestApp::new().await;
+    let token = scim_token(&app).await;
+    let user = create_user(&app, &token, &get_random_email()).await;
+
+    let response = app.patch_scim(&token, &format!("Users/{}", user.id), &patch(serde_json::json!([
+        { "op": "replace", "path": "userName", "value": get_random_email() },
+    ]))).await;
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(response.json::<serde_json::Value>().await.unwrap()["scimType"], "mutability");
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_delete_a_user() {
+    let mut app = TestApp::new().await;
+    let token = scim_token(&app).await;
+    let user = create_user(&app, &token, &get_random_email()).await;
+    let resource = format!("Users/{}", user.id);
+
+    assert_eq!(app.delete_scim(&token, &resource).await.status().as_u16(), 204);
+    assert_eq!(app.get_scim(&token, &resource, &[]).await.status().as_u16(), 404);
+    assert_eq!(app.delete_scim(&token, &resource).await.status().as_u16(), 404);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_manage_group_members() {
+    let mut app = TestApp::new().await;
+    let token = scim_token(&app).await;
+    let jane = create_user(&app, &token, &get_random_email()).await;
+    let john = create_user(&app, &token, &get_random_email()).await;
+
+    let response = app.post_scim(&token, "Groups", &serde_json::json!({
+        "schemas": [GROUP_SCHEMA],
+        "displayName": "Engineering",
+        "members": [{ "value": jane.id }],
+    })).await;
+    assert_eq!(response.status().as_u16(), 201);
+    let group = response.json::<ScimGroup>().await.unwrap();
+    let resource = format!("Groups/{}", group.id);
+
+    let response = app.patch_scim(&token, &resource, &patch(serde_json::json!([
+        { "op": "add", "path": "members", "value": [{ "value": john.id }] },
+        { "op": "remove", "path": format!("members[value eq \"{}\"]", jane.id) },
+        { "op": "replace", "path": "displayName", "value": "Platform" },
+    ]))).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let group = response.json::<ScimGroup>().await.unwrap();
+    assert_eq!(group.display_name, "Platform");
+    assert_eq!(group.members, vec![ScimReference { value: john.id.clone(), display: john.user_name.clone() }]);
+
+    let response = app.get_scim(&token, &format!("Users/{}", john.id), &[]).await;
+    let groups = response.json::<ScimUser>().await.unwrap().groups;
+    assert_eq!(groups, vec![ScimReference { value: group.id.clone(), display: "Platform".to_owned() }]);
+
+    let response = app.get_scim(&token, "Groups", &[("filter", "displayName eq \"platform\"")]).await;
+    assert_eq!(response.json::<ListResponse>().await.unwrap().total_results, 1);
+
+    assert_eq!(app.delete_scim(&token, &resource).await.status().as_u16(), 204);
+    assert_eq!(app.get_scim(&token, &resource, &[]).await.status().as_u16(), 404);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_reject_unknown_group_members() {
+    let mut app = TestApp::new().await;
+    let token = scim_token(&app).await;
+
+    let response = app.post_scim(&token, "Groups", &serde_json::json!({
+        "schemas": [GROUP_SCHEMA],
+        "displayName": "Engineering",
+        "members": [{ "value": uuid::Uuid::new_v4().to_string() }],
+    })).await;
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(response.json::<serde_json::Value>().await.unwrap()["scimType"], "invalidValue");
+
+    app.clean_up().await;
+}
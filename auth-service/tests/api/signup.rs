@@ -130,3 +130,53 @@ async fn should_return_409_if_email_already_exists() {
 
     app.clean_up().await;
 }
+
+#[tokio::test]
+async fn should_return_409_if_username_already_taken() {
+    let mut app = TestApp::new().await;
+
+    let response = app.post_signup(&serde_json::json!({
+        "email": get_random_email(),
+        "password": "password123",
+        "requires2FA": false,
+        "username": "jane.doe",
+    })).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let response = app.post_signup(&serde_json::json!({
+        "email": get_random_email(),
+        "password": "password123",
+        "requires2FA": false,
+        "username": "Jane.Doe",
+    })).await;
+
+    assert_eq!(response.status().as_u16(), 409);
+
+    assert_eq!(
+        response
+            .json::<ErrorResponse>()
+            .await
+            .expect("Could not deserialize response body to ErrorResponse")
+            .error,
+        "Username already taken".to_string()
+    );
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_invalid_username() {
+    let mut app = TestApp::new().await;
+
+    for username in ["jd", "jane doe", "jane@example.com"] {
+        let response = app.post_signup(&serde_json::json!({
+            "email": get_random_email(),
+            "password": "password123",
+            "requires2FA": false,
+            "username": username,
+        })).await;
+        assert_eq!(response.status().as_u16(), 400, "Accepted {}", username);
+    }
+
+    app.clean_up().await;
+}
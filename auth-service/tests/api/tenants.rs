@@ -0,0 +1,151 @@
+use auth_service::{
+    domain::{Role, TenantId},
+    routes::{ListTenantsResponse, VerifyTokenResponse},
+    utils::parsable::Parsable,
+};
+use uuid::Uuid;
+
+use crate::helpers::{auth_token, get_random_email, TestApp};
+
+// Signs in as an admin of the default tenant and creates a new tenant
+async fn create_tenant(app: &TestApp) -> String {
+    let admin = get_random_email();
+    let credentials = serde_json::json!({ "email": admin, "password": "password123" });
+
+    let response = app.post_signup(&serde_json::json!({
+        "email": admin,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    assert_eq!(response.status().as_u16(), 201);
+    app.grant_role(&admin, "admin").await;
+    assert_eq!(app.post_login(&credentials).await.status().as_u16(), 200);
+
+    let tenant = format!("tenant-{}", Uuid::new_v4().simple());
+    let response = app.post_tenant(&serde_json::json!({ "id": tenant, "name": "Acme" })).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    tenant
+}
+
+async fn verified_token(app: &TestApp) -> VerifyTokenResponse {
+    let token = auth_token(app).expect("No auth cookie found");
+    let response = app.post_verify_token(&serde_json::json!({ "token": token })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    response.json::<VerifyTokenResponse>().await.unwrap()
+}
+
+#[tokio::test]
+async fn should_create_and_list_tenants_as_admin() {
+    let mut app = TestApp::new().await;
+    let tenant = create_tenant(&app).await;
+
+    let response = app.post_tenant(&serde_json::json!({ "id": tenant, "name": "Acme" })).await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    let tenants = app.get_tenants().await.json::<ListTenantsResponse>().await.unwrap();
+    assert!(tenants.tenants.iter().any(|t| t.id.as_ref() == tenant));
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_403_if_creating_tenant_without_admin_role() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+
+    app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    app.post_login(&serde_json::json!({ "email": email, "password": "password123" })).await;
+
+    let response = app.post_tenant(&serde_json::json!({ "id": "acme", "name": "Acme" })).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_404_for_unknown_tenant() {
+    let mut app = TestApp::new().await;
+
+    let response = app.post_tenant_signup("unknown", &serde_json::json!({
+        "email": get_random_email(),
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_keep_same_email_separate_in_each_tenant() {
+    let mut app = TestApp::new().await;
+    let tenant = create_tenant(&app).await;
+    let email = get_random_email();
+
+    let response = app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let response = app.post_tenant_signup(&tenant, &serde_json::json!({
+        "email": email,
+        "password": "tenant-password",
+        "requires2FA": false,
+    })).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    // Each tenant only accepts its own account's password
+    let response = app.post_tenant_login(&tenant, &serde_json::json!({
+        "email": email,
+        "password": "password123",
+    })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_tenant_login(&tenant, &serde_json::json!({
+        "email": email,
+        "password": "tenant-password",
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let claims = verified_token(&app).await;
+    assert_eq!(claims.email, email);
+    assert_eq!(claims.tenant, tenant);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_not_let_tenant_admin_manage_other_tenants() {
+    let mut app = TestApp::new().await;
+    let tenant = create_tenant(&app).await;
+    let email = get_random_email();
+
+    app.post_tenant_signup(&tenant, &serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    app.user_store
+        .write()
+        .await
+        .assign_role(&TenantId::parse(&tenant).unwrap(), &email, &Role::admin())
+        .await
+        .unwrap();
+    app.post_tenant_login(&tenant, &serde_json::json!({
+        "email": email,
+        "password": "password123",
+    })).await;
+    assert_eq!(verified_token(&app).await.roles, vec!["admin".to_owned()]);
+
+    let response = app.get_tenants().await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.clean_up().await;
+}
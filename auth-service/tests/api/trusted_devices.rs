@@ -0,0 +1,97 @@
+use auth_service::{
+    domain::{Email, TenantId},
+    routes::{ListTrustedDevicesResponse, LoginResponse},
+    utils::parsable::Parsable,
+};
+use secrecy::ExposeSecret;
+
+use crate::helpers::{get_random_email, TestApp};
+
+async fn sign_up(app: &TestApp, email: &str) {
+    let response = app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": true,
+    })).await;
+    assert_eq!(response.status().as_u16(), 201);
+}
+
+async fn log_in(app: &TestApp, email: &str) -> reqwest::Response {
+    app.post_login(&serde_json::json!({ "email": email, "password": "password123" })).await
+}
+
+// Signs in through 2FA, asking to remember the browser or not
+async fn log_in_with_2fa(app: &TestApp, email: &str, remember_device: bool) {
+    let response = log_in(app, email).await;
+    assert_eq!(response.status().as_u16(), 206);
+    let login_attempt_id = match response.json::<LoginResponse>().await.unwrap() {
+        LoginResponse::TwoFactorAuth(response) => response.login_attempt_id,
+        _ => panic!("Expected a 2FA response"),
+    };
+
+    let (_, code) = app.two_fa_code_store
+        .read()
+        .await
+        .get_code(&TenantId::default(), Email::parse(email).unwrap())
+        .await
+        .unwrap();
+    let response = app.post_verify_2fa(&serde_json::json!({
+        "email": email,
+        "loginAttemptId": login_attempt_id,
+        "2FACode": code.as_ref().expose_secret(),
+        "rememberDevice": remember_device,
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn should_skip_2fa_on_trusted_device() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    sign_up(&app, &email).await;
+
+    log_in_with_2fa(&app, &email, false).await;
+    assert_eq!(log_in(&app, &email).await.status().as_u16(), 206);
+
+    log_in_with_2fa(&app, &email, true).await;
+    assert_eq!(log_in(&app, &email).await.status().as_u16(), 200);
+
+    let response = app.get_trusted_devices().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let devices = response.json::<ListTrustedDevicesResponse>().await.unwrap().devices;
+    assert_eq!(devices.len(), 1);
+    assert!(devices[0].current);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_not_skip_2fa_for_another_user_of_the_device() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    let other = get_random_email();
+    sign_up(&app, &email).await;
+    sign_up(&app, &other).await;
+
+    log_in_with_2fa(&app, &email, true).await;
+    assert_eq!(log_in(&app, &other).await.status().as_u16(), 206);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_require_2fa_again_after_revoking_device() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    sign_up(&app, &email).await;
+    log_in_with_2fa(&app, &email, true).await;
+
+    let devices = app.get_trusted_devices().await.json::<ListTrustedDevicesResponse>().await.unwrap().devices;
+    let body = serde_json::json!({ "id": devices[0].id });
+    assert_eq!(app.post_revoke_trusted_device(&body).await.status().as_u16(), 200);
+    assert_eq!(app.post_revoke_trusted_device(&body).await.status().as_u16(), 404);
+
+    assert_eq!(log_in(&app, &email).await.status().as_u16(), 206);
+
+    app.clean_up().await;
+}
@@ -0,0 +1,189 @@
+use chrono::{Duration, Utc};
+use secrecy::Secret;
+use wiremock::{matchers::{method, path}, Mock, MockServer, ResponseTemplate};
+
+use auth_service::{
+    domain::{WebhookDeliveryStatus, WebhookEventType, MAX_WEBHOOK_DELIVERY_ATTEMPTS},
+    routes::{CreateWebhookResponse, ListWebhookDeliveriesResponse, ListWebhooksResponse},
+    services::{
+        webhook_address_policy::WebhookAddressPolicy,
+        webhook_dispatcher::{sign_webhook_payload, WebhookDispatcher, WEBHOOK_SIGNATURE_HEADER, WEBHOOK_TIMESTAMP_HEADER},
+    },
+};
+
+use crate::helpers::{get_random_email, TestApp};
+
+// Signs up an admin of the default tenant and signs them in
+async fn sign_in_as_admin(app: &TestApp) {
+    let admin = get_random_email();
+
+    let response = app.post_signup(&serde_json::json!({
+        "email": admin,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    assert_eq!(response.status().as_u16(), 201);
+    app.grant_role(&admin, "admin").await;
+
+    let response = app.post_login(&serde_json::json!({ "email": admin, "password": "password123" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+async fn create_webhook(app: &TestApp, url: &str, events: &[&str]) -> CreateWebhookResponse {
+    let response = app.post_webhook(&serde_json::json!({ "url": url, "events": events })).await;
+    assert_eq!(response.status().as_u16(), 201);
+    response.json::<CreateWebhookResponse>().await.unwrap()
+}
+
+fn dispatcher(app: &TestApp) -> WebhookDispatcher {
+    WebhookDispatcher::new(app.webhook_store.clone(), reqwest::Client::new())
+        .with_address_policy(WebhookAddressPolicy::allowing_private_addresses())
+}
+
+#[tokio::test]
+async fn should_deliver_signed_signup_event() {
+    let mut app = TestApp::new().await;
+    sign_in_as_admin(&app).await;
+
+    let receiver = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/hooks"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&receiver)
+        .await;
+    let webhook = create_webhook(&app, &format!("{}/hooks", receiver.uri()), &["user.signed_up"]).await;
+    assert!(webhook.secret.starts_with("whsec_"));
+
+    let email = get_random_email();
+    app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+
+    assert_eq!(dispatcher(&app).dispatch(Utc::now()).await.unwrap(), 1);
+
+    let request = &receiver.received_requests().await.unwrap()[0];
+    let timestamp: i64 = request.headers[WEBHOOK_TIMESTAMP_HEADER].to_str().unwrap().parse().unwrap();
+    let body = String::from_utf8(request.body.clone()).unwrap();
+    assert_eq!(
+        request.headers[WEBHOOK_SIGNATURE_HEADER].to_str().unwrap(),
+        sign_webhook_payload(&Secret::new(webhook.secret.clone()), timestamp, &body)
+    );
+
+    let payload: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(payload["type"], "user.signed_up");
+    assert_eq!(payload["data"]["email"], email);
+
+    let response = app.get_webhook_deliveries(&webhook.webhook.id).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let deliveries = response.json::<ListWebhookDeliveriesResponse>().await.unwrap().deliveries;
+    assert_eq!(deliveries.len(), 1);
+    assert_eq!(deliveries[0].event, WebhookEventType::UserSignedUp);
+    assert_eq!(deliveries[0].status, WebhookDeliveryStatus::Delivered);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_dead_letter_delivery_after_repeated_failures() {
+    let mut app = TestApp::new().await;
+    sign_in_as_admin(&app).await;
+
+    let receiver = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(MAX_WEBHOOK_DELIVERY_ATTEMPTS as u64)
+        .mount(&receiver)
+        .await;
+    let webhook = create_webhook(&app, &receiver.uri(), &["user.signed_up"]).await;
+
+    app.post_signup(&serde_json::json!({
+        "email": get_random_email(),
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+
+    // Each run is far enough ahead for the backoff to be over.
+    let dispatcher = dispatcher(&app);
+    for day in 0..=MAX_WEBHOOK_DELIVERY_ATTEMPTS {
+        assert_eq!(dispatcher.dispatch(Utc::now() + Duration::days(day.into())).await.unwrap(), 0);
+    }
+
+    let deliveries = app
+        .get_webhook_deliveries(&webhook.webhook.id)
+        .await
+        .json::<ListWebhookDeliveriesResponse>()
+        .await
+        .unwrap()
+        .deliveries;
+    assert_eq!(deliveries[0].status, WebhookDeliveryStatus::DeadLetter);
+    assert_eq!(deliveries[0].attempts, MAX_WEBHOOK_DELIVERY_ATTEMPTS);
+    assert_eq!(deliveries[0].next_attempt_at, None);
+    assert_eq!(deliveries[0].last_error.as_deref(), Some("HTTP 503 Service Unavailable"));
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_list_and_delete_webhooks() {
+    let mut app = TestApp::new().await;
+    sign_in_as_admin(&app).await;
+
+    let webhook = create_webhook(&app, "https://example.com/hooks", &["user.deleted", "user.deleted"]).await;
+    assert_eq!(webhook.webhook.events, vec![WebhookEventType::UserDeleted]);
+
+    let webhooks = app.get_webhooks().await.json::<ListWebhooksResponse>().await.unwrap().webhooks;
+    assert_eq!(webhooks.len(), 1);
+    assert_eq!(webhooks[0].id, webhook.webhook.id);
+    assert_eq!(webhooks[0].url, "https://example.com/hooks");
+
+    let body = serde_json::json!({ "id": webhooks[0].id });
+    assert_eq!(app.post_delete_webhook(&body).await.status().as_u16(), 200);
+    assert_eq!(app.post_delete_webhook(&body).await.status().as_u16(), 404);
+    assert_eq!(app.get_webhook_deliveries(&webhooks[0].id).await.status().as_u16(), 404);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_invalid_webhook() {
+    let mut app = TestApp::new().await;
+    sign_in_as_admin(&app).await;
+
+    let test_cases = [
+        serde_json::json!({ "url": "not a url", "events": ["user.signed_up"] }),
+        serde_json::json!({ "url": "ftp://example.com/hooks", "events": ["user.signed_up"] }),
+        serde_json::json!({ "url": "https://example.com/hooks", "events": [] }),
+        serde_json::json!({ "url": "https://example.com/hooks", "events": ["user.logged_in"] }),
+    ];
+
+    for test_case in test_cases.iter() {
+        let response = app.post_webhook(test_case).await;
+        assert_eq!(response.status().as_u16(), 400, "Failed for input: {:?}", test_case);
+    }
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_403_if_not_admin() {
+    let mut app = TestApp::new().await;
+    let email = get_random_email();
+    app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": false,
+    })).await;
+    app.post_login(&serde_json::json!({ "email": email, "password": "password123" })).await;
+
+    let response = app.post_webhook(&serde_json::json!({
+        "url": "https://example.com/hooks",
+        "events": ["user.signed_up"],
+    })).await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(app.get_webhooks().await.status().as_u16(), 403);
+
+    app.clean_up().await;
+}
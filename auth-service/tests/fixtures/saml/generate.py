@@ -0,0 +1,126 @@
+#!/usr/bin/env python3
+# Regenerates signed_response.xml. The assertion is signed with idp_key.pem
+# using xmllint for canonicalization, so the fixture does not depend on the
+# service's own canonicalization code.
+#
+# Requires xmllint and the `cryptography` package.
+
+import base64
+import hashlib
+import os
+import subprocess
+
+from cryptography.hazmat.primitives import hashes, serialization
+from cryptography.hazmat.primitives.asymmetric import padding
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+
+PROTOCOL_NS = "urn:oasis:names:tc:SAML:2.0:protocol"
+ASSERTION_NS = "urn:oasis:names:tc:SAML:2.0:assertion"
+DSIG_NS = "http://www.w3.org/2000/09/xmldsig#"
+
+# Deliberately not in canonical form: namespaces are declared on the response,
+# attributes are unordered, elements are self-closing and there is whitespace
+# and escaped text to exercise canonicalization.
+RESPONSE = """<samlp:Response xmlns:samlp="{protocol}" xmlns:saml="{assertion}" xmlns:unused="urn:example:unused"
+    Version="2.0" ID="_response-1" IssueInstant="2026-10-19T09:00:00Z"
+    InResponseTo="_request-1" Destination="https://auth.example.com/saml/acs">
+  <saml:Issuer>https://idp.example.com/metadata</saml:Issuer>
+  <samlp:Status><samlp:StatusCode Value="urn:oasis:names:tc:SAML:2.0:status:Success"/></samlp:Status>
+  <saml:Assertion Version='2.0' IssueInstant="2026-10-19T09:00:00Z" ID="_assertion-1">
+    <saml:Issuer>https://idp.example.com/metadata</saml:Issuer>{signature}
+    <saml:Subject>
+      <saml:NameID Format="urn:oasis:names:tc:SAML:1.1:nameid-format:unspecified">jane.doe</saml:NameID>
+      <saml:SubjectConfirmation Method="urn:oasis:names:tc:SAML:2.0:cm:bearer">
+        <saml:SubjectConfirmationData Recipient="https://auth.example.com/saml/acs"
+          NotOnOrAfter="2026-10-19T09:05:00Z" InResponseTo="_request-1"/>
+      </saml:SubjectConfirmation>
+    </saml:Subject>
+    <saml:Conditions NotOnOrAfter="2026-10-19T09:05:00Z" NotBefore="2026-10-19T08:59:00Z">
+      <saml:AudienceRestriction>
+        <saml:Audience>https://auth.example.com/saml/metadata</saml:Audience>
+      </saml:AudienceRestriction>
+    </saml:Conditions>
+    <saml:AuthnStatement SessionIndex="_session-1" AuthnInstant="2026-10-19T09:00:00Z">
+      <saml:AuthnContext>
+        <saml:AuthnContextClassRef>urn:oasis:names:tc:SAML:2.0:ac:classes:PasswordProtectedTransport</saml:AuthnContextClassRef>
+      </saml:AuthnContext>
+    </saml:AuthnStatement>
+    <saml:AttributeStatement>
+      <saml:Attribute Name="mail" NameFormat="urn:oasis:names:tc:SAML:2.0:attrname-format:basic">
+        <saml:AttributeValue>jane.doe@example.com</saml:AttributeValue>
+      </saml:Attribute>
+      <saml:Attribute Name="displayName">
+        <saml:AttributeValue>Jane &amp; John&apos;s &lt;Doe&gt;</saml:AttributeValue>
+      </saml:Attribute>
+    </saml:AttributeStatement>
+  </saml:Assertion>
+</samlp:Response>
+"""
+
+SIGNED_INFO = """<ds:SignedInfo xmlns:ds="{dsig}">
+        <ds:CanonicalizationMethod Algorithm="http://www.w3.org/2001/10/xml-exc-c14n#"/>
+        <ds:SignatureMethod Algorithm="http://www.w3.org/2001/04/xmldsig-more#rsa-sha256"/>
+        <ds:Reference URI="#_assertion-1">
+          <ds:Transforms>
+            <ds:Transform Algorithm="http://www.w3.org/2000/09/xmldsig#enveloped-signature"/>
+            <ds:Transform Algorithm="http://www.w3.org/2001/10/xml-exc-c14n#"/>
+          </ds:Transforms>
+          <ds:DigestMethod Algorithm="http://www.w3.org/2001/04/xmlenc#sha256"/>
+          <ds:DigestValue>{digest}</ds:DigestValue>
+        </ds:Reference>
+      </ds:SignedInfo>"""
+
+SIGNATURE = """<ds:Signature xmlns:ds="{dsig}">
+      {signed_info}
+      <ds:SignatureValue>{signature}</ds:SignatureValue>
+    </ds:Signature>"""
+
+
+def exc_c14n(document):
+    return subprocess.run(
+        ["xmllint", "--exc-c14n", "-"],
+        input=document.encode(),
+        capture_output=True,
+        check=True,
+    ).stdout
+
+
+def extract_assertion(response):
+    start = response.index("<saml:Assertion")
+    end = response.index("</saml:Assertion>") + len("</saml:Assertion>")
+    # Exclusive canonicalization only renders the namespaces an element uses,
+    # so re-declaring them on the assertion gives the same output as
+    # canonicalizing it in place.
+    assertion = response[start:end]
+    return assertion.replace(
+        "<saml:Assertion",
+        '<saml:Assertion xmlns:saml="{}"'.format(ASSERTION_NS),
+        1,
+    )
+
+
+def main():
+    with open(os.path.join(HERE, "idp_key.pem"), "rb") as f:
+        key = serialization.load_pem_private_key(f.read(), password=None)
+
+    unsigned = RESPONSE.format(protocol=PROTOCOL_NS, assertion=ASSERTION_NS, signature="")
+    digest = hashlib.sha256(exc_c14n(extract_assertion(unsigned))).digest()
+
+    signed_info = SIGNED_INFO.format(dsig=DSIG_NS, digest=base64.b64encode(digest).decode())
+    signature = key.sign(exc_c14n(signed_info), padding.PKCS1v15(), hashes.SHA256())
+
+    signed_info = signed_info.replace(' xmlns:ds="{}"'.format(DSIG_NS), "", 1)
+    signature_element = SIGNATURE.format(
+        dsig=DSIG_NS,
+        signed_info=signed_info,
+        signature=base64.b64encode(signature).decode(),
+    )
+    signed = RESPONSE.format(protocol=PROTOCOL_NS, assertion=ASSERTION_NS, signature=signature_element)
+
+    with open(os.path.join(HERE, "signed_response.xml"), "w") as f:
+        f.write(signed)
+
+
+if __name__ == "__main__":
+    main()